@@ -240,7 +240,7 @@ impl DatabaseConnectorAsync for ChartTransaction {
         .fetch_one(&database_pool.database_pool)
         .await?;
 
-        Ok(inserted.id.into())
+        Ok(inserted.id)
     }
 
     #[instrument(level = "trace", skip(database_pool), err(Debug))]
@@ -56,6 +56,83 @@ impl Configuration {
         );
         Self::upsert(database_pool, &config).await
     }
+
+    pub async fn get_control_api_tokens(
+        database_pool: &crate::DbPool,
+    ) -> crate::Result<Vec<crate::ControlApiToken>> {
+        let erg = Self::get_by_key(database_pool, "control_api_tokens").await?;
+        let tokens = erg
+            .map(|c| serde_json::from_value(c.value.0))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(tokens)
+    }
+
+    pub async fn set_control_api_tokens(
+        database_pool: &crate::DbPool,
+        tokens: &[crate::ControlApiToken],
+    ) -> crate::Result<()> {
+        let config = Self::new(
+            "control_api_tokens".to_string(),
+            serde_json::to_value(tokens)?,
+        );
+        Self::upsert(database_pool, &config).await
+    }
+    /// All stored versions of the runtime config, oldest first
+    pub async fn get_config_versions(
+        database_pool: &crate::DbPool,
+    ) -> crate::Result<Vec<serde_json::Value>> {
+        let erg = sqlx::query_as!(
+            Configuration,
+            r#"
+                SELECT
+                    key,
+                    value as "value!: sqlx::types::Json<serde_json::Value>",
+                    updated_at,
+                    created_at
+                FROM configuration
+                WHERE key LIKE 'config_version:%'
+                ORDER BY key ASC
+            "#
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+
+        Ok(erg.into_iter().map(|c| c.value.0).collect())
+    }
+
+    pub async fn get_latest_config_version(
+        database_pool: &crate::DbPool,
+    ) -> crate::Result<Option<serde_json::Value>> {
+        let erg = sqlx::query_as!(
+            Configuration,
+            r#"
+                SELECT
+                    key,
+                    value as "value!: sqlx::types::Json<serde_json::Value>",
+                    updated_at,
+                    created_at
+                FROM configuration
+                WHERE key LIKE 'config_version:%'
+                ORDER BY key DESC
+                LIMIT 1
+            "#
+        )
+        .fetch_optional(&database_pool.database_pool)
+        .await?;
+
+        Ok(erg.map(|c| c.value.0))
+    }
+
+    /// The version is zero padded in the key, so the keys sort like the versions
+    pub async fn set_config_version(
+        database_pool: &crate::DbPool,
+        version: i64,
+        value: serde_json::Value,
+    ) -> crate::Result<()> {
+        let config = Self::new(format!("config_version:{:012}", version), value);
+        Self::upsert(database_pool, &config).await
+    }
 }
 
 impl DatabaseConnectorAsync for Configuration {
@@ -625,4 +625,36 @@ impl Contract {
 
         Ok(())
     }
+
+    /// Marks an offered contract as not worth accepting, the upsert of the contract keeps the mark
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn mark_declined(database_pool: &DbPool, contract_id: &str) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE contract
+            SET declined = true
+            WHERE id = $1
+        "#,
+            contract_id
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_declined_ids(database_pool: &DbPool) -> crate::Result<Vec<String>> {
+        let erg = sqlx::query!(
+            r#"
+            SELECT id
+            FROM contract
+            WHERE declined AND NOT accepted
+        "#
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+
+        Ok(erg.into_iter().map(|r| r.id).collect())
+    }
 }
@@ -0,0 +1,228 @@
+use tracing::instrument;
+
+use super::{DatabaseConnectorAsync, DbPool, PaginatedQuery, PaginatedResult, run_paginated_query};
+
+/// What a token of the control api is allowed to do, an `Operator` can do everything a `ReadOnly` can
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    sqlx::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    async_graphql::Enum,
+)]
+#[sqlx(type_name = "control_role")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ControlRole {
+    #[sqlx(rename = "READ_ONLY")]
+    ReadOnly,
+    #[sqlx(rename = "OPERATOR")]
+    Operator,
+}
+
+impl std::str::FromStr for ControlRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().replace('-', "_").as_str() {
+            "READ_ONLY" | "READONLY" => Ok(ControlRole::ReadOnly),
+            "OPERATOR" => Ok(ControlRole::Operator),
+            _ => Err(format!("Unknown control role: {}", s)),
+        }
+    }
+}
+
+/// A token accepted by the control api, the name identifies it in the audit log
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ControlApiToken {
+    pub name: String,
+    pub role: ControlRole,
+    pub token: String,
+}
+
+/// One mutation executed through the control api
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct ControlAuditLog {
+    pub id: i64,
+    pub token_name: String,
+    pub role: ControlRole,
+    pub operation: String,
+    pub arguments: serde_json::Value,
+    pub success: bool,
+    pub error: Option<String>,
+    pub created_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+}
+
+impl DatabaseConnectorAsync for ControlAuditLog {
+    type ID = i64;
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn insert_new(database_pool: &DbPool, item: &ControlAuditLog) -> crate::Result<Self::ID> {
+        let erg = sqlx::query!(
+            r#"
+            insert into control_audit_log (
+            token_name,
+            role,
+            operation,
+            arguments,
+            success,
+            error
+            ) values (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6
+            )
+            RETURNING id
+            "#,
+            item.token_name,
+            item.role as ControlRole,
+            item.operation,
+            item.arguments,
+            item.success,
+            item.error
+        )
+        .fetch_one(&database_pool.database_pool)
+        .await?;
+
+        Ok(erg.id)
+    }
+
+    /// The audit log is append only, existing entries are never changed
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn upsert(database_pool: &DbPool, item: &ControlAuditLog) -> crate::Result<()> {
+        if item.id == 0 {
+            let _ = Self::insert_new(database_pool, item).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn update(database_pool: &DbPool, item: &ControlAuditLog) -> crate::Result<()> {
+        Self::upsert(database_pool, item).await
+    }
+
+    #[instrument(level = "trace", skip(database_pool, items))]
+    async fn insert_bulk(database_pool: &DbPool, items: &[ControlAuditLog]) -> crate::Result<()> {
+        for item in items {
+            Self::upsert(database_pool, item).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn get_all(
+        database_pool: &DbPool,
+        query: PaginatedQuery,
+    ) -> crate::Result<PaginatedResult<ControlAuditLog>> {
+        run_paginated_query(
+            query,
+            |page_size, offset| async move {
+                let items = sqlx::query_as!(
+                    ControlAuditLog,
+                    r#"
+                        SELECT
+                          id,
+                          token_name,
+                          role as "role: ControlRole",
+                          operation,
+                          arguments,
+                          success,
+                          error,
+                          created_at
+                        FROM control_audit_log
+                        ORDER BY id DESC
+                        LIMIT $1 OFFSET $2
+                    "#,
+                    page_size,
+                    offset
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let items = sqlx::query_as!(
+                    ControlAuditLog,
+                    r#"
+                        SELECT
+                          id,
+                          token_name,
+                          role as "role: ControlRole",
+                          operation,
+                          arguments,
+                          success,
+                          error,
+                          created_at
+                        FROM control_audit_log
+                        ORDER BY id DESC
+                    "#
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let count = sqlx::query!(
+                    r#"
+                        SELECT COUNT(*) as "count!"
+                        FROM control_audit_log
+                    "#
+                )
+                .fetch_one(database_pool.get_cache_pool())
+                .await?;
+                Ok(count.count)
+            },
+        )
+        .await
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn get_by_id(database_pool: &DbPool, id: &Self::ID) -> crate::Result<Option<Self>> {
+        let erg = sqlx::query_as!(
+            ControlAuditLog,
+            r#"
+                SELECT
+                  id,
+                  token_name,
+                  role as "role: ControlRole",
+                  operation,
+                  arguments,
+                  success,
+                  error,
+                  created_at
+                FROM control_audit_log WHERE id = $1
+            "#,
+            *id
+        )
+        .fetch_optional(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn delete_by_id(database_pool: &DbPool, id: &Self::ID) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+                DELETE FROM control_audit_log
+                WHERE id = $1
+            "#,
+            *id
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(())
+    }
+
+    fn set_id(&mut self, id: Self::ID) {
+        self.id = id;
+    }
+}
@@ -22,6 +22,102 @@ pub struct Extraction {
     pub created_at: DateTime<Utc>,
 }
 
+/// What the extractions at one waypoint yielded of one good
+#[derive(Debug, Clone, PartialEq, async_graphql::SimpleObject)]
+pub struct ExtractionYield {
+    pub waypoint_symbol: String,
+    pub yield_symbol: models::TradeSymbol,
+    /// the extractions of the waypoint which yielded the good
+    pub extractions: i64,
+    pub units: i64,
+    /// the same only counting the latest extractions of the waypoint
+    pub recent_extractions: i64,
+    pub recent_units: i64,
+    pub last_extraction: DateTime<Utc>,
+}
+
+/// How one survey of a waypoint was used so far
+#[derive(Debug, Clone, PartialEq, async_graphql::SimpleObject)]
+pub struct SurveyUsage {
+    pub signature: String,
+    pub extractions: i64,
+    /// the ships which extracted with the survey since the given time
+    pub recent_ships: Vec<String>,
+}
+
+impl ExtractionYield {
+    /// The yields of every waypoint in the system, `recent` is the number of latest extractions per waypoint counted as recent
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_system(
+        database_pool: &DbPool,
+        system_symbol: &str,
+        recent: i64,
+    ) -> crate::Result<Vec<ExtractionYield>> {
+        let erg = sqlx::query_as!(
+            ExtractionYield,
+            r#"
+                WITH ranked AS (
+                    SELECT
+                      extraction.waypoint_symbol,
+                      extraction.yield_symbol,
+                      extraction.yield_units,
+                      extraction.created_at,
+                      row_number() OVER (
+                        PARTITION BY extraction.waypoint_symbol
+                        ORDER BY extraction.created_at DESC, extraction.id DESC
+                      ) AS recency
+                    FROM extraction JOIN waypoint ON extraction.waypoint_symbol = waypoint.symbol
+                    WHERE waypoint.system_symbol = $1
+                )
+                SELECT
+                  waypoint_symbol as "waypoint_symbol!",
+                  yield_symbol as "yield_symbol!: models::TradeSymbol",
+                  count(*) as "extractions!",
+                  sum(yield_units)::bigint as "units!",
+                  count(*) FILTER (WHERE recency <= $2) as "recent_extractions!",
+                  COALESCE(sum(yield_units) FILTER (WHERE recency <= $2), 0)::bigint as "recent_units!",
+                  max(created_at) as "last_extraction!"
+                FROM ranked
+                GROUP BY 1, 2
+                ORDER BY 1, 2
+            "#,
+            system_symbol,
+            recent
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl SurveyUsage {
+    /// The surveys used at the waypoint, ships count as recent when they extracted with the survey after `since`
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_waypoint(
+        database_pool: &DbPool,
+        waypoint_symbol: &str,
+        since: DateTime<Utc>,
+    ) -> crate::Result<Vec<SurveyUsage>> {
+        let erg = sqlx::query_as!(
+            SurveyUsage,
+            r#"
+                SELECT
+                  survey as "signature!",
+                  count(*) as "extractions!",
+                  COALESCE(array_agg(DISTINCT ship_symbol) FILTER (WHERE created_at > $2), '{}') as "recent_ships!"
+                FROM extraction
+                WHERE waypoint_symbol = $1 AND survey IS NOT NULL
+                GROUP BY survey
+            "#,
+            waypoint_symbol,
+            since
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+}
+
 impl Extraction {
     #[instrument(level = "trace", skip(database_pool), err(Debug))]
     pub async fn get_by_waypoint_symbol(
@@ -87,6 +87,12 @@ pub struct Fleet {
     // contract config
     #[graphql(skip)]
     contract_ship_count: Option<i32>,
+
+    // refining config
+    #[graphql(skip)]
+    refinery_ship_count: Option<i32>,
+    #[graphql(skip)]
+    refine_profit_threshold: Option<i32>,
 }
 
 impl Default for Fleet {
@@ -126,6 +132,8 @@ impl Default for Fleet {
             construction_ship_count: None,
             construction_waypoint: None,
             contract_ship_count: None,
+            refinery_ship_count: None,
+            refine_profit_threshold: None,
         }
     }
 }
@@ -228,13 +236,15 @@ impl DatabaseConnectorAsync for Fleet {
                                     chart_only_jump_gates,
                                     construction_ship_count,
                                     construction_waypoint,
-                                    contract_ship_count
+                                    contract_ship_count,
+                                    refinery_ship_count,
+                                    refine_profit_threshold
                                 )
                                 VALUES (
                                     $1, $2::fleet_type, $3, NOW(), NOW(),
                                     $4, $5, $6, $7, $8, $9::trade_mode, $10,
                                     $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24,
-                                    $25, $26, $27, $28, $29, $30, $31
+                                    $25, $26, $27, COALESCE($28, FALSE), $29, $30, $31, $32, $33
                                 )
                                 RETURNING id;
                         "#,
@@ -269,6 +279,8 @@ impl DatabaseConnectorAsync for Fleet {
                         &item.construction_ship_count as &Option<i32>,
                         &item.construction_waypoint as &Option<String>,
                         &item.contract_ship_count as &Option<i32>,
+                        &item.refinery_ship_count as &Option<i32>,
+                        &item.refine_profit_threshold as &Option<i32>,
                 )
                 .fetch_one(&database_pool.database_pool)
                 .await?;
@@ -313,13 +325,15 @@ impl DatabaseConnectorAsync for Fleet {
                   chart_only_jump_gates,
                   construction_ship_count,
                   construction_waypoint,
-                  contract_ship_count
+                  contract_ship_count,
+                  refinery_ship_count,
+                  refine_profit_threshold
                 )
                 VALUES (
                   $1, $2, $3::fleet_type, $4, NOW(), NOW(),
                   $5, $6, $7, $8, $9, $10::trade_mode, $11,
                   $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24,
-                  $25, $26, $27, $28, $29, $30, $31, $32
+                  $25, $26, $27, $28, COALESCE($29, FALSE), $30, $31, $32, $33, $34
                 )
                 ON CONFLICT (id) DO UPDATE SET
                   system_symbol = EXCLUDED.system_symbol,
@@ -353,7 +367,9 @@ impl DatabaseConnectorAsync for Fleet {
                   chart_only_jump_gates = EXCLUDED.chart_only_jump_gates,
                   construction_ship_count = EXCLUDED.construction_ship_count,
                   construction_waypoint = EXCLUDED.construction_waypoint,
-                  contract_ship_count = EXCLUDED.contract_ship_count;
+                  contract_ship_count = EXCLUDED.contract_ship_count,
+                  refinery_ship_count = EXCLUDED.refinery_ship_count,
+                  refine_profit_threshold = EXCLUDED.refine_profit_threshold;
             "#,
             &item.id,
             &item.system_symbol,
@@ -387,6 +403,8 @@ impl DatabaseConnectorAsync for Fleet {
             &item.construction_ship_count as &Option<i32>,
             &item.construction_waypoint as &Option<String>,
             &item.contract_ship_count as &Option<i32>,
+            &item.refinery_ship_count as &Option<i32>,
+            &item.refine_profit_threshold as &Option<i32>,
         )
         .execute(&database_pool.database_pool)
         .await?;
@@ -451,7 +469,9 @@ impl DatabaseConnectorAsync for Fleet {
                           chart_only_jump_gates,
                           construction_ship_count,
                           construction_waypoint,
-                          contract_ship_count
+                          contract_ship_count,
+                          refinery_ship_count,
+                          refine_profit_threshold
                         FROM fleet
                         LIMIT $1 OFFSET $2
                     "#,
@@ -500,7 +520,9 @@ impl DatabaseConnectorAsync for Fleet {
                           chart_only_jump_gates,
                           construction_ship_count,
                           construction_waypoint,
-                          contract_ship_count
+                          contract_ship_count,
+                          refinery_ship_count,
+                          refine_profit_threshold
                         FROM fleet
                     "#
                 )
@@ -562,7 +584,9 @@ impl DatabaseConnectorAsync for Fleet {
                   chart_only_jump_gates,
                   construction_ship_count,
                   construction_waypoint,
-                  contract_ship_count
+                  contract_ship_count,
+                  refinery_ship_count,
+                  refine_profit_threshold
                 FROM fleet
                 WHERE id = $1
             "#,
@@ -985,7 +1009,7 @@ impl Fleet {
         }
         if chart_only_jump_gates.is_some() {
             sqlx::query!(
-                r#"UPDATE fleet SET chart_only_jump_gates = $1, fleet_type = $2::fleet_type, updated_at = NOW() WHERE id = $3"#,
+                r#"UPDATE fleet SET chart_only_jump_gates = COALESCE($1, FALSE), fleet_type = $2::fleet_type, updated_at = NOW() WHERE id = $3"#,
                 &chart_only_jump_gates as &Option<bool>,
                 &ft as &FleetType,
                 id
@@ -1049,6 +1073,38 @@ impl Fleet {
         Ok(())
     }
 
+    /// Update refining fields selectively.
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn update_refining_config(
+        database_pool: &DbPool,
+        id: i32,
+        refinery_ship_count: Option<i32>,
+        refine_profit_threshold: Option<i32>,
+    ) -> crate::Result<()> {
+        let ft = FleetType::Refining;
+        if refinery_ship_count.is_some() {
+            sqlx::query!(
+                r#"UPDATE fleet SET refinery_ship_count = $1, fleet_type = $2::fleet_type, updated_at = NOW() WHERE id = $3"#,
+                &refinery_ship_count as &Option<i32>,
+                &ft as &FleetType,
+                id
+            )
+            .execute(&database_pool.database_pool)
+            .await?;
+        }
+        if refine_profit_threshold.is_some() {
+            sqlx::query!(
+                r#"UPDATE fleet SET refine_profit_threshold = $1, fleet_type = $2::fleet_type, updated_at = NOW() WHERE id = $3"#,
+                &refine_profit_threshold as &Option<i32>,
+                &ft as &FleetType,
+                id
+            )
+            .execute(&database_pool.database_pool)
+            .await?;
+        }
+        Ok(())
+    }
+
     #[instrument(level = "trace", skip(database_pool), err(Debug))]
     pub async fn insert_new(database_pool: &DbPool, item: &Fleet) -> crate::Result<i32> {
         let erg = sqlx::query!(
@@ -1086,13 +1142,15 @@ impl Fleet {
                   chart_only_jump_gates,
                   construction_ship_count,
                   construction_waypoint,
-                  contract_ship_count
+                  contract_ship_count,
+                  refinery_ship_count,
+                  refine_profit_threshold
                 )
                 VALUES (
                   $1, $2::fleet_type, $3, NOW(), NOW(),
                   $4, $5, $6, $7, $8, $9::trade_mode, $10,
                   $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24,
-                  $25, $26, $27, $28, $29, $30, $31
+                  $25, $26, $27, COALESCE($28, FALSE), $29, $30, $31, $32, $33
                 )
                 RETURNING id;
             "#,
@@ -1127,6 +1185,8 @@ impl Fleet {
             &item.construction_ship_count as &Option<i32>,
             &item.construction_waypoint as &Option<String>,
             &item.contract_ship_count as &Option<i32>,
+            &item.refinery_ship_count as &Option<i32>,
+            &item.refine_profit_threshold as &Option<i32>,
         )
         .fetch_one(&database_pool.database_pool)
         .await?;
@@ -1177,7 +1237,9 @@ impl Fleet {
                 chart_only_jump_gates,
                 construction_ship_count,
                 construction_waypoint,
-                contract_ship_count
+                contract_ship_count,
+                refinery_ship_count,
+                refine_profit_threshold
               FROM fleet
               WHERE id = ANY($1)
           "#,
@@ -1238,7 +1300,9 @@ impl Fleet {
                           chart_only_jump_gates,
                           construction_ship_count,
                           construction_waypoint,
-                          contract_ship_count
+                          contract_ship_count,
+                          refinery_ship_count,
+                          refine_profit_threshold
                         FROM fleet
                         WHERE system_symbol = $1
                         LIMIT $2 OFFSET $3
@@ -1289,7 +1353,9 @@ impl Fleet {
                           chart_only_jump_gates,
                           construction_ship_count,
                           construction_waypoint,
-                          contract_ship_count
+                          contract_ship_count,
+                          refinery_ship_count,
+                          refine_profit_threshold
                         FROM fleet
                         WHERE system_symbol = $1
                     "#,
@@ -1358,7 +1424,9 @@ impl Fleet {
                   chart_only_jump_gates,
                   construction_ship_count,
                   construction_waypoint,
-                  contract_ship_count
+                  contract_ship_count,
+                  refinery_ship_count,
+                  refine_profit_threshold
                 FROM fleet
                 WHERE system_symbol = ANY($1)
             "#,
@@ -1415,7 +1483,9 @@ impl Fleet {
                           chart_only_jump_gates,
                           construction_ship_count,
                           construction_waypoint,
-                          contract_ship_count
+                          contract_ship_count,
+                          refinery_ship_count,
+                          refine_profit_threshold
                         FROM fleet
                         WHERE fleet_type = $1
                         LIMIT $2 OFFSET $3
@@ -1466,7 +1536,9 @@ impl Fleet {
                           chart_only_jump_gates,
                           construction_ship_count,
                           construction_waypoint,
-                          contract_ship_count
+                          contract_ship_count,
+                          refinery_ship_count,
+                          refine_profit_threshold
                         FROM fleet
                         WHERE fleet_type = $1
                     "#,
@@ -1532,7 +1604,9 @@ impl Fleet {
                   chart_only_jump_gates,
                   construction_ship_count,
                   construction_waypoint,
-                  contract_ship_count
+                  contract_ship_count,
+                  refinery_ship_count,
+                  refine_profit_threshold
                 FROM fleet
                 WHERE id = $1
             "#,
@@ -1649,6 +1723,11 @@ impl Fleet {
                 self.fleet_type = FleetType::Contract;
                 self.contract_ship_count = Some(cfg.contract_ship_count);
             }
+            FleetConfig::Refining(cfg) => {
+                self.fleet_type = FleetType::Refining;
+                self.refinery_ship_count = Some(cfg.refinery_ship_count);
+                self.refine_profit_threshold = Some(cfg.refine_profit_threshold);
+            }
             FleetConfig::Manuel(_) => {
                 self.fleet_type = FleetType::Manuel;
             }
@@ -1681,6 +1760,10 @@ impl Fleet {
                 self.get_contract_config()
                     .ok_or(crate::Error::IncompleteFleetConfig { fleet_id: self.id })?,
             )),
+            FleetType::Refining => Ok(FleetConfig::Refining(
+                self.get_refining_config()
+                    .ok_or(crate::Error::IncompleteFleetConfig { fleet_id: self.id })?,
+            )),
             FleetType::Manuel => Ok(FleetConfig::Manuel(ManuelConfig::default())),
         }
     }
@@ -1743,6 +1826,13 @@ impl Fleet {
             contract_ship_count: self.contract_ship_count?,
         })
     }
+
+    pub fn get_refining_config(&self) -> Option<RefiningConfig> {
+        Some(RefiningConfig {
+            refinery_ship_count: self.refinery_ship_count?,
+            refine_profit_threshold: self.refine_profit_threshold?,
+        })
+    }
 }
 
 #[derive(
@@ -1777,6 +1867,7 @@ pub enum TradeMode {
     sqlx::Type,
     PartialEq,
     Eq,
+    Hash,
     async_graphql::Enum,
 )]
 #[sqlx(type_name = "fleet_type")]
@@ -1789,6 +1880,7 @@ pub enum FleetType {
     #[default]
     Manuel,
     Contract,
+    Refining,
 }
 
 #[derive(
@@ -1802,6 +1894,7 @@ pub enum FleetConfig {
     Charting(ChartingConfig),
     Construction(ConstructionConfig),
     Contract(ContractConfig),
+    Refining(RefiningConfig),
     Manuel(ManuelConfig),
 }
 
@@ -1831,6 +1924,7 @@ pub struct TradingConfig {
 #[graphql(input_name = "InputTotalScrappingConfig")]
 pub struct ScrapingConfig {
     pub ship_market_ratio: f64,
+    /// market scrapes per hour, shared by the markets by what their outdated prices cost
     pub allowed_requests: i32,
     pub notify_on_shipyard: bool,
 }
@@ -1882,6 +1976,16 @@ pub struct ContractConfig {
     pub contract_ship_count: i32,
 }
 
+#[derive(
+    Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject,
+)]
+#[graphql(input_name = "InputTotalRefiningConfig")]
+pub struct RefiningConfig {
+    pub refinery_ship_count: i32,
+    /// minimum profit of one refining cycle (30 ore into 10 refined goods) to be worth it
+    pub refine_profit_threshold: i32,
+}
+
 #[derive(
     Debug,
     Clone,
@@ -0,0 +1,412 @@
+use tracing::instrument;
+
+use super::{DbPool, PaginatedQuery, PaginatedResult, run_paginated_query};
+
+/// The table a ledger entry comes from
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    sqlx::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    async_graphql::Enum,
+)]
+#[sqlx(type_name = "ledger_source")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LedgerSource {
+    #[sqlx(rename = "MARKET")]
+    Market,
+    #[sqlx(rename = "SHIPYARD")]
+    Shipyard,
+    #[sqlx(rename = "CHART")]
+    Chart,
+    #[sqlx(rename = "REPAIR")]
+    Repair,
+    #[sqlx(rename = "SCRAP")]
+    Scrap,
+    #[sqlx(rename = "SHIP_MODIFICATION")]
+    ShipModification,
+    #[sqlx(rename = "CONTRACT")]
+    Contract,
+}
+
+/// What a ledger entry was spent or earned for, market transactions use their transaction reason
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    sqlx::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    async_graphql::Enum,
+)]
+#[sqlx(type_name = "ledger_activity")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LedgerActivity {
+    #[sqlx(rename = "TRADE_ROUTE")]
+    TradeRoute,
+    #[sqlx(rename = "CONTRACT")]
+    Contract,
+    #[sqlx(rename = "CONSTRUCTION")]
+    Construction,
+    #[sqlx(rename = "MINING")]
+    Mining,
+    /// market transactions without a reason, mostly fuel
+    #[sqlx(rename = "MARKET")]
+    Market,
+    #[sqlx(rename = "SHIP_PURCHASE")]
+    ShipPurchase,
+    #[sqlx(rename = "CHARTING")]
+    Charting,
+    #[sqlx(rename = "REPAIR")]
+    Repair,
+    #[sqlx(rename = "SCRAP")]
+    Scrap,
+    #[sqlx(rename = "SHIP_MODIFICATION")]
+    ShipModification,
+}
+
+/// One credit movement from any of the transaction tables or a contract payment
+///
+/// Only our own ships are included, the fleet is the one the ship was assigned to at the time of the entry
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct LedgerEntry {
+    pub source: LedgerSource,
+    /// the id of the row in the source table, contract payments are `<contract id>:accepted` or `<contract id>:fulfilled`
+    pub source_id: String,
+    pub timestamp: sqlx::types::chrono::DateTime<chrono::Utc>,
+    /// positive for income, negative for expenses
+    pub amount: i64,
+    pub activity: LedgerActivity,
+    pub ship_symbol: Option<String>,
+    pub fleet_id: Option<i32>,
+    pub waypoint_symbol: Option<String>,
+    /// the good, ship type or module traded
+    pub trade_symbol: Option<String>,
+    pub units: Option<i32>,
+    pub contract_id: Option<String>,
+    pub trade_route_id: Option<i32>,
+    pub construction_shipment_id: Option<i64>,
+    pub mining_waypoint_symbol: Option<String>,
+}
+
+/// The profit and loss of one hour
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct HourlyPnl {
+    pub hour: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub income: i64,
+    pub expenses: i64,
+    pub net: i64,
+    pub entry_count: i64,
+}
+
+/// The profit and loss of one fleet, entries without a ship or an assigned ship have no fleet
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct FleetPnl {
+    pub fleet_id: Option<i32>,
+    pub income: i64,
+    pub expenses: i64,
+    pub net: i64,
+    pub entry_count: i64,
+}
+
+/// The profit and loss of one activity
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct ActivityPnl {
+    pub activity: LedgerActivity,
+    pub income: i64,
+    pub expenses: i64,
+    pub net: i64,
+    pub entry_count: i64,
+}
+
+/// The credit change of an agent between two of its snapshots compared to the ledger entries in between
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct LedgerReconciliation {
+    pub period_start: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub period_end: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub credits_before: i64,
+    pub credits_after: i64,
+    pub agent_delta: i64,
+    pub ledger_delta: i64,
+    /// agent delta minus ledger delta, credits the ledger can not explain
+    pub difference: i64,
+}
+
+impl LedgerEntry {
+    /// The entries within the range, newest first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_range(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+        query: PaginatedQuery,
+    ) -> crate::Result<PaginatedResult<LedgerEntry>> {
+        run_paginated_query(
+            query,
+            |page_size, offset| async move {
+                let items = sqlx::query_as!(
+                    LedgerEntry,
+                    r#"
+                        SELECT
+                          source as "source!: LedgerSource",
+                          source_id as "source_id!",
+                          timestamp as "timestamp!",
+                          amount as "amount!",
+                          activity as "activity!: LedgerActivity",
+                          ship_symbol,
+                          fleet_id,
+                          waypoint_symbol,
+                          trade_symbol,
+                          units,
+                          contract_id,
+                          trade_route_id,
+                          construction_shipment_id,
+                          mining_waypoint_symbol
+                        FROM ledger_entry
+                        WHERE timestamp >= $1 AND timestamp <= $2
+                        ORDER BY timestamp DESC, source_id DESC
+                        LIMIT $3 OFFSET $4
+                    "#,
+                    since,
+                    until,
+                    page_size,
+                    offset
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let items = sqlx::query_as!(
+                    LedgerEntry,
+                    r#"
+                        SELECT
+                          source as "source!: LedgerSource",
+                          source_id as "source_id!",
+                          timestamp as "timestamp!",
+                          amount as "amount!",
+                          activity as "activity!: LedgerActivity",
+                          ship_symbol,
+                          fleet_id,
+                          waypoint_symbol,
+                          trade_symbol,
+                          units,
+                          contract_id,
+                          trade_route_id,
+                          construction_shipment_id,
+                          mining_waypoint_symbol
+                        FROM ledger_entry
+                        WHERE timestamp >= $1 AND timestamp <= $2
+                        ORDER BY timestamp DESC, source_id DESC
+                    "#,
+                    since,
+                    until
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let count = sqlx::query!(
+                    r#"
+                        SELECT COUNT(*) as "count!"
+                        FROM ledger_entry
+                        WHERE timestamp >= $1 AND timestamp <= $2
+                    "#,
+                    since,
+                    until
+                )
+                .fetch_one(database_pool.get_cache_pool())
+                .await?;
+                Ok(count.count)
+            },
+        )
+        .await
+    }
+
+    /// The sum of all entries within the range
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_net(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<i64> {
+        let erg = sqlx::query!(
+            r#"
+                SELECT COALESCE(sum(amount), 0)::bigint as "net!"
+                FROM ledger_entry
+                WHERE timestamp >= $1 AND timestamp <= $2
+            "#,
+            since,
+            until
+        )
+        .fetch_one(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg.net)
+    }
+}
+
+impl HourlyPnl {
+    /// Oldest first, hours without entries are left out
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_range(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<HourlyPnl>> {
+        let erg = sqlx::query_as!(
+            HourlyPnl,
+            r#"
+                SELECT
+                  date_trunc('hour', timestamp) as "hour!",
+                  COALESCE(sum(amount) FILTER (WHERE amount > 0), 0)::bigint as "income!",
+                  COALESCE(-sum(amount) FILTER (WHERE amount < 0), 0)::bigint as "expenses!",
+                  COALESCE(sum(amount), 0)::bigint as "net!",
+                  count(*) as "entry_count!"
+                FROM ledger_entry
+                WHERE timestamp >= $1 AND timestamp <= $2
+                GROUP BY 1
+                ORDER BY 1 ASC
+            "#,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl FleetPnl {
+    /// Most profitable fleet first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_range(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<FleetPnl>> {
+        let erg = sqlx::query_as!(
+            FleetPnl,
+            r#"
+                SELECT
+                  fleet_id,
+                  COALESCE(sum(amount) FILTER (WHERE amount > 0), 0)::bigint as "income!",
+                  COALESCE(-sum(amount) FILTER (WHERE amount < 0), 0)::bigint as "expenses!",
+                  COALESCE(sum(amount), 0)::bigint as "net!",
+                  count(*) as "entry_count!"
+                FROM ledger_entry
+                WHERE timestamp >= $1 AND timestamp <= $2
+                GROUP BY 1
+                ORDER BY 4 DESC
+            "#,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl ActivityPnl {
+    /// Most profitable activity first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_range(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<ActivityPnl>> {
+        let erg = sqlx::query_as!(
+            ActivityPnl,
+            r#"
+                SELECT
+                  activity as "activity!: LedgerActivity",
+                  COALESCE(sum(amount) FILTER (WHERE amount > 0), 0)::bigint as "income!",
+                  COALESCE(-sum(amount) FILTER (WHERE amount < 0), 0)::bigint as "expenses!",
+                  COALESCE(sum(amount), 0)::bigint as "net!",
+                  count(*) as "entry_count!"
+                FROM ledger_entry
+                WHERE timestamp >= $1 AND timestamp <= $2
+                GROUP BY 1
+                ORDER BY 4 DESC
+            "#,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl LedgerReconciliation {
+    /// Compares every pair of consecutive credit snapshots of the agent with the ledger, oldest first
+    ///
+    /// With `only_unexplained` only the periods with a difference are returned
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_agent(
+        database_pool: &DbPool,
+        agent_symbol: &str,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+        only_unexplained: bool,
+    ) -> crate::Result<Vec<LedgerReconciliation>> {
+        let erg = sqlx::query_as!(
+            LedgerReconciliation,
+            r#"
+                WITH periods AS (
+                    SELECT
+                      lag(created_at) OVER w AS period_start,
+                      created_at AS period_end,
+                      lag(credits) OVER w AS credits_before,
+                      credits AS credits_after
+                    FROM agent
+                    WHERE symbol = $1 AND created_at >= $2 AND created_at <= $3
+                    WINDOW w AS (ORDER BY created_at ASC)
+                ),
+                compared AS (
+                    SELECT
+                      periods.period_start,
+                      periods.period_end,
+                      periods.credits_before,
+                      periods.credits_after,
+                      periods.credits_after - periods.credits_before AS agent_delta,
+                      COALESCE((
+                        SELECT sum(ledger_entry.amount)
+                        FROM ledger_entry
+                        WHERE ledger_entry.timestamp > periods.period_start
+                          AND ledger_entry.timestamp <= periods.period_end
+                      ), 0)::bigint AS ledger_delta
+                    FROM periods
+                    WHERE periods.period_start IS NOT NULL
+                )
+                SELECT
+                  period_start as "period_start!",
+                  period_end as "period_end!",
+                  credits_before as "credits_before!",
+                  credits_after as "credits_after!",
+                  agent_delta as "agent_delta!",
+                  ledger_delta as "ledger_delta!",
+                  agent_delta - ledger_delta as "difference!"
+                FROM compared
+                WHERE NOT $4 OR agent_delta <> ledger_delta
+                ORDER BY period_end ASC
+            "#,
+            agent_symbol,
+            since,
+            until,
+            only_unexplained
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
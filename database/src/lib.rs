@@ -6,11 +6,14 @@ mod construction_shipment;
 mod contract;
 mod contract_delivery;
 mod contract_shipment;
+mod market_price_analytics;
 mod market_trade;
 mod market_trade_good;
 mod route;
+mod ship_command;
 mod ship_info;
 mod system;
+mod trade_plan;
 mod trade_route;
 mod waypoint;
 
@@ -32,11 +35,16 @@ mod mount_info;
 mod reactor_info;
 
 mod configuration;
+mod control_audit_log;
 mod export_import;
 mod extraction;
 mod fleet;
 mod jump_gate_connection;
+mod ledger;
+mod mining_state;
 mod reserved_fund;
+mod reset_prior;
+mod reset_statistics;
 mod ship_assignment;
 mod ship_jump;
 mod ship_state;
@@ -51,9 +59,10 @@ pub use contract::Contract;
 pub use contract_delivery::ContractDelivery;
 pub use contract_shipment::ContractShipment;
 pub use contract_shipment::ShipmentStatus;
+pub use control_audit_log::{ControlApiToken, ControlAuditLog, ControlRole};
 pub use engine_info::EngineInfo;
 pub use export_import::ExportImportMapping;
-pub use extraction::Extraction;
+pub use extraction::{Extraction, ExtractionYield, SurveyUsage};
 pub use fleet::ChartingConfig as ChartingFleetConfig;
 pub use fleet::ConstructionConfig as ConstructionFleetConfig;
 pub use fleet::ContractConfig as ContractFleetConfig;
@@ -64,27 +73,41 @@ pub use fleet::FleetLoader;
 pub use fleet::FleetType;
 pub use fleet::ManuelConfig as ManuelFleetConfig;
 pub use fleet::MiningConfig as MiningFleetConfig;
+pub use fleet::RefiningConfig as RefiningFleetConfig;
 pub use fleet::ScrapingConfig as ScrapingFleetConfig;
 pub use fleet::TradeMode;
 pub use fleet::TradingConfig as TradingFleetConfig;
 pub use frame_info::FrameInfo;
 pub use jump_gate_connection::JumpGateConnection;
+pub use ledger::{
+    ActivityPnl, FleetPnl, HourlyPnl, LedgerActivity, LedgerEntry, LedgerReconciliation,
+    LedgerSource,
+};
+pub use market_price_analytics::{
+    MarketPriceBucket, MarketSpread, MarketVolatility, SystemPriceIndex,
+};
 pub use market_trade::MarketTrade;
 pub use market_trade_good::MarketTradeGood;
 pub use market_transaction::MarketTransaction;
 pub use market_transaction::TransactionReason;
 pub use market_transaction::TransactionSummary;
+pub use mining_state::{MiningAssignLevel, MiningAssignment, MiningTransfer, MiningTransferStatus};
 pub use module_info::ModuleInfo;
 pub use mount_info::MountInfo;
 pub use reactor_info::ReactorInfo;
 pub use repair_transaction::RepairTransaction;
 pub use reserved_fund::FundStatus;
 pub use reserved_fund::ReservedFund;
+pub use reset_prior::{
+    ConstructionCostPrior, ExtractionYieldPrior, GoodPricePrior, ResetPriors, ShipTypePrior,
+};
+pub use reset_statistics::{FleetSize, ShipCountPoint, ShipPayback, TradeRouteResult};
 pub use route::Route;
 pub use scrap_transaction::ScrapTransaction;
 pub use ship_assignment::AssignmentsByFleetLoader;
 pub use ship_assignment::ShipAssignment;
 pub use ship_assignment::SimpleShipRequirement;
+pub use ship_command::{ShipCommand, ShipCommandStatus, ShipCommandType};
 pub use ship_info::ShipInfo;
 pub use ship_jump::ShipJump;
 pub use ship_modification_transaction::ShipModificationTransaction;
@@ -95,6 +118,7 @@ pub use shipyard_ship_types::ShipyardShipTypes;
 pub use shipyard_transaction::ShipyardTransaction;
 pub use survey::Survey;
 pub use system::System;
+pub use trade_plan::TradePlan;
 pub use trade_route::TradeRoute;
 pub use waypoint::Waypoint;
 pub use waypoint::WaypointLoader;
@@ -125,6 +149,8 @@ pub enum Error {
 
     #[error("Incomplete fleet config for fleet ID {fleet_id:?}")]
     IncompleteFleetConfig { fleet_id: i32 },
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -137,15 +163,22 @@ pub struct DbPool {
         tokio::sync::broadcast::Sender<Agent>,
         tokio::sync::broadcast::Receiver<Agent>,
     ),
+    /// every transaction made by our ships, inserted one at a time
+    pub market_transaction_broadcast_channel: (
+        tokio::sync::broadcast::Sender<MarketTransaction>,
+        tokio::sync::broadcast::Receiver<MarketTransaction>,
+    ),
 }
 
 impl DbPool {
     pub fn new(database_pool: sqlx::PgPool, readyset_pool: Option<sqlx::PgPool>) -> DbPool {
         let agent_broadcast_channel = tokio::sync::broadcast::channel(10);
+        let market_transaction_broadcast_channel = tokio::sync::broadcast::channel(100);
         DbPool {
             database_pool,
             readyset_pool,
             agent_broadcast_channel,
+            market_transaction_broadcast_channel,
         }
     }
     pub fn get_cache_pool(&self) -> &sqlx::PgPool {
@@ -166,6 +199,10 @@ impl Clone for DbPool {
                 self.agent_broadcast_channel.0.clone(),
                 self.agent_broadcast_channel.0.subscribe(),
             ),
+            market_transaction_broadcast_channel: (
+                self.market_transaction_broadcast_channel.0.clone(),
+                self.market_transaction_broadcast_channel.0.subscribe(),
+            ),
         }
     }
 }
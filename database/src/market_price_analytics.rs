@@ -0,0 +1,275 @@
+use space_traders_client::models;
+use tracing::instrument;
+
+use super::DbPool;
+
+/// All buckets start at multiples of the bucket size after this origin
+const BUCKET_ORIGIN: &str = "2000-01-01T00:00:00Z";
+
+/// The snapshots of one good at one market within one time bucket
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct MarketPriceBucket {
+    pub bucket_start: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub purchase_open: i32,
+    pub purchase_high: i32,
+    pub purchase_low: i32,
+    pub purchase_close: i32,
+    pub sell_open: i32,
+    pub sell_high: i32,
+    pub sell_low: i32,
+    pub sell_close: i32,
+    pub supply_open: models::SupplyLevel,
+    pub supply_close: models::SupplyLevel,
+    /// how often the supply level changed within the bucket
+    pub supply_changes: i64,
+    pub activity_open: Option<models::ActivityLevel>,
+    pub activity_close: Option<models::ActivityLevel>,
+    /// how often the activity level changed within the bucket
+    pub activity_changes: i64,
+    pub avg_trade_volume: f64,
+    pub max_trade_volume: i32,
+    pub snapshots: i64,
+}
+
+/// The best place to buy and to sell one good in a system, from the latest snapshot of every market
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct MarketSpread {
+    pub symbol: models::TradeSymbol,
+    pub buy_waypoint_symbol: String,
+    pub buy_price: i32,
+    pub sell_waypoint_symbol: String,
+    pub sell_price: i32,
+    /// best sell price minus best purchase price, negative when no market pair is profitable
+    pub spread: i32,
+    pub market_count: i64,
+}
+
+/// The price level of a whole system within one time bucket
+///
+/// Every good is weighted equally, 100 is the average price of the good over the queried range
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct SystemPriceIndex {
+    pub bucket_start: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub purchase_index: f64,
+    pub sell_index: f64,
+    pub good_count: i64,
+}
+
+/// How much the prices of one market moved between its scrapes
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct MarketVolatility {
+    pub waypoint_symbol: String,
+    pub scrapes: i64,
+    /// the part of the rescraped goods whose purchase or sell price had changed
+    pub changed_share: f64,
+    /// the average relative price change of a good per hour
+    pub drift_per_hour: f64,
+    pub last_scrape: sqlx::types::chrono::DateTime<chrono::Utc>,
+}
+
+fn origin() -> sqlx::types::chrono::DateTime<chrono::Utc> {
+    BUCKET_ORIGIN.parse().unwrap_or_default()
+}
+
+impl MarketPriceBucket {
+    /// The OHLC series of one good at one market, oldest first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_series(
+        database_pool: &DbPool,
+        waypoint_symbol: &str,
+        trade_symbol: &models::TradeSymbol,
+        bucket_seconds: f64,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<MarketPriceBucket>> {
+        let erg = sqlx::query_as!(
+            MarketPriceBucket,
+            r#"
+            WITH snapshots AS (
+                SELECT
+                    date_bin(make_interval(secs => $3), created, $4) AS bucket,
+                    created,
+                    purchase_price,
+                    sell_price,
+                    trade_volume,
+                    supply,
+                    activity,
+                    supply IS DISTINCT FROM lag(supply) OVER w AS supply_changed,
+                    activity IS DISTINCT FROM lag(activity) OVER w AS activity_changed,
+                    row_number() OVER w AS position
+                FROM public.market_trade_good
+                WHERE waypoint_symbol = $1 AND symbol = $2 AND created >= $5 AND created <= $6
+                WINDOW w AS (ORDER BY created ASC)
+            )
+            SELECT
+                bucket as "bucket_start!",
+                (array_agg(purchase_price ORDER BY created ASC))[1] as "purchase_open!",
+                max(purchase_price) as "purchase_high!",
+                min(purchase_price) as "purchase_low!",
+                (array_agg(purchase_price ORDER BY created DESC))[1] as "purchase_close!",
+                (array_agg(sell_price ORDER BY created ASC))[1] as "sell_open!",
+                max(sell_price) as "sell_high!",
+                min(sell_price) as "sell_low!",
+                (array_agg(sell_price ORDER BY created DESC))[1] as "sell_close!",
+                (array_agg(supply ORDER BY created ASC))[1] as "supply_open!: models::SupplyLevel",
+                (array_agg(supply ORDER BY created DESC))[1] as "supply_close!: models::SupplyLevel",
+                count(*) FILTER (WHERE supply_changed AND position > 1) as "supply_changes!",
+                (array_agg(activity ORDER BY created ASC))[1] as "activity_open: models::ActivityLevel",
+                (array_agg(activity ORDER BY created DESC))[1] as "activity_close: models::ActivityLevel",
+                count(*) FILTER (WHERE activity_changed AND position > 1) as "activity_changes!",
+                avg(trade_volume)::float8 as "avg_trade_volume!",
+                max(trade_volume) as "max_trade_volume!",
+                count(*) as "snapshots!"
+            FROM snapshots
+            GROUP BY bucket
+            ORDER BY bucket ASC
+            "#,
+            waypoint_symbol,
+            *trade_symbol as models::TradeSymbol,
+            bucket_seconds,
+            origin(),
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl MarketSpread {
+    /// The spreads of all goods traded in the system, largest spread first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_system(
+        database_pool: &DbPool,
+        system_symbol: &str,
+    ) -> crate::Result<Vec<MarketSpread>> {
+        let erg = sqlx::query_as!(
+            MarketSpread,
+            r#"
+            WITH latest AS (
+                SELECT DISTINCT ON (market_trade_good.waypoint_symbol, market_trade_good.symbol)
+                    market_trade_good.waypoint_symbol,
+                    market_trade_good.symbol,
+                    market_trade_good.purchase_price,
+                    market_trade_good.sell_price
+                FROM public.market_trade_good left join public.waypoint ON waypoint.symbol = market_trade_good.waypoint_symbol
+                WHERE waypoint.system_symbol = $1
+                ORDER BY market_trade_good.waypoint_symbol, market_trade_good.symbol, market_trade_good.created DESC
+            )
+            SELECT
+                symbol as "symbol!: models::TradeSymbol",
+                (array_agg(waypoint_symbol ORDER BY purchase_price ASC))[1] as "buy_waypoint_symbol!",
+                min(purchase_price) as "buy_price!",
+                (array_agg(waypoint_symbol ORDER BY sell_price DESC))[1] as "sell_waypoint_symbol!",
+                max(sell_price) as "sell_price!",
+                max(sell_price) - min(purchase_price) as "spread!",
+                count(*) as "market_count!"
+            FROM latest
+            GROUP BY symbol
+            ORDER BY 6 DESC
+            "#,
+            system_symbol
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl SystemPriceIndex {
+    /// The price index series of a system, oldest first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_series(
+        database_pool: &DbPool,
+        system_symbol: &str,
+        bucket_seconds: f64,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<SystemPriceIndex>> {
+        let erg = sqlx::query_as!(
+            SystemPriceIndex,
+            r#"
+            WITH buckets AS (
+                SELECT
+                    date_bin(make_interval(secs => $2), market_trade_good.created, $3) AS bucket,
+                    market_trade_good.symbol,
+                    avg(market_trade_good.purchase_price)::float8 AS purchase_price,
+                    avg(market_trade_good.sell_price)::float8 AS sell_price
+                FROM public.market_trade_good left join public.waypoint ON waypoint.symbol = market_trade_good.waypoint_symbol
+                WHERE waypoint.system_symbol = $1 AND market_trade_good.created >= $4 AND market_trade_good.created <= $5
+                GROUP BY 1, 2
+            ),
+            bases AS (
+                SELECT
+                    symbol,
+                    avg(purchase_price) AS purchase_base,
+                    avg(sell_price) AS sell_base
+                FROM buckets
+                GROUP BY symbol
+            )
+            SELECT
+                buckets.bucket as "bucket_start!",
+                COALESCE(100 * avg(buckets.purchase_price / NULLIF(bases.purchase_base, 0)), 100)::float8 as "purchase_index!",
+                COALESCE(100 * avg(buckets.sell_price / NULLIF(bases.sell_base, 0)), 100)::float8 as "sell_index!",
+                count(*) as "good_count!"
+            FROM buckets JOIN bases ON bases.symbol = buckets.symbol
+            GROUP BY buckets.bucket
+            ORDER BY buckets.bucket ASC
+            "#,
+            system_symbol,
+            bucket_seconds,
+            origin(),
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl MarketVolatility {
+    /// The volatility of every market in the system, measured on the snapshots since `since`
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_system(
+        database_pool: &DbPool,
+        system_symbol: &str,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<MarketVolatility>> {
+        let erg = sqlx::query_as!(
+            MarketVolatility,
+            r#"
+            WITH snapshots AS (
+                SELECT
+                    market_trade_good.waypoint_symbol,
+                    market_trade_good.created,
+                    extract(epoch FROM market_trade_good.created - lag(market_trade_good.created) OVER w)::float8 / 3600.0 AS hours,
+                    (
+                        abs(market_trade_good.purchase_price - lag(market_trade_good.purchase_price) OVER w)::float8
+                            / GREATEST(lag(market_trade_good.purchase_price) OVER w, 1)
+                        + abs(market_trade_good.sell_price - lag(market_trade_good.sell_price) OVER w)::float8
+                            / GREATEST(lag(market_trade_good.sell_price) OVER w, 1)
+                    ) / 2.0 AS change
+                FROM public.market_trade_good left join public.waypoint ON waypoint.symbol = market_trade_good.waypoint_symbol
+                WHERE waypoint.system_symbol = $1 AND market_trade_good.created >= $2
+                WINDOW w AS (PARTITION BY market_trade_good.waypoint_symbol, market_trade_good.symbol ORDER BY market_trade_good.created ASC)
+            )
+            SELECT
+                waypoint_symbol as "waypoint_symbol!",
+                count(DISTINCT created) as "scrapes!",
+                COALESCE(count(*) FILTER (WHERE change > 0)::float8 / NULLIF(count(change), 0), 0)::float8 as "changed_share!",
+                COALESCE(sum(change) / NULLIF(sum(hours), 0), 0)::float8 as "drift_per_hour!",
+                max(created) as "last_scrape!"
+            FROM snapshots
+            GROUP BY waypoint_symbol
+            ORDER BY waypoint_symbol ASC
+            "#,
+            system_symbol,
+            since
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
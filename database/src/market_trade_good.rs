@@ -668,6 +668,39 @@ impl MarketTradeGood {
         Ok(erg)
     }
 
+    /// All snapshots of the markets in the system since `since`, oldest first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_history_by_system_since(
+        database_pool: &DbPool,
+        system_symbol: &str,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<MarketTradeGood>> {
+        let erg = sqlx::query_as!(
+            MarketTradeGood,
+            r#"
+            SELECT
+                market_trade_good.created_at,
+                market_trade_good.created,
+                market_trade_good.waypoint_symbol,
+                market_trade_good.symbol as "symbol: models::TradeSymbol",
+                market_trade_good."type" as "type: models::market_trade_good::Type",
+                market_trade_good.trade_volume,
+                market_trade_good.supply as "supply: models::SupplyLevel",
+                market_trade_good.activity as "activity: models::ActivityLevel",
+                market_trade_good.purchase_price,
+                market_trade_good.sell_price
+            FROM public.market_trade_good left join public.waypoint ON waypoint.symbol = market_trade_good.waypoint_symbol
+            WHERE waypoint.system_symbol = $1 AND market_trade_good.created >= $2
+            ORDER BY market_trade_good.created ASC
+        "#,
+            system_symbol,
+            since
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+
     #[instrument(level = "trace", skip(database_pool), err(Debug))]
     pub async fn get_last(
         database_pool: &DbPool,
@@ -226,6 +226,43 @@ impl MarketTransaction {
         .await
     }
 
+    /// All transactions at the markets in the system since `since`, oldest first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_system_since(
+        database_pool: &DbPool,
+        system: &str,
+        since: DateTime<Utc>,
+    ) -> crate::Result<Vec<MarketTransaction>> {
+        let system_qr = format!("{}-%", system);
+        let erg = sqlx::query_as!(
+            MarketTransaction,
+            r#"
+          select 
+          id,
+          waypoint_symbol,
+          ship_symbol,
+          trade_symbol as "trade_symbol: models::TradeSymbol",
+          "type" as "type: models::market_transaction::Type",
+          units,
+          price_per_unit,
+          total_price,
+          "timestamp",
+          contract,
+          trade_route,
+          mining,
+          construction
+          from market_transaction
+          where waypoint_symbol like $1 and "timestamp" >= $2
+          order by "timestamp"
+        "#,
+            system_qr,
+            since
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+
     #[instrument(level = "trace", skip(database_pool), err(Debug))]
     pub async fn get_by_contract(
         database_pool: &DbPool,
@@ -1576,6 +1613,14 @@ impl DatabaseConnectorAsync for MarketTransaction {
   .fetch_one(&database_pool.database_pool)
   .await?;
 
+        let _erg = database_pool
+            .market_transaction_broadcast_channel
+            .0
+            .send(MarketTransaction {
+                id: erg.id,
+                ..item.clone()
+            });
+
         Ok(erg.id)
     }
 
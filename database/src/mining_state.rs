@@ -0,0 +1,257 @@
+use space_traders_client::models;
+use tracing::instrument;
+
+use super::DbPool;
+
+/// The waypoint a mining ship is assigned to by the mining manager
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct MiningAssignment {
+    pub ship_symbol: String,
+    pub waypoint_symbol: String,
+    pub level: MiningAssignLevel,
+    pub updated_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    sqlx::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    async_graphql::Enum,
+)]
+#[sqlx(type_name = "mining_assign_level")]
+pub enum MiningAssignLevel {
+    #[sqlx(rename = "INACTIVE")]
+    Inactive,
+    #[sqlx(rename = "ON_THE_WAY")]
+    OnTheWay,
+    #[sqlx(rename = "ACTIVE")]
+    Active,
+}
+
+/// A cargo transfer from an extractor to a transporter, with the cargo of both ships before it to tell whether it happened
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct MiningTransfer {
+    pub id: i64,
+    pub waypoint_symbol: String,
+    pub from_ship: String,
+    pub to_ship: String,
+    pub trade_symbol: models::TradeSymbol,
+    pub units: i32,
+    pub from_units_before: i32,
+    pub to_units_before: i32,
+    pub status: MiningTransferStatus,
+    pub created_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub updated_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    sqlx::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    Default,
+    async_graphql::Enum,
+)]
+#[sqlx(type_name = "mining_transfer_status")]
+pub enum MiningTransferStatus {
+    #[default]
+    #[sqlx(rename = "PENDING")]
+    Pending,
+    #[sqlx(rename = "DONE")]
+    Done,
+    /// the cargo never moved, the extractor still has it
+    #[sqlx(rename = "ROLLED_BACK")]
+    RolledBack,
+    /// one of the ships is gone
+    #[sqlx(rename = "FAILED")]
+    Failed,
+}
+
+impl MiningAssignment {
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn upsert(
+        database_pool: &DbPool,
+        ship_symbol: &str,
+        waypoint_symbol: &str,
+        level: MiningAssignLevel,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO mining_assignment (ship_symbol, waypoint_symbol, level)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (ship_symbol) DO UPDATE
+                SET waypoint_symbol = EXCLUDED.waypoint_symbol,
+                    level = EXCLUDED.level,
+                    updated_at = NOW()
+            "#,
+            ship_symbol,
+            waypoint_symbol,
+            level as MiningAssignLevel
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn delete_by_ship(database_pool: &DbPool, ship_symbol: &str) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+                DELETE FROM mining_assignment
+                WHERE ship_symbol = $1
+            "#,
+            ship_symbol
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_all(database_pool: &DbPool) -> crate::Result<Vec<MiningAssignment>> {
+        let erg = sqlx::query_as!(
+            MiningAssignment,
+            r#"
+                SELECT
+                  ship_symbol,
+                  waypoint_symbol,
+                  level as "level: MiningAssignLevel",
+                  updated_at
+                FROM mining_assignment
+                ORDER BY ship_symbol ASC
+            "#
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl MiningTransfer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        waypoint_symbol: String,
+        from_ship: String,
+        to_ship: String,
+        trade_symbol: models::TradeSymbol,
+        units: i32,
+        from_units_before: i32,
+        to_units_before: i32,
+    ) -> Self {
+        MiningTransfer {
+            id: 0,
+            waypoint_symbol,
+            from_ship,
+            to_ship,
+            trade_symbol,
+            units,
+            from_units_before,
+            to_units_before,
+            status: MiningTransferStatus::Pending,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Whether the transfer happened, judged by the current cargo of both ships
+    pub fn outcome(&self, from_units: Option<i32>, to_units: Option<i32>) -> MiningTransferStatus {
+        let (Some(from_units), Some(to_units)) = (from_units, to_units) else {
+            return MiningTransferStatus::Failed;
+        };
+
+        if from_units <= self.from_units_before - self.units
+            || to_units >= self.to_units_before + self.units
+        {
+            MiningTransferStatus::Done
+        } else {
+            MiningTransferStatus::RolledBack
+        }
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn insert_new(database_pool: &DbPool, item: &MiningTransfer) -> crate::Result<i64> {
+        let erg = sqlx::query!(
+            r#"
+                INSERT INTO mining_transfer (
+                  waypoint_symbol,
+                  from_ship,
+                  to_ship,
+                  trade_symbol,
+                  units,
+                  from_units_before,
+                  to_units_before,
+                  status
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                RETURNING id
+            "#,
+            item.waypoint_symbol,
+            item.from_ship,
+            item.to_ship,
+            item.trade_symbol as models::TradeSymbol,
+            item.units,
+            item.from_units_before,
+            item.to_units_before,
+            item.status as MiningTransferStatus
+        )
+        .fetch_one(&database_pool.database_pool)
+        .await?;
+        Ok(erg.id)
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn update_status(
+        database_pool: &DbPool,
+        id: i64,
+        status: MiningTransferStatus,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE mining_transfer
+                SET status = $2, updated_at = NOW()
+                WHERE id = $1
+            "#,
+            id,
+            status as MiningTransferStatus
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The transfers which were still running when the bot stopped
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_pending(database_pool: &DbPool) -> crate::Result<Vec<MiningTransfer>> {
+        let erg = sqlx::query_as!(
+            MiningTransfer,
+            r#"
+                SELECT
+                  id,
+                  waypoint_symbol,
+                  from_ship,
+                  to_ship,
+                  trade_symbol as "trade_symbol: models::TradeSymbol",
+                  units,
+                  from_units_before,
+                  to_units_before,
+                  status as "status: MiningTransferStatus",
+                  created_at,
+                  updated_at
+                FROM mining_transfer
+                WHERE status = 'PENDING'
+                ORDER BY id ASC
+            "#
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+}
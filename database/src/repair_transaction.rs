@@ -267,7 +267,7 @@ impl DatabaseConnectorAsync for RepairTransaction {
         .await?;
 
         if let Some(inserted) = inserted {
-            return Ok(inserted.id.into());
+            return Ok(inserted.id);
         }
 
         let existing = sqlx::query!(
@@ -286,7 +286,7 @@ impl DatabaseConnectorAsync for RepairTransaction {
         .fetch_one(database_pool.get_cache_pool())
         .await?;
 
-        Ok(existing.id.into())
+        Ok(existing.id)
     }
 
     #[instrument(level = "trace", skip(database_pool, item))]
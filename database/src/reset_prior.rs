@@ -0,0 +1,281 @@
+use space_traders_client::models;
+use tracing::instrument;
+
+use super::{DbPool, FleetType};
+
+/// Knowledge learned in an earlier reset, only used until the current reset has data of its own
+///
+/// Waypoint symbols change every reset, so everything is keyed by types instead
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ResetPriors {
+    /// the export the priors were taken from
+    pub source_reset: String,
+    pub reset_date: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub good_prices: Vec<GoodPricePrior>,
+    pub ship_types: Vec<ShipTypePrior>,
+    pub extraction_yields: Vec<ExtractionYieldPrior>,
+    pub jump_gate_materials: Vec<ConstructionCostPrior>,
+}
+
+/// The average market snapshot of one good at one kind of market
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GoodPricePrior {
+    pub symbol: models::TradeSymbol,
+    pub trade_type: models::market_trade_good::Type,
+    pub avg_purchase_price: f64,
+    pub avg_sell_price: f64,
+    pub avg_trade_volume: f64,
+    pub samples: i64,
+}
+
+/// How the purchased ships of one type did in one kind of fleet
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ShipTypePrior {
+    pub ship_type: String,
+    /// the fleet the ships were assigned to at the end, `None` for unassigned ships
+    pub fleet_type: Option<FleetType>,
+    pub purchased: i64,
+    pub avg_price: f64,
+    /// the ledger sum of a ship per hour since its purchase
+    pub avg_earned_per_hour: f64,
+}
+
+/// What extractions at one kind of waypoint yielded, with and without a survey
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExtractionYieldPrior {
+    pub waypoint_type: models::WaypointType,
+    pub surveyed: bool,
+    pub yield_symbol: models::TradeSymbol,
+    pub extractions: i64,
+    pub avg_units: f64,
+    /// the part of the extractions at this kind of waypoint which yielded the good
+    pub share: f64,
+}
+
+/// One material of the jump gate construction and what it cost to buy
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConstructionCostPrior {
+    pub trade_symbol: models::TradeSymbol,
+    pub avg_required: f64,
+    /// `None` when nothing was bought for the construction
+    pub avg_price_per_unit: Option<f64>,
+}
+
+impl ResetPriors {
+    /// Collects the priors of everything recorded in the range
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn collect(
+        database_pool: &DbPool,
+        source_reset: &str,
+        agent_symbol: &str,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<ResetPriors> {
+        Ok(ResetPriors {
+            source_reset: source_reset.to_string(),
+            reset_date: since,
+            good_prices: GoodPricePrior::get_by_range(database_pool, since, until).await?,
+            ship_types: ShipTypePrior::get_by_agent(database_pool, agent_symbol, since, until)
+                .await?,
+            extraction_yields: ExtractionYieldPrior::get_by_range(database_pool, since, until)
+                .await?,
+            jump_gate_materials: ConstructionCostPrior::get_jump_gate(database_pool, since, until)
+                .await?,
+        })
+    }
+
+    /// Stores the priors, returns false when priors of the same source were already imported
+    #[instrument(level = "trace", skip(database_pool, self), fields(source_reset = %self.source_reset), err(Debug))]
+    pub async fn insert(&self, database_pool: &DbPool) -> crate::Result<bool> {
+        let priors = serde_json::to_value(self)?;
+        let erg = sqlx::query!(
+            r#"
+                INSERT INTO reset_prior (source_reset, reset_date, priors)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (source_reset) DO NOTHING
+            "#,
+            self.source_reset,
+            self.reset_date,
+            priors
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(erg.rows_affected() > 0)
+    }
+
+    /// The priors of the most recent reset before the given one, an export of the running reset is no prior
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_latest_before(
+        database_pool: &DbPool,
+        reset_date: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Option<ResetPriors>> {
+        let erg = sqlx::query!(
+            r#"
+                SELECT priors
+                FROM reset_prior
+                WHERE reset_date < $1
+                ORDER BY reset_date DESC, id DESC
+                LIMIT 1
+            "#,
+            reset_date
+        )
+        .fetch_optional(database_pool.get_cache_pool())
+        .await?;
+
+        erg.map(|row| serde_json::from_value(row.priors).map_err(Into::into))
+            .transpose()
+    }
+}
+
+impl GoodPricePrior {
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_range(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<GoodPricePrior>> {
+        let erg = sqlx::query_as!(
+            GoodPricePrior,
+            r#"
+                SELECT
+                  symbol as "symbol!: models::TradeSymbol",
+                  "type" as "trade_type!: models::market_trade_good::Type",
+                  avg(purchase_price)::float8 as "avg_purchase_price!",
+                  avg(sell_price)::float8 as "avg_sell_price!",
+                  avg(trade_volume)::float8 as "avg_trade_volume!",
+                  count(*) as "samples!"
+                FROM market_trade_good
+                WHERE created >= $1 AND created <= $2
+                GROUP BY 1, 2
+                ORDER BY 1, 2
+            "#,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl ShipTypePrior {
+    /// The ships the agent purchased in the range, earnings are counted up to the end of the range
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_agent(
+        database_pool: &DbPool,
+        agent_symbol: &str,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<ShipTypePrior>> {
+        let erg = sqlx::query_as!(
+            ShipTypePrior,
+            r#"
+                WITH purchases AS (
+                    SELECT
+                      shipyard_transaction.ship_type,
+                      shipyard_transaction.price,
+                      shipyard_transaction."timestamp",
+                      fleet.fleet_type,
+                      COALESCE((
+                        SELECT sum(ledger_entry.amount)
+                        FROM ledger_entry
+                        WHERE ledger_entry.ship_symbol = ship_info.symbol
+                          AND ledger_entry.timestamp >= shipyard_transaction."timestamp"
+                          AND ledger_entry.timestamp <= $3
+                      ), 0)::float8 AS earned
+                    FROM shipyard_transaction
+                    LEFT JOIN ship_info ON ship_info.purchase_id = shipyard_transaction.id
+                    LEFT JOIN ship_assignment ON ship_assignment.id = ship_info.assignment_id
+                    LEFT JOIN fleet ON fleet.id = ship_assignment.fleet_id
+                    WHERE shipyard_transaction.agent_symbol = $1
+                      AND shipyard_transaction."timestamp" >= $2
+                      AND shipyard_transaction."timestamp" <= $3
+                )
+                SELECT
+                  ship_type as "ship_type!",
+                  fleet_type as "fleet_type?: FleetType",
+                  count(*) as "purchased!",
+                  avg(price)::float8 as "avg_price!",
+                  avg(earned / GREATEST(extract(epoch FROM $3 - "timestamp")::float8 / 3600.0, 1.0))::float8 as "avg_earned_per_hour!"
+                FROM purchases
+                GROUP BY 1, 2
+                ORDER BY 1, 2
+            "#,
+            agent_symbol,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl ExtractionYieldPrior {
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_range(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<ExtractionYieldPrior>> {
+        let erg = sqlx::query_as!(
+            ExtractionYieldPrior,
+            r#"
+                SELECT
+                  waypoint.type as "waypoint_type!: models::WaypointType",
+                  extraction.survey IS NOT NULL as "surveyed!",
+                  extraction.yield_symbol as "yield_symbol!: models::TradeSymbol",
+                  count(*) as "extractions!",
+                  avg(extraction.yield_units)::float8 as "avg_units!",
+                  (count(*)::float8 / sum(count(*)) OVER (PARTITION BY waypoint.type, extraction.survey IS NOT NULL))::float8 as "share!"
+                FROM extraction
+                JOIN waypoint ON waypoint.symbol = extraction.waypoint_symbol
+                WHERE extraction.created_at >= $1 AND extraction.created_at <= $2
+                GROUP BY 1, 2, 3
+                ORDER BY 1, 2, 3
+            "#,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl ConstructionCostPrior {
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_jump_gate(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<ConstructionCostPrior>> {
+        let erg = sqlx::query_as!(
+            ConstructionCostPrior,
+            r#"
+                SELECT
+                  construction_material.trade_symbol as "trade_symbol!: models::TradeSymbol",
+                  avg(construction_material.required)::float8 as "avg_required!",
+                  (
+                    SELECT avg(market_transaction.price_per_unit)
+                    FROM market_transaction
+                    WHERE market_transaction.construction IS NOT NULL
+                      AND market_transaction.type = 'PURCHASE'
+                      AND market_transaction.trade_symbol = construction_material.trade_symbol
+                      AND market_transaction.timestamp >= $1
+                      AND market_transaction.timestamp <= $2
+                  )::float8 as "avg_price_per_unit?"
+                FROM construction_material
+                JOIN waypoint ON waypoint.symbol = construction_material.waypoint_symbol
+                WHERE waypoint.type = 'JUMP_GATE'
+                GROUP BY 1
+                ORDER BY 1
+            "#,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
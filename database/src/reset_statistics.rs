@@ -0,0 +1,236 @@
+use space_traders_client::models;
+use tracing::instrument;
+
+use super::{DbPool, FleetType};
+
+/// The ship count of the agent at the end of one hour
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct ShipCountPoint {
+    pub hour: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub ship_count: i32,
+}
+
+/// The number of ships assigned to a fleet at the end of one hour
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct FleetSize {
+    pub hour: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub fleet_id: i32,
+    pub system_symbol: String,
+    pub fleet_type: FleetType,
+    pub active: bool,
+    pub ship_count: i64,
+}
+
+/// All trade routes of one good between the same two markets, combined from the ledger
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct TradeRouteResult {
+    pub symbol: models::TradeSymbol,
+    pub purchase_waypoint: String,
+    pub sell_waypoint: String,
+    pub runs: i64,
+    pub units_sold: i64,
+    pub income: i64,
+    pub expenses: i64,
+    pub profit: i64,
+}
+
+/// A purchased ship and how long it took to earn its price back
+#[derive(Debug, Clone, PartialEq, serde::Serialize, async_graphql::SimpleObject)]
+pub struct ShipPayback {
+    pub transaction_id: i64,
+    /// unknown when the ship was not stored after the purchase
+    pub ship_symbol: Option<String>,
+    pub ship_type: String,
+    pub waypoint_symbol: String,
+    pub price: i32,
+    pub purchased_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+    /// the ledger sum of the ship since its purchase
+    pub earned: i64,
+    /// when the ledger sum of the ship first reached its price
+    pub paid_back_at: Option<sqlx::types::chrono::DateTime<chrono::Utc>>,
+    pub payback_seconds: Option<f64>,
+}
+
+impl ShipCountPoint {
+    /// Oldest first, hours without an agent snapshot are left out
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_agent(
+        database_pool: &DbPool,
+        agent_symbol: &str,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<ShipCountPoint>> {
+        let erg = sqlx::query_as!(
+            ShipCountPoint,
+            r#"
+                SELECT
+                  date_trunc('hour', created_at) as "hour!",
+                  (array_agg(ship_count ORDER BY created_at DESC))[1] as "ship_count!"
+                FROM agent
+                WHERE symbol = $1 AND created_at >= $2 AND created_at <= $3
+                GROUP BY 1
+                ORDER BY 1 ASC
+            "#,
+            agent_symbol,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl FleetSize {
+    /// Oldest first, by the assignment history of the ships, fleets without ships in an hour are left out
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_range(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<FleetSize>> {
+        let erg = sqlx::query_as!(
+            FleetSize,
+            r#"
+                SELECT
+                  hours.hour as "hour!",
+                  fleet.id as "fleet_id!",
+                  fleet.system_symbol as "system_symbol!",
+                  fleet.fleet_type as "fleet_type!: FleetType",
+                  fleet.active as "active!",
+                  count(*) as "ship_count!"
+                FROM generate_series(date_trunc('hour', $1::timestamptz), $2::timestamptz, interval '1 hour') AS hours(hour)
+                CROSS JOIN ship_info
+                JOIN LATERAL (
+                  SELECT ship_assignment_history.fleet_id
+                  FROM ship_assignment_history
+                  WHERE ship_assignment_history.ship_symbol = ship_info.symbol
+                    AND ship_assignment_history.valid_from < hours.hour + interval '1 hour'
+                  ORDER BY ship_assignment_history.valid_from DESC
+                  LIMIT 1
+                ) AS assigned ON true
+                JOIN fleet ON fleet.id = assigned.fleet_id
+                GROUP BY hours.hour, fleet.id
+                ORDER BY hours.hour ASC, fleet.id ASC
+            "#,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl TradeRouteResult {
+    /// The most profitable routes within the range
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_best(
+        database_pool: &DbPool,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> crate::Result<Vec<TradeRouteResult>> {
+        let erg = sqlx::query_as!(
+            TradeRouteResult,
+            r#"
+                SELECT
+                  trade_route.symbol as "symbol!: models::TradeSymbol",
+                  trade_route.purchase_waypoint as "purchase_waypoint!",
+                  trade_route.sell_waypoint as "sell_waypoint!",
+                  count(DISTINCT trade_route.id) as "runs!",
+                  COALESCE(sum(ledger_entry.units) FILTER (WHERE ledger_entry.amount > 0), 0)::bigint as "units_sold!",
+                  COALESCE(sum(ledger_entry.amount) FILTER (WHERE ledger_entry.amount > 0), 0)::bigint as "income!",
+                  COALESCE(-sum(ledger_entry.amount) FILTER (WHERE ledger_entry.amount < 0), 0)::bigint as "expenses!",
+                  COALESCE(sum(ledger_entry.amount), 0)::bigint as "profit!"
+                FROM ledger_entry
+                JOIN trade_route ON trade_route.id = ledger_entry.trade_route_id
+                WHERE ledger_entry.timestamp >= $1 AND ledger_entry.timestamp <= $2
+                GROUP BY 1, 2, 3
+                ORDER BY 8 DESC
+                LIMIT $3
+            "#,
+            since,
+            until,
+            limit
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
+
+impl ShipPayback {
+    /// The ships the agent purchased within the range, oldest first
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_agent(
+        database_pool: &DbPool,
+        agent_symbol: &str,
+        since: sqlx::types::chrono::DateTime<chrono::Utc>,
+        until: sqlx::types::chrono::DateTime<chrono::Utc>,
+    ) -> crate::Result<Vec<ShipPayback>> {
+        let erg = sqlx::query_as!(
+            ShipPayback,
+            r#"
+                WITH purchases AS (
+                    SELECT
+                      shipyard_transaction.id,
+                      ship_info.symbol AS ship_symbol,
+                      shipyard_transaction.ship_type,
+                      shipyard_transaction.waypoint_symbol,
+                      shipyard_transaction.price,
+                      shipyard_transaction."timestamp"
+                    FROM shipyard_transaction
+                    LEFT JOIN ship_info ON ship_info.purchase_id = shipyard_transaction.id
+                    WHERE shipyard_transaction.agent_symbol = $1
+                      AND shipyard_transaction."timestamp" >= $2
+                      AND shipyard_transaction."timestamp" <= $3
+                ),
+                earnings AS (
+                    SELECT
+                      purchases.id,
+                      ledger_entry.timestamp,
+                      sum(ledger_entry.amount) OVER (
+                        PARTITION BY purchases.id
+                        ORDER BY ledger_entry.timestamp ASC, ledger_entry.source_id ASC
+                      ) AS earned
+                    FROM purchases
+                    JOIN ledger_entry ON ledger_entry.ship_symbol = purchases.ship_symbol
+                      AND ledger_entry.timestamp >= purchases."timestamp"
+                ),
+                paid_back AS (
+                    SELECT
+                      purchases.id,
+                      min(earnings.timestamp) AS paid_back_at
+                    FROM purchases
+                    JOIN earnings ON earnings.id = purchases.id AND earnings.earned >= purchases.price
+                    GROUP BY purchases.id
+                )
+                SELECT
+                  purchases.id as "transaction_id!",
+                  purchases.ship_symbol as "ship_symbol?",
+                  purchases.ship_type as "ship_type!",
+                  purchases.waypoint_symbol as "waypoint_symbol!",
+                  purchases.price as "price!",
+                  purchases."timestamp" as "purchased_at!",
+                  COALESCE((
+                    SELECT sum(ledger_entry.amount)
+                    FROM ledger_entry
+                    WHERE ledger_entry.ship_symbol = purchases.ship_symbol
+                      AND ledger_entry.timestamp >= purchases."timestamp"
+                  ), 0)::bigint as "earned!",
+                  paid_back.paid_back_at as "paid_back_at?",
+                  extract(epoch FROM paid_back.paid_back_at - purchases."timestamp")::float8 as "payback_seconds?"
+                FROM purchases
+                LEFT JOIN paid_back ON paid_back.id = purchases.id
+                ORDER BY purchases."timestamp" ASC
+            "#,
+            agent_symbol,
+            since,
+            until
+        )
+        .fetch_all(database_pool.get_cache_pool())
+        .await?;
+        Ok(erg)
+    }
+}
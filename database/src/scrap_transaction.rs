@@ -266,7 +266,7 @@ impl DatabaseConnectorAsync for ScrapTransaction {
         .await?;
 
         if let Some(inserted) = inserted {
-            return Ok(inserted.id.into());
+            return Ok(inserted.id);
         }
 
         let existing = sqlx::query!(
@@ -285,7 +285,7 @@ impl DatabaseConnectorAsync for ScrapTransaction {
         .fetch_one(database_pool.get_cache_pool())
         .await?;
 
-        Ok(existing.id.into())
+        Ok(existing.id)
     }
 
     #[instrument(level = "trace", skip(database_pool, item))]
@@ -20,7 +20,7 @@ pub struct ShipAssignment {
     pub extractor: bool,
     pub siphon: bool,
     pub warp_drive: bool,
-    // pub refinery: bool,
+    pub refinery: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -34,7 +34,7 @@ pub struct SimpleShipRequirement {
     pub extractor: bool,
     pub siphon: bool,
     pub warp_drive: bool,
-    // pub refinery: bool,
+    pub refinery: bool,
 }
 
 impl From<ShipAssignment> for SimpleShipRequirement {
@@ -49,6 +49,7 @@ impl From<ShipAssignment> for SimpleShipRequirement {
             extractor: value.extractor,
             siphon: value.siphon,
             warp_drive: value.warp_drive,
+            refinery: value.refinery,
         }
     }
 }
@@ -60,7 +61,7 @@ impl ShipAssignment {
     /// - extractor is the same
     /// - siphon is the same
     /// - warp_drive is the same
-    /// # - refinery is the same
+    /// - refinery is the same
     /// - range may change, except from infinite to finite or vice versa
     /// - cargo_min may change, but not from 0 to non-0 or vice versa
     /// - max_purchase_price may change
@@ -83,9 +84,9 @@ impl ShipAssignment {
         if self.warp_drive != b.warp_drive {
             return false;
         }
-        // if self.refinery != b.refinery {
-        //     return false;
-        // }
+        if self.refinery != b.refinery {
+            return false;
+        }
         if (self.range_min < 0 && b.range_min >= 0) || (self.range_min >= 0 && b.range_min < 0) {
             return false;
         }
@@ -151,7 +152,8 @@ impl ShipAssignment {
                   survey,
                   extractor,
                   siphon,
-                  warp_drive
+                  warp_drive,
+                  refinery
                 FROM ship_assignment
                 WHERE id = $1
             "#,
@@ -187,7 +189,8 @@ impl ShipAssignment {
                           survey,
                           extractor,
                           siphon,
-                          warp_drive
+                          warp_drive,
+                          refinery
                         FROM ship_assignment
                         WHERE fleet_id = $1
                         LIMIT $2 OFFSET $3
@@ -217,7 +220,8 @@ impl ShipAssignment {
                           survey,
                           extractor,
                           siphon,
-                          warp_drive
+                          warp_drive,
+                          refinery
                         FROM ship_assignment
                         WHERE fleet_id = $1
                     "#,
@@ -266,7 +270,8 @@ impl ShipAssignment {
                   survey,
                   extractor,
                   siphon,
-                  warp_drive
+                  warp_drive,
+                  refinery
                 FROM ship_assignment
                 WHERE fleet_id = ANY($1)
             "#,
@@ -302,7 +307,8 @@ impl ShipAssignment {
                           sa.survey,
                           sa.extractor,
                           sa.siphon,
-                          sa.warp_drive
+                          sa.warp_drive,
+                          sa.refinery
                         FROM ship_assignment sa
                         JOIN fleet f ON sa.fleet_id = f.id
                         left JOIN ship_info si ON (sa.id = si.assignment_id OR sa.id = si.temp_assignment_id)
@@ -333,7 +339,8 @@ impl ShipAssignment {
                           sa.survey,
                           sa.extractor,
                           sa.siphon,
-                          sa.warp_drive
+                          sa.warp_drive,
+                          sa.refinery
                         FROM ship_assignment sa
                         JOIN fleet f ON sa.fleet_id = f.id
                         left JOIN ship_info si ON (sa.id = si.assignment_id OR sa.id = si.temp_assignment_id)
@@ -379,10 +386,11 @@ impl ShipAssignment {
                   survey,
                   extractor,
                   siphon,
-                  warp_drive
+                  warp_drive,
+                  refinery
                 )
                 VALUES (
-                  $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11
+                  $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12
                 )
                 RETURNING id
             "#,
@@ -397,6 +405,7 @@ impl ShipAssignment {
             &item.extractor,
             &item.siphon,
             &item.warp_drive,
+            &item.refinery,
         )
         .fetch_one(&database_pool.database_pool)
         .await?;
@@ -463,10 +472,11 @@ impl DatabaseConnectorAsync for ShipAssignment {
                                     survey,
                                     extractor,
                                     siphon,
-                                    warp_drive
+                                    warp_drive,
+                                    refinery
                                 )
                                 VALUES (
-                                    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11
+                                    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12
                                 )
                                 RETURNING id
                         "#,
@@ -481,6 +491,7 @@ impl DatabaseConnectorAsync for ShipAssignment {
             &item.extractor,
             &item.siphon,
             &item.warp_drive,
+            &item.refinery,
         )
         .fetch_one(&database_pool.database_pool)
         .await?;
@@ -503,10 +514,11 @@ impl DatabaseConnectorAsync for ShipAssignment {
                   survey,
                   extractor,
                   siphon,
-                  warp_drive
+                  warp_drive,
+                  refinery
                 )
                 VALUES (
-                  $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12
+                  $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
                 )
                 ON CONFLICT (id) DO UPDATE SET
                   fleet_id = EXCLUDED.fleet_id,
@@ -519,7 +531,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                   survey = EXCLUDED.survey,
                   extractor = EXCLUDED.extractor,
                   siphon = EXCLUDED.siphon,
-                  warp_drive = EXCLUDED.warp_drive;
+                  warp_drive = EXCLUDED.warp_drive,
+                  refinery = EXCLUDED.refinery;
             "#,
             &item.id,
             &item.fleet_id,
@@ -533,6 +546,7 @@ impl DatabaseConnectorAsync for ShipAssignment {
             &item.extractor,
             &item.siphon,
             &item.warp_drive,
+            &item.refinery,
         )
         .execute(&database_pool.database_pool)
         .await?;
@@ -546,6 +560,7 @@ impl DatabaseConnectorAsync for ShipAssignment {
 
     #[instrument(level = "trace", skip(database_pool, items))]
     async fn insert_bulk(database_pool: &DbPool, items: &[ShipAssignment]) -> crate::Result<()> {
+        #[allow(clippy::type_complexity)]
         let (
             ids,
             fleet_ids,
@@ -588,6 +603,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                 sa.warp_drive,
             )
         }));
+        // multiunzip only supports tuples up to 12 elements
+        let refinery_values: Vec<_> = items.iter().map(|sa| sa.refinery).collect();
 
         sqlx::query!(
             r#"
@@ -603,7 +620,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                 survey,
                 extractor,
                 siphon,
-                warp_drive
+                warp_drive,
+                refinery
             )
             SELECT
                 id,
@@ -617,7 +635,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                 sur,
                 ext,
                 sip,
-                wd
+                wd,
+                rf
             FROM UNNEST(
                 $1::bigint[],
                 $2::integer[],
@@ -630,8 +649,9 @@ impl DatabaseConnectorAsync for ShipAssignment {
                 $9::boolean[],
                 $10::boolean[],
                 $11::boolean[],
-                $12::boolean[]
-            ) AS t(id, fid, pr, mxpp, ct, dis, rm, cm, sur, ext, sip, wd)
+                $12::boolean[],
+                $13::boolean[]
+            ) AS t(id, fid, pr, mxpp, ct, dis, rm, cm, sur, ext, sip, wd, rf)
             ON CONFLICT (id) DO UPDATE
             SET fleet_id = EXCLUDED.fleet_id,
                 disabled = EXCLUDED.disabled,
@@ -643,7 +663,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                 survey = EXCLUDED.survey,
                 extractor = EXCLUDED.extractor,
                 siphon = EXCLUDED.siphon,
-                warp_drive = EXCLUDED.warp_drive;
+                warp_drive = EXCLUDED.warp_drive,
+                refinery = EXCLUDED.refinery;
             "#,
             &ids,
             &fleet_ids,
@@ -657,6 +678,7 @@ impl DatabaseConnectorAsync for ShipAssignment {
             &extractor_values,
             &siphon_values,
             &warp_drive_values,
+            &refinery_values,
         )
         .execute(&database_pool.database_pool)
         .await?;
@@ -686,7 +708,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                           survey,
                           extractor,
                           siphon,
-                          warp_drive
+                          warp_drive,
+                          refinery
                         FROM ship_assignment
                         LIMIT $1 OFFSET $2
                     "#,
@@ -713,7 +736,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                           survey,
                           extractor,
                           siphon,
-                          warp_drive
+                          warp_drive,
+                          refinery
                         FROM ship_assignment
                     "#
                 )
@@ -753,7 +777,8 @@ impl DatabaseConnectorAsync for ShipAssignment {
                   survey,
                   extractor,
                   siphon,
-                  warp_drive
+                  warp_drive,
+                  refinery
                 FROM ship_assignment
                 WHERE id = $1
             "#,
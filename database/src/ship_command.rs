@@ -0,0 +1,452 @@
+use space_traders_client::models;
+use tracing::instrument;
+
+use super::{DatabaseConnectorAsync, DbPool, PaginatedQuery, PaginatedResult, run_paginated_query};
+
+/// One manual command for a ship of a Manuel fleet, the commands of a ship are executed in the order of their id.
+/// Only the fields the command needs are set.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, async_graphql::SimpleObject)]
+#[graphql(name = "DBShipCommand")]
+pub struct ShipCommand {
+    pub id: i64,
+    pub ship_symbol: String,
+    pub command: ShipCommandType,
+    /// destination of `Navigate` and `Jump`
+    pub waypoint_symbol: Option<String>,
+    /// good of `Purchase`, `Sell`, `Transfer` and `Jettison`
+    pub trade_symbol: Option<models::TradeSymbol>,
+    /// units of `Purchase`, `Sell`, `Transfer` and `Jettison`, fuel units of `Refuel`
+    pub units: Option<i32>,
+    /// receiving ship of `Transfer`
+    pub target_ship: Option<String>,
+    /// `Refuel` from the cargo instead of the market
+    pub from_cargo: Option<bool>,
+    pub status: ShipCommandStatus,
+    pub error: Option<String>,
+    pub created_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+    pub updated_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    sqlx::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    async_graphql::Enum,
+)]
+#[sqlx(type_name = "ship_command_type")]
+pub enum ShipCommandType {
+    #[sqlx(rename = "NAVIGATE")]
+    Navigate,
+    #[sqlx(rename = "DOCK")]
+    Dock,
+    #[sqlx(rename = "ORBIT")]
+    Orbit,
+    #[sqlx(rename = "PURCHASE")]
+    Purchase,
+    #[sqlx(rename = "SELL")]
+    Sell,
+    #[sqlx(rename = "TRANSFER")]
+    Transfer,
+    #[sqlx(rename = "JETTISON")]
+    Jettison,
+    #[sqlx(rename = "REFUEL")]
+    Refuel,
+    #[sqlx(rename = "SURVEY")]
+    Survey,
+    #[sqlx(rename = "EXTRACT")]
+    Extract,
+    #[sqlx(rename = "CHART")]
+    Chart,
+    #[sqlx(rename = "JUMP")]
+    Jump,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    sqlx::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    Default,
+    async_graphql::Enum,
+)]
+#[sqlx(type_name = "ship_command_status")]
+pub enum ShipCommandStatus {
+    #[default]
+    #[sqlx(rename = "PENDING")]
+    Pending,
+    #[sqlx(rename = "RUNNING")]
+    Running,
+    #[sqlx(rename = "DONE")]
+    Done,
+    #[sqlx(rename = "FAILED")]
+    Failed,
+    #[sqlx(rename = "CANCELLED")]
+    Cancelled,
+}
+
+impl ShipCommand {
+    pub fn new(ship_symbol: String, command: ShipCommandType) -> Self {
+        ShipCommand {
+            id: 0,
+            ship_symbol,
+            command,
+            waypoint_symbol: None,
+            trade_symbol: None,
+            units: None,
+            target_ship: None,
+            from_cargo: None,
+            status: ShipCommandStatus::Pending,
+            error: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// The first command of the ship which is pending or was left running
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_next_by_ship(
+        database_pool: &DbPool,
+        ship_symbol: &str,
+    ) -> crate::Result<Option<ShipCommand>> {
+        let erg = sqlx::query_as!(
+            ShipCommand,
+            r#"
+                SELECT
+                  id,
+                  ship_symbol,
+                  command as "command: ShipCommandType",
+                  waypoint_symbol,
+                  trade_symbol as "trade_symbol: models::TradeSymbol",
+                  units,
+                  target_ship,
+                  from_cargo,
+                  status as "status: ShipCommandStatus",
+                  error,
+                  created_at,
+                  updated_at
+                FROM ship_command
+                WHERE ship_symbol = $1 AND status IN ('PENDING', 'RUNNING')
+                ORDER BY id ASC
+                LIMIT 1
+            "#,
+            ship_symbol
+        )
+        .fetch_optional(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+
+    /// Cancels all pending commands of the ship, returns how many were cancelled
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn cancel_pending_by_ship(
+        database_pool: &DbPool,
+        ship_symbol: &str,
+    ) -> crate::Result<u64> {
+        let erg = sqlx::query!(
+            r#"
+                UPDATE ship_command
+                SET status = 'CANCELLED', updated_at = NOW()
+                WHERE ship_symbol = $1 AND status = 'PENDING'
+            "#,
+            ship_symbol
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(erg.rows_affected())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_ship(
+        database_pool: &DbPool,
+        ship_symbol: &str,
+        query: PaginatedQuery,
+    ) -> crate::Result<PaginatedResult<ShipCommand>> {
+        run_paginated_query(
+            query,
+            |page_size, offset| async move {
+                let items = sqlx::query_as!(
+                    ShipCommand,
+                    r#"
+                        SELECT
+                          id,
+                          ship_symbol,
+                          command as "command: ShipCommandType",
+                          waypoint_symbol,
+                          trade_symbol as "trade_symbol: models::TradeSymbol",
+                          units,
+                          target_ship,
+                          from_cargo,
+                          status as "status: ShipCommandStatus",
+                          error,
+                          created_at,
+                          updated_at
+                        FROM ship_command
+                        WHERE ship_symbol = $1
+                        ORDER BY id DESC
+                        LIMIT $2 OFFSET $3
+                    "#,
+                    ship_symbol,
+                    page_size,
+                    offset
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let items = sqlx::query_as!(
+                    ShipCommand,
+                    r#"
+                        SELECT
+                          id,
+                          ship_symbol,
+                          command as "command: ShipCommandType",
+                          waypoint_symbol,
+                          trade_symbol as "trade_symbol: models::TradeSymbol",
+                          units,
+                          target_ship,
+                          from_cargo,
+                          status as "status: ShipCommandStatus",
+                          error,
+                          created_at,
+                          updated_at
+                        FROM ship_command
+                        WHERE ship_symbol = $1
+                        ORDER BY id DESC
+                    "#,
+                    ship_symbol
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let count = sqlx::query!(
+                    r#"
+                        SELECT COUNT(*) as "count!"
+                        FROM ship_command
+                        WHERE ship_symbol = $1
+                    "#,
+                    ship_symbol
+                )
+                .fetch_one(database_pool.get_cache_pool())
+                .await?;
+                Ok(count.count)
+            },
+        )
+        .await
+    }
+}
+
+impl DatabaseConnectorAsync for ShipCommand {
+    type ID = i64;
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn insert_new(database_pool: &DbPool, item: &ShipCommand) -> crate::Result<Self::ID> {
+        let erg = sqlx::query!(
+            r#"
+            insert into ship_command (
+            ship_symbol,
+            command,
+            waypoint_symbol,
+            trade_symbol,
+            units,
+            target_ship,
+            from_cargo,
+            status,
+            error
+            ) values (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8,
+            $9
+            )
+            RETURNING id
+            "#,
+            item.ship_symbol,
+            item.command as ShipCommandType,
+            item.waypoint_symbol,
+            item.trade_symbol as Option<models::TradeSymbol>,
+            item.units,
+            item.target_ship,
+            item.from_cargo,
+            item.status as ShipCommandStatus,
+            item.error
+        )
+        .fetch_one(&database_pool.database_pool)
+        .await?;
+
+        Ok(erg.id)
+    }
+
+    /// Only the status and error of an existing command change
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn upsert(database_pool: &DbPool, item: &ShipCommand) -> crate::Result<()> {
+        if item.id == 0 {
+            let _ = Self::insert_new(database_pool, item).await?;
+            return Ok(());
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE ship_command
+            SET status = $2, error = $3, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            item.id,
+            item.status as ShipCommandStatus,
+            item.error
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn update(database_pool: &DbPool, item: &ShipCommand) -> crate::Result<()> {
+        Self::upsert(database_pool, item).await
+    }
+
+    #[instrument(level = "trace", skip(database_pool, items))]
+    async fn insert_bulk(database_pool: &DbPool, items: &[ShipCommand]) -> crate::Result<()> {
+        for item in items {
+            Self::upsert(database_pool, item).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn get_all(
+        database_pool: &DbPool,
+        query: PaginatedQuery,
+    ) -> crate::Result<PaginatedResult<ShipCommand>> {
+        run_paginated_query(
+            query,
+            |page_size, offset| async move {
+                let items = sqlx::query_as!(
+                    ShipCommand,
+                    r#"
+                        SELECT
+                          id,
+                          ship_symbol,
+                          command as "command: ShipCommandType",
+                          waypoint_symbol,
+                          trade_symbol as "trade_symbol: models::TradeSymbol",
+                          units,
+                          target_ship,
+                          from_cargo,
+                          status as "status: ShipCommandStatus",
+                          error,
+                          created_at,
+                          updated_at
+                        FROM ship_command
+                        ORDER BY id DESC
+                        LIMIT $1 OFFSET $2
+                    "#,
+                    page_size,
+                    offset
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let items = sqlx::query_as!(
+                    ShipCommand,
+                    r#"
+                        SELECT
+                          id,
+                          ship_symbol,
+                          command as "command: ShipCommandType",
+                          waypoint_symbol,
+                          trade_symbol as "trade_symbol: models::TradeSymbol",
+                          units,
+                          target_ship,
+                          from_cargo,
+                          status as "status: ShipCommandStatus",
+                          error,
+                          created_at,
+                          updated_at
+                        FROM ship_command
+                        ORDER BY id DESC
+                    "#
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let count = sqlx::query!(
+                    r#"
+                        SELECT COUNT(*) as "count!"
+                        FROM ship_command
+                    "#
+                )
+                .fetch_one(database_pool.get_cache_pool())
+                .await?;
+                Ok(count.count)
+            },
+        )
+        .await
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn get_by_id(database_pool: &DbPool, id: &Self::ID) -> crate::Result<Option<Self>> {
+        let erg = sqlx::query_as!(
+            ShipCommand,
+            r#"
+                SELECT
+                  id,
+                  ship_symbol,
+                  command as "command: ShipCommandType",
+                  waypoint_symbol,
+                  trade_symbol as "trade_symbol: models::TradeSymbol",
+                  units,
+                  target_ship,
+                  from_cargo,
+                  status as "status: ShipCommandStatus",
+                  error,
+                  created_at,
+                  updated_at
+                FROM ship_command WHERE id = $1
+            "#,
+            *id
+        )
+        .fetch_optional(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn delete_by_id(database_pool: &DbPool, id: &Self::ID) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+                DELETE FROM ship_command
+                WHERE id = $1
+            "#,
+            *id
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(())
+    }
+
+    fn set_id(&mut self, id: Self::ID) {
+        self.id = id;
+    }
+}
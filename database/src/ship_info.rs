@@ -124,6 +124,34 @@ impl ShipInfo {
         Ok(())
     }
 
+    /// The ships whose assignment belongs to a fleet of the type, a temporary assignment takes precedence like in the pilot
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_by_fleet_type(
+        database_pool: &super::DbPool,
+        fleet_type: crate::FleetType,
+    ) -> crate::Result<Vec<ShipInfo>> {
+        let erg = sqlx::query_as!(
+            ShipInfo,
+            r#"
+                SELECT
+                    ship_info.symbol,
+                    ship_info.display_name,
+                    ship_info.active,
+                    ship_info.assignment_id,
+                    ship_info.temp_assignment_id,
+                    ship_info.purchase_id
+                FROM ship_info
+                JOIN ship_assignment ON ship_assignment.id = COALESCE(ship_info.temp_assignment_id, ship_info.assignment_id)
+                JOIN fleet ON fleet.id = ship_assignment.fleet_id
+                WHERE fleet.fleet_type = $1
+            "#,
+            fleet_type as crate::FleetType
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+
     #[instrument(level = "trace", skip(database_pool), err(Debug))]
     pub async fn unassign_temp_ship(
         database_pool: &super::DbPool,
@@ -202,7 +230,7 @@ impl DatabaseConnectorAsync for ShipInfo {
             Vec<_>,
             Vec<_>,
             Vec<_>,
-        ) = itertools::Itertools::multiunzip(items.iter().cloned().map(|s| {
+        ) = itertools::Itertools::multiunzip(items.iter().map(|s| {
             (
                 s.symbol.clone(),
                 s.display_name.clone(),
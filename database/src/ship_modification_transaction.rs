@@ -282,7 +282,7 @@ impl DatabaseConnectorAsync for ShipModificationTransaction {
         .await?;
 
         if let Some(inserted) = inserted {
-            return Ok(inserted.id.into());
+            return Ok(inserted.id);
         }
 
         let existing = sqlx::query!(
@@ -303,7 +303,7 @@ impl DatabaseConnectorAsync for ShipModificationTransaction {
         .fetch_one(database_pool.get_cache_pool())
         .await?;
 
-        Ok(existing.id.into())
+        Ok(existing.id)
     }
 
     #[instrument(level = "trace", skip(database_pool, item))]
@@ -0,0 +1,324 @@
+use space_traders_client::models;
+use tracing::instrument;
+
+use super::{
+    DatabaseConnectorAsync, DbPool, PaginatedQuery, PaginatedResult, ShipmentStatus, TradeRoute,
+    run_paginated_query,
+};
+
+/// A chain of trade legs flown by one ship, every good of a leg is a `TradeRoute` attached to the plan.
+/// The sell waypoint of a leg is the purchase waypoint of the next one.
+#[derive(Debug, Clone, PartialEq, Eq, async_graphql::SimpleObject)]
+#[graphql(name = "DBTradePlan")]
+pub struct TradePlan {
+    pub id: i32,
+    pub ship_symbol: String,
+    pub status: ShipmentStatus,
+    pub predicted_profit: i32,
+    pub created_at: sqlx::types::chrono::DateTime<chrono::Utc>,
+}
+
+impl TradePlan {
+    pub fn complete(self) -> Self {
+        TradePlan {
+            status: ShipmentStatus::Delivered,
+            ..self
+        }
+    }
+
+    pub fn fail(self) -> Self {
+        TradePlan {
+            status: ShipmentStatus::Failed,
+            ..self
+        }
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_unfinished_by_ship(
+        database_pool: &DbPool,
+        ship_symbol: &str,
+    ) -> crate::Result<Vec<TradePlan>> {
+        let erg = sqlx::query_as!(
+            TradePlan,
+            r#"
+                SELECT
+                  id,
+                  ship_symbol,
+                  status as "status: ShipmentStatus",
+                  predicted_profit,
+                  created_at
+                 FROM trade_plan
+                 WHERE ship_symbol = $1 AND status = 'IN_TRANSIT'
+                 ORDER BY created_at ASC, id ASC
+            "#,
+            ship_symbol
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+
+    /// Attaches a trade route as one good of the leg `leg_index` to the plan
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn add_route(
+        database_pool: &DbPool,
+        id: i32,
+        leg_index: i32,
+        trade_route_id: i32,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE trade_route
+                SET trade_plan_id = $1, leg_index = $2
+                WHERE id = $3
+            "#,
+            id,
+            leg_index,
+            trade_route_id
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The trade routes of the plan together with the index of their leg, ordered by leg
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    pub async fn get_routes(
+        database_pool: &DbPool,
+        id: i32,
+    ) -> crate::Result<Vec<(i32, TradeRoute)>> {
+        let erg = sqlx::query!(
+            r#"
+                SELECT
+                  id,
+                  symbol as "symbol: models::TradeSymbol",
+                  ship_symbol,
+                  purchase_waypoint,
+                  sell_waypoint,
+                  status as "status: ShipmentStatus",
+                  trade_volume,
+                  predicted_purchase_price,
+                  predicted_sell_price,
+                  created_at,
+                  reserved_fund,
+                  leg_index as "leg_index!"
+                 FROM trade_route
+                 WHERE trade_plan_id = $1 AND leg_index IS NOT NULL
+                 ORDER BY leg_index ASC, id ASC
+            "#,
+            id
+        )
+        .fetch_all(&database_pool.database_pool)
+        .await?;
+
+        Ok(erg
+            .into_iter()
+            .map(|r| {
+                (
+                    r.leg_index,
+                    TradeRoute {
+                        id: r.id,
+                        symbol: r.symbol,
+                        ship_symbol: r.ship_symbol,
+                        purchase_waypoint: r.purchase_waypoint,
+                        sell_waypoint: r.sell_waypoint,
+                        status: r.status,
+                        trade_volume: r.trade_volume,
+                        predicted_purchase_price: r.predicted_purchase_price,
+                        predicted_sell_price: r.predicted_sell_price,
+                        created_at: r.created_at,
+                        reserved_fund: r.reserved_fund,
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+impl Default for TradePlan {
+    fn default() -> TradePlan {
+        TradePlan {
+            id: 0,
+            ship_symbol: String::new(),
+            status: ShipmentStatus::InTransit,
+            predicted_profit: 0,
+            created_at: sqlx::types::chrono::DateTime::<chrono::Utc>::MIN_UTC,
+        }
+    }
+}
+
+impl DatabaseConnectorAsync for TradePlan {
+    type ID = i32;
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn insert_new(database_pool: &DbPool, item: &TradePlan) -> crate::Result<Self::ID> {
+        let erg = sqlx::query!(
+            r#"
+            insert into trade_plan (
+            ship_symbol,
+            status,
+            predicted_profit
+            ) values (
+            $1,
+            $2,
+            $3
+            )
+            RETURNING id
+            "#,
+            item.ship_symbol,
+            item.status as crate::ShipmentStatus,
+            item.predicted_profit
+        )
+        .fetch_one(&database_pool.database_pool)
+        .await?;
+
+        Ok(erg.id)
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn upsert(database_pool: &DbPool, item: &TradePlan) -> crate::Result<()> {
+        if item.id == 0 {
+            let _ = Self::insert_new(database_pool, item).await?;
+            return Ok(());
+        }
+
+        sqlx::query!(
+            r#"
+            insert into trade_plan (
+            id,
+            ship_symbol,
+            status,
+            predicted_profit
+            ) values (
+            $1,
+            $2,
+            $3,
+            $4
+            )
+            on conflict (id) do update
+            set status = EXCLUDED.status,
+            predicted_profit = EXCLUDED.predicted_profit
+            "#,
+            item.id,
+            item.ship_symbol,
+            item.status as crate::ShipmentStatus,
+            item.predicted_profit
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn update(database_pool: &DbPool, item: &TradePlan) -> crate::Result<()> {
+        Self::upsert(database_pool, item).await
+    }
+
+    #[instrument(level = "trace", skip(database_pool, items))]
+    async fn insert_bulk(database_pool: &DbPool, items: &[TradePlan]) -> crate::Result<()> {
+        for item in items {
+            Self::upsert(database_pool, item).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn get_all(
+        database_pool: &DbPool,
+        query: PaginatedQuery,
+    ) -> crate::Result<PaginatedResult<TradePlan>> {
+        run_paginated_query(
+            query,
+            |page_size, offset| async move {
+                let items = sqlx::query_as!(
+                    TradePlan,
+                    r#"
+                        SELECT
+                          id,
+                          ship_symbol,
+                          status as "status: ShipmentStatus",
+                          predicted_profit,
+                          created_at
+                        FROM trade_plan
+                        ORDER BY created_at DESC, id DESC
+                        LIMIT $1 OFFSET $2
+                    "#,
+                    page_size,
+                    offset
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let items = sqlx::query_as!(
+                    TradePlan,
+                    r#"
+                        SELECT
+                          id,
+                          ship_symbol,
+                          status as "status: ShipmentStatus",
+                          predicted_profit,
+                          created_at
+                        FROM trade_plan
+                        ORDER BY created_at DESC, id DESC
+                    "#
+                )
+                .fetch_all(database_pool.get_cache_pool())
+                .await?;
+                Ok(items)
+            },
+            || async move {
+                let count = sqlx::query!(
+                    r#"
+                        SELECT COUNT(*) as "count!"
+                        FROM trade_plan
+                    "#
+                )
+                .fetch_one(database_pool.get_cache_pool())
+                .await?;
+                Ok(count.count)
+            },
+        )
+        .await
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn get_by_id(database_pool: &DbPool, id: &Self::ID) -> crate::Result<Option<Self>> {
+        let erg = sqlx::query_as!(
+            TradePlan,
+            r#"
+                SELECT
+                  id,
+                  ship_symbol,
+                  status as "status: ShipmentStatus",
+                  predicted_profit,
+                  created_at
+                 FROM trade_plan WHERE id = $1
+            "#,
+            *id
+        )
+        .fetch_optional(&database_pool.database_pool)
+        .await?;
+        Ok(erg)
+    }
+
+    #[instrument(level = "trace", skip(database_pool), err(Debug))]
+    async fn delete_by_id(database_pool: &DbPool, id: &Self::ID) -> crate::Result<()> {
+        sqlx::query!(
+            r#"
+                DELETE FROM trade_plan
+                WHERE id = $1
+            "#,
+            *id
+        )
+        .execute(&database_pool.database_pool)
+        .await?;
+        Ok(())
+    }
+
+    fn set_id(&mut self, id: Self::ID) {
+        self.id = id;
+    }
+}
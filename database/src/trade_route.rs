@@ -24,6 +24,7 @@ pub struct TradeRoute {
     pub reserved_fund: Option<i64>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TradeRouteSummary {
     pub id: i32,
@@ -49,6 +50,13 @@ impl TradeRoute {
             ..self
         }
     }
+
+    pub fn fail(self) -> Self {
+        TradeRoute {
+            status: ShipmentStatus::Failed,
+            ..self
+        }
+    }
 }
 
 impl Default for TradeRoute {
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE fleet
+ALTER COLUMN chart_only_jump_gates DROP NOT NULL;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+UPDATE fleet
+SET chart_only_jump_gates = FALSE
+WHERE chart_only_jump_gates IS NULL;
+
+ALTER TABLE fleet
+ALTER COLUMN chart_only_jump_gates SET NOT NULL;
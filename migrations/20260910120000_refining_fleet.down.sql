@@ -0,0 +1,9 @@
+-- Add down migration script here
+ALTER TABLE ship_assignment
+DROP COLUMN refinery;
+
+ALTER TABLE fleet
+DROP COLUMN refinery_ship_count,
+DROP COLUMN refine_profit_threshold;
+
+ALTER TYPE fleet_type DROP VALUE 'Refining';
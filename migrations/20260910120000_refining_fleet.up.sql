@@ -0,0 +1,10 @@
+-- Add up migration script here
+ALTER TYPE fleet_type
+ADD VALUE 'Refining';
+
+ALTER TABLE fleet
+ADD COLUMN refinery_ship_count INTEGER,
+ADD COLUMN refine_profit_threshold INTEGER;
+
+ALTER TABLE ship_assignment
+ADD COLUMN refinery boolean NOT NULL DEFAULT false;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+ALTER TABLE trade_route
+DROP CONSTRAINT trade_route_trade_plan,
+DROP COLUMN trade_plan_id,
+DROP COLUMN leg_index;
+
+DROP TABLE IF EXISTS public.trade_plan;
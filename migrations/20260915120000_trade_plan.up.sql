@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS public.trade_plan (
+  id SERIAL PRIMARY KEY,
+  ship_symbol character varying NOT NULL,
+  "status" shipment_status NOT NULL,
+  predicted_profit integer NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT now ()
+);
+
+ALTER TABLE trade_route
+ADD COLUMN trade_plan_id integer,
+ADD COLUMN leg_index integer,
+ADD CONSTRAINT trade_route_trade_plan FOREIGN KEY (trade_plan_id) REFERENCES public.trade_plan (id) MATCH SIMPLE ON UPDATE NO ACTION ON DELETE NO ACTION;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS public.ship_command;
+DROP TYPE IF EXISTS ship_command_status;
+DROP TYPE IF EXISTS ship_command_type;
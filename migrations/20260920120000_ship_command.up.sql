@@ -0,0 +1,38 @@
+-- Add up migration script here
+CREATE TYPE ship_command_type AS ENUM (
+  'NAVIGATE',
+  'DOCK',
+  'ORBIT',
+  'PURCHASE',
+  'SELL',
+  'TRANSFER',
+  'JETTISON',
+  'REFUEL',
+  'SURVEY',
+  'EXTRACT',
+  'CHART',
+  'JUMP'
+);
+CREATE TYPE ship_command_status AS ENUM (
+  'PENDING',
+  'RUNNING',
+  'DONE',
+  'FAILED',
+  'CANCELLED'
+);
+CREATE TABLE public.ship_command (
+  id bigserial NOT NULL,
+  ship_symbol character varying NOT NULL,
+  command ship_command_type NOT NULL,
+  waypoint_symbol character varying,
+  trade_symbol trade_symbol,
+  units integer,
+  target_ship character varying,
+  from_cargo boolean,
+  status ship_command_status NOT NULL DEFAULT 'PENDING',
+  error character varying,
+  created_at timestamp with time zone NOT NULL DEFAULT now (),
+  updated_at timestamp with time zone NOT NULL DEFAULT now (),
+  PRIMARY KEY (id)
+);
+CREATE INDEX ship_command_ship_symbol_status ON public.ship_command (ship_symbol, status);
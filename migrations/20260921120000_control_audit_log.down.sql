@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS public.control_audit_log;
+DROP TYPE IF EXISTS control_role;
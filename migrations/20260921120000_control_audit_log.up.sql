@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TYPE control_role AS ENUM ('READ_ONLY', 'OPERATOR');
+CREATE TABLE public.control_audit_log (
+  id bigserial NOT NULL,
+  token_name character varying NOT NULL,
+  role control_role NOT NULL,
+  operation character varying NOT NULL,
+  arguments jsonb NOT NULL,
+  success boolean NOT NULL,
+  error character varying,
+  created_at timestamp with time zone NOT NULL DEFAULT now (),
+  PRIMARY KEY (id)
+);
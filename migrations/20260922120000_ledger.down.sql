@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP VIEW IF EXISTS public.ledger_entry;
+DROP TYPE IF EXISTS ledger_activity;
+DROP TYPE IF EXISTS ledger_source;
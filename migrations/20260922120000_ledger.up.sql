@@ -0,0 +1,186 @@
+-- Add up migration script here
+CREATE TYPE ledger_source AS ENUM (
+  'MARKET',
+  'SHIPYARD',
+  'CHART',
+  'REPAIR',
+  'SCRAP',
+  'SHIP_MODIFICATION',
+  'CONTRACT'
+);
+CREATE TYPE ledger_activity AS ENUM (
+  'TRADE_ROUTE',
+  'CONTRACT',
+  'CONSTRUCTION',
+  'MINING',
+  'MARKET',
+  'SHIP_PURCHASE',
+  'CHARTING',
+  'REPAIR',
+  'SCRAP',
+  'SHIP_MODIFICATION'
+);
+-- Every credit movement as one signed amount, income is positive and expenses are negative.
+-- Contract payments have no transaction, they are dated by the contract timestamps.
+CREATE VIEW public.ledger_entry AS
+SELECT
+  entries.source,
+  entries.source_id,
+  entries."timestamp",
+  entries.amount,
+  entries.activity,
+  entries.ship_symbol,
+  ship_assignment.fleet_id,
+  entries.waypoint_symbol,
+  entries.trade_symbol,
+  entries.units,
+  entries.contract_id,
+  entries.trade_route_id,
+  entries.construction_shipment_id,
+  entries.mining_waypoint_symbol
+FROM (
+    SELECT
+      'MARKET'::ledger_source AS source,
+      id::character varying AS source_id,
+      "timestamp",
+      (
+        CASE
+          WHEN type = 'PURCHASE' THEN - total_price
+          ELSE total_price
+        END
+      )::bigint AS amount,
+      (
+        CASE
+          WHEN contract IS NOT NULL THEN 'CONTRACT'
+          WHEN trade_route IS NOT NULL THEN 'TRADE_ROUTE'
+          WHEN construction IS NOT NULL THEN 'CONSTRUCTION'
+          WHEN mining IS NOT NULL THEN 'MINING'
+          ELSE 'MARKET'
+        END
+      )::ledger_activity AS activity,
+      ship_symbol,
+      waypoint_symbol,
+      trade_symbol::character varying AS trade_symbol,
+      units,
+      contract AS contract_id,
+      trade_route AS trade_route_id,
+      construction AS construction_shipment_id,
+      mining AS mining_waypoint_symbol
+    FROM public.market_transaction
+    UNION ALL
+    SELECT
+      'SHIPYARD',
+      id::character varying,
+      "timestamp",
+      - price::bigint,
+      'SHIP_PURCHASE',
+      NULL,
+      waypoint_symbol,
+      ship_type::character varying,
+      1,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.shipyard_transaction
+    UNION ALL
+    SELECT
+      'CHART',
+      id::character varying,
+      "timestamp",
+      total_price::bigint,
+      'CHARTING',
+      ship_symbol,
+      waypoint_symbol,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.chart_transaction
+    UNION ALL
+    SELECT
+      'REPAIR',
+      id::character varying,
+      "timestamp",
+      - total_price::bigint,
+      'REPAIR',
+      ship_symbol,
+      waypoint_symbol,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.repair_transaction
+    UNION ALL
+    SELECT
+      'SCRAP',
+      id::character varying,
+      "timestamp",
+      total_price::bigint,
+      'SCRAP',
+      ship_symbol,
+      waypoint_symbol,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.scrap_transaction
+    UNION ALL
+    SELECT
+      'SHIP_MODIFICATION',
+      id::character varying,
+      "timestamp",
+      - total_price::bigint,
+      'SHIP_MODIFICATION',
+      ship_symbol,
+      waypoint_symbol,
+      trade_symbol::character varying,
+      1,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.ship_modification_transaction
+    UNION ALL
+    SELECT
+      'CONTRACT',
+      id || ':accepted',
+      created_at,
+      on_accepted::bigint,
+      'CONTRACT',
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      id,
+      NULL,
+      NULL,
+      NULL
+    FROM public.contract
+    WHERE accepted
+    UNION ALL
+    SELECT
+      'CONTRACT',
+      id || ':fulfilled',
+      updated_at,
+      on_fulfilled::bigint,
+      'CONTRACT',
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      id,
+      NULL,
+      NULL,
+      NULL
+    FROM public.contract
+    WHERE fulfilled
+  ) AS entries
+  LEFT JOIN public.ship_info ON ship_info.symbol = entries.ship_symbol
+  LEFT JOIN public.ship_assignment ON ship_assignment.id = ship_info.assignment_id;
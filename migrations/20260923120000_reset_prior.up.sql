@@ -0,0 +1,11 @@
+-- Add up migration script here
+-- Knowledge carried over from earlier resets, it is only a prior until the current reset has data of its own.
+CREATE TABLE public.reset_prior (
+  id bigserial NOT NULL,
+  source_reset character varying NOT NULL,
+  reset_date timestamp with time zone NOT NULL,
+  priors jsonb NOT NULL,
+  imported_at timestamp with time zone NOT NULL DEFAULT now (),
+  PRIMARY KEY (id),
+  CONSTRAINT unique_reset_prior_source UNIQUE (source_reset)
+);
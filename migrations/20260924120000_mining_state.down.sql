@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS public.mining_transfer;
+DROP TYPE IF EXISTS mining_transfer_status;
+DROP TABLE IF EXISTS public.mining_assignment;
+DROP TYPE IF EXISTS mining_assign_level;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+-- The in-memory state of the mining manager, restored and reconciled against the ships after a restart.
+CREATE TYPE mining_assign_level AS ENUM ('INACTIVE', 'ON_THE_WAY', 'ACTIVE');
+CREATE TABLE public.mining_assignment (
+  ship_symbol character varying NOT NULL,
+  waypoint_symbol character varying NOT NULL,
+  level mining_assign_level NOT NULL,
+  updated_at timestamp with time zone NOT NULL DEFAULT now (),
+  PRIMARY KEY (ship_symbol)
+);
+CREATE TYPE mining_transfer_status AS ENUM ('PENDING', 'DONE', 'ROLLED_BACK', 'FAILED');
+CREATE TABLE public.mining_transfer (
+  id bigserial NOT NULL,
+  waypoint_symbol character varying NOT NULL,
+  from_ship character varying NOT NULL,
+  to_ship character varying NOT NULL,
+  trade_symbol trade_symbol NOT NULL,
+  units integer NOT NULL,
+  from_units_before integer NOT NULL,
+  to_units_before integer NOT NULL,
+  status mining_transfer_status NOT NULL DEFAULT 'PENDING',
+  created_at timestamp with time zone NOT NULL DEFAULT now (),
+  updated_at timestamp with time zone NOT NULL DEFAULT now (),
+  PRIMARY KEY (id)
+);
+CREATE INDEX mining_transfer_status_idx ON public.mining_transfer (status);
@@ -0,0 +1,217 @@
+-- Add up migration script here
+-- Every assignment a ship had, so ledger entries can be attributed to the fleet at the time of the entry.
+CREATE TABLE public.ship_assignment_history (
+  id bigserial NOT NULL,
+  ship_symbol character varying NOT NULL,
+  assignment_id bigint,
+  fleet_id integer,
+  valid_from timestamp with time zone NOT NULL DEFAULT now (),
+  PRIMARY KEY (id),
+  CONSTRAINT ship_symbol_fk FOREIGN KEY (ship_symbol) REFERENCES public.ship_info (symbol)
+);
+CREATE INDEX ship_assignment_history_ship_idx ON public.ship_assignment_history (ship_symbol, valid_from);
+-- the ships already known keep their current assignment for their whole past
+INSERT INTO public.ship_assignment_history (ship_symbol, assignment_id, fleet_id, valid_from)
+SELECT
+  ship_info.symbol,
+  ship_info.assignment_id,
+  ship_assignment.fleet_id,
+  '-infinity'
+FROM public.ship_info
+  LEFT JOIN public.ship_assignment ON ship_assignment.id = ship_info.assignment_id;
+CREATE FUNCTION public.record_ship_assignment () RETURNS trigger AS $$
+BEGIN
+  INSERT INTO public.ship_assignment_history (ship_symbol, assignment_id, fleet_id)
+  SELECT NEW.symbol, NEW.assignment_id, ship_assignment.fleet_id
+  FROM (SELECT 1) AS one
+    LEFT JOIN public.ship_assignment ON ship_assignment.id = NEW.assignment_id;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+CREATE TRIGGER ship_info_assignment_inserted
+AFTER INSERT ON public.ship_info FOR EACH ROW
+EXECUTE FUNCTION public.record_ship_assignment ();
+CREATE TRIGGER ship_info_assignment_changed
+AFTER UPDATE OF assignment_id ON public.ship_info FOR EACH ROW
+WHEN (OLD.assignment_id IS DISTINCT FROM NEW.assignment_id)
+EXECUTE FUNCTION public.record_ship_assignment ();
+DROP VIEW public.ledger_entry;
+-- Every credit movement of our own agent as one signed amount, income is positive and expenses are negative.
+-- The markets and shipyards also report the trades of other agents, only those of our ships are kept.
+-- The fleet is the one the ship was assigned to at the time of the entry, a bought ship counts for the fleet it was bought for.
+-- Contract payments have no transaction, they are dated by the contract timestamps.
+CREATE VIEW public.ledger_entry AS
+SELECT
+  entries.source,
+  entries.source_id,
+  entries."timestamp",
+  entries.amount,
+  entries.activity,
+  entries.ship_symbol,
+  assigned.fleet_id,
+  entries.waypoint_symbol,
+  entries.trade_symbol,
+  entries.units,
+  entries.contract_id,
+  entries.trade_route_id,
+  entries.construction_shipment_id,
+  entries.mining_waypoint_symbol
+FROM (
+    SELECT
+      'MARKET'::ledger_source AS source,
+      id::character varying AS source_id,
+      "timestamp",
+      (
+        CASE
+          WHEN type = 'PURCHASE' THEN - total_price
+          ELSE total_price
+        END
+      )::bigint AS amount,
+      (
+        CASE
+          WHEN contract IS NOT NULL THEN 'CONTRACT'
+          WHEN trade_route IS NOT NULL THEN 'TRADE_ROUTE'
+          WHEN construction IS NOT NULL THEN 'CONSTRUCTION'
+          WHEN mining IS NOT NULL THEN 'MINING'
+          ELSE 'MARKET'
+        END
+      )::ledger_activity AS activity,
+      ship_symbol,
+      waypoint_symbol,
+      trade_symbol::character varying AS trade_symbol,
+      units,
+      contract AS contract_id,
+      trade_route AS trade_route_id,
+      construction AS construction_shipment_id,
+      mining AS mining_waypoint_symbol
+    FROM public.market_transaction
+      JOIN public.ship_info ON ship_info.symbol = market_transaction.ship_symbol
+    UNION ALL
+    SELECT
+      'SHIPYARD',
+      id::character varying,
+      "timestamp",
+      - price::bigint,
+      'SHIP_PURCHASE',
+      ship_info.symbol,
+      waypoint_symbol,
+      ship_type::character varying,
+      1,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.shipyard_transaction
+      JOIN public.ship_info ON ship_info.purchase_id = shipyard_transaction.id
+    UNION ALL
+    SELECT
+      'CHART',
+      id::character varying,
+      "timestamp",
+      total_price::bigint,
+      'CHARTING',
+      ship_symbol,
+      waypoint_symbol,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.chart_transaction
+    UNION ALL
+    SELECT
+      'REPAIR',
+      id::character varying,
+      "timestamp",
+      - total_price::bigint,
+      'REPAIR',
+      ship_symbol,
+      waypoint_symbol,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.repair_transaction
+    UNION ALL
+    SELECT
+      'SCRAP',
+      id::character varying,
+      "timestamp",
+      total_price::bigint,
+      'SCRAP',
+      ship_symbol,
+      waypoint_symbol,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.scrap_transaction
+    UNION ALL
+    SELECT
+      'SHIP_MODIFICATION',
+      id::character varying,
+      "timestamp",
+      - total_price::bigint,
+      'SHIP_MODIFICATION',
+      ship_symbol,
+      waypoint_symbol,
+      trade_symbol::character varying,
+      1,
+      NULL,
+      NULL,
+      NULL,
+      NULL
+    FROM public.ship_modification_transaction
+    UNION ALL
+    SELECT
+      'CONTRACT',
+      id || ':accepted',
+      created_at,
+      on_accepted::bigint,
+      'CONTRACT',
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      id,
+      NULL,
+      NULL,
+      NULL
+    FROM public.contract
+    WHERE accepted
+    UNION ALL
+    SELECT
+      'CONTRACT',
+      id || ':fulfilled',
+      updated_at,
+      on_fulfilled::bigint,
+      'CONTRACT',
+      NULL,
+      NULL,
+      NULL,
+      NULL,
+      id,
+      NULL,
+      NULL,
+      NULL
+    FROM public.contract
+    WHERE fulfilled
+  ) AS entries
+  LEFT JOIN LATERAL (
+    SELECT
+      ship_assignment_history.fleet_id
+    FROM public.ship_assignment_history
+    WHERE ship_assignment_history.ship_symbol = entries.ship_symbol
+    ORDER BY
+      ship_assignment_history.valid_from > entries."timestamp",
+      CASE
+        WHEN ship_assignment_history.valid_from <= entries."timestamp" THEN ship_assignment_history.valid_from
+      END DESC NULLS LAST,
+      ship_assignment_history.valid_from
+    LIMIT 1
+  ) AS assigned ON true;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE public.contract
+DROP COLUMN IF EXISTS declined;
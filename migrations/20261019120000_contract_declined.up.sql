@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- Offered contracts the contract manager decided not to accept, they are left to expire and not evaluated again.
+ALTER TABLE public.contract
+ADD COLUMN declined boolean NOT NULL DEFAULT false;
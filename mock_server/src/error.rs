@@ -0,0 +1,57 @@
+use space_traders_client::models::error_codes;
+use warp::http::StatusCode;
+
+/// Error answered in the same `{"error": {"message", "code"}}` shape the real API uses
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{status} {code}: {message}")]
+pub struct MockError {
+    pub status: StatusCode,
+    pub code: u32,
+    pub message: String,
+}
+
+pub type Result<T> = std::result::Result<T, MockError>;
+
+impl MockError {
+    pub fn new(status: StatusCode, code: u32, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Game rule violation, answered with 400 like the real server
+    pub fn game(code: u32, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, 404, message)
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::new(
+            StatusCode::UNAUTHORIZED,
+            error_codes::UNAUTHORIZED,
+            "Missing or invalid bearer token",
+        )
+    }
+
+    pub fn unprocessable(message: impl Into<String>) -> Self {
+        Self::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            error_codes::UNPROCESSABLE_INPUT_ERROR,
+            message,
+        )
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "message": self.message,
+                "code": self.code,
+            }
+        })
+    }
+}
@@ -0,0 +1,120 @@
+//! In-process mock of the SpaceTraders v2 HTTP API.
+//!
+//! Serves a small stateful universe built from the `space_traders_client` models, so the
+//! bot can run end to end without touching the live game. Point an [`space_traders_client::Api`]
+//! at [`MockServer::base_path`] to use it.
+
+mod error;
+mod routes;
+mod state;
+pub mod universe;
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::oneshot;
+use warp::{Filter, Reply};
+
+pub use error::MockError;
+
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    /// Wall clock seconds per game second for travel and cooldowns, 0.01 turns a 100s flight into 1s
+    pub time_scale: f64,
+    /// Seed of the random extraction yields, surveys and contracts
+    pub seed: u64,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            time_scale: 0.01,
+            seed: 0x5EED,
+        }
+    }
+}
+
+pub struct MockServer {
+    address: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts the server in the background, use port 0 to let the OS pick a free one
+    pub async fn start(config: MockConfig, address: SocketAddr) -> MockServer {
+        let world = Arc::new(Mutex::new(state::World::new(config)));
+
+        let api = warp::method()
+            .and(warp::path::full())
+            .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::body::bytes())
+            .map(
+                move |method, path: warp::path::FullPath, query, authorization, body| {
+                    let request = routes::Request {
+                        method,
+                        path: path.as_str().to_string(),
+                        query,
+                        authorization,
+                        body,
+                    };
+                    let result = {
+                        let mut world = world.lock().unwrap();
+                        routes::handle(&mut world, &request)
+                    };
+                    match result {
+                        Ok((status, Some(body))) => {
+                            warp::reply::with_status(warp::reply::json(&body), status)
+                                .into_response()
+                        }
+                        Ok((status, None)) => {
+                            warp::reply::with_status(warp::reply(), status).into_response()
+                        }
+                        Err(err) => {
+                            tracing::debug!(
+                                method = %request.method,
+                                path = %request.path,
+                                error = %err,
+                                "Mock request failed"
+                            );
+                            warp::reply::with_status(warp::reply::json(&err.to_json()), err.status)
+                                .into_response()
+                        }
+                    }
+                },
+            );
+
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        let (address, server) = warp::serve(api).bind_with_graceful_shutdown(address, async move {
+            shutdown_rx.await.ok();
+        });
+        let handle = tokio::spawn(server);
+
+        tracing::info!(%address, "Mock SpaceTraders server listening");
+
+        MockServer {
+            address,
+            shutdown: Some(shutdown),
+            handle,
+        }
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Base path to put into the client `Configuration`
+    pub fn base_path(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        let _ = (&mut self.handle).await;
+    }
+}
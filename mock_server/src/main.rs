@@ -0,0 +1,26 @@
+use std::env;
+
+use mock_server::{MockConfig, MockServer};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let address = env::var("MOCK_SOCKET_ADDRESS").unwrap_or("127.0.0.1:8790".to_string());
+    let mut config = MockConfig::default();
+    if let Ok(time_scale) = env::var("MOCK_TIME_SCALE") {
+        config.time_scale = time_scale.parse()?;
+    }
+    if let Ok(seed) = env::var("MOCK_SEED") {
+        config.seed = seed.parse()?;
+    }
+
+    let server = MockServer::start(config, address.parse()?).await;
+    tracing::info!(base_path = %server.base_path(), "Set API_BASE_PATH to this to run the bot against the mock");
+
+    tokio::signal::ctrl_c().await?;
+    server.shutdown().await;
+    Ok(())
+}
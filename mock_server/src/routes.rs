@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use space_traders_client::models;
+use warp::http::{Method, StatusCode};
+
+use crate::{
+    error::{MockError, Result},
+    state::World,
+};
+
+pub struct Request {
+    pub method: Method,
+    pub path: String,
+    pub query: String,
+    pub authorization: Option<String>,
+    pub body: warp::hyper::body::Bytes,
+}
+
+impl Request {
+    fn token(&self) -> Option<&str> {
+        self.authorization
+            .as_deref()
+            .and_then(|header| header.strip_prefix("Bearer "))
+    }
+
+    fn query(&self) -> HashMap<String, String> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn page(&self) -> Result<(i32, i32)> {
+        let query = self.query();
+        let number = |key: &str, default: i32| {
+            query
+                .get(key)
+                .map(|value| {
+                    value
+                        .parse::<i32>()
+                        .map_err(|_| MockError::unprocessable(format!("{} must be a number", key)))
+                })
+                .unwrap_or(Ok(default))
+        };
+        Ok((number("page", 1)?, number("limit", 10)?))
+    }
+
+    fn query_enum<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.query()
+            .get(key)
+            .map(|value| {
+                serde_json::from_value(Value::String(value.clone()))
+                    .map_err(|_| MockError::unprocessable(format!("Invalid {} {}", key, value)))
+            })
+            .transpose()
+    }
+
+    fn body<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_slice(&self.body)
+            .map_err(|err| MockError::unprocessable(format!("Invalid request body: {}", err)))
+    }
+
+    /// Body of requests where every field is optional and the client may send nothing or `null`
+    fn optional_body<T: DeserializeOwned + Default>(&self) -> Result<T> {
+        if self.body.iter().all(|b| b.is_ascii_whitespace()) {
+            return Ok(T::default());
+        }
+        Ok(self.body::<Option<T>>()?.unwrap_or_default())
+    }
+}
+
+/// Answers a request, `None` as body means 204 no content
+pub fn handle(world: &mut World, request: &Request) -> Result<(StatusCode, Option<Value>)> {
+    let segments = request
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .skip_while(|segment| *segment == "v2")
+        .collect::<Vec<_>>();
+
+    let ok = |value: Value| Ok((StatusCode::OK, Some(value)));
+    let created = |value: Value| Ok((StatusCode::CREATED, Some(value)));
+
+    if segments.first() == Some(&"my") {
+        let agent = world.agent_for_token(request.token())?;
+        return handle_my(world, request, &agent, &segments[1..]);
+    }
+    // public endpoints still show market and shipyard details if the agent has a ship there
+    let agent = world.agent_for_token(request.token()).ok();
+    let agent = agent.as_deref();
+
+    match (&request.method, segments.as_slice()) {
+        (&Method::GET, []) => ok(world.status()?),
+        (&Method::POST, ["register"]) => {
+            if request.token().is_none() {
+                return Err(MockError::new(
+                    StatusCode::UNAUTHORIZED,
+                    models::error_codes::MISSING_ACCOUNT_TOKEN_REQUEST,
+                    "Registering requires an account token",
+                ));
+            }
+            created(world.register(request.body()?)?)
+        }
+        (&Method::GET, ["agents"]) => {
+            let (page, limit) = request.page()?;
+            ok(world.agents(page, limit)?)
+        }
+        (&Method::GET, ["agents", symbol]) => ok(world.public_agent(symbol)?),
+        (&Method::GET, ["market", "supply-chain"]) => ok(world.supply_chain()?),
+        (&Method::GET, ["systems"]) => {
+            let (page, limit) = request.page()?;
+            ok(world.systems(page, limit)?)
+        }
+        (&Method::GET, ["systems", system]) => ok(world.system(system)?),
+        (&Method::GET, ["systems", system, "waypoints"]) => {
+            let (page, limit) = request.page()?;
+            ok(world.waypoints(
+                system,
+                page,
+                limit,
+                request.query_enum("type")?,
+                request.query_enum("traits")?,
+            )?)
+        }
+        (&Method::GET, ["systems", _, "waypoints", waypoint]) => ok(world.get_waypoint(waypoint)?),
+        (&Method::GET, ["systems", _, "waypoints", waypoint, "market"]) => {
+            ok(world.market(agent, waypoint)?)
+        }
+        (&Method::GET, ["systems", _, "waypoints", waypoint, "shipyard"]) => {
+            ok(world.shipyard(agent, waypoint)?)
+        }
+        (&Method::GET, ["systems", _, "waypoints", waypoint, "jump-gate"]) => {
+            ok(world.jump_gate(agent, waypoint)?)
+        }
+        (&Method::GET, ["systems", _, "waypoints", waypoint, "construction"]) => {
+            ok(world.construction(waypoint)?)
+        }
+        (
+            &Method::POST,
+            [
+                "systems",
+                _,
+                "waypoints",
+                waypoint,
+                "construction",
+                "supply",
+            ],
+        ) => {
+            let agent = agent.ok_or_else(MockError::unauthorized)?;
+            created(world.supply_construction(agent, waypoint, request.body()?)?)
+        }
+        _ => Err(MockError::not_found(format!(
+            "{} {} is not supported by the mock server",
+            request.method, request.path
+        ))),
+    }
+}
+
+fn handle_my(
+    world: &mut World,
+    request: &Request,
+    agent: &str,
+    segments: &[&str],
+) -> Result<(StatusCode, Option<Value>)> {
+    let ok = |value: Value| Ok((StatusCode::OK, Some(value)));
+    let created = |value: Value| Ok((StatusCode::CREATED, Some(value)));
+
+    match (&request.method, segments) {
+        (&Method::GET, ["agent"]) => ok(world.my_agent(agent)?),
+
+        (&Method::GET, ["contracts"]) => {
+            let (page, limit) = request.page()?;
+            ok(world.contracts(agent, page, limit)?)
+        }
+        (&Method::GET, ["contracts", id]) => ok(world.contract(agent, id)?),
+        (&Method::POST, ["contracts", id, "accept"]) => ok(world.accept_contract(agent, id)?),
+        (&Method::POST, ["contracts", id, "deliver"]) => {
+            ok(world.deliver_contract(agent, id, request.body()?)?)
+        }
+        (&Method::POST, ["contracts", id, "fulfill"]) => ok(world.fulfill_contract(agent, id)?),
+
+        (&Method::GET, ["ships"]) => {
+            let (page, limit) = request.page()?;
+            ok(world.ships(agent, page, limit)?)
+        }
+        (&Method::POST, ["ships"]) => created(world.purchase_ship(agent, request.body()?)?),
+        (&Method::GET, ["ships", ship]) => ok(world.get_ship(agent, ship)?),
+        (&Method::GET, ["ships", ship, "nav"]) => ok(world.ship_nav(agent, ship)?),
+        (&Method::PATCH, ["ships", ship, "nav"]) => {
+            ok(world.patch_nav(agent, ship, request.optional_body()?)?)
+        }
+        (&Method::GET, ["ships", ship, "cargo"]) => ok(world.ship_cargo(agent, ship)?),
+        (&Method::GET, ["ships", ship, "cooldown"]) => match world.ship_cooldown(agent, ship)? {
+            Some(cooldown) => ok(cooldown),
+            None => Ok((StatusCode::NO_CONTENT, None)),
+        },
+        (&Method::GET, ["ships", ship, "mounts"]) => ok(world.ship_mounts(agent, ship)?),
+        (&Method::GET, ["ships", ship, "modules"]) => ok(world.ship_modules(agent, ship)?),
+        (&Method::POST, ["ships", ship, "dock"]) => ok(world.dock(agent, ship)?),
+        (&Method::POST, ["ships", ship, "orbit"]) => ok(world.orbit(agent, ship)?),
+        (&Method::POST, ["ships", ship, "navigate"]) => {
+            ok(world.navigate(agent, ship, request.body()?)?)
+        }
+        (&Method::POST, ["ships", ship, "warp"]) => ok(world.warp(agent, ship, request.body()?)?),
+        (&Method::POST, ["ships", ship, "jump"]) => ok(world.jump(agent, ship, request.body()?)?),
+        (&Method::POST, ["ships", ship, "refuel"]) => {
+            ok(world.refuel(agent, ship, request.optional_body()?)?)
+        }
+        (&Method::POST, ["ships", ship, "purchase"]) => {
+            created(world.purchase_cargo(agent, ship, request.body()?)?)
+        }
+        (&Method::POST, ["ships", ship, "sell"]) => {
+            created(world.sell_cargo(agent, ship, request.body()?)?)
+        }
+        (&Method::POST, ["ships", ship, "jettison"]) => {
+            ok(world.jettison(agent, ship, request.body()?)?)
+        }
+        (&Method::POST, ["ships", ship, "transfer"]) => {
+            ok(world.transfer_cargo(agent, ship, request.body()?)?)
+        }
+        (&Method::POST, ["ships", ship, "extract"]) => created(world.extract(agent, ship, None)?),
+        (&Method::POST, ["ships", ship, "extract", "survey"]) => {
+            created(world.extract(agent, ship, Some(request.body()?))?)
+        }
+        (&Method::POST, ["ships", ship, "siphon"]) => created(world.siphon(agent, ship)?),
+        (&Method::POST, ["ships", ship, "survey"]) => created(world.survey(agent, ship)?),
+        (&Method::POST, ["ships", ship, "refine"]) => {
+            created(world.refine(agent, ship, request.body()?)?)
+        }
+        (&Method::POST, ["ships", ship, "chart"]) => created(world.chart(agent, ship)?),
+        (&Method::POST, ["ships", ship, "negotiate", "contract"]) => {
+            created(world.negotiate_contract(agent, ship)?)
+        }
+        (&Method::GET, ["ships", ship, "repair"]) => ok(world.get_repair(agent, ship)?),
+        (&Method::POST, ["ships", ship, "repair"]) => ok(world.repair(agent, ship)?),
+        (&Method::GET, ["ships", ship, "scrap"]) => ok(world.get_scrap(agent, ship)?),
+        (&Method::POST, ["ships", ship, "scrap"]) => ok(world.scrap(agent, ship)?),
+        (&Method::POST, ["ships", ship, "mounts", "install"]) => {
+            let body: models::InstallMountRequest = request.body()?;
+            created(world.install_mount(agent, ship, &body.symbol)?)
+        }
+        (&Method::POST, ["ships", ship, "mounts", "remove"]) => {
+            let body: models::RemoveMountRequest = request.body()?;
+            created(world.remove_mount(agent, ship, &body.symbol)?)
+        }
+        (&Method::POST, ["ships", ship, "modules", "install"]) => {
+            let body: models::InstallShipModuleRequest = request.body()?;
+            created(world.install_module(agent, ship, &body.symbol)?)
+        }
+        (&Method::POST, ["ships", ship, "modules", "remove"]) => {
+            let body: models::RemoveShipModuleRequest = request.body()?;
+            created(world.remove_module(agent, ship, &body.symbol)?)
+        }
+        _ => Err(MockError::not_found(format!(
+            "{} {} is not supported by the mock server",
+            request.method, request.path
+        ))),
+    }
+}
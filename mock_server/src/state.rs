@@ -0,0 +1,2520 @@
+//! Game state of the mock server and the rules applied to it.
+//!
+//! Every handler works on a locked [`World`], reads and writes the `space_traders_client`
+//! model types directly and answers with the `data` part of the response.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Value, json};
+use space_traders_client::models::{
+    self, ShipNavFlightMode, ShipNavStatus, TradeSymbol, error_codes, market_trade_good,
+    ship_module, ship_mount,
+};
+use warp::http::StatusCode;
+
+use crate::{
+    MockConfig,
+    error::{MockError, Result},
+    universe,
+};
+
+/// Antimatter consumed and paid per jump
+const JUMP_ANTIMATTER_PRICE: i32 = 5_000;
+/// Survey lifetime, not scaled so surveys stay usable on fast clocks
+const SURVEY_LIFETIME_SECONDS: i64 = 30 * 60;
+/// Condition every navigation takes from frame, reactor and engine
+const NAVIGATION_WEAR: f64 = 0.002;
+
+struct AgentState {
+    agent: models::Agent,
+    next_ship: u32,
+}
+
+struct ShipState {
+    owner: String,
+    ship_type: models::ShipType,
+    ship: models::Ship,
+    cooldown_until: Option<DateTime<Utc>>,
+}
+
+struct MarketGood {
+    symbol: TradeSymbol,
+    kind: market_trade_good::Type,
+    base_price: i32,
+    trade_volume: i32,
+    /// Positive when the good got scarce through purchases, negative when flooded by sales
+    pressure: f64,
+    updated: DateTime<Utc>,
+}
+
+struct MarketState {
+    goods: Vec<MarketGood>,
+    transactions: Vec<models::MarketTransaction>,
+}
+
+struct ShipyardState {
+    ship_types: Vec<models::ShipType>,
+    modifications_fee: i32,
+    transactions: Vec<models::ShipyardTransaction>,
+}
+
+struct SurveyState {
+    survey: models::Survey,
+    remaining: i32,
+}
+
+pub struct World {
+    config: MockConfig,
+    rng: u64,
+    next_id: u64,
+    agents: BTreeMap<String, AgentState>,
+    tokens: HashMap<String, String>,
+    ships: BTreeMap<String, ShipState>,
+    contracts: BTreeMap<String, (String, models::Contract)>,
+    systems: BTreeMap<String, models::System>,
+    waypoints: BTreeMap<String, models::Waypoint>,
+    markets: BTreeMap<String, MarketState>,
+    shipyards: BTreeMap<String, ShipyardState>,
+    jump_gates: HashMap<String, Vec<String>>,
+    constructions: BTreeMap<String, models::Construction>,
+    surveys: HashMap<String, SurveyState>,
+    supply_chain: HashMap<String, Vec<String>>,
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn distance(a: (i32, i32), b: (i32, i32)) -> f64 {
+    (((a.0 - b.0) as f64).powi(2) + ((a.1 - b.1) as f64).powi(2)).sqrt()
+}
+
+/// Fuel cost and travel time multiplier, same formula the game uses
+fn fuel_and_multiplier(mode: ShipNavFlightMode, distance: f64) -> (i32, f64) {
+    match mode {
+        ShipNavFlightMode::Burn => ((2.0 * distance.max(1.0)).ceil() as i32, 12.5),
+        ShipNavFlightMode::Cruise => (distance.max(1.0).ceil() as i32, 25.0),
+        ShipNavFlightMode::Stealth => (distance.max(1.0).ceil() as i32, 30.0),
+        ShipNavFlightMode::Drift => (1, 250.0),
+    }
+}
+
+fn trade_good(symbol: TradeSymbol) -> models::TradeGood {
+    models::TradeGood {
+        symbol,
+        name: universe::title(&symbol.to_string()),
+        description: String::new(),
+    }
+}
+
+fn waypoint_trait(symbol: models::WaypointTraitSymbol) -> models::WaypointTrait {
+    models::WaypointTrait {
+        symbol,
+        name: universe::title(&symbol.to_string()),
+        description: String::new(),
+    }
+}
+
+fn paged<T: serde::Serialize>(items: Vec<T>, page: i32, limit: i32) -> Result<Value> {
+    if !(1..=20).contains(&limit) || page < 1 {
+        return Err(MockError::unprocessable(
+            "page must be at least 1 and limit between 1 and 20",
+        ));
+    }
+    let total = items.len() as i32;
+    let data = items
+        .into_iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
+        .collect::<Vec<_>>();
+    Ok(json!({
+        "data": data,
+        "meta": models::Meta { total, page, limit },
+    }))
+}
+
+fn data<T: serde::Serialize>(data: T) -> Result<Value> {
+    Ok(json!({ "data": data }))
+}
+
+impl MarketGood {
+    fn decay(&mut self, now: DateTime<Utc>, time_scale: f64) {
+        let elapsed = (now - self.updated).num_milliseconds().max(0) as f64 / 1000.0;
+        // half of the pressure is gone after half an hour of game time
+        let half_life = 1800.0 * time_scale.max(f64::EPSILON);
+        self.pressure *= 0.5f64.powf(elapsed / half_life);
+        self.updated = now;
+    }
+
+    fn factor(&self) -> f64 {
+        1.0 + self.pressure
+    }
+
+    fn purchase_price(&self) -> i32 {
+        let markup = match self.kind {
+            market_trade_good::Type::Import => 1.1,
+            _ => 1.0,
+        };
+        ((self.base_price as f64) * markup * self.factor())
+            .round()
+            .max(1.0) as i32
+    }
+
+    fn sell_price(&self) -> i32 {
+        let markdown = match self.kind {
+            market_trade_good::Type::Export => 0.5,
+            market_trade_good::Type::Exchange => 0.92,
+            market_trade_good::Type::Import => 1.0,
+        };
+        ((self.base_price as f64) * markdown * self.factor())
+            .round()
+            .max(1.0) as i32
+    }
+
+    fn supply(&self) -> models::SupplyLevel {
+        match self.pressure {
+            p if p > 0.6 => models::SupplyLevel::Scarce,
+            p if p > 0.25 => models::SupplyLevel::Limited,
+            p if p > -0.1 => models::SupplyLevel::Moderate,
+            p if p > -0.3 => models::SupplyLevel::High,
+            _ => models::SupplyLevel::Abundant,
+        }
+    }
+
+    /// Moves the price after a trade, positive units for purchases and negative for sales
+    fn trade(&mut self, units: i32) {
+        self.pressure = (self.pressure + 0.08 * (units as f64) / (self.trade_volume.max(1) as f64))
+            .clamp(-0.6, 1.5);
+    }
+
+    fn to_model(&self) -> models::MarketTradeGood {
+        models::MarketTradeGood {
+            symbol: self.symbol,
+            r#type: self.kind,
+            trade_volume: self.trade_volume,
+            supply: self.supply(),
+            activity: Some(models::ActivityLevel::Growing),
+            purchase_price: self.purchase_price(),
+            sell_price: self.sell_price(),
+        }
+    }
+}
+
+impl World {
+    pub fn new(config: MockConfig) -> Self {
+        let now = Utc::now();
+        let mut systems = BTreeMap::new();
+        let mut waypoints = BTreeMap::new();
+
+        for system in universe::systems() {
+            let mut system_waypoints = Vec::new();
+            for spec in &system.waypoints {
+                let orbitals = system
+                    .waypoints
+                    .iter()
+                    .filter(|other| other.orbits == Some(spec.symbol))
+                    .map(|other| models::WaypointOrbital {
+                        symbol: other.symbol.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                let mut traits = spec
+                    .traits
+                    .iter()
+                    .map(|symbol| waypoint_trait(*symbol))
+                    .collect::<Vec<_>>();
+                if spec.under_construction {
+                    traits.push(waypoint_trait(
+                        models::WaypointTraitSymbol::UnderConstruction,
+                    ));
+                }
+                let home = system.symbol == universe::HOME_SYSTEM;
+                waypoints.insert(
+                    spec.symbol.to_string(),
+                    models::Waypoint {
+                        symbol: spec.symbol.to_string(),
+                        r#type: spec.kind,
+                        system_symbol: system.symbol.to_string(),
+                        x: spec.x,
+                        y: spec.y,
+                        orbitals: orbitals.clone(),
+                        orbits: spec.orbits.map(|s| s.to_string()),
+                        faction: home.then(|| {
+                            Box::new(models::WaypointFaction {
+                                symbol: models::FactionSymbol::Cosmic,
+                            })
+                        }),
+                        traits,
+                        modifiers: Some(Vec::new()),
+                        chart: spec.charted.then(|| {
+                            Box::new(models::Chart {
+                                waypoint_symbol: spec.symbol.to_string(),
+                                submitted_by: "COSMIC".to_string(),
+                                submitted_on: timestamp(now),
+                            })
+                        }),
+                        is_under_construction: spec.under_construction,
+                    },
+                );
+                system_waypoints.push(models::SystemWaypoint {
+                    symbol: spec.symbol.to_string(),
+                    r#type: spec.kind,
+                    x: spec.x,
+                    y: spec.y,
+                    orbitals,
+                    orbits: spec.orbits.map(|s| s.to_string()),
+                });
+            }
+            systems.insert(
+                system.symbol.to_string(),
+                models::System {
+                    constellation: Some("Mock".to_string()),
+                    symbol: system.symbol.to_string(),
+                    sector_symbol: "X1".to_string(),
+                    r#type: system.kind,
+                    x: system.x,
+                    y: system.y,
+                    waypoints: system_waypoints,
+                    factions: vec![models::SystemFaction {
+                        symbol: models::FactionSymbol::Cosmic,
+                    }],
+                    name: Some(system.symbol.to_string()),
+                },
+            );
+        }
+
+        let markets = universe::markets()
+            .into_iter()
+            .map(|(symbol, goods)| {
+                let goods = goods
+                    .into_iter()
+                    .map(|(symbol, kind, base_price, trade_volume)| MarketGood {
+                        symbol,
+                        kind,
+                        base_price,
+                        trade_volume,
+                        pressure: 0.0,
+                        updated: now,
+                    })
+                    .collect();
+                (
+                    symbol.to_string(),
+                    MarketState {
+                        goods,
+                        transactions: Vec::new(),
+                    },
+                )
+            })
+            .collect();
+
+        let shipyards = universe::shipyards()
+            .into_iter()
+            .map(|(symbol, ship_types, modifications_fee)| {
+                (
+                    symbol.to_string(),
+                    ShipyardState {
+                        ship_types,
+                        modifications_fee,
+                        transactions: Vec::new(),
+                    },
+                )
+            })
+            .collect();
+
+        let constructions = universe::constructions()
+            .into_iter()
+            .map(|(symbol, materials)| {
+                (
+                    symbol.to_string(),
+                    models::Construction {
+                        symbol: symbol.to_string(),
+                        materials: materials
+                            .into_iter()
+                            .map(|(trade_symbol, required)| models::ConstructionMaterial {
+                                trade_symbol,
+                                required,
+                                fulfilled: 0,
+                            })
+                            .collect(),
+                        is_complete: false,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            rng: config.seed.max(1),
+            config,
+            next_id: 1,
+            agents: BTreeMap::new(),
+            tokens: HashMap::new(),
+            ships: BTreeMap::new(),
+            contracts: BTreeMap::new(),
+            systems,
+            waypoints,
+            markets,
+            shipyards,
+            jump_gates: universe::jump_gates(),
+            constructions,
+            surveys: HashMap::new(),
+            supply_chain: universe::supply_chain(),
+        }
+    }
+
+    /// xorshift, deterministic for a given seed
+    fn random(&mut self, bound: u64) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng % bound.max(1)
+    }
+
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{:08x}", prefix, self.next_id)
+    }
+
+    /// Game time span scaled to wall clock time
+    fn scaled(&self, seconds: f64) -> chrono::Duration {
+        chrono::Duration::milliseconds((seconds * self.config.time_scale * 1000.0).round() as i64)
+    }
+
+    pub fn agent_for_token(&self, token: Option<&str>) -> Result<String> {
+        token
+            .and_then(|token| self.tokens.get(token))
+            .cloned()
+            .ok_or_else(MockError::unauthorized)
+    }
+
+    fn agent(&self, agent: &str) -> Result<&models::Agent> {
+        self.agents
+            .get(agent)
+            .map(|state| &state.agent)
+            .ok_or_else(|| MockError::game(error_codes::AGENT_NOT_EXISTS_ERROR, "Agent not found"))
+    }
+
+    fn agent_mut(&mut self, agent: &str) -> Result<&mut models::Agent> {
+        self.agents
+            .get_mut(agent)
+            .map(|state| &mut state.agent)
+            .ok_or_else(|| MockError::game(error_codes::AGENT_NOT_EXISTS_ERROR, "Agent not found"))
+    }
+
+    fn pay(&mut self, agent: &str, amount: i64, code: u32) -> Result<()> {
+        let agent = self.agent_mut(agent)?;
+        if agent.credits < amount {
+            return Err(MockError::game(
+                code,
+                format!(
+                    "Agent has insufficient funds, {} needed but {} available",
+                    amount, agent.credits
+                ),
+            ));
+        }
+        agent.credits -= amount;
+        Ok(())
+    }
+
+    fn waypoint(&self, symbol: &str) -> Result<&models::Waypoint> {
+        self.waypoints
+            .get(symbol)
+            .ok_or_else(|| MockError::not_found(format!("Waypoint {} not found", symbol)))
+    }
+
+    fn waypoint_has(&self, symbol: &str, waypoint_trait: models::WaypointTraitSymbol) -> bool {
+        self.waypoints
+            .get(symbol)
+            .map(|waypoint| waypoint.traits.iter().any(|t| t.symbol == waypoint_trait))
+            .unwrap_or(false)
+    }
+
+    /// Waypoint as the public sees it, uncharted waypoints hide their traits
+    fn visible_waypoint(&self, waypoint: &models::Waypoint) -> models::Waypoint {
+        let mut waypoint = waypoint.clone();
+        if waypoint.chart.is_none() {
+            waypoint.traits = vec![waypoint_trait(models::WaypointTraitSymbol::Uncharted)];
+        }
+        waypoint
+    }
+
+    fn system_position(&self, system: &str) -> (i32, i32) {
+        self.systems
+            .get(system)
+            .map(|system| (system.x, system.y))
+            .unwrap_or_default()
+    }
+
+    /// Whether one of the agent's ships is currently present at the waypoint
+    fn has_ship_at(&self, agent: &str, waypoint: &str) -> bool {
+        let now = Utc::now();
+        self.ships.values().any(|state| {
+            state.owner == agent
+                && state.ship.nav.waypoint_symbol == waypoint
+                && (state.ship.nav.status != ShipNavStatus::InTransit
+                    || parse_time(&state.ship.nav.route.arrival)
+                        .is_some_and(|arrival| arrival <= now))
+        })
+    }
+
+    /// Looks up a ship of the agent and applies everything that happened since the last request
+    fn ship(&mut self, agent: &str, symbol: &str) -> Result<&mut ShipState> {
+        let now = Utc::now();
+        let state = self
+            .ships
+            .get_mut(symbol)
+            .filter(|state| state.owner == agent)
+            .ok_or_else(|| MockError::not_found(format!("Ship {} not found", symbol)))?;
+
+        if state.ship.nav.status == ShipNavStatus::InTransit
+            && parse_time(&state.ship.nav.route.arrival).is_some_and(|arrival| arrival <= now)
+        {
+            state.ship.nav.status = ShipNavStatus::InOrbit;
+        }
+
+        match state.cooldown_until {
+            Some(until) if until > now => {
+                state.ship.cooldown.remaining_seconds =
+                    ((until - now).num_milliseconds() as f64 / 1000.0).ceil() as i32;
+            }
+            _ => {
+                state.cooldown_until = None;
+                state.ship.cooldown.remaining_seconds = 0;
+                state.ship.cooldown.total_seconds = 0;
+                state.ship.cooldown.expiration = None;
+            }
+        }
+
+        Ok(state)
+    }
+
+    fn ship_not_in_transit(&mut self, agent: &str, symbol: &str) -> Result<&mut ShipState> {
+        let state = self.ship(agent, symbol)?;
+        if state.ship.nav.status == ShipNavStatus::InTransit {
+            return Err(MockError::game(
+                error_codes::SHIP_IN_TRANSIT_ERROR,
+                format!("Ship {} is currently in transit", symbol),
+            ));
+        }
+        Ok(state)
+    }
+
+    fn ship_docked(&mut self, agent: &str, symbol: &str) -> Result<&mut ShipState> {
+        let state = self.ship_not_in_transit(agent, symbol)?;
+        if state.ship.nav.status != ShipNavStatus::Docked {
+            return Err(MockError::game(
+                error_codes::SHIP_NOT_DOCKED_ERROR,
+                format!("Ship {} must be docked", symbol),
+            ));
+        }
+        Ok(state)
+    }
+
+    fn ship_in_orbit(&mut self, agent: &str, symbol: &str) -> Result<&mut ShipState> {
+        let state = self.ship_not_in_transit(agent, symbol)?;
+        if state.ship.nav.status != ShipNavStatus::InOrbit {
+            return Err(MockError::game(
+                error_codes::SHIP_NOT_IN_ORBIT_ERROR,
+                format!("Ship {} must be in orbit", symbol),
+            ));
+        }
+        Ok(state)
+    }
+
+    fn ship_ready(&mut self, agent: &str, symbol: &str) -> Result<&mut ShipState> {
+        let state = self.ship_in_orbit(agent, symbol)?;
+        if state.cooldown_until.is_some() {
+            return Err(MockError::new(
+                StatusCode::CONFLICT,
+                error_codes::COOLDOWN_CONFLICT_ERROR,
+                format!(
+                    "Ship {} is on cooldown for {} seconds",
+                    symbol, state.ship.cooldown.remaining_seconds
+                ),
+            ));
+        }
+        Ok(state)
+    }
+
+    fn start_cooldown(&mut self, ship: &str, seconds: f64) -> models::Cooldown {
+        let until = Utc::now() + self.scaled(seconds);
+        let state = self.ships.get_mut(ship).unwrap();
+        state.cooldown_until = Some(until);
+        *state.ship.cooldown = models::Cooldown {
+            ship_symbol: ship.to_string(),
+            total_seconds: seconds.round() as i32,
+            remaining_seconds: seconds.round() as i32,
+            expiration: Some(timestamp(until)),
+        };
+        (*state.ship.cooldown).clone()
+    }
+
+    fn build_ship(
+        &mut self,
+        agent: &str,
+        ship_type: models::ShipType,
+        waypoint: &str,
+    ) -> Result<models::Ship> {
+        let state = self.agents.get_mut(agent).ok_or_else(|| {
+            MockError::game(error_codes::AGENT_NOT_EXISTS_ERROR, "Agent not found")
+        })?;
+        let symbol = format!("{}-{:X}", agent, state.next_ship);
+        state.next_ship += 1;
+        state.agent.ship_count += 1;
+        let faction = state.agent.starting_faction.clone();
+
+        let template = universe::ship_template(ship_type);
+        let waypoint = self.waypoint(waypoint)?.clone();
+        let route_waypoint = models::ShipNavRouteWaypoint {
+            symbol: waypoint.symbol.clone(),
+            r#type: waypoint.r#type,
+            system_symbol: waypoint.system_symbol.clone(),
+            x: waypoint.x,
+            y: waypoint.y,
+        };
+        let now = timestamp(Utc::now());
+
+        let ship = models::Ship {
+            symbol: symbol.clone(),
+            registration: Box::new(models::ShipRegistration {
+                name: symbol.clone(),
+                faction_symbol: faction,
+                role: template.role,
+            }),
+            nav: Box::new(models::ShipNav {
+                system_symbol: waypoint.system_symbol.clone(),
+                waypoint_symbol: waypoint.symbol.clone(),
+                route: Box::new(models::ShipNavRoute {
+                    destination: Box::new(route_waypoint.clone()),
+                    origin: Box::new(route_waypoint),
+                    departure_time: now.clone(),
+                    arrival: now,
+                }),
+                status: ShipNavStatus::Docked,
+                flight_mode: ShipNavFlightMode::Cruise,
+            }),
+            crew: Box::new(models::ShipCrew {
+                current: template.crew_required,
+                required: template.crew_required,
+                capacity: template.crew_capacity,
+                rotation: models::ship_crew::Rotation::Strict,
+                morale: 100,
+                wages: 0,
+            }),
+            cargo: Box::new(models::ShipCargo {
+                capacity: template.cargo_capacity(),
+                units: 0,
+                inventory: Vec::new(),
+            }),
+            fuel: Box::new(models::ShipFuel {
+                current: template.frame.fuel_capacity,
+                capacity: template.frame.fuel_capacity,
+                consumed: None,
+            }),
+            cooldown: Box::new(models::Cooldown {
+                ship_symbol: symbol.clone(),
+                total_seconds: 0,
+                remaining_seconds: 0,
+                expiration: None,
+            }),
+            frame: Box::new(template.frame),
+            reactor: Box::new(template.reactor),
+            engine: Box::new(template.engine),
+            modules: template.modules,
+            mounts: template.mounts,
+        };
+
+        self.ships.insert(
+            symbol,
+            ShipState {
+                owner: agent.to_string(),
+                ship_type,
+                ship: ship.clone(),
+                cooldown_until: None,
+            },
+        );
+        Ok(ship)
+    }
+
+    fn new_contract(&mut self, agent: &str) -> models::Contract {
+        let goods = [
+            (TradeSymbol::IronOre, "X1-MK1-A2", 45),
+            (TradeSymbol::CopperOre, "X1-MK1-A2", 50),
+            (TradeSymbol::AluminumOre, "X1-MK1-A2", 55),
+            (TradeSymbol::QuartzSand, "X1-MK1-A1", 60),
+            (TradeSymbol::Iron, "X1-MK1-A1", 150),
+        ];
+        let (good, destination, unit_reward) = goods[self.random(goods.len() as u64) as usize];
+        let units = 30 + self.random(8) as i32 * 10;
+        let now = Utc::now();
+        let contract = models::Contract {
+            id: self.next_id("mockcontract"),
+            faction_symbol: "COSMIC".to_string(),
+            r#type: models::contract::Type::Procurement,
+            terms: Box::new(models::ContractTerms {
+                deadline: timestamp(now + chrono::Duration::days(7)),
+                payment: Box::new(models::ContractPayment {
+                    on_accepted: units * unit_reward / 5,
+                    on_fulfilled: units * unit_reward,
+                }),
+                deliver: Some(vec![models::ContractDeliverGood {
+                    trade_symbol: good.to_string(),
+                    destination_symbol: destination.to_string(),
+                    units_required: units,
+                    units_fulfilled: 0,
+                }]),
+            }),
+            accepted: false,
+            fulfilled: false,
+            expiration: timestamp(now + chrono::Duration::days(1)),
+            deadline_to_accept: Some(timestamp(now + chrono::Duration::days(1))),
+        };
+        self.contracts
+            .insert(contract.id.clone(), (agent.to_string(), contract.clone()));
+        contract
+    }
+
+    fn contract_mut(&mut self, agent: &str, id: &str) -> Result<&mut models::Contract> {
+        self.contracts
+            .get_mut(id)
+            .filter(|(owner, _)| owner == agent)
+            .map(|(_, contract)| contract)
+            .ok_or_else(|| MockError::not_found(format!("Contract {} not found", id)))
+    }
+
+    fn record_transaction(
+        &mut self,
+        waypoint: &str,
+        ship: &str,
+        symbol: TradeSymbol,
+        kind: models::market_transaction::Type,
+        units: i32,
+        price_per_unit: i32,
+    ) -> models::MarketTransaction {
+        let transaction = models::MarketTransaction {
+            waypoint_symbol: waypoint.to_string(),
+            ship_symbol: ship.to_string(),
+            trade_symbol: symbol.to_string(),
+            r#type: kind,
+            units,
+            price_per_unit,
+            total_price: units * price_per_unit,
+            timestamp: timestamp(Utc::now()),
+        };
+        if let Some(market) = self.markets.get_mut(waypoint) {
+            market.transactions.push(transaction.clone());
+            if market.transactions.len() > 50 {
+                market.transactions.remove(0);
+            }
+        }
+        transaction
+    }
+
+    fn market_good(&mut self, waypoint: &str, symbol: TradeSymbol) -> Option<&mut MarketGood> {
+        let now = Utc::now();
+        let time_scale = self.config.time_scale;
+        let good = self
+            .markets
+            .get_mut(waypoint)?
+            .goods
+            .iter_mut()
+            .find(|good| good.symbol == symbol)?;
+        good.decay(now, time_scale);
+        Some(good)
+    }
+
+    // ---------------------------------------------------------------- global
+
+    pub fn status(&self) -> Result<Value> {
+        let now = Utc::now();
+        Ok(json!({
+            "status": "SpaceTraders is currently online and available to play",
+            "version": "v2.3.0-mock",
+            "resetDate": now.format("%Y-%m-%d").to_string(),
+            "description": "In-process mock of the SpaceTraders API",
+            "stats": {
+                "accounts": self.agents.len(),
+                "agents": self.agents.len(),
+                "ships": self.ships.len(),
+                "systems": self.systems.len(),
+                "waypoints": self.waypoints.len(),
+            },
+            "health": { "lastMarketUpdate": timestamp(now) },
+            "leaderboards": {
+                "mostCredits": self.agents.values().map(|state| json!({
+                    "agentSymbol": state.agent.symbol,
+                    "credits": state.agent.credits,
+                })).collect::<Vec<_>>(),
+                "mostSubmittedCharts": [],
+            },
+            "serverResets": {
+                "next": timestamp(now + chrono::Duration::days(7)),
+                "frequency": "weekly",
+            },
+            "announcements": [],
+            "links": [],
+        }))
+    }
+
+    pub fn register(&mut self, request: models::RegisterRequest) -> Result<Value> {
+        let symbol = request.symbol.to_uppercase();
+        if !(3..=14).contains(&symbol.len()) {
+            return Err(MockError::unprocessable(
+                "Agent symbol must be between 3 and 14 characters",
+            ));
+        }
+        if self.agents.contains_key(&symbol) {
+            return Err(MockError::new(
+                StatusCode::CONFLICT,
+                error_codes::REGISTER_AGENT_CONFLICT_SYMBOL_ERROR,
+                format!("Agent symbol {} has already been claimed", symbol),
+            ));
+        }
+
+        let token = self.next_id(&format!("mock-token-{}-", symbol.to_lowercase()));
+        let account_id = self.next_id("mockaccount");
+        self.tokens.insert(token.clone(), symbol.clone());
+        self.agents.insert(
+            symbol.clone(),
+            AgentState {
+                agent: models::Agent {
+                    account_id,
+                    symbol: symbol.clone(),
+                    headquarters: universe::HEADQUARTERS.to_string(),
+                    credits: universe::STARTING_CREDITS,
+                    starting_faction: request.faction.to_string(),
+                    ship_count: 0,
+                },
+                next_ship: 1,
+            },
+        );
+
+        let ships = vec![
+            self.build_ship(
+                &symbol,
+                models::ShipType::CommandFrigate,
+                universe::HEADQUARTERS,
+            )?,
+            self.build_ship(&symbol, models::ShipType::Probe, universe::HEADQUARTERS)?,
+        ];
+        let contract = self.new_contract(&symbol);
+
+        data(models::Register201ResponseData {
+            token,
+            agent: Box::new(self.agent(&symbol)?.clone()),
+            faction: Box::new(models::Faction {
+                symbol: request.faction,
+                name: universe::title(&request.faction.to_string()),
+                description: String::new(),
+                headquarters: Some(universe::HEADQUARTERS.to_string()),
+                traits: Vec::new(),
+                is_recruiting: true,
+            }),
+            contract: Box::new(contract),
+            ships,
+        })
+    }
+
+    pub fn supply_chain(&self) -> Result<Value> {
+        data(json!({ "exportToImportMap": self.supply_chain }))
+    }
+
+    // ---------------------------------------------------------------- agents
+
+    pub fn my_agent(&self, agent: &str) -> Result<Value> {
+        data(self.agent(agent)?)
+    }
+
+    pub fn public_agent(&self, symbol: &str) -> Result<Value> {
+        data(self.agent(symbol)?)
+    }
+
+    pub fn agents(&self, page: i32, limit: i32) -> Result<Value> {
+        paged(
+            self.agents
+                .values()
+                .map(|state| state.agent.clone())
+                .collect(),
+            page,
+            limit,
+        )
+    }
+
+    // ---------------------------------------------------------------- systems
+
+    pub fn systems(&self, page: i32, limit: i32) -> Result<Value> {
+        paged(self.systems.values().cloned().collect(), page, limit)
+    }
+
+    pub fn system(&self, symbol: &str) -> Result<Value> {
+        data(
+            self.systems
+                .get(symbol)
+                .ok_or_else(|| MockError::not_found(format!("System {} not found", symbol)))?,
+        )
+    }
+
+    pub fn waypoints(
+        &self,
+        system: &str,
+        page: i32,
+        limit: i32,
+        kind: Option<models::WaypointType>,
+        waypoint_trait: Option<models::WaypointTraitSymbol>,
+    ) -> Result<Value> {
+        if !self.systems.contains_key(system) {
+            return Err(MockError::not_found(format!("System {} not found", system)));
+        }
+        let waypoints = self
+            .waypoints
+            .values()
+            .filter(|waypoint| waypoint.system_symbol == system)
+            .map(|waypoint| self.visible_waypoint(waypoint))
+            .filter(|waypoint| kind.is_none_or(|kind| waypoint.r#type == kind))
+            .filter(|waypoint| {
+                waypoint_trait
+                    .is_none_or(|symbol| waypoint.traits.iter().any(|t| t.symbol == symbol))
+            })
+            .collect();
+        paged(waypoints, page, limit)
+    }
+
+    pub fn get_waypoint(&self, symbol: &str) -> Result<Value> {
+        data(self.visible_waypoint(self.waypoint(symbol)?))
+    }
+
+    pub fn market(&mut self, agent: Option<&str>, symbol: &str) -> Result<Value> {
+        if !self.waypoint_has(symbol, models::WaypointTraitSymbol::Marketplace) {
+            return Err(MockError::game(
+                error_codes::MARKET_NOT_FOUND_ERROR,
+                format!("Waypoint {} has no marketplace", symbol),
+            ));
+        }
+        let present = agent.is_some_and(|agent| self.has_ship_at(agent, symbol));
+        let now = Utc::now();
+        let time_scale = self.config.time_scale;
+        let market = self.markets.get_mut(symbol).unwrap();
+        for good in market.goods.iter_mut() {
+            good.decay(now, time_scale);
+        }
+        let of_kind = |kind: market_trade_good::Type| {
+            market
+                .goods
+                .iter()
+                .filter(|good| good.kind == kind)
+                .map(|good| trade_good(good.symbol))
+                .collect::<Vec<_>>()
+        };
+        data(models::Market {
+            symbol: symbol.to_string(),
+            exports: of_kind(market_trade_good::Type::Export),
+            imports: of_kind(market_trade_good::Type::Import),
+            exchange: of_kind(market_trade_good::Type::Exchange),
+            transactions: present.then(|| market.transactions.clone()),
+            trade_goods: present.then(|| market.goods.iter().map(MarketGood::to_model).collect()),
+        })
+    }
+
+    pub fn shipyard(&self, agent: Option<&str>, symbol: &str) -> Result<Value> {
+        let shipyard = self
+            .shipyards
+            .get(symbol)
+            .ok_or_else(|| MockError::not_found(format!("Waypoint {} has no shipyard", symbol)))?;
+        let present = agent.is_some_and(|agent| self.has_ship_at(agent, symbol));
+        data(models::Shipyard {
+            symbol: symbol.to_string(),
+            ship_types: shipyard
+                .ship_types
+                .iter()
+                .map(|ship_type| models::ShipyardShipTypesInner { r#type: *ship_type })
+                .collect(),
+            transactions: present.then(|| shipyard.transactions.clone()),
+            ships: present.then(|| {
+                shipyard
+                    .ship_types
+                    .iter()
+                    .map(|ship_type| {
+                        universe::ship_template(*ship_type)
+                            .to_shipyard_ship(models::SupplyLevel::Moderate)
+                    })
+                    .collect()
+            }),
+            modifications_fee: shipyard.modifications_fee,
+        })
+    }
+
+    pub fn jump_gate(&self, agent: Option<&str>, symbol: &str) -> Result<Value> {
+        let waypoint = self.waypoint(symbol)?;
+        let connections = self
+            .jump_gates
+            .get(symbol)
+            .ok_or_else(|| MockError::not_found(format!("Waypoint {} is no jump gate", symbol)))?;
+        let present = agent.is_some_and(|agent| self.has_ship_at(agent, symbol));
+        if waypoint.chart.is_none() && !present {
+            return Err(MockError::game(
+                error_codes::WAYPOINT_NO_ACCESS_ERROR,
+                format!("Jump gate {} is uncharted", symbol),
+            ));
+        }
+        data(models::JumpGate {
+            symbol: symbol.to_string(),
+            connections: connections.clone(),
+        })
+    }
+
+    pub fn construction(&self, symbol: &str) -> Result<Value> {
+        data(self.constructions.get(symbol).ok_or_else(|| {
+            MockError::not_found(format!("Waypoint {} has no construction site", symbol))
+        })?)
+    }
+
+    pub fn supply_construction(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::SupplyConstructionRequest,
+    ) -> Result<Value> {
+        let at = self
+            .ship_docked(agent, &request.ship_symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        if at != symbol {
+            return Err(MockError::game(
+                error_codes::SHIP_CONSTRUCTION_INVALID_LOCATION_ERROR,
+                format!("Ship must be docked at {}", symbol),
+            ));
+        }
+        let material = self
+            .constructions
+            .get(symbol)
+            .and_then(|construction| {
+                construction
+                    .materials
+                    .iter()
+                    .find(|material| material.trade_symbol == request.trade_symbol)
+            })
+            .ok_or_else(|| {
+                MockError::game(
+                    error_codes::CONSTRUCTION_MATERIAL_NOT_REQUIRED,
+                    format!("{} is not required here", request.trade_symbol),
+                )
+            })?;
+        if material.fulfilled + request.units > material.required {
+            return Err(MockError::game(
+                error_codes::CONSTRUCTION_MATERIAL_FULFILLED,
+                format!(
+                    "Only {} units are still required",
+                    material.required - material.fulfilled
+                ),
+            ));
+        }
+
+        let cargo = self.remove_cargo(
+            agent,
+            &request.ship_symbol,
+            request.trade_symbol,
+            request.units,
+        )?;
+
+        let construction = self.constructions.get_mut(symbol).unwrap();
+        for material in construction.materials.iter_mut() {
+            if material.trade_symbol == request.trade_symbol {
+                material.fulfilled += request.units;
+            }
+        }
+        construction.is_complete = construction
+            .materials
+            .iter()
+            .all(|material| material.fulfilled >= material.required);
+        let construction = construction.clone();
+
+        if construction.is_complete {
+            let waypoint = self.waypoints.get_mut(symbol).unwrap();
+            waypoint.is_under_construction = false;
+            waypoint
+                .traits
+                .retain(|t| t.symbol != models::WaypointTraitSymbol::UnderConstruction);
+        }
+
+        data(models::SupplyConstruction201ResponseData {
+            construction: Box::new(construction),
+            cargo: Box::new(cargo),
+        })
+    }
+
+    // ---------------------------------------------------------------- contracts
+
+    pub fn contracts(&self, agent: &str, page: i32, limit: i32) -> Result<Value> {
+        paged(
+            self.contracts
+                .values()
+                .filter(|(owner, _)| owner == agent)
+                .map(|(_, contract)| contract.clone())
+                .collect(),
+            page,
+            limit,
+        )
+    }
+
+    pub fn contract(&mut self, agent: &str, id: &str) -> Result<Value> {
+        data(self.contract_mut(agent, id)?.clone())
+    }
+
+    pub fn accept_contract(&mut self, agent: &str, id: &str) -> Result<Value> {
+        let contract = self.contract_mut(agent, id)?;
+        if contract.accepted {
+            return Err(MockError::game(
+                error_codes::ACCEPT_CONTRACT_CONFLICT_ERROR,
+                format!("Contract {} has already been accepted", id),
+            ));
+        }
+        contract.accepted = true;
+        let contract = contract.clone();
+        self.agent_mut(agent)?.credits += contract.terms.payment.on_accepted as i64;
+        data(models::AcceptContract200ResponseData {
+            contract: Box::new(contract),
+            agent: Box::new(self.agent(agent)?.clone()),
+        })
+    }
+
+    pub fn deliver_contract(
+        &mut self,
+        agent: &str,
+        id: &str,
+        request: models::DeliverContractRequest,
+    ) -> Result<Value> {
+        let at = self
+            .ship_docked(agent, &request.ship_symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let contract = self.contract_mut(agent, id)?;
+        if !contract.accepted {
+            return Err(MockError::game(
+                error_codes::CONTRACT_NOT_ACCEPTED_ERROR,
+                format!("Contract {} has not been accepted", id),
+            ));
+        }
+        let deliver = contract
+            .terms
+            .deliver
+            .iter()
+            .flatten()
+            .find(|deliver| deliver.trade_symbol == request.trade_symbol)
+            .ok_or_else(|| {
+                MockError::game(
+                    error_codes::SHIP_DELIVER_TERMS_ERROR,
+                    format!("Contract does not require {}", request.trade_symbol),
+                )
+            })?;
+        if deliver.destination_symbol != at {
+            return Err(MockError::game(
+                error_codes::SHIP_DELIVER_INVALID_LOCATION_ERROR,
+                format!("Goods must be delivered to {}", deliver.destination_symbol),
+            ));
+        }
+        if deliver.units_fulfilled + request.units > deliver.units_required {
+            return Err(MockError::game(
+                error_codes::SHIP_DELIVER_FULFILLED_ERROR,
+                "Delivery would exceed the required units",
+            ));
+        }
+        let trade_symbol = request
+            .trade_symbol
+            .parse::<TradeSymbol>()
+            .map_err(|_| MockError::unprocessable("Unknown trade symbol"))?;
+
+        let cargo = self.remove_cargo(agent, &request.ship_symbol, trade_symbol, request.units)?;
+        let contract = self.contract_mut(agent, id)?;
+        for deliver in contract.terms.deliver.iter_mut().flatten() {
+            if deliver.trade_symbol == request.trade_symbol {
+                deliver.units_fulfilled += request.units;
+            }
+        }
+        data(models::DeliverContract200ResponseData {
+            contract: Box::new(contract.clone()),
+            cargo: Box::new(cargo),
+        })
+    }
+
+    pub fn fulfill_contract(&mut self, agent: &str, id: &str) -> Result<Value> {
+        let contract = self.contract_mut(agent, id)?;
+        if contract.fulfilled {
+            return Err(MockError::game(
+                error_codes::CONTRACT_FULFILLED_ERROR,
+                format!("Contract {} has already been fulfilled", id),
+            ));
+        }
+        if !contract.accepted
+            || contract
+                .terms
+                .deliver
+                .iter()
+                .flatten()
+                .any(|deliver| deliver.units_fulfilled < deliver.units_required)
+        {
+            return Err(MockError::game(
+                error_codes::FULFILL_CONTRACT_DELIVERY_ERROR,
+                format!("Contract {} has not been delivered", id),
+            ));
+        }
+        contract.fulfilled = true;
+        let contract = contract.clone();
+        self.agent_mut(agent)?.credits += contract.terms.payment.on_fulfilled as i64;
+        data(models::AcceptContract200ResponseData {
+            contract: Box::new(contract),
+            agent: Box::new(self.agent(agent)?.clone()),
+        })
+    }
+
+    pub fn negotiate_contract(&mut self, agent: &str, ship: &str) -> Result<Value> {
+        let at = self
+            .ship_docked(agent, ship)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        if self.waypoint(&at)?.faction.is_none() {
+            return Err(MockError::game(
+                error_codes::WAYPOINT_NO_FACTION_ERROR,
+                format!("Waypoint {} has no faction", at),
+            ));
+        }
+        if self
+            .contracts
+            .values()
+            .any(|(owner, contract)| owner == agent && contract.accepted && !contract.fulfilled)
+        {
+            return Err(MockError::game(
+                error_codes::EXISTING_CONTRACT_ERROR,
+                "Agent already has an active contract",
+            ));
+        }
+        let contract = self.new_contract(agent);
+        data(models::NegotiateContract201ResponseData {
+            contract: Box::new(contract),
+        })
+    }
+
+    // ---------------------------------------------------------------- fleet
+
+    pub fn ships(&mut self, agent: &str, page: i32, limit: i32) -> Result<Value> {
+        let symbols = self
+            .ships
+            .values()
+            .filter(|state| state.owner == agent)
+            .map(|state| state.ship.symbol.clone())
+            .collect::<Vec<_>>();
+        let mut ships = Vec::new();
+        for symbol in symbols {
+            ships.push(self.ship(agent, &symbol)?.ship.clone());
+        }
+        paged(ships, page, limit)
+    }
+
+    pub fn get_ship(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        data(&self.ship(agent, symbol)?.ship)
+    }
+
+    pub fn ship_nav(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        data(&self.ship(agent, symbol)?.ship.nav)
+    }
+
+    pub fn ship_cargo(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        data(&self.ship(agent, symbol)?.ship.cargo)
+    }
+
+    pub fn ship_mounts(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        data(&self.ship(agent, symbol)?.ship.mounts)
+    }
+
+    pub fn ship_modules(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        data(&self.ship(agent, symbol)?.ship.modules)
+    }
+
+    /// `None` when the ship has no cooldown, answered with 204
+    pub fn ship_cooldown(&mut self, agent: &str, symbol: &str) -> Result<Option<Value>> {
+        let state = self.ship(agent, symbol)?;
+        if state.cooldown_until.is_none() {
+            return Ok(None);
+        }
+        data(&state.ship.cooldown).map(Some)
+    }
+
+    pub fn dock(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        let state = self.ship_not_in_transit(agent, symbol)?;
+        state.ship.nav.status = ShipNavStatus::Docked;
+        data(json!({ "nav": state.ship.nav }))
+    }
+
+    pub fn orbit(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        let state = self.ship_not_in_transit(agent, symbol)?;
+        state.ship.nav.status = ShipNavStatus::InOrbit;
+        data(json!({ "nav": state.ship.nav }))
+    }
+
+    pub fn patch_nav(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::PatchShipNavRequest,
+    ) -> Result<Value> {
+        let state = self.ship(agent, symbol)?;
+        if let Some(flight_mode) = request.flight_mode {
+            state.ship.nav.flight_mode = flight_mode;
+        }
+        data(models::NavigateShip200ResponseData {
+            nav: state.ship.nav.clone(),
+            fuel: state.ship.fuel.clone(),
+            events: Vec::new(),
+        })
+    }
+
+    /// Shared part of navigate and warp, moves the ship and burns fuel
+    fn travel(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        destination: &str,
+        distance: f64,
+    ) -> Result<Value> {
+        let destination = self.waypoint(destination)?.clone();
+        let now = Utc::now();
+        let (speed, flight_mode) = {
+            let state = self.ship(agent, symbol)?;
+            (state.ship.engine.speed.max(1), state.ship.nav.flight_mode)
+        };
+        let (fuel_cost, multiplier) = fuel_and_multiplier(flight_mode, distance);
+        let travel_seconds =
+            (distance.max(1.0).round() * (multiplier / speed as f64) + 15.0).round();
+        let arrival = now + self.scaled(travel_seconds);
+
+        let state = self.ship(agent, symbol)?;
+        if state.ship.fuel.capacity > 0 {
+            if state.ship.fuel.current < fuel_cost {
+                return Err(MockError::game(
+                    error_codes::NAVIGATE_INSUFFICIENT_FUEL_ERROR,
+                    format!(
+                        "Navigation requires {} fuel but only {} is available",
+                        fuel_cost, state.ship.fuel.current
+                    ),
+                ));
+            }
+            state.ship.fuel.current -= fuel_cost;
+            state.ship.fuel.consumed = Some(Box::new(models::ShipFuelConsumed {
+                amount: fuel_cost,
+                timestamp: timestamp(now),
+            }));
+        }
+
+        let origin = models::ShipNavRouteWaypoint {
+            symbol: state.ship.nav.waypoint_symbol.clone(),
+            r#type: state.ship.nav.route.destination.r#type,
+            system_symbol: state.ship.nav.system_symbol.clone(),
+            x: state.ship.nav.route.destination.x,
+            y: state.ship.nav.route.destination.y,
+        };
+        *state.ship.nav.route = models::ShipNavRoute {
+            destination: Box::new(models::ShipNavRouteWaypoint {
+                symbol: destination.symbol.clone(),
+                r#type: destination.r#type,
+                system_symbol: destination.system_symbol.clone(),
+                x: destination.x,
+                y: destination.y,
+            }),
+            origin: Box::new(origin),
+            departure_time: timestamp(now),
+            arrival: timestamp(arrival),
+        };
+        state.ship.nav.system_symbol = destination.system_symbol.clone();
+        state.ship.nav.waypoint_symbol = destination.symbol.clone();
+        state.ship.nav.status = ShipNavStatus::InTransit;
+
+        state.ship.frame.condition = (state.ship.frame.condition - NAVIGATION_WEAR).max(0.0);
+        state.ship.reactor.condition = (state.ship.reactor.condition - NAVIGATION_WEAR).max(0.0);
+        state.ship.engine.condition = (state.ship.engine.condition - NAVIGATION_WEAR).max(0.0);
+
+        data(models::NavigateShip200ResponseData {
+            nav: state.ship.nav.clone(),
+            fuel: state.ship.fuel.clone(),
+            events: Vec::new(),
+        })
+    }
+
+    pub fn navigate(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::NavigateShipRequest,
+    ) -> Result<Value> {
+        let (system, from) = {
+            let state = self.ship(agent, symbol)?;
+            if state.ship.nav.status == ShipNavStatus::InTransit {
+                return Err(MockError::game(
+                    error_codes::NAVIGATE_IN_TRANSIT_ERROR,
+                    format!("Ship {} is currently in transit", symbol),
+                ));
+            }
+            if state.ship.nav.status != ShipNavStatus::InOrbit {
+                return Err(MockError::game(
+                    error_codes::SHIP_NOT_IN_ORBIT_ERROR,
+                    format!("Ship {} must be in orbit", symbol),
+                ));
+            }
+            (
+                state.ship.nav.system_symbol.clone(),
+                state.ship.nav.waypoint_symbol.clone(),
+            )
+        };
+        let destination = self.waypoint(&request.waypoint_symbol)?;
+        if destination.system_symbol != system {
+            return Err(MockError::game(
+                error_codes::NAVIGATE_OUTSIDE_SYSTEM_ERROR,
+                "Destination is outside of the current system",
+            ));
+        }
+        if destination.symbol == from {
+            return Err(MockError::game(
+                error_codes::NAVIGATE_SAME_DESTINATION_ERROR,
+                "Ship is already at the destination",
+            ));
+        }
+        let from = self.waypoint(&from)?;
+        let distance = distance((from.x, from.y), (destination.x, destination.y));
+        self.travel(agent, symbol, &request.waypoint_symbol, distance)
+    }
+
+    pub fn warp(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::NavigateShipRequest,
+    ) -> Result<Value> {
+        let system = {
+            let state = self.ship_in_orbit(agent, symbol)?;
+            if !state.ship.modules.iter().any(|module| {
+                matches!(
+                    module.symbol,
+                    ship_module::Symbol::WarpDriveI
+                        | ship_module::Symbol::WarpDriveIi
+                        | ship_module::Symbol::WarpDriveIii
+                )
+            }) {
+                return Err(MockError::game(
+                    error_codes::SHIP_MISSING_WARP_DRIVE_ERROR,
+                    format!("Ship {} has no warp drive", symbol),
+                ));
+            }
+            state.ship.nav.system_symbol.clone()
+        };
+        let destination = self.waypoint(&request.waypoint_symbol)?;
+        if destination.system_symbol == system {
+            return Err(MockError::game(
+                error_codes::WARP_INSIDE_SYSTEM_ERROR,
+                "Use navigate to travel inside a system",
+            ));
+        }
+        let distance = distance(
+            self.system_position(&system),
+            self.system_position(&destination.system_symbol),
+        );
+        self.travel(agent, symbol, &request.waypoint_symbol, distance)
+    }
+
+    pub fn jump(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::JumpShipRequest,
+    ) -> Result<Value> {
+        let from = self
+            .ship_ready(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let connections = self.jump_gates.get(&from).ok_or_else(|| {
+            MockError::game(
+                error_codes::SHIP_JUMP_INVALID_ORIGIN_ERROR,
+                format!("Ship {} is not at a jump gate", symbol),
+            )
+        })?;
+        if !connections.contains(&request.waypoint_symbol) {
+            return Err(MockError::game(
+                error_codes::SHIP_JUMP_INVALID_WAYPOINT_ERROR,
+                format!("{} is not connected to {}", request.waypoint_symbol, from),
+            ));
+        }
+        if self.waypoint(&from)?.is_under_construction {
+            return Err(MockError::game(
+                error_codes::SHIP_JUMP_ORIGIN_UNDER_CONSTRUCTION_ERROR,
+                format!("Jump gate {} is under construction", from),
+            ));
+        }
+        let destination = self.waypoint(&request.waypoint_symbol)?.clone();
+        if destination.is_under_construction {
+            return Err(MockError::game(
+                error_codes::SHIP_JUMP_DESTINATION_UNDER_CONSTRUCTION_ERROR,
+                format!("Jump gate {} is under construction", destination.symbol),
+            ));
+        }
+        self.pay(
+            agent,
+            JUMP_ANTIMATTER_PRICE as i64,
+            error_codes::AGENT_INSUFFICIENT_CREDITS_ERROR,
+        )?;
+
+        let from_system = self.waypoint(&from)?.system_symbol.clone();
+        let distance = distance(
+            self.system_position(&from_system),
+            self.system_position(&destination.system_symbol),
+        );
+        let now = timestamp(Utc::now());
+        let state = self.ship(agent, symbol)?;
+        let origin = (*state.ship.nav.route.destination).clone();
+        *state.ship.nav.route = models::ShipNavRoute {
+            destination: Box::new(models::ShipNavRouteWaypoint {
+                symbol: destination.symbol.clone(),
+                r#type: destination.r#type,
+                system_symbol: destination.system_symbol.clone(),
+                x: destination.x,
+                y: destination.y,
+            }),
+            origin: Box::new(origin),
+            departure_time: now.clone(),
+            arrival: now,
+        };
+        state.ship.nav.system_symbol = destination.system_symbol.clone();
+        state.ship.nav.waypoint_symbol = destination.symbol.clone();
+        let nav = state.ship.nav.clone();
+
+        let cooldown = self.start_cooldown(symbol, (15.0 + 0.3 * distance).round());
+        let transaction = models::MarketTransaction {
+            waypoint_symbol: from,
+            ship_symbol: symbol.to_string(),
+            trade_symbol: TradeSymbol::Antimatter.to_string(),
+            r#type: models::market_transaction::Type::Purchase,
+            units: 1,
+            price_per_unit: JUMP_ANTIMATTER_PRICE,
+            total_price: JUMP_ANTIMATTER_PRICE,
+            timestamp: timestamp(Utc::now()),
+        };
+        data(models::JumpShip200ResponseData {
+            nav,
+            cooldown: Box::new(cooldown),
+            transaction: Box::new(transaction),
+            agent: Box::new(self.agent(agent)?.clone()),
+        })
+    }
+
+    pub fn refuel(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::RefuelShipRequest,
+    ) -> Result<Value> {
+        let from_cargo = request.from_cargo.unwrap_or(false);
+        let (at, missing) = {
+            let state = self.ship_docked(agent, symbol)?;
+            (
+                state.ship.nav.waypoint_symbol.clone(),
+                state.ship.fuel.capacity - state.ship.fuel.current,
+            )
+        };
+        let units = request.units.unwrap_or(missing).min(missing).max(0);
+        // market units are sold in steps of 100 fuel
+        let market_units = (units + 99) / 100;
+
+        let (transaction, cargo) = if from_cargo {
+            let cargo = self.remove_cargo(agent, symbol, TradeSymbol::Fuel, market_units)?;
+            let transaction = models::MarketTransaction {
+                waypoint_symbol: at,
+                ship_symbol: symbol.to_string(),
+                trade_symbol: TradeSymbol::Fuel.to_string(),
+                r#type: models::market_transaction::Type::Purchase,
+                units,
+                price_per_unit: 0,
+                total_price: 0,
+                timestamp: timestamp(Utc::now()),
+            };
+            (transaction, Some(Box::new(cargo)))
+        } else {
+            let price = self
+                .market_good(&at, TradeSymbol::Fuel)
+                .map(|good| good.purchase_price())
+                .ok_or_else(|| {
+                    MockError::game(
+                        error_codes::SHIP_REFUEL_INVALID_WAYPOINT_ERROR,
+                        format!("Waypoint {} does not sell fuel", at),
+                    )
+                })?;
+            self.pay(
+                agent,
+                (market_units * price) as i64,
+                error_codes::MARKET_TRADE_INSUFFICIENT_CREDITS_ERROR,
+            )?;
+            let transaction = self.record_transaction(
+                &at,
+                symbol,
+                TradeSymbol::Fuel,
+                models::market_transaction::Type::Purchase,
+                market_units,
+                price,
+            );
+            (transaction, None)
+        };
+
+        let state = self.ship(agent, symbol)?;
+        state.ship.fuel.current = (state.ship.fuel.current + units).min(state.ship.fuel.capacity);
+        let fuel = state.ship.fuel.clone();
+        data(models::RefuelShip200ResponseData {
+            agent: Box::new(self.agent(agent)?.clone()),
+            fuel,
+            cargo,
+            transaction: Box::new(transaction),
+        })
+    }
+
+    fn add_cargo(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        good: TradeSymbol,
+        units: i32,
+    ) -> Result<models::ShipCargo> {
+        let cargo = &mut self.ship(agent, symbol)?.ship.cargo;
+        if cargo.units + units > cargo.capacity {
+            return Err(MockError::game(
+                error_codes::SHIP_CARGO_EXCEEDS_LIMIT_ERROR,
+                format!(
+                    "Ship {} cannot hold {} more units, {} of {} used",
+                    symbol, units, cargo.units, cargo.capacity
+                ),
+            ));
+        }
+        match cargo.inventory.iter_mut().find(|item| item.symbol == good) {
+            Some(item) => item.units += units,
+            None => cargo.inventory.push(models::ShipCargoItem {
+                symbol: good,
+                name: universe::title(&good.to_string()),
+                description: String::new(),
+                units,
+            }),
+        }
+        cargo.units += units;
+        Ok((**cargo).clone())
+    }
+
+    fn remove_cargo(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        good: TradeSymbol,
+        units: i32,
+    ) -> Result<models::ShipCargo> {
+        let cargo = &mut self.ship(agent, symbol)?.ship.cargo;
+        let item = cargo
+            .inventory
+            .iter_mut()
+            .find(|item| item.symbol == good)
+            .ok_or_else(|| {
+                MockError::game(
+                    error_codes::SHIP_CARGO_MISSING_ERROR,
+                    format!("Ship {} has no {} in its cargo", symbol, good),
+                )
+            })?;
+        if item.units < units || units < 0 {
+            return Err(MockError::game(
+                error_codes::SHIP_CARGO_UNIT_COUNT_ERROR,
+                format!("Ship {} only has {} units of {}", symbol, item.units, good),
+            ));
+        }
+        item.units -= units;
+        cargo.units -= units;
+        cargo.inventory.retain(|item| item.units > 0);
+        Ok((**cargo).clone())
+    }
+
+    pub fn purchase_cargo(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::PurchaseCargoRequest,
+    ) -> Result<Value> {
+        let at = self
+            .ship_docked(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let (price, trade_volume) = self
+            .market_good(&at, request.symbol)
+            .map(|good| (good.purchase_price(), good.trade_volume))
+            .ok_or_else(|| {
+                MockError::game(
+                    error_codes::MARKET_TRADE_NO_PURCHASE_ERROR,
+                    format!("{} is not sold at {}", request.symbol, at),
+                )
+            })?;
+        if request.units > trade_volume || request.units <= 0 {
+            return Err(MockError::game(
+                error_codes::MARKET_TRADE_UNIT_LIMIT_ERROR,
+                format!("Market only trades {} units at once", trade_volume),
+            ));
+        }
+        {
+            let cargo = &self.ship(agent, symbol)?.ship.cargo;
+            if cargo.units + request.units > cargo.capacity {
+                return Err(MockError::game(
+                    error_codes::SHIP_CARGO_EXCEEDS_LIMIT_ERROR,
+                    format!("Ship {} has not enough cargo space", symbol),
+                ));
+            }
+        }
+        self.pay(
+            agent,
+            (price * request.units) as i64,
+            error_codes::MARKET_TRADE_INSUFFICIENT_CREDITS_ERROR,
+        )?;
+        let cargo = self.add_cargo(agent, symbol, request.symbol, request.units)?;
+        if let Some(good) = self.market_good(&at, request.symbol) {
+            good.trade(request.units);
+        }
+        let transaction = self.record_transaction(
+            &at,
+            symbol,
+            request.symbol,
+            models::market_transaction::Type::Purchase,
+            request.units,
+            price,
+        );
+        data(models::PurchaseCargo201ResponseData {
+            cargo: Box::new(cargo),
+            transaction: Box::new(transaction),
+            agent: Box::new(self.agent(agent)?.clone()),
+        })
+    }
+
+    pub fn sell_cargo(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::SellCargoRequest,
+    ) -> Result<Value> {
+        let at = self
+            .ship_docked(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let (price, trade_volume) = self
+            .market_good(&at, request.symbol)
+            .map(|good| (good.sell_price(), good.trade_volume))
+            .ok_or_else(|| {
+                MockError::game(
+                    error_codes::MARKET_TRADE_NOT_SOLD_ERROR,
+                    format!("{} is not bought at {}", request.symbol, at),
+                )
+            })?;
+        if request.units > trade_volume || request.units <= 0 {
+            return Err(MockError::game(
+                error_codes::MARKET_TRADE_UNIT_LIMIT_ERROR,
+                format!("Market only trades {} units at once", trade_volume),
+            ));
+        }
+        let cargo = self.remove_cargo(agent, symbol, request.symbol, request.units)?;
+        self.agent_mut(agent)?.credits += (price * request.units) as i64;
+        if let Some(good) = self.market_good(&at, request.symbol) {
+            good.trade(-request.units);
+        }
+        let transaction = self.record_transaction(
+            &at,
+            symbol,
+            request.symbol,
+            models::market_transaction::Type::Sell,
+            request.units,
+            price,
+        );
+        data(models::PurchaseCargo201ResponseData {
+            cargo: Box::new(cargo),
+            transaction: Box::new(transaction),
+            agent: Box::new(self.agent(agent)?.clone()),
+        })
+    }
+
+    pub fn jettison(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::JettisonRequest,
+    ) -> Result<Value> {
+        self.ship_not_in_transit(agent, symbol)?;
+        let cargo = self.remove_cargo(agent, symbol, request.symbol, request.units)?;
+        data(models::Jettison200ResponseData {
+            cargo: Box::new(cargo),
+        })
+    }
+
+    pub fn transfer_cargo(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::TransferCargoRequest,
+    ) -> Result<Value> {
+        if symbol == request.ship_symbol {
+            return Err(MockError::game(
+                error_codes::SHIP_TRANSFER_SAME_SHIP_CONFLICT,
+                "Cannot transfer cargo to the same ship",
+            ));
+        }
+        let (from_at, from_status) = {
+            let state = self.ship_not_in_transit(agent, symbol)?;
+            (
+                state.ship.nav.waypoint_symbol.clone(),
+                state.ship.nav.status,
+            )
+        };
+        let (to_at, to_status) = {
+            let state = self
+                .ship_not_in_transit(agent, &request.ship_symbol)
+                .map_err(|err| match err.code {
+                    404 => MockError::game(
+                        error_codes::SHIP_TRANSFER_SHIP_NOT_FOUND,
+                        format!("Ship {} not found", request.ship_symbol),
+                    ),
+                    _ => err,
+                })?;
+            (
+                state.ship.nav.waypoint_symbol.clone(),
+                state.ship.nav.status,
+            )
+        };
+        if from_at != to_at {
+            return Err(MockError::game(
+                error_codes::SHIP_TRANSFER_LOCATION_CONFLICT,
+                "Both ships must be at the same waypoint",
+            ));
+        }
+        if from_status != to_status {
+            return Err(MockError::game(
+                error_codes::SHIP_TRANSFER_DOCKED_ORBIT_CONFLICT,
+                "Both ships must either be docked or in orbit",
+            ));
+        }
+        {
+            let target = &self.ship(agent, &request.ship_symbol)?.ship.cargo;
+            if target.units + request.units > target.capacity {
+                return Err(MockError::game(
+                    error_codes::SHIP_CARGO_EXCEEDS_LIMIT_ERROR,
+                    format!("Ship {} has not enough cargo space", request.ship_symbol),
+                ));
+            }
+        }
+        let cargo = self.remove_cargo(agent, symbol, request.trade_symbol, request.units)?;
+        let target_cargo = self.add_cargo(
+            agent,
+            &request.ship_symbol,
+            request.trade_symbol,
+            request.units,
+        )?;
+        data(models::TransferCargo200ResponseData {
+            cargo: Box::new(cargo),
+            target_cargo: Box::new(target_cargo),
+        })
+    }
+
+    fn mount_strength(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        kinds: &[ship_mount::Symbol],
+    ) -> Result<i32> {
+        Ok(self
+            .ship(agent, symbol)?
+            .ship
+            .mounts
+            .iter()
+            .filter(|mount| kinds.contains(&mount.symbol))
+            .map(|mount| mount.strength.unwrap_or(1))
+            .sum())
+    }
+
+    pub fn extract(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        survey: Option<models::Survey>,
+    ) -> Result<Value> {
+        use ship_mount::Symbol as Mt;
+        let at = self
+            .ship_ready(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let strength = self.mount_strength(
+            agent,
+            symbol,
+            &[Mt::MiningLaserI, Mt::MiningLaserIi, Mt::MiningLaserIii],
+        )?;
+        if strength == 0 {
+            return Err(MockError::game(
+                error_codes::SHIP_MISSING_MINING_LASERS_ERROR,
+                format!("Ship {} has no mining lasers", symbol),
+            ));
+        }
+        let mut pool = universe::deposits(self.waypoint(&at)?);
+        if pool.is_empty() {
+            return Err(MockError::game(
+                error_codes::SHIP_EXTRACT_INVALID_WAYPOINT_ERROR,
+                format!("Waypoint {} has no deposits", at),
+            ));
+        }
+
+        if let Some(survey) = survey {
+            let state = self.surveys.get_mut(&survey.signature).ok_or_else(|| {
+                MockError::game(
+                    error_codes::SHIP_SURVEY_VERIFICATION_ERROR,
+                    format!("Survey {} is not valid", survey.signature),
+                )
+            })?;
+            if state.survey.symbol != at {
+                return Err(MockError::game(
+                    error_codes::SHIP_EXTRACT_INVALID_SURVEY_LOCATION_ERROR,
+                    format!("Survey is for {}", state.survey.symbol),
+                ));
+            }
+            if parse_time(&state.survey.expiration)
+                .is_none_or(|expiration| expiration <= Utc::now())
+            {
+                return Err(MockError::game(
+                    error_codes::SHIP_SURVEY_EXPIRATION_ERROR,
+                    format!("Survey {} has expired", survey.signature),
+                ));
+            }
+            if state.remaining <= 0 {
+                return Err(MockError::game(
+                    error_codes::SHIP_SURVEY_EXHAUSTED_ERROR,
+                    format!("Survey {} has been exhausted", survey.signature),
+                ));
+            }
+            state.remaining -= 1;
+            pool = state
+                .survey
+                .deposits
+                .iter()
+                .map(|deposit| deposit.symbol)
+                .collect();
+        }
+
+        let free = {
+            let cargo = &self.ship(agent, symbol)?.ship.cargo;
+            cargo.capacity - cargo.units
+        };
+        if free <= 0 {
+            return Err(MockError::game(
+                error_codes::SHIP_CARGO_FULL_ERROR,
+                format!("Ship {} cargo is full", symbol),
+            ));
+        }
+
+        let good = pool[self.random(pool.len() as u64) as usize];
+        let units = (strength + self.random(strength as u64 + 1) as i32).min(free);
+        let cargo = self.add_cargo(agent, symbol, good, units)?;
+        let cooldown = self.start_cooldown(symbol, 70.0);
+        data(models::ExtractResources201ResponseData {
+            extraction: Box::new(models::Extraction {
+                ship_symbol: symbol.to_string(),
+                r#yield: Box::new(models::ExtractionYield {
+                    symbol: good,
+                    units,
+                }),
+            }),
+            cooldown: Box::new(cooldown),
+            cargo: Box::new(cargo),
+            modifiers: None,
+            events: Vec::new(),
+        })
+    }
+
+    pub fn siphon(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        use ship_mount::Symbol as Mt;
+        let at = self
+            .ship_ready(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let strength = self.mount_strength(
+            agent,
+            symbol,
+            &[Mt::GasSiphonI, Mt::GasSiphonIi, Mt::GasSiphonIii],
+        )?;
+        if strength == 0 {
+            return Err(MockError::game(
+                error_codes::SHIP_MISSING_GAS_SIPHONS_ERROR,
+                format!("Ship {} has no gas siphons", symbol),
+            ));
+        }
+        let pool = universe::siphon_yields(self.waypoint(&at)?);
+        if pool.is_empty() {
+            return Err(MockError::game(
+                error_codes::SHIP_SIPHON_INVALID_WAYPOINT_ERROR,
+                format!("Waypoint {} can not be siphoned", at),
+            ));
+        }
+        let free = {
+            let cargo = &self.ship(agent, symbol)?.ship.cargo;
+            cargo.capacity - cargo.units
+        };
+        if free <= 0 {
+            return Err(MockError::game(
+                error_codes::SHIP_CARGO_FULL_ERROR,
+                format!("Ship {} cargo is full", symbol),
+            ));
+        }
+        let good = pool[self.random(pool.len() as u64) as usize];
+        let units = (strength / 2 + self.random(strength as u64 / 2 + 1) as i32)
+            .max(1)
+            .min(free);
+        let cargo = self.add_cargo(agent, symbol, good, units)?;
+        let cooldown = self.start_cooldown(symbol, 70.0);
+        data(models::SiphonResources201ResponseData {
+            siphon: Box::new(models::Siphon {
+                ship_symbol: symbol.to_string(),
+                r#yield: Box::new(models::SiphonYield {
+                    symbol: good,
+                    units,
+                }),
+            }),
+            cooldown: Box::new(cooldown),
+            cargo: Box::new(cargo),
+            events: Vec::new(),
+        })
+    }
+
+    pub fn survey(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        use ship_mount::Symbol as Mt;
+        let at = self
+            .ship_ready(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let strength = self.mount_strength(
+            agent,
+            symbol,
+            &[Mt::SurveyorI, Mt::SurveyorIi, Mt::SurveyorIii],
+        )?;
+        if strength == 0 {
+            return Err(MockError::game(
+                error_codes::SHIP_MISSING_SURVEYOR_ERROR,
+                format!("Ship {} has no surveyor", symbol),
+            ));
+        }
+        let pool = universe::deposits(self.waypoint(&at)?);
+        if pool.is_empty() {
+            return Err(MockError::game(
+                error_codes::SHIP_SURVEY_WAYPOINT_TYPE_ERROR,
+                format!("Waypoint {} can not be surveyed", at),
+            ));
+        }
+
+        let mut surveys = Vec::new();
+        for _ in 0..strength {
+            let (size, remaining) = match self.random(10) {
+                0..=5 => (models::SurveySize::Small, 10),
+                6..=8 => (models::SurveySize::Moderate, 25),
+                _ => (models::SurveySize::Large, 50),
+            };
+            let count = 3 + self.random(5) as usize;
+            let deposits = (0..count)
+                .map(|_| models::SurveyDeposit {
+                    symbol: pool[self.random(pool.len() as u64) as usize],
+                })
+                .collect();
+            let survey = models::Survey {
+                signature: self.next_id(&format!("{}-", at)),
+                symbol: at.clone(),
+                deposits,
+                expiration: timestamp(
+                    Utc::now() + chrono::Duration::seconds(SURVEY_LIFETIME_SECONDS),
+                ),
+                size,
+            };
+            self.surveys.insert(
+                survey.signature.clone(),
+                SurveyState {
+                    survey: survey.clone(),
+                    remaining,
+                },
+            );
+            surveys.push(survey);
+        }
+
+        let cooldown = self.start_cooldown(symbol, 60.0);
+        data(models::CreateSurvey201ResponseData {
+            cooldown: Box::new(cooldown),
+            surveys,
+        })
+    }
+
+    pub fn refine(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        request: models::ShipRefineRequest,
+    ) -> Result<Value> {
+        const INPUT_UNITS: i32 = 30;
+        const OUTPUT_UNITS: i32 = 10;
+        let produce: TradeSymbol =
+            serde_json::from_value(serde_json::to_value(request.produce).unwrap())
+                .map_err(|_| MockError::unprocessable("Unknown refinery good"))?;
+
+        let modules = self.ship_ready(agent, symbol)?.ship.modules.clone();
+        let has_module = |module_symbol: ship_module::Symbol| {
+            modules.iter().any(|module| module.symbol == module_symbol)
+        };
+        let allowed = match produce {
+            TradeSymbol::Fuel => has_module(ship_module::Symbol::FuelRefineryI),
+            _ => has_module(ship_module::Symbol::OreRefineryI),
+        };
+        if !allowed {
+            let code = if has_module(ship_module::Symbol::FuelRefineryI)
+                || has_module(ship_module::Symbol::OreRefineryI)
+            {
+                error_codes::SHIP_INVALID_REFINERY_TYPE_ERROR
+            } else {
+                error_codes::SHIP_MISSING_REFINERY_ERROR
+            };
+            return Err(MockError::game(
+                code,
+                format!("Ship {} can not refine {}", symbol, produce),
+            ));
+        }
+        let input = universe::refinery_inputs()
+            .into_iter()
+            .find(|(_, output)| *output == produce)
+            .map(|(input, _)| input)
+            .ok_or_else(|| {
+                MockError::game(
+                    error_codes::SHIP_INVALID_REFINERY_GOOD_ERROR,
+                    format!("{} can not be refined", produce),
+                )
+            })?;
+
+        self.remove_cargo(agent, symbol, input, INPUT_UNITS)?;
+        let cargo = self.add_cargo(agent, symbol, produce, OUTPUT_UNITS)?;
+        let cooldown = self.start_cooldown(symbol, 30.0);
+        data(models::ShipRefine201ResponseData {
+            cargo: Box::new(cargo),
+            cooldown: Box::new(cooldown),
+            produced: vec![models::ShipRefine201ResponseDataProducedInner {
+                trade_symbol: produce,
+                units: OUTPUT_UNITS,
+            }],
+            consumed: vec![models::ShipRefine201ResponseDataProducedInner {
+                trade_symbol: input,
+                units: INPUT_UNITS,
+            }],
+        })
+    }
+
+    pub fn chart(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        let at = self
+            .ship_not_in_transit(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        let now = timestamp(Utc::now());
+        let waypoint = self.waypoints.get_mut(&at).unwrap();
+        if waypoint.chart.is_some() {
+            return Err(MockError::game(
+                error_codes::WAYPOINT_CHARTED_ERROR,
+                format!("Waypoint {} is already charted", at),
+            ));
+        }
+        let chart = models::Chart {
+            waypoint_symbol: at.clone(),
+            submitted_by: agent.to_string(),
+            submitted_on: now.clone(),
+        };
+        waypoint.chart = Some(Box::new(chart.clone()));
+        let waypoint = waypoint.clone();
+        data(models::CreateChart201ResponseData {
+            chart: Box::new(chart),
+            waypoint: Box::new(waypoint),
+            transaction: Box::new(models::ChartTransaction {
+                waypoint_symbol: at,
+                ship_symbol: symbol.to_string(),
+                total_price: 0,
+                timestamp: now,
+            }),
+            agent: Box::new(self.agent(agent)?.clone()),
+        })
+    }
+
+    // ---------------------------------------------------------------- shipyard
+
+    pub fn purchase_ship(
+        &mut self,
+        agent: &str,
+        request: models::PurchaseShipRequest,
+    ) -> Result<Value> {
+        let shipyard = self
+            .shipyards
+            .get(&request.waypoint_symbol)
+            .ok_or_else(|| {
+                MockError::not_found(format!(
+                    "Waypoint {} has no shipyard",
+                    request.waypoint_symbol
+                ))
+            })?;
+        if !shipyard.ship_types.contains(&request.ship_type) {
+            return Err(MockError::game(
+                error_codes::SHIP_NOT_AVAILABLE_FOR_PURCHASE_ERROR,
+                format!(
+                    "{} is not sold at {}",
+                    request.ship_type, request.waypoint_symbol
+                ),
+            ));
+        }
+        if !self.has_ship_at(agent, &request.waypoint_symbol) {
+            return Err(MockError::game(
+                error_codes::PURCHASE_SHIP_NOT_PRESENT_ERROR,
+                format!("Agent has no ship at {}", request.waypoint_symbol),
+            ));
+        }
+        let price = universe::ship_template(request.ship_type).price;
+        self.pay(
+            agent,
+            price as i64,
+            error_codes::PURCHASE_SHIP_CREDITS_ERROR,
+        )?;
+        let ship = self.build_ship(agent, request.ship_type, &request.waypoint_symbol)?;
+        let transaction = models::ShipyardTransaction {
+            waypoint_symbol: request.waypoint_symbol.clone(),
+            ship_symbol: ship.symbol.clone(),
+            ship_type: request.ship_type.to_string(),
+            price,
+            agent_symbol: agent.to_string(),
+            timestamp: timestamp(Utc::now()),
+        };
+        self.shipyards
+            .get_mut(&request.waypoint_symbol)
+            .unwrap()
+            .transactions
+            .push(transaction.clone());
+        data(models::PurchaseShip201ResponseData {
+            ship: Box::new(ship),
+            agent: Box::new(self.agent(agent)?.clone()),
+            transaction: Box::new(transaction),
+        })
+    }
+
+    /// Shipyard the ship is docked at, for repairs, scrapping and modifications
+    fn docked_shipyard(&mut self, agent: &str, symbol: &str, code: u32) -> Result<String> {
+        let at = self
+            .ship_docked(agent, symbol)?
+            .ship
+            .nav
+            .waypoint_symbol
+            .clone();
+        if !self.shipyards.contains_key(&at) {
+            return Err(MockError::game(
+                code,
+                format!("Waypoint {} has no shipyard", at),
+            ));
+        }
+        Ok(at)
+    }
+
+    fn repair_price(&mut self, agent: &str, symbol: &str) -> Result<i32> {
+        let state = self.ship(agent, symbol)?;
+        let price = universe::ship_template(state.ship_type).price as f64;
+        let wear = 3.0
+            - state.ship.frame.condition
+            - state.ship.reactor.condition
+            - state.ship.engine.condition;
+        Ok((price * 0.1 * wear).ceil() as i32)
+    }
+
+    fn scrap_price(&mut self, agent: &str, symbol: &str) -> Result<i32> {
+        let state = self.ship(agent, symbol)?;
+        let price = universe::ship_template(state.ship_type).price as f64;
+        let condition = (state.ship.frame.condition
+            + state.ship.reactor.condition
+            + state.ship.engine.condition)
+            / 3.0;
+        Ok((price * 0.3 * condition).floor() as i32)
+    }
+
+    fn transaction(&self, waypoint: &str, ship: &str, total_price: i32) -> Value {
+        json!({
+            "waypointSymbol": waypoint,
+            "shipSymbol": ship,
+            "totalPrice": total_price,
+            "timestamp": timestamp(Utc::now()),
+        })
+    }
+
+    pub fn get_repair(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        let at =
+            self.docked_shipyard(agent, symbol, error_codes::SHIP_REPAIR_INVALID_TRAIT_ERROR)?;
+        let price = self.repair_price(agent, symbol)?;
+        data(json!({ "transaction": self.transaction(&at, symbol, price) }))
+    }
+
+    pub fn repair(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        let at =
+            self.docked_shipyard(agent, symbol, error_codes::SHIP_REPAIR_INVALID_TRAIT_ERROR)?;
+        let price = self.repair_price(agent, symbol)?;
+        self.pay(
+            agent,
+            price as i64,
+            error_codes::AGENT_INSUFFICIENT_CREDITS_ERROR,
+        )?;
+        let state = self.ship(agent, symbol)?;
+        state.ship.frame.condition = 1.0;
+        state.ship.reactor.condition = 1.0;
+        state.ship.engine.condition = 1.0;
+        let ship = state.ship.clone();
+        data(json!({
+            "agent": self.agent(agent)?,
+            "ship": ship,
+            "transaction": self.transaction(&at, symbol, price),
+        }))
+    }
+
+    pub fn get_scrap(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        let at =
+            self.docked_shipyard(agent, symbol, error_codes::SHIP_SCRAP_INVALID_TRAIT_ERROR)?;
+        let price = self.scrap_price(agent, symbol)?;
+        data(json!({ "transaction": self.transaction(&at, symbol, price) }))
+    }
+
+    pub fn scrap(&mut self, agent: &str, symbol: &str) -> Result<Value> {
+        let at =
+            self.docked_shipyard(agent, symbol, error_codes::SHIP_SCRAP_INVALID_TRAIT_ERROR)?;
+        let price = self.scrap_price(agent, symbol)?;
+        self.ships.remove(symbol);
+        let agent_model = self.agent_mut(agent)?;
+        agent_model.credits += price as i64;
+        agent_model.ship_count -= 1;
+        data(json!({
+            "agent": self.agent(agent)?,
+            "transaction": self.transaction(&at, symbol, price),
+        }))
+    }
+
+    fn modification(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        no_shipyard: u32,
+        insufficient_credits: u32,
+    ) -> Result<(String, i32)> {
+        let at = self.docked_shipyard(agent, symbol, no_shipyard)?;
+        let fee = self.shipyards[&at].modifications_fee;
+        self.pay(agent, fee as i64, insufficient_credits)?;
+        Ok((at, fee))
+    }
+
+    fn modification_response(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        at: &str,
+        trade_symbol: TradeSymbol,
+        fee: i32,
+        field: &str,
+    ) -> Result<Value> {
+        let state = self.ship(agent, symbol)?;
+        let installed = match field {
+            "mounts" => serde_json::to_value(&state.ship.mounts).unwrap(),
+            _ => serde_json::to_value(&state.ship.modules).unwrap(),
+        };
+        let cargo = state.ship.cargo.clone();
+        let mut response = json!({
+            "agent": self.agent(agent)?,
+            "cargo": cargo,
+            "transaction": models::ShipModificationTransaction {
+                waypoint_symbol: at.to_string(),
+                ship_symbol: symbol.to_string(),
+                trade_symbol: trade_symbol.to_string(),
+                total_price: fee,
+                timestamp: timestamp(Utc::now()),
+            },
+        });
+        response[field] = installed;
+        data(response)
+    }
+
+    fn parse_symbol<S: serde::de::DeserializeOwned>(symbol: &str) -> Result<S> {
+        serde_json::from_value(Value::String(symbol.to_string()))
+            .map_err(|_| MockError::unprocessable(format!("Unknown symbol {}", symbol)))
+    }
+
+    pub fn install_mount(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        mount_symbol: &str,
+    ) -> Result<Value> {
+        let mount: ship_mount::Symbol = Self::parse_symbol(mount_symbol)?;
+        let trade_symbol: TradeSymbol = Self::parse_symbol(mount_symbol)?;
+        {
+            let state = self.ship_docked(agent, symbol)?;
+            if state.ship.mounts.len() as i32 >= state.ship.frame.mounting_points {
+                return Err(MockError::game(
+                    error_codes::SHIP_MISSING_MOUNTS_ERROR,
+                    format!("Ship {} has no free mounting point", symbol),
+                ));
+            }
+        }
+        let (at, fee) = self.modification(
+            agent,
+            symbol,
+            error_codes::SHIP_MOUNT_NO_SHIPYARD_ERROR,
+            error_codes::SHIP_MOUNT_INSUFFICIENT_CREDITS_ERROR,
+        )?;
+        if let Err(err) = self.remove_cargo(agent, symbol, trade_symbol, 1) {
+            self.agent_mut(agent)?.credits += fee as i64;
+            return Err(err);
+        }
+        self.ship(agent, symbol)?
+            .ship
+            .mounts
+            .push(universe::mount(mount));
+        self.modification_response(agent, symbol, &at, trade_symbol, fee, "mounts")
+    }
+
+    pub fn remove_mount(&mut self, agent: &str, symbol: &str, mount_symbol: &str) -> Result<Value> {
+        let mount: ship_mount::Symbol = Self::parse_symbol(mount_symbol)?;
+        let trade_symbol: TradeSymbol = Self::parse_symbol(mount_symbol)?;
+        if !self
+            .ship_docked(agent, symbol)?
+            .ship
+            .mounts
+            .iter()
+            .any(|installed| installed.symbol == mount)
+        {
+            return Err(MockError::game(
+                error_codes::SHIP_MISSING_MOUNT_ERROR,
+                format!("Ship {} has no {} installed", symbol, mount_symbol),
+            ));
+        }
+        let (at, fee) = self.modification(
+            agent,
+            symbol,
+            error_codes::SHIP_MOUNT_NO_SHIPYARD_ERROR,
+            error_codes::SHIP_MOUNT_INSUFFICIENT_CREDITS_ERROR,
+        )?;
+        if let Err(err) = self.add_cargo(agent, symbol, trade_symbol, 1) {
+            self.agent_mut(agent)?.credits += fee as i64;
+            return Err(err);
+        }
+        let mounts = &mut self.ship(agent, symbol)?.ship.mounts;
+        if let Some(index) = mounts
+            .iter()
+            .position(|installed| installed.symbol == mount)
+        {
+            mounts.remove(index);
+        }
+        self.modification_response(agent, symbol, &at, trade_symbol, fee, "mounts")
+    }
+
+    pub fn install_module(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        module_symbol: &str,
+    ) -> Result<Value> {
+        let module: ship_module::Symbol = Self::parse_symbol(module_symbol)?;
+        let trade_symbol: TradeSymbol = Self::parse_symbol(module_symbol)?;
+        {
+            let state = self.ship_docked(agent, symbol)?;
+            if state.ship.modules.len() as i32 >= state.ship.frame.module_slots {
+                return Err(MockError::game(
+                    error_codes::SHIP_MISSING_SLOTS_ERROR,
+                    format!("Ship {} has no free module slot", symbol),
+                ));
+            }
+        }
+        let (at, fee) = self.modification(
+            agent,
+            symbol,
+            error_codes::SHIP_MODULE_NO_SHIPYARD,
+            error_codes::SHIP_MODULE_INSUFFICIENT_CREDITS,
+        )?;
+        if let Err(err) = self.remove_cargo(agent, symbol, trade_symbol, 1) {
+            self.agent_mut(agent)?.credits += fee as i64;
+            return Err(err);
+        }
+        let ship = &mut self.ship(agent, symbol)?.ship;
+        ship.modules.push(universe::module(module));
+        ship.cargo.capacity = universe::cargo_capacity(&ship.modules);
+        self.modification_response(agent, symbol, &at, trade_symbol, fee, "modules")
+    }
+
+    pub fn remove_module(
+        &mut self,
+        agent: &str,
+        symbol: &str,
+        module_symbol: &str,
+    ) -> Result<Value> {
+        let module: ship_module::Symbol = Self::parse_symbol(module_symbol)?;
+        let trade_symbol: TradeSymbol = Self::parse_symbol(module_symbol)?;
+        {
+            let ship = &self.ship_docked(agent, symbol)?.ship;
+            let Some(index) = ship
+                .modules
+                .iter()
+                .position(|installed| installed.symbol == module)
+            else {
+                return Err(MockError::game(
+                    error_codes::SHIP_MODULE_NOT_INSTALLED,
+                    format!("Ship {} has no {} installed", symbol, module_symbol),
+                ));
+            };
+            let mut remaining = ship.modules.clone();
+            remaining.remove(index);
+            // the removed module itself takes one unit of the remaining cargo space
+            if universe::cargo_capacity(&remaining) < ship.cargo.units + 1 {
+                return Err(MockError::game(
+                    error_codes::SHIP_CARGO_EXCEEDS_LIMIT_ERROR,
+                    format!(
+                        "Ship {} has too much cargo to remove {}",
+                        symbol, module_symbol
+                    ),
+                ));
+            }
+        }
+        let (at, fee) = self.modification(
+            agent,
+            symbol,
+            error_codes::SHIP_MODULE_NO_SHIPYARD,
+            error_codes::SHIP_MODULE_INSUFFICIENT_CREDITS,
+        )?;
+        let ship = &mut self.ship(agent, symbol)?.ship;
+        if let Some(index) = ship
+            .modules
+            .iter()
+            .position(|installed| installed.symbol == module)
+        {
+            ship.modules.remove(index);
+        }
+        ship.cargo.capacity = universe::cargo_capacity(&ship.modules);
+        self.add_cargo(agent, symbol, trade_symbol, 1)?;
+        self.modification_response(agent, symbol, &at, trade_symbol, fee, "modules")
+    }
+}
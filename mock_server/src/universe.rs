@@ -0,0 +1,805 @@
+//! Static content of the mock universe.
+//!
+//! A small deterministic galaxy: a fully charted home system with markets, shipyards,
+//! asteroids, a gas giant and a jump gate under construction, plus two uncharted systems,
+//! one behind the jump gate and one only reachable by warp.
+
+use std::collections::HashMap;
+
+use space_traders_client::models::{
+    self, ShipType, TradeSymbol, WaypointTraitSymbol as T, WaypointType, market_trade_good,
+    ship_engine, ship_frame, ship_module, ship_mount, ship_reactor,
+};
+
+pub const HOME_SYSTEM: &str = "X1-MK1";
+pub const HEADQUARTERS: &str = "X1-MK1-A1";
+pub const STARTING_CREDITS: i64 = 175_000;
+
+pub struct WaypointSpec {
+    pub symbol: &'static str,
+    pub kind: WaypointType,
+    pub x: i32,
+    pub y: i32,
+    pub orbits: Option<&'static str>,
+    pub traits: &'static [T],
+    pub charted: bool,
+    pub under_construction: bool,
+}
+
+pub struct SystemSpec {
+    pub symbol: &'static str,
+    pub kind: models::SystemType,
+    pub x: i32,
+    pub y: i32,
+    pub waypoints: Vec<WaypointSpec>,
+}
+
+#[allow(clippy::too_many_arguments)]
+const fn wp(
+    symbol: &'static str,
+    kind: WaypointType,
+    x: i32,
+    y: i32,
+    orbits: Option<&'static str>,
+    traits: &'static [T],
+    charted: bool,
+    under_construction: bool,
+) -> WaypointSpec {
+    WaypointSpec {
+        symbol,
+        kind,
+        x,
+        y,
+        orbits,
+        traits,
+        charted,
+        under_construction,
+    }
+}
+
+pub fn systems() -> Vec<SystemSpec> {
+    use WaypointType::*;
+    vec![
+        SystemSpec {
+            symbol: "X1-MK1",
+            kind: models::SystemType::OrangeStar,
+            x: 0,
+            y: 0,
+            waypoints: vec![
+                wp(
+                    "X1-MK1-A1",
+                    Planet,
+                    10,
+                    5,
+                    None,
+                    &[T::Marketplace, T::Shipyard, T::Industrial, T::Rocky],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-A2",
+                    Moon,
+                    10,
+                    5,
+                    Some("X1-MK1-A1"),
+                    &[T::Marketplace, T::Shipyard, T::Barren],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-A3",
+                    OrbitalStation,
+                    10,
+                    5,
+                    Some("X1-MK1-A1"),
+                    &[T::Marketplace, T::HighTech],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-B4",
+                    EngineeredAsteroid,
+                    -30,
+                    20,
+                    None,
+                    &[T::CommonMetalDeposits, T::Marketplace, T::Stripped],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-B5",
+                    Asteroid,
+                    -40,
+                    35,
+                    None,
+                    &[T::MineralDeposits, T::DeepCraters],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-B6",
+                    Asteroid,
+                    45,
+                    -50,
+                    None,
+                    &[T::CommonMetalDeposits],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-B7",
+                    AsteroidBase,
+                    40,
+                    -45,
+                    None,
+                    &[T::Marketplace, T::Outpost, T::HollowedInterior],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-C8",
+                    GasGiant,
+                    60,
+                    60,
+                    None,
+                    &[T::StrongMagnetosphere, T::Jovian],
+                    true,
+                    false,
+                ),
+                wp(
+                    "X1-MK1-C9",
+                    FuelStation,
+                    62,
+                    58,
+                    None,
+                    &[T::Marketplace],
+                    true,
+                    false,
+                ),
+                wp("X1-MK1-I10", JumpGate, -80, -70, None, &[], true, true),
+            ],
+        },
+        SystemSpec {
+            symbol: "X1-MK2",
+            kind: models::SystemType::RedStar,
+            x: 240,
+            y: -180,
+            waypoints: vec![
+                wp("X1-MK2-J1", JumpGate, 30, 30, None, &[], false, false),
+                wp(
+                    "X1-MK2-A1",
+                    Planet,
+                    -20,
+                    15,
+                    None,
+                    &[T::Marketplace, T::Shipyard, T::Temperate],
+                    false,
+                    false,
+                ),
+                wp(
+                    "X1-MK2-B2",
+                    Asteroid,
+                    50,
+                    -60,
+                    None,
+                    &[T::CommonMetalDeposits],
+                    false,
+                    false,
+                ),
+            ],
+        },
+        SystemSpec {
+            symbol: "X1-MK3",
+            kind: models::SystemType::BlueStar,
+            x: -320,
+            y: 260,
+            waypoints: vec![
+                wp(
+                    "X1-MK3-A1",
+                    Planet,
+                    5,
+                    -10,
+                    None,
+                    &[T::Marketplace, T::Frozen],
+                    false,
+                    false,
+                ),
+                wp(
+                    "X1-MK3-C2",
+                    GasGiant,
+                    -70,
+                    40,
+                    None,
+                    &[T::Jovian],
+                    false,
+                    false,
+                ),
+            ],
+        },
+    ]
+}
+
+pub fn jump_gates() -> HashMap<String, Vec<String>> {
+    HashMap::from([
+        ("X1-MK1-I10".to_string(), vec!["X1-MK2-J1".to_string()]),
+        ("X1-MK2-J1".to_string(), vec!["X1-MK1-I10".to_string()]),
+    ])
+}
+
+/// Materials missing on construction sites, `(site, [(good, required)])`
+pub fn constructions() -> Vec<(&'static str, Vec<(TradeSymbol, i32)>)> {
+    vec![(
+        "X1-MK1-I10",
+        vec![
+            (TradeSymbol::FabMats, 200),
+            (TradeSymbol::AdvancedCircuitry, 50),
+        ],
+    )]
+}
+
+/// `(symbol, type, base price, trade volume)` of a good traded at a marketplace
+pub type MarketGoodTemplate = (TradeSymbol, market_trade_good::Type, i32, i32);
+
+/// Goods traded at each marketplace
+pub fn markets() -> Vec<(&'static str, Vec<MarketGoodTemplate>)> {
+    use TradeSymbol::*;
+    use market_trade_good::Type::{Exchange, Export, Import};
+    vec![
+        (
+            "X1-MK1-A1",
+            vec![
+                (FabMats, Export, 120, 20),
+                (Iron, Import, 90, 20),
+                (QuartzSand, Import, 40, 40),
+                (Fuel, Exchange, 72, 100),
+            ],
+        ),
+        (
+            "X1-MK1-A2",
+            vec![
+                (Iron, Export, 55, 20),
+                (Copper, Export, 65, 20),
+                (Aluminum, Export, 75, 20),
+                (IronOre, Import, 28, 40),
+                (CopperOre, Import, 32, 40),
+                (AluminumOre, Import, 38, 40),
+                (IceWater, Import, 18, 40),
+                (MountMiningLaserIi, Export, 28_000, 5),
+                (MountSurveyorI, Export, 12_000, 5),
+                (Fuel, Exchange, 70, 100),
+            ],
+        ),
+        (
+            "X1-MK1-A3",
+            vec![
+                (AdvancedCircuitry, Export, 950, 10),
+                (Electronics, Export, 420, 10),
+                (Copper, Import, 115, 20),
+                (SiliconCrystals, Import, 60, 40),
+                (Hydrocarbon, Import, 65, 40),
+                (ModuleCargoHoldIi, Export, 16_000, 5),
+                (ModuleOreRefineryI, Export, 60_000, 5),
+                (Fuel, Exchange, 74, 100),
+            ],
+        ),
+        ("X1-MK1-B4", vec![(Fuel, Exchange, 80, 100)]),
+        (
+            "X1-MK1-B7",
+            vec![
+                (IronOre, Import, 36, 40),
+                (AluminumOre, Import, 46, 40),
+                (QuartzSand, Import, 42, 40),
+                (SiliconCrystals, Import, 72, 40),
+                (PreciousStones, Import, 95, 20),
+                (Fuel, Exchange, 78, 100),
+            ],
+        ),
+        (
+            "X1-MK1-C9",
+            vec![
+                (Hydrocarbon, Import, 58, 40),
+                (LiquidHydrogen, Import, 44, 40),
+                (LiquidNitrogen, Import, 48, 40),
+                (Fuel, Exchange, 66, 200),
+            ],
+        ),
+        (
+            "X1-MK2-A1",
+            vec![
+                (FabMats, Import, 210, 20),
+                (Machinery, Export, 160, 20),
+                (Fuel, Exchange, 90, 100),
+            ],
+        ),
+        (
+            "X1-MK3-A1",
+            vec![(Electronics, Import, 640, 10), (Fuel, Exchange, 95, 100)],
+        ),
+    ]
+}
+
+/// Ships offered at each shipyard and its modification fee
+pub fn shipyards() -> Vec<(&'static str, Vec<ShipType>, i32)> {
+    vec![
+        (
+            "X1-MK1-A1",
+            vec![
+                ShipType::Probe,
+                ShipType::MiningDrone,
+                ShipType::SiphonDrone,
+                ShipType::LightHauler,
+            ],
+            500,
+        ),
+        (
+            "X1-MK1-A2",
+            vec![
+                ShipType::Surveyor,
+                ShipType::LightShuttle,
+                ShipType::OreHound,
+                ShipType::RefiningFreighter,
+                ShipType::Explorer,
+            ],
+            500,
+        ),
+        (
+            "X1-MK2-A1",
+            vec![ShipType::Probe, ShipType::HeavyFreighter],
+            800,
+        ),
+    ]
+}
+
+pub fn supply_chain() -> HashMap<String, Vec<String>> {
+    [
+        ("FUEL", vec!["HYDROCARBON"]),
+        ("IRON", vec!["IRON_ORE"]),
+        ("COPPER", vec!["COPPER_ORE"]),
+        ("ALUMINUM", vec!["ALUMINUM_ORE"]),
+        ("FAB_MATS", vec!["IRON", "QUARTZ_SAND"]),
+        ("ELECTRONICS", vec!["SILICON_CRYSTALS", "COPPER"]),
+        ("ADVANCED_CIRCUITRY", vec!["ELECTRONICS", "COPPER"]),
+        ("MACHINERY", vec!["IRON"]),
+    ]
+    .into_iter()
+    .map(|(export, imports)| {
+        (
+            export.to_string(),
+            imports.into_iter().map(|s| s.to_string()).collect(),
+        )
+    })
+    .collect()
+}
+
+/// Goods that can be extracted from a waypoint, empty if nothing can be mined there
+pub fn deposits(waypoint: &models::Waypoint) -> Vec<TradeSymbol> {
+    use TradeSymbol::*;
+    let mut goods = Vec::new();
+    for waypoint_trait in &waypoint.traits {
+        match waypoint_trait.symbol {
+            T::CommonMetalDeposits => goods.extend([
+                IronOre,
+                CopperOre,
+                AluminumOre,
+                QuartzSand,
+                SiliconCrystals,
+                IceWater,
+            ]),
+            T::MineralDeposits => goods.extend([
+                QuartzSand,
+                SiliconCrystals,
+                PreciousStones,
+                IceWater,
+                AmmoniaIce,
+                IronOre,
+            ]),
+            T::PreciousMetalDeposits => goods.extend([SilverOre, GoldOre, PlatinumOre]),
+            _ => {}
+        }
+    }
+    goods
+}
+
+pub fn siphon_yields(waypoint: &models::Waypoint) -> Vec<TradeSymbol> {
+    if waypoint.r#type == WaypointType::GasGiant {
+        vec![
+            TradeSymbol::Hydrocarbon,
+            TradeSymbol::LiquidHydrogen,
+            TradeSymbol::LiquidNitrogen,
+        ]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Refined good produced from a raw good, `(input, output)`
+pub fn refinery_inputs() -> Vec<(TradeSymbol, TradeSymbol)> {
+    use TradeSymbol::*;
+    vec![
+        (IronOre, Iron),
+        (CopperOre, Copper),
+        (SilverOre, Silver),
+        (GoldOre, Gold),
+        (AluminumOre, Aluminum),
+        (PlatinumOre, Platinum),
+        (UraniteOre, Uranite),
+        (MeritiumOre, Meritium),
+        (Hydrocarbon, Fuel),
+    ]
+}
+
+/// Everything needed to build a ship of the given type
+pub struct ShipTemplate {
+    pub ship_type: ShipType,
+    pub role: models::ShipRole,
+    pub price: i32,
+    pub frame: models::ShipFrame,
+    pub reactor: models::ShipReactor,
+    pub engine: models::ShipEngine,
+    pub modules: Vec<models::ShipModule>,
+    pub mounts: Vec<models::ShipMount>,
+    pub crew_required: i32,
+    pub crew_capacity: i32,
+}
+
+impl ShipTemplate {
+    pub fn cargo_capacity(&self) -> i32 {
+        cargo_capacity(&self.modules)
+    }
+
+    pub fn to_shipyard_ship(&self, supply: models::SupplyLevel) -> models::ShipyardShip {
+        models::ShipyardShip {
+            r#type: self.ship_type,
+            name: title(&self.ship_type.to_string()),
+            description: String::new(),
+            activity: Some(models::ActivityLevel::Growing),
+            supply,
+            purchase_price: self.price,
+            frame: Box::new(self.frame.clone()),
+            reactor: Box::new(self.reactor.clone()),
+            engine: Box::new(self.engine.clone()),
+            modules: self.modules.clone(),
+            mounts: self.mounts.clone(),
+            crew: Box::new(models::ShipyardShipCrew {
+                required: self.crew_required,
+                capacity: self.crew_capacity,
+            }),
+        }
+    }
+}
+
+pub fn cargo_capacity(modules: &[models::ShipModule]) -> i32 {
+    modules
+        .iter()
+        .filter(|module| {
+            matches!(
+                module.symbol,
+                ship_module::Symbol::CargoHoldI
+                    | ship_module::Symbol::CargoHoldIi
+                    | ship_module::Symbol::CargoHoldIii
+            )
+        })
+        .map(|module| module.capacity.unwrap_or(0))
+        .sum()
+}
+
+pub fn ship_template(ship_type: ShipType) -> ShipTemplate {
+    use models::ShipRole as R;
+    use ship_module::Symbol as Mo;
+    use ship_mount::Symbol as Mt;
+
+    let (role, price, frame, reactor, engine, modules, mounts, crew) = match ship_type {
+        ShipType::Probe => (
+            R::Satellite,
+            25_000,
+            frame(ship_frame::Symbol::Probe, 0, 0, 0),
+            reactor(ship_reactor::Symbol::SolarI, 3),
+            engine(ship_engine::Symbol::ImpulseDriveI, 3),
+            vec![],
+            vec![],
+            (0, 0),
+        ),
+        ShipType::MiningDrone => (
+            R::Excavator,
+            45_000,
+            frame(ship_frame::Symbol::Drone, 2, 2, 80),
+            reactor(ship_reactor::Symbol::ChemicalI, 15),
+            engine(ship_engine::Symbol::ImpulseDriveI, 3),
+            vec![module(Mo::CargoHoldI), module(Mo::MineralProcessorI)],
+            vec![mount(Mt::MiningLaserI)],
+            (0, 0),
+        ),
+        ShipType::SiphonDrone => (
+            R::Excavator,
+            40_000,
+            frame(ship_frame::Symbol::Drone, 2, 2, 80),
+            reactor(ship_reactor::Symbol::ChemicalI, 15),
+            engine(ship_engine::Symbol::ImpulseDriveI, 3),
+            vec![module(Mo::CargoHoldI), module(Mo::GasProcessorI)],
+            vec![mount(Mt::GasSiphonI)],
+            (0, 0),
+        ),
+        ShipType::Surveyor => (
+            R::Surveyor,
+            30_000,
+            frame(ship_frame::Symbol::Drone, 2, 2, 80),
+            reactor(ship_reactor::Symbol::SolarI, 3),
+            engine(ship_engine::Symbol::ImpulseDriveI, 3),
+            vec![],
+            vec![mount(Mt::SurveyorI)],
+            (0, 0),
+        ),
+        ShipType::LightShuttle => (
+            R::Transport,
+            90_000,
+            frame(ship_frame::Symbol::Shuttle, 3, 1, 400),
+            reactor(ship_reactor::Symbol::ChemicalI, 15),
+            engine(ship_engine::Symbol::IonDriveI, 10),
+            vec![module(Mo::CargoHoldI), module(Mo::CargoHoldIi)],
+            vec![],
+            (1, 4),
+        ),
+        ShipType::LightHauler => (
+            R::Hauler,
+            250_000,
+            frame(ship_frame::Symbol::LightFreighter, 4, 2, 600),
+            reactor(ship_reactor::Symbol::FissionI, 31),
+            engine(ship_engine::Symbol::IonDriveI, 10),
+            vec![
+                module(Mo::CargoHoldIi),
+                module(Mo::CargoHoldIi),
+                module(Mo::CrewQuartersI),
+            ],
+            vec![],
+            (6, 12),
+        ),
+        ShipType::OreHound => (
+            R::Excavator,
+            180_000,
+            frame(ship_frame::Symbol::Miner, 4, 3, 400),
+            reactor(ship_reactor::Symbol::FissionI, 31),
+            engine(ship_engine::Symbol::IonDriveI, 10),
+            vec![
+                module(Mo::CargoHoldIi),
+                module(Mo::MineralProcessorI),
+                module(Mo::CrewQuartersI),
+            ],
+            vec![mount(Mt::MiningLaserIi), mount(Mt::SurveyorI)],
+            (10, 20),
+        ),
+        ShipType::RefiningFreighter => (
+            R::Refinery,
+            1_200_000,
+            frame(ship_frame::Symbol::HeavyFreighter, 6, 2, 1200),
+            reactor(ship_reactor::Symbol::FissionI, 31),
+            engine(ship_engine::Symbol::IonDriveI, 10),
+            vec![
+                module(Mo::CargoHoldIii),
+                module(Mo::OreRefineryI),
+                module(Mo::FuelRefineryI),
+                module(Mo::CrewQuartersI),
+            ],
+            vec![],
+            (20, 40),
+        ),
+        ShipType::Explorer => (
+            R::Explorer,
+            320_000,
+            frame(ship_frame::Symbol::Explorer, 5, 2, 800),
+            reactor(ship_reactor::Symbol::FissionI, 31),
+            engine(ship_engine::Symbol::IonDriveIi, 30),
+            vec![
+                module(Mo::CargoHoldIi),
+                module(Mo::WarpDriveI),
+                module(Mo::CrewQuartersI),
+            ],
+            vec![mount(Mt::SensorArrayI)],
+            (8, 16),
+        ),
+        ShipType::HeavyFreighter | ShipType::BulkFreighter => (
+            R::Hauler,
+            900_000,
+            frame(ship_frame::Symbol::HeavyFreighter, 6, 2, 1200),
+            reactor(ship_reactor::Symbol::FissionI, 31),
+            engine(ship_engine::Symbol::IonDriveI, 10),
+            vec![
+                module(Mo::CargoHoldIii),
+                module(Mo::CargoHoldIii),
+                module(Mo::CrewQuartersI),
+            ],
+            vec![],
+            (20, 40),
+        ),
+        ShipType::CommandFrigate | ShipType::Interceptor => (
+            R::Command,
+            400_000,
+            frame(ship_frame::Symbol::Frigate, 5, 4, 400),
+            reactor(ship_reactor::Symbol::FissionI, 31),
+            engine(ship_engine::Symbol::IonDriveIi, 30),
+            vec![
+                module(Mo::CargoHoldIi),
+                module(Mo::MineralProcessorI),
+                module(Mo::GasProcessorI),
+                module(Mo::CrewQuartersI),
+            ],
+            vec![
+                mount(Mt::SensorArrayIi),
+                mount(Mt::GasSiphonIi),
+                mount(Mt::MiningLaserIi),
+                mount(Mt::SurveyorIi),
+            ],
+            (57, 80),
+        ),
+    };
+
+    ShipTemplate {
+        ship_type,
+        role,
+        price,
+        frame,
+        reactor,
+        engine,
+        modules,
+        mounts,
+        crew_required: crew.0,
+        crew_capacity: crew.1,
+    }
+}
+
+fn requirements(
+    power: Option<i32>,
+    crew: Option<i32>,
+    slots: Option<i32>,
+) -> Box<models::ShipRequirements> {
+    Box::new(models::ShipRequirements { power, crew, slots })
+}
+
+fn frame(
+    symbol: ship_frame::Symbol,
+    module_slots: i32,
+    mounting_points: i32,
+    fuel_capacity: i32,
+) -> models::ShipFrame {
+    models::ShipFrame {
+        symbol,
+        name: title(&symbol_str(&symbol)),
+        condition: 1.0,
+        integrity: 1.0,
+        description: String::new(),
+        module_slots,
+        mounting_points,
+        fuel_capacity,
+        requirements: requirements(Some(1), Some(0), None),
+        quality: Some(4.0),
+    }
+}
+
+fn reactor(symbol: ship_reactor::Symbol, power_output: i32) -> models::ShipReactor {
+    models::ShipReactor {
+        symbol,
+        name: title(&symbol_str(&symbol)),
+        condition: 1.0,
+        integrity: 1.0,
+        description: String::new(),
+        power_output,
+        requirements: requirements(None, Some(0), None),
+        quality: Some(4.0),
+    }
+}
+
+fn engine(symbol: ship_engine::Symbol, speed: i32) -> models::ShipEngine {
+    models::ShipEngine {
+        symbol,
+        name: title(&symbol_str(&symbol)),
+        condition: 1.0,
+        integrity: 1.0,
+        description: String::new(),
+        speed,
+        requirements: requirements(Some(1), Some(0), None),
+        quality: Some(4.0),
+    }
+}
+
+pub fn module(symbol: ship_module::Symbol) -> models::ShipModule {
+    use ship_module::Symbol as Mo;
+    let (capacity, range) = match symbol {
+        Mo::CargoHoldI => (Some(15), None),
+        Mo::CargoHoldIi => (Some(40), None),
+        Mo::CargoHoldIii => (Some(60), None),
+        Mo::CrewQuartersI => (Some(40), None),
+        Mo::WarpDriveI => (None, Some(2000)),
+        Mo::WarpDriveIi => (None, Some(6000)),
+        Mo::WarpDriveIii => (None, Some(12000)),
+        _ => (None, None),
+    };
+    models::ShipModule {
+        symbol,
+        name: title(&symbol_str(&symbol)),
+        description: String::new(),
+        capacity,
+        range,
+        requirements: requirements(Some(1), Some(0), Some(1)),
+    }
+}
+
+pub fn mount(symbol: ship_mount::Symbol) -> models::ShipMount {
+    use ship_mount::Deposits as D;
+    use ship_mount::Symbol as Mt;
+    let strength = match symbol {
+        Mt::MiningLaserI => Some(3),
+        Mt::MiningLaserIi => Some(5),
+        Mt::MiningLaserIii => Some(8),
+        Mt::SurveyorI => Some(1),
+        Mt::SurveyorIi => Some(2),
+        Mt::SurveyorIii => Some(3),
+        Mt::GasSiphonI => Some(5),
+        Mt::GasSiphonIi => Some(10),
+        Mt::GasSiphonIii => Some(15),
+        Mt::SensorArrayI => Some(1),
+        Mt::SensorArrayIi => Some(4),
+        Mt::SensorArrayIii => Some(7),
+        _ => None,
+    };
+    let deposits = match symbol {
+        Mt::MiningLaserI
+        | Mt::MiningLaserIi
+        | Mt::MiningLaserIii
+        | Mt::SurveyorI
+        | Mt::SurveyorIi
+        | Mt::SurveyorIii => Some(vec![
+            D::QuartzSand,
+            D::SiliconCrystals,
+            D::PreciousStones,
+            D::IceWater,
+            D::AmmoniaIce,
+            D::IronOre,
+            D::CopperOre,
+            D::SilverOre,
+            D::AluminumOre,
+            D::GoldOre,
+            D::PlatinumOre,
+        ]),
+        _ => None,
+    };
+    models::ShipMount {
+        symbol,
+        name: title(&symbol_str(&symbol)),
+        description: String::new(),
+        strength,
+        deposits,
+        requirements: requirements(Some(1), Some(0), None),
+    }
+}
+
+/// Symbol string of any serde enum, like `MOUNT_MINING_LASER_I`
+pub fn symbol_str<S: serde::Serialize>(symbol: &S) -> String {
+    serde_json::to_value(symbol)
+        .ok()
+        .and_then(|value| value.as_str().map(|s| s.to_string()))
+        .unwrap_or_default()
+}
+
+/// Human readable name of a symbol, `MOUNT_MINING_LASER_I` becomes `Mining Laser I`
+pub fn title(symbol: &str) -> String {
+    symbol
+        .split('_')
+        .skip_while(|part| {
+            matches!(
+                *part,
+                "SHIP" | "FRAME" | "REACTOR" | "ENGINE" | "MODULE" | "MOUNT"
+            )
+        })
+        .map(|part| {
+            if part.chars().all(|c| c == 'I') {
+                part.to_string()
+            } else {
+                let mut chars = part.chars();
+                match chars.next() {
+                    Some(first) => first.to_string() + &chars.as_str().to_lowercase(),
+                    None => String::new(),
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,162 @@
+use std::num::NonZeroU32;
+
+use mock_server::{MockConfig, MockServer, universe};
+use space_traders_client::{Api, models};
+
+async fn start() -> (MockServer, Api) {
+    let server = MockServer::start(MockConfig::default(), "127.0.0.1:0".parse().unwrap()).await;
+    let account_api =
+        Api::new(None, 10, NonZeroU32::new(10).unwrap()).with_base_path(server.base_path());
+    let registered = account_api
+        .register(
+            "MOCKER".to_string(),
+            models::FactionSymbol::Cosmic,
+            "account-token".to_string(),
+        )
+        .await
+        .unwrap();
+    let api = Api::new(Some(registered.token), 10, NonZeroU32::new(10).unwrap())
+        .with_base_path(server.base_path());
+    (server, api)
+}
+
+async fn wait_for_arrival(api: &Api, ship: &str) {
+    loop {
+        let nav = api.get_ship_nav(ship).await.unwrap().data;
+        if nav.status != models::ShipNavStatus::InTransit {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test]
+async fn mine_and_sell_round_trip() {
+    let (server, api) = start().await;
+
+    let agent = api.get_my_agent().await.unwrap().data;
+    assert_eq!(agent.headquarters, universe::HEADQUARTERS);
+    assert_eq!(agent.credits, universe::STARTING_CREDITS);
+
+    let ships = api.get_all_my_ships(20).await.unwrap();
+    assert_eq!(ships.len(), 2);
+    let command = ships
+        .iter()
+        .find(|ship| ship.registration.role == models::ShipRole::Command)
+        .unwrap()
+        .symbol
+        .clone();
+
+    api.orbit_ship(&command).await.unwrap();
+    let navigation = api
+        .navigate_ship(
+            &command,
+            models::NavigateShipRequest {
+                waypoint_symbol: "X1-MK1-B6".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert!(navigation.data.fuel.current < navigation.data.fuel.capacity);
+    wait_for_arrival(&api, &command).await;
+
+    let extraction = api.extract_resources(&command).await.unwrap().data;
+    assert!(extraction.extraction.r#yield.units > 0);
+    assert!(extraction.cooldown.remaining_seconds > 0);
+    let mined = extraction.extraction.r#yield;
+
+    // a second extraction right away collides with the cooldown
+    let err = api.extract_resources(&command).await.unwrap_err();
+    match err {
+        space_traders_client::apis::Error::ResponseError(response) => assert_eq!(
+            response.get_error_code(),
+            Some(models::error_codes::COOLDOWN_CONFLICT_ERROR)
+        ),
+        err => panic!("unexpected error {:?}", err),
+    }
+
+    let market = if mined.symbol == models::TradeSymbol::PreciousStones
+        || mined.symbol == models::TradeSymbol::QuartzSand
+        || mined.symbol == models::TradeSymbol::SiliconCrystals
+    {
+        "X1-MK1-B7"
+    } else {
+        "X1-MK1-A2"
+    };
+    api.navigate_ship(
+        &command,
+        models::NavigateShipRequest {
+            waypoint_symbol: market.to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    wait_for_arrival(&api, &command).await;
+    api.dock_ship(&command).await.unwrap();
+
+    let sale = api
+        .sell_cargo(
+            &command,
+            models::SellCargoRequest {
+                symbol: mined.symbol,
+                units: mined.units,
+            },
+        )
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(sale.cargo.units, 0);
+    assert_eq!(
+        sale.agent.credits,
+        universe::STARTING_CREDITS + sale.transaction.total_price as i64
+    );
+
+    let refuel = api.refuel_ship(&command, None).await.unwrap().data;
+    assert_eq!(refuel.fuel.current, refuel.fuel.capacity);
+
+    let market = api.get_market("X1-MK1", market).await.unwrap().data;
+    assert_eq!(market.transactions.map(|t| t.len()), Some(2));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn contract_and_shipyard() {
+    let (server, api) = start().await;
+
+    let contract = api.get_all_contracts(20).await.unwrap().remove(0);
+    let accepted = api.accept_contract(&contract.id).await.unwrap().data;
+    assert!(accepted.contract.accepted);
+    assert_eq!(
+        accepted.agent.credits,
+        universe::STARTING_CREDITS + contract.terms.payment.on_accepted as i64
+    );
+
+    let shipyard = api
+        .get_shipyard("X1-MK1", universe::HEADQUARTERS)
+        .await
+        .unwrap()
+        .data;
+    assert!(shipyard.ships.is_some());
+
+    let purchase = api
+        .purchase_ship(models::PurchaseShipRequest {
+            ship_type: models::ShipType::MiningDrone,
+            waypoint_symbol: universe::HEADQUARTERS.to_string(),
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(purchase.agent.ship_count, 3);
+    assert_eq!(purchase.ship.nav.waypoint_symbol, universe::HEADQUARTERS);
+
+    let waypoints = api.get_all_waypoints("X1-MK2", 20).await.unwrap();
+    assert!(waypoints.iter().all(|waypoint| {
+        waypoint
+            .traits
+            .iter()
+            .any(|waypoint_trait| waypoint_trait.symbol == models::WaypointTraitSymbol::Uncharted)
+    }));
+
+    server.shutdown().await;
+}
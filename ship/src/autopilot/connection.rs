@@ -1,7 +1,6 @@
-use async_graphql::{SimpleObject, Union};
-use space_traders_client::models::{self, ShipNavFlightMode};
+use async_graphql::SimpleObject;
+use space_traders_client::models::{self};
 use std::hash::Hash;
-use utils::get_system_symbol;
 
 #[derive(Debug, Clone)]
 pub struct SimpleConnection {
@@ -110,14 +110,7 @@ impl<T: Clone + Send + Sync, State: Send + Sync> RustShip<T, State> {
                     }))
                 }
                 ConnectionType::Warp { nav_mode } => {
-                    let stats = get_travel_stats(
-                        self.engine_speed,
-                        nav_mode,
-                        self.conditions.engine.condition,
-                        self.conditions.frame.condition,
-                        self.conditions.reactor.condition,
-                        c.distance,
-                    );
+                    let stats = get_travel_stats(self.engine_speed, nav_mode, c.distance);
                     if c.start_is_marketplace {
                         needed_fuel = 0;
                     } else {
@@ -140,14 +133,7 @@ impl<T: Clone + Send + Sync, State: Send + Sync> RustShip<T, State> {
                     }))
                 }
                 ConnectionType::Navigate { nav_mode } => {
-                    let stats = get_travel_stats(
-                        self.engine_speed,
-                        nav_mode,
-                        self.conditions.engine.condition,
-                        self.conditions.frame.condition,
-                        self.conditions.reactor.condition,
-                        c.distance,
-                    );
+                    let stats = get_travel_stats(self.engine_speed, nav_mode, c.distance);
                     let refuel = Refuel {
                         fuel_needed: stats.fuel_cost,
                         fuel_required: needed_fuel,
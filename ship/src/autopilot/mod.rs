@@ -16,6 +16,7 @@ mod pathfinder;
 mod simple_pathfinding;
 mod stats;
 mod utils;
+mod warp_nav;
 
 pub use connection::ConcreteConnection;
 pub use connection::JumpConnection;
@@ -52,6 +53,7 @@ impl<T: Clone + Send + Sync> RustShip<T, Mutable> {
         .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn nav_to_prepare(
         &mut self,
         waypoint: &str,
@@ -168,6 +170,7 @@ impl<T: Clone + Send + Sync, State: Send + Sync> RustShip<T, State> {
                     || module == &models::ship_module::Symbol::WarpDriveIi
                     || module == &models::ship_module::Symbol::WarpDriveIii
             }),
+            engine_speed: self.engine_speed,
             database_pool: database_pool.clone(),
             api: api.clone(),
         })
@@ -499,6 +499,7 @@ impl RefuelRequirements {
         self.refuel_amount > 0 || self.restock_amount > 0
     }
 
+    #[allow(dead_code)]
     fn needs_marketplace_action(&self) -> bool {
         self.refuel_amount > 0 || self.restock_amount > 0
     }
@@ -1,10 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use utils::get_system_symbol;
+use database::DatabaseConnectorAsync;
+
+use utils::{WaypointCan, distance_between_waypoints, get_system_symbol};
 
 use crate::autopilot::jump_gate_nav::{self, JumpPathfinder};
 
-use super::{SimpleConnection, nav_mode::NavMode, simple_pathfinding::SimplePathfinder};
+use super::{
+    SimpleConnection,
+    connection::ConnectionType,
+    nav_mode::NavMode,
+    simple_pathfinding::SimplePathfinder,
+    warp_nav::{LegType, WarpNode, WarpPathfinder},
+};
 
 pub struct Pathfinder {
     pub range: u32,
@@ -12,6 +20,7 @@ pub struct Pathfinder {
     pub start_range: u32,
     pub only_markets: bool,
     pub can_warp: bool,
+    pub engine_speed: i32,
     pub database_pool: database::DbPool,
     pub api: space_traders_client::Api,
 }
@@ -97,7 +106,148 @@ impl Pathfinder {
 
             return Ok(route);
         }
-        todo!()
+        self.get_warp_route(start_symbol, end_symbol).await
+    }
+
+    /// Route to another system for ships with a warp drive, mixing jump gates and warps between marketplaces
+    async fn get_warp_route(
+        &self,
+        start_symbol: &str,
+        end_symbol: &str,
+    ) -> crate::error::Result<Vec<SimpleConnection>> {
+        let start_system = get_system_symbol(start_symbol);
+        let end_system = get_system_symbol(end_symbol);
+
+        let systems =
+            database::System::get_all(&self.database_pool, database::PaginatedQuery::unpaged())
+                .await?
+                .items
+                .into_iter()
+                .map(|s| (s.symbol.clone(), (s.x, s.y)))
+                .collect::<HashMap<_, _>>();
+        let start_pos = *systems.get(&start_system).ok_or_else(|| {
+            crate::error::Error::General(format!("Unknown system {}", start_system))
+        })?;
+        let end_pos = *systems.get(&end_system).ok_or_else(|| {
+            crate::error::Error::General(format!("Unknown system {}", end_system))
+        })?;
+
+        // only systems roughly between start and end are worth loading the waypoints for
+        let direct = distance_between_waypoints(start_pos, end_pos);
+        let slack = (direct * 0.5).max(self.range as f64 * 2.0);
+        let candidate_systems = systems
+            .iter()
+            .filter(|(_, pos)| {
+                distance_between_waypoints(start_pos, **pos)
+                    + distance_between_waypoints(**pos, end_pos)
+                    <= direct + slack
+            })
+            .map(|(symbol, _)| symbol.as_str())
+            .collect::<Vec<_>>();
+
+        let mut waypoints: HashMap<String, HashMap<String, database::Waypoint>> = HashMap::new();
+        for waypoint in
+            database::Waypoint::get_by_systems(&self.database_pool, &candidate_systems).await?
+        {
+            waypoints
+                .entry(waypoint.system_symbol.clone())
+                .or_default()
+                .insert(waypoint.symbol.clone(), waypoint);
+        }
+
+        let connections = jump_gate_nav::generate_all_connections(&self.database_pool).await?;
+
+        let mut nodes = waypoints
+            .values()
+            .flat_map(|system| system.values())
+            .filter(|w| {
+                w.is_marketplace()
+                    || w.is_jump_gate()
+                    || w.symbol == start_symbol
+                    || w.symbol == end_symbol
+            })
+            .map(|w| WarpNode {
+                symbol: w.symbol.clone(),
+                system_symbol: w.system_symbol.clone(),
+                pos: Some((w.x, w.y)),
+                system_pos: systems.get(&w.system_symbol).copied().unwrap_or_default(),
+                is_marketplace: w.is_marketplace(),
+            })
+            .collect::<Vec<_>>();
+        let mut known = nodes
+            .iter()
+            .map(|n| n.symbol.clone())
+            .collect::<HashSet<_>>();
+        for conn in connections.iter() {
+            for (symbol, system_symbol, system_pos) in [
+                (&conn.point_a, &conn.system_point_a, conn.pos_point_a),
+                (&conn.point_b, &conn.system_point_b, conn.pos_point_b),
+            ] {
+                if known.insert(symbol.clone()) {
+                    nodes.push(WarpNode {
+                        symbol: symbol.clone(),
+                        system_symbol: system_symbol.clone(),
+                        pos: None,
+                        system_pos,
+                        is_marketplace: false,
+                    });
+                }
+            }
+        }
+
+        let warp_pathfinder = WarpPathfinder::new(
+            nodes,
+            &connections,
+            self.range,
+            self.start_range,
+            self.nav_mode,
+            self.engine_speed,
+        );
+        let legs = warp_pathfinder
+            .find_route(start_symbol, end_symbol)
+            .ok_or_else(|| crate::error::Error::General("No Route found".to_string()))?;
+
+        let mut route = vec![];
+        for leg in legs {
+            match leg.leg_type {
+                LegType::Navigate => {
+                    let system = waypoints.get(&leg.system_symbol).cloned().ok_or_else(|| {
+                        crate::error::Error::General(format!(
+                            "No waypoints for system {}",
+                            leg.system_symbol
+                        ))
+                    })?;
+                    let simple = SimplePathfinder {
+                        start_range: leg.start_fuel,
+                        ..self.get_simple(system)
+                    };
+                    route
+                        .append(&mut simple.find_route_system(&leg.start_symbol, &leg.end_symbol)?);
+                }
+                LegType::Jump => route.push(SimpleConnection {
+                    start_symbol: leg.start_symbol,
+                    end_symbol: leg.end_symbol,
+                    connection_type: ConnectionType::JumpGate,
+                    start_is_marketplace: leg.start_is_marketplace,
+                    end_is_marketplace: leg.end_is_marketplace,
+                    cost: leg.cost,
+                    re_cost: leg.cost,
+                    distance: leg.distance,
+                }),
+                LegType::Warp { nav_mode } => route.push(SimpleConnection {
+                    start_symbol: leg.start_symbol,
+                    end_symbol: leg.end_symbol,
+                    connection_type: ConnectionType::Warp { nav_mode },
+                    start_is_marketplace: leg.start_is_marketplace,
+                    end_is_marketplace: leg.end_is_marketplace,
+                    cost: leg.cost,
+                    re_cost: leg.cost,
+                    distance: leg.distance,
+                }),
+            }
+        }
+
+        Ok(route)
     }
 
     pub fn get_simple(&self, waypoints: HashMap<String, database::Waypoint>) -> SimplePathfinder {
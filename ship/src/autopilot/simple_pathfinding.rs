@@ -95,6 +95,7 @@ impl SimplePathfinder {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_current_node(
         &self,
         current_route: &SimpleConnection,
@@ -10,20 +10,10 @@ pub struct TravelStats {
 pub fn get_travel_stats(
     engine_speed: i32,
     flight_mode: models::ShipNavFlightMode,
-    engine_condition: f64,
-    frame_condition: f64,
-    reactor_condition: f64,
     distance: f64,
 ) -> TravelStats {
     let (fuel_cost, multiplier) = calculate_fuel_and_multiplier(flight_mode, distance);
-    let travel_time = calculate_travel_time(
-        distance,
-        multiplier,
-        engine_speed,
-        engine_condition,
-        frame_condition,
-        reactor_condition,
-    );
+    let travel_time = calculate_travel_time(distance, multiplier, engine_speed);
 
     TravelStats {
         distance,
@@ -48,13 +38,6 @@ pub fn calculate_jump_cooldown(distance: f64) -> f64 {
     (15.0 + 0.3 * distance).round()
 }
 
-fn calculate_travel_time(
-    distance: f64,
-    multiplier: f64,
-    engine_speed: i32,
-    engine_condition: f64,
-    frame_condition: f64,
-    reactor_condition: f64,
-) -> f64 {
+fn calculate_travel_time(distance: f64, multiplier: f64, engine_speed: i32) -> f64 {
     ((distance.max(1.0).round()) * (multiplier / (engine_speed as f64)) + 15.0).round()
 }
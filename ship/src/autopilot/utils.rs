@@ -29,6 +29,7 @@ pub(crate) fn get_route(
     Some(route)
 }
 
+#[allow(dead_code)]
 pub fn estimate_route_cost(
     route: &[super::connection::SimpleConnection],
     fuel_cost: i64,
@@ -0,0 +1,419 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use priority_queue::PriorityQueue;
+use space_traders_client::models;
+use tracing::debug;
+use utils::distance_between_waypoints;
+
+use super::{jump_gate_nav::GateConnection, nav_mode::NavMode, stats};
+
+/// every leg costs at least this many seconds, so we don't add stops which don't save time
+const LEG_PENALTY: f64 = 5.0;
+
+/// A waypoint a warp route can stop at, a marketplace to refuel, a jump gate or the start and end of the route
+#[derive(Debug, Clone)]
+pub struct WarpNode {
+    pub symbol: String,
+    pub system_symbol: String,
+    /// position inside the system, `None` if the waypoints of the system are not loaded and the node is only a jump gate to pass through
+    pub pos: Option<(i32, i32)>,
+    pub system_pos: (i32, i32),
+    pub is_marketplace: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LegType {
+    /// travel inside one system, has to be resolved by the simple pathfinder
+    Navigate,
+    Jump,
+    Warp {
+        nav_mode: models::ShipNavFlightMode,
+    },
+}
+
+/// a node and the fuel left in the tanks when reaching it
+type SearchState = (usize, u32);
+
+struct Edge {
+    next: usize,
+    leg_type: LegType,
+    distance: f64,
+    /// travel time in seconds
+    cost: f64,
+    fuel_cost: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct WarpLeg {
+    pub start_symbol: String,
+    pub end_symbol: String,
+    pub system_symbol: String,
+    pub leg_type: LegType,
+    pub distance: f64,
+    /// travel time of this leg in seconds
+    pub cost: f64,
+    pub start_is_marketplace: bool,
+    pub end_is_marketplace: bool,
+    /// fuel in the tanks and cargo at the start of the leg
+    pub start_fuel: u32,
+}
+
+/// Finds routes between systems combining jump gates and warps, refueling at marketplaces between warps
+pub struct WarpPathfinder {
+    nodes: Vec<WarpNode>,
+    indices: HashMap<String, usize>,
+    by_system: HashMap<String, Vec<usize>>,
+    gates: HashMap<usize, Vec<(usize, f64)>>,
+    range: u32,
+    start_range: u32,
+    nav_mode: NavMode,
+    engine_speed: i32,
+}
+
+impl WarpPathfinder {
+    pub fn new(
+        nodes: Vec<WarpNode>,
+        connections: &[GateConnection],
+        range: u32,
+        start_range: u32,
+        nav_mode: NavMode,
+        engine_speed: i32,
+    ) -> Self {
+        let indices = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.symbol.clone(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut by_system: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, node) in nodes.iter().enumerate() {
+            if node.pos.is_some() {
+                by_system
+                    .entry(node.system_symbol.clone())
+                    .or_default()
+                    .push(i);
+            }
+        }
+
+        let mut gates: HashMap<usize, Vec<(usize, f64)>> = HashMap::new();
+        for conn in connections {
+            if conn.under_construction_a || conn.under_construction_b {
+                continue;
+            }
+            let (Some(a), Some(b)) = (indices.get(&conn.point_a), indices.get(&conn.point_b))
+            else {
+                continue;
+            };
+            gates.entry(*a).or_default().push((*b, conn.distance));
+            gates.entry(*b).or_default().push((*a, conn.distance));
+        }
+
+        Self {
+            nodes,
+            indices,
+            by_system,
+            gates,
+            range,
+            start_range,
+            nav_mode,
+            engine_speed: engine_speed.max(1),
+        }
+    }
+
+    /// Dijkstra over `(node, fuel in the tanks)`, the tanks are filled at every marketplace the route stops at
+    pub fn find_route(&self, start_symbol: &str, end_symbol: &str) -> Option<Vec<WarpLeg>> {
+        let start = *self.indices.get(start_symbol)?;
+        let end = *self.indices.get(end_symbol)?;
+
+        let start_state = if self.nodes[start].is_marketplace {
+            (start, self.range)
+        } else {
+            (start, self.start_range.min(self.range))
+        };
+
+        let mut costs: HashMap<SearchState, f64> = HashMap::new();
+        let mut previous: HashMap<SearchState, (SearchState, LegType, f64, f64)> = HashMap::new();
+        // the most fuel a node was reached with so far, states with less fuel and a higher cost are never better
+        let mut settled: HashMap<usize, u32> = HashMap::new();
+        let mut to_visit: PriorityQueue<SearchState, Reverse<i64>> = PriorityQueue::new();
+
+        costs.insert(start_state, 0.0);
+        to_visit.push(start_state, Reverse(0));
+
+        while let Some((current, _)) = to_visit.pop() {
+            if current.0 == end {
+                return Some(self.get_route(&previous, start_state, current));
+            }
+            if settled
+                .get(&current.0)
+                .is_some_and(|fuel| *fuel >= current.1)
+            {
+                continue;
+            }
+            settled.insert(current.0, current.1);
+            let current_cost = costs[&current];
+
+            for edge in self.get_edges(current) {
+                let next = (
+                    edge.next,
+                    self.fuel_on_arrival(edge.next, current.1, edge.fuel_cost),
+                );
+                if settled.get(&next.0).is_some_and(|fuel| *fuel >= next.1) {
+                    continue;
+                }
+                let next_cost = current_cost + edge.cost + LEG_PENALTY;
+                if costs.get(&next).is_some_and(|c| *c <= next_cost) {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                previous.insert(next, (current, edge.leg_type, edge.distance, edge.cost));
+                to_visit.push_increase(next, Reverse((next_cost * 1_000.0) as i64));
+            }
+        }
+
+        debug!(start_symbol, end_symbol, "No warp route found");
+        None
+    }
+
+    /// the fuel left after a leg, the tanks are filled again when the leg ends at a marketplace
+    fn fuel_on_arrival(&self, next: usize, fuel: u32, fuel_cost: u32) -> u32 {
+        if self.nodes[next].is_marketplace {
+            self.range
+        } else {
+            fuel.saturating_sub(fuel_cost)
+        }
+    }
+
+    /// all legs leaving a node which can be flown with the fuel of the state
+    fn get_edges(&self, (current, fuel): SearchState) -> Vec<Edge> {
+        let node = &self.nodes[current];
+        let mut edges = Vec::new();
+
+        if let (Some(pos), Some(system)) = (node.pos, self.by_system.get(&node.system_symbol)) {
+            for next in system.iter().filter(|n| **n != current) {
+                let Some(next_pos) = self.nodes[*next].pos else {
+                    continue;
+                };
+                let distance = distance_between_waypoints(pos, next_pos);
+                if let Some((_, cost, fuel_cost)) = self.best_mode(fuel, distance) {
+                    edges.push(Edge {
+                        next: *next,
+                        leg_type: LegType::Navigate,
+                        distance,
+                        cost,
+                        fuel_cost,
+                    });
+                }
+            }
+        }
+
+        for (next, distance) in self.gates.get(&current).into_iter().flatten() {
+            edges.push(Edge {
+                next: *next,
+                leg_type: LegType::Jump,
+                distance: *distance,
+                cost: stats::calculate_jump_cooldown(*distance),
+                fuel_cost: 0,
+            });
+        }
+
+        for (next, next_node) in self.nodes.iter().enumerate() {
+            if next_node.system_symbol == node.system_symbol {
+                continue;
+            }
+            let distance = distance_between_waypoints(node.system_pos, next_node.system_pos);
+            if let Some((nav_mode, cost, fuel_cost)) = self.best_mode(fuel, distance) {
+                edges.push(Edge {
+                    next,
+                    leg_type: LegType::Warp { nav_mode },
+                    distance,
+                    cost,
+                    fuel_cost,
+                });
+            }
+        }
+
+        edges
+    }
+
+    /// the fastest flight mode covering the distance with the fuel as `(mode, seconds, fuel cost)`,
+    /// ships without fuel tanks fly without using fuel
+    fn best_mode(&self, fuel: u32, distance: f64) -> Option<(models::ShipNavFlightMode, f64, u32)> {
+        if self.range == 0 {
+            let mode = models::ShipNavFlightMode::Cruise;
+            return Some((mode, self.travel_time(mode, distance), 0));
+        }
+        if fuel == 0 {
+            return None;
+        }
+
+        self.nav_mode
+            .get_flight_modes(fuel)
+            .iter()
+            .filter(|mode| distance <= mode.radius)
+            .map(|mode| {
+                let stats = stats::get_travel_stats(self.engine_speed, mode.mode, distance);
+                (mode.mode, stats.travel_time, stats.fuel_cost.max(0) as u32)
+            })
+            .filter(|(_, _, fuel_cost)| *fuel_cost <= fuel)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    fn travel_time(&self, flight_mode: models::ShipNavFlightMode, distance: f64) -> f64 {
+        stats::get_travel_stats(self.engine_speed, flight_mode, distance).travel_time
+    }
+
+    fn get_route(
+        &self,
+        previous: &HashMap<SearchState, (SearchState, LegType, f64, f64)>,
+        start: SearchState,
+        end: SearchState,
+    ) -> Vec<WarpLeg> {
+        let mut route = Vec::new();
+        let mut current = end;
+        while current != start {
+            let (from, leg_type, distance, cost) = previous[&current];
+            let from_node = &self.nodes[from.0];
+            let to_node = &self.nodes[current.0];
+            route.push(WarpLeg {
+                start_symbol: from_node.symbol.clone(),
+                end_symbol: to_node.symbol.clone(),
+                system_symbol: from_node.system_symbol.clone(),
+                leg_type,
+                distance,
+                cost,
+                start_is_marketplace: from_node.is_marketplace,
+                end_is_marketplace: to_node.is_marketplace,
+                start_fuel: from.1,
+            });
+            current = from;
+        }
+        route.reverse();
+
+        debug!(route = ?route, "Warp route calculated");
+        route
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space_traders_client::models;
+
+    use super::{LegType, WarpNode, WarpPathfinder};
+    use crate::autopilot::{jump_gate_nav::GateConnection, nav_mode::NavMode};
+
+    /// one waypoint per system, the systems lie 100 apart on a line
+    fn line_of_systems(markets: &[bool]) -> Vec<WarpNode> {
+        markets
+            .iter()
+            .enumerate()
+            .map(|(i, is_marketplace)| WarpNode {
+                symbol: format!("X1-S{i}-A"),
+                system_symbol: format!("X1-S{i}"),
+                pos: Some((0, 0)),
+                system_pos: (i as i32 * 100, 0),
+                is_marketplace: *is_marketplace,
+            })
+            .collect()
+    }
+
+    fn warp_pathfinder(nodes: Vec<WarpNode>, range: u32, start_range: u32) -> WarpPathfinder {
+        WarpPathfinder::new(nodes, &[], range, start_range, NavMode::Cruise, 10)
+    }
+
+    #[test]
+    fn warps_over_several_legs_refueling_between() {
+        let pathfinder = warp_pathfinder(line_of_systems(&[false, true, false]), 120, 120);
+
+        let route = pathfinder.find_route("X1-S0-A", "X1-S2-A").unwrap();
+
+        assert_eq!(
+            route
+                .iter()
+                .map(|leg| (leg.end_symbol.as_str(), leg.start_fuel))
+                .collect::<Vec<_>>(),
+            vec![("X1-S1-A", 120), ("X1-S2-A", 120)]
+        );
+        let cruise = LegType::Warp {
+            nav_mode: models::ShipNavFlightMode::Cruise,
+        };
+        assert!(route.iter().all(|leg| leg.leg_type == cruise));
+        // every leg carries its own travel time, not the time of the route so far
+        assert_eq!(route[0].cost, route[1].cost);
+    }
+
+    /// a jump gate far away whose system lies 80 from the destination system
+    fn gate_and_warp(range: u32, start_range: u32) -> WarpPathfinder {
+        let mut nodes = line_of_systems(&[false, false]);
+        nodes[1].system_pos = (1000, 0);
+        nodes.push(WarpNode {
+            symbol: "X1-S2-A".to_string(),
+            system_symbol: "X1-S2".to_string(),
+            pos: Some((0, 0)),
+            system_pos: (1080, 0),
+            is_marketplace: false,
+        });
+        let connection = GateConnection {
+            point_a: "X1-S0-A".to_string(),
+            pos_point_a: (0, 0),
+            system_point_a: "X1-S0".to_string(),
+            point_b: "X1-S1-A".to_string(),
+            pos_point_b: (1000, 0),
+            system_point_b: "X1-S1".to_string(),
+            under_construction_a: false,
+            under_construction_b: false,
+            from_a: true,
+            from_b: true,
+            distance: 1000.0,
+        };
+        WarpPathfinder::new(
+            nodes,
+            &[connection],
+            range,
+            start_range,
+            NavMode::Cruise,
+            10,
+        )
+    }
+
+    #[test]
+    fn carries_the_fuel_left_into_the_next_warp() {
+        let route = gate_and_warp(200, 100)
+            .find_route("X1-S0-A", "X1-S2-A")
+            .unwrap();
+
+        assert_eq!(
+            route
+                .iter()
+                .map(|leg| (leg.leg_type, leg.start_fuel))
+                .collect::<Vec<_>>(),
+            vec![
+                (LegType::Jump, 100),
+                (
+                    LegType::Warp {
+                        nav_mode: models::ShipNavFlightMode::Cruise
+                    },
+                    100
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn no_route_without_enough_fuel() {
+        // the second warp needs 100 fuel, only 50 are left without a marketplace in between
+        let pathfinder = warp_pathfinder(line_of_systems(&[false, false, false]), 150, 150);
+        assert!(pathfinder.find_route("X1-S0-A", "X1-S2-A").is_none());
+
+        // the tanks are not full at the start and the first marketplace is out of reach
+        let pathfinder = warp_pathfinder(line_of_systems(&[false, true, false]), 120, 60);
+        assert!(pathfinder.find_route("X1-S0-A", "X1-S2-A").is_none());
+
+        // the jump is free but the warp after it needs more than the fuel at the start
+        assert!(
+            gate_and_warp(200, 50)
+                .find_route("X1-S0-A", "X1-S2-A")
+                .is_none()
+        );
+    }
+}
@@ -165,6 +165,7 @@ impl<T: Clone + Send + Sync> RustShip<T, Mutable> {
         Ok(volumes)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn execute_trade(
         &mut self,
         api: &space_traders_client::Api,
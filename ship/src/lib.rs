@@ -2,11 +2,13 @@ pub mod autopilot;
 mod cargo;
 mod error;
 mod fuel;
+mod maintenance;
 mod mining;
 mod modules;
 mod mounts;
 pub mod my_ship_update;
 mod nav;
+mod outfitting;
 mod ship_manager;
 mod ship_models;
 pub mod status;
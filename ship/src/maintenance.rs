@@ -0,0 +1,87 @@
+use database::DatabaseConnectorAsync;
+
+use crate::error;
+
+use super::{Mutable, RustShip};
+
+impl<T: Clone + Send + Sync> RustShip<T, Mutable> {
+    /// The lowest condition of the engine, frame and reactor, repairs restore it up to the integrity
+    pub fn get_min_condition(&self) -> f64 {
+        self.conditions
+            .engine
+            .condition
+            .min(self.conditions.frame.condition)
+            .min(self.conditions.reactor.condition)
+    }
+
+    /// The lowest integrity of the engine, frame and reactor, it wears down permanently
+    pub fn get_min_integrity(&self) -> f64 {
+        self.conditions
+            .engine
+            .integrity
+            .min(self.conditions.frame.integrity)
+            .min(self.conditions.reactor.integrity)
+    }
+
+    /// The cost of repairing the ship at the current shipyard, nothing is recorded
+    pub async fn get_repair_quote(
+        &self,
+        api: &space_traders_client::Api,
+    ) -> error::Result<database::RepairTransaction> {
+        let quote = api.get_repair_ship(&self.symbol).await?;
+        let transaction = database::RepairTransaction::try_from(*quote.data.transaction)?;
+        Ok(transaction)
+    }
+
+    pub async fn repair(
+        &mut self,
+        api: &space_traders_client::Api,
+        database_pool: &database::DbPool,
+        update_funds_fn: impl Fn(i64),
+    ) -> error::Result<database::RepairTransaction> {
+        let repair_data = api.repair_ship(&self.symbol).await?.data;
+
+        self.update(*repair_data.ship);
+        self.notify(true).await;
+
+        update_funds_fn(repair_data.agent.credits);
+
+        database::Agent::upsert(database_pool, &database::Agent::from(*repair_data.agent)).await?;
+
+        let mut transaction = database::RepairTransaction::try_from(*repair_data.transaction)?;
+        transaction.id =
+            database::RepairTransaction::insert_new(database_pool, &transaction).await?;
+
+        Ok(transaction)
+    }
+
+    /// The credits the ship would be scrapped for at the current shipyard, nothing is recorded
+    pub async fn get_scrap_quote(
+        &self,
+        api: &space_traders_client::Api,
+    ) -> error::Result<database::ScrapTransaction> {
+        let quote = api.get_scrap_ship(&self.symbol).await?;
+        let transaction = database::ScrapTransaction::try_from(*quote.data.transaction)?;
+        Ok(transaction)
+    }
+
+    /// Scraps the ship, afterwards it no longer exists and has to be removed from the ship manager
+    pub async fn scrap(
+        &mut self,
+        api: &space_traders_client::Api,
+        database_pool: &database::DbPool,
+        update_funds_fn: impl Fn(i64),
+    ) -> error::Result<database::ScrapTransaction> {
+        let scrap_data = api.scrap_ship(&self.symbol).await?.data;
+
+        update_funds_fn(scrap_data.agent.credits);
+
+        database::Agent::upsert(database_pool, &database::Agent::from(*scrap_data.agent)).await?;
+
+        let mut transaction = database::ScrapTransaction::try_from(*scrap_data.transaction)?;
+        transaction.id =
+            database::ScrapTransaction::insert_new(database_pool, &transaction).await?;
+
+        Ok(transaction)
+    }
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use space_traders_client::{
-    apis::fleet_api::{CreateSurveyError, ExtractResourcesError, SiphonResourcesError},
+    apis::fleet_api::{
+        CreateSurveyError, ExtractResourcesError, ShipRefineError, SiphonResourcesError,
+    },
     models::{self},
 };
 
@@ -93,6 +95,23 @@ impl<T: Clone + Send + Sync> RustShip<T, Mutable> {
         Ok(survey)
     }
 
+    pub async fn refine(
+        &mut self,
+        api: &space_traders_client::Api,
+        produce: models::ship_refine_request::Produce,
+    ) -> Result<models::ShipRefine201Response, space_traders_client::apis::Error<ShipRefineError>>
+    {
+        let refine = api
+            .ship_refine(&self.symbol, models::ShipRefineRequest::new(produce))
+            .await?;
+
+        self.update_cooldown(&refine.data.cooldown);
+        self.cargo.update(&refine.data.cargo);
+        self.notify(true).await;
+
+        Ok(refine)
+    }
+
     pub fn update_cooldown(&mut self, cooldown: &models::Cooldown) {
         let cool_text = cooldown.expiration.as_ref().map_or("", |v| v);
         self.cooldown_expiration = DateTime::parse_from_rfc3339(cool_text)
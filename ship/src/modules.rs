@@ -17,4 +17,10 @@ impl ModuleState {
                 || m == &models::ship_module::Symbol::CargoHoldIii
         })
     }
+
+    pub fn can_refine_ore(&self) -> bool {
+        self.modules
+            .iter()
+            .any(|m| m == &models::ship_module::Symbol::OreRefineryI)
+    }
 }
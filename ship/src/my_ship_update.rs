@@ -76,6 +76,7 @@ impl<T: Clone + Send + Sync> RustShip<T, Mutable> {
         }
     }
 
+    #[allow(dead_code)]
     async fn receive_update_loop(
         &mut self,
         cancel: &tokio_util::sync::CancellationToken,
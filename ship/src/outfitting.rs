@@ -0,0 +1,158 @@
+use database::DatabaseConnectorAsync;
+use space_traders_client::models;
+
+use crate::error;
+
+use super::{Mutable, RustShip};
+
+impl<T: Clone + Send + Sync> RustShip<T, Mutable> {
+    /// Installs a mount from the cargo, the ship has to be docked at a shipyard
+    pub async fn install_mount(
+        &mut self,
+        api: &space_traders_client::Api,
+        symbol: models::ship_mount::Symbol,
+        database_pool: &database::DbPool,
+        update_funds_fn: impl Fn(i64),
+    ) -> error::Result<database::ShipModificationTransaction> {
+        let install_data = api
+            .install_mount(
+                &self.symbol,
+                models::InstallMountRequest {
+                    symbol: part_symbol(&symbol)?,
+                },
+            )
+            .await?
+            .data;
+
+        self.mounts.update(&install_data.mounts);
+        self.cargo.update(&install_data.cargo);
+        self.notify(true).await;
+
+        self.record_modification(
+            database_pool,
+            *install_data.agent,
+            *install_data.transaction,
+            update_funds_fn,
+        )
+        .await
+    }
+
+    /// Removes a mount into the cargo, the ship has to be docked at a shipyard
+    pub async fn remove_mount(
+        &mut self,
+        api: &space_traders_client::Api,
+        symbol: models::ship_mount::Symbol,
+        database_pool: &database::DbPool,
+        update_funds_fn: impl Fn(i64),
+    ) -> error::Result<database::ShipModificationTransaction> {
+        let remove_data = api
+            .remove_mount(
+                &self.symbol,
+                models::RemoveMountRequest {
+                    symbol: part_symbol(&symbol)?,
+                },
+            )
+            .await?
+            .data;
+
+        self.mounts.update(&remove_data.mounts);
+        self.cargo.update(&remove_data.cargo);
+        self.notify(true).await;
+
+        self.record_modification(
+            database_pool,
+            *remove_data.agent,
+            *remove_data.transaction,
+            update_funds_fn,
+        )
+        .await
+    }
+
+    /// Installs a module from the cargo, the ship has to be docked at a shipyard
+    pub async fn install_module(
+        &mut self,
+        api: &space_traders_client::Api,
+        symbol: models::ship_module::Symbol,
+        database_pool: &database::DbPool,
+        update_funds_fn: impl Fn(i64),
+    ) -> error::Result<database::ShipModificationTransaction> {
+        let install_data = api
+            .install_ship_module(
+                &self.symbol,
+                models::InstallShipModuleRequest {
+                    symbol: part_symbol(&symbol)?,
+                },
+            )
+            .await?
+            .data;
+
+        self.modules.update(&install_data.modules);
+        self.cargo.update(&install_data.cargo);
+        self.notify(true).await;
+
+        self.record_modification(
+            database_pool,
+            *install_data.agent,
+            *install_data.transaction,
+            update_funds_fn,
+        )
+        .await
+    }
+
+    /// Removes a module into the cargo, the ship has to be docked at a shipyard
+    pub async fn remove_module(
+        &mut self,
+        api: &space_traders_client::Api,
+        symbol: models::ship_module::Symbol,
+        database_pool: &database::DbPool,
+        update_funds_fn: impl Fn(i64),
+    ) -> error::Result<database::ShipModificationTransaction> {
+        let remove_data = api
+            .remove_ship_module(
+                &self.symbol,
+                models::RemoveShipModuleRequest {
+                    symbol: part_symbol(&symbol)?,
+                },
+            )
+            .await?
+            .data;
+
+        self.modules.update(&remove_data.modules);
+        self.cargo.update(&remove_data.cargo);
+        self.notify(true).await;
+
+        self.record_modification(
+            database_pool,
+            *remove_data.agent,
+            *remove_data.transaction,
+            update_funds_fn,
+        )
+        .await
+    }
+
+    async fn record_modification(
+        &self,
+        database_pool: &database::DbPool,
+        agent: models::Agent,
+        transaction: models::ShipModificationTransaction,
+        update_funds_fn: impl Fn(i64),
+    ) -> error::Result<database::ShipModificationTransaction> {
+        update_funds_fn(agent.credits);
+
+        database::Agent::upsert(database_pool, &database::Agent::from(agent)).await?;
+
+        let mut transaction = database::ShipModificationTransaction::try_from(transaction)?;
+        transaction.id =
+            database::ShipModificationTransaction::insert_new(database_pool, &transaction).await?;
+
+        Ok(transaction)
+    }
+}
+
+/// the symbol of a mount or module as the api expects it, the generated enums don't implement `Display`
+fn part_symbol<S: serde::Serialize>(symbol: &S) -> error::Result<String> {
+    serde_json::to_value(symbol)
+        .ok()
+        .and_then(|value| value.as_str().map(|s| s.to_string()))
+        .ok_or_else(|| "Invalid part symbol".into())
+}
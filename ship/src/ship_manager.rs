@@ -117,6 +117,15 @@ impl<T: Clone + Send + Sync> ShipManager<T> {
             .unwrap()
     }
 
+    /// Removes a ship which no longer exists, e.g. after it was scrapped. Takes the guard so nobody else holds the ship while it's removed.
+    pub async fn remove_ship(&self, mut guard: ShipGuard<'_, T>) -> Option<RustShip<T, Mutable>> {
+        let ship = guard.remove();
+        if let Some(ship) = &ship {
+            self.copy.write().await.remove(&ship.symbol);
+        }
+        ship
+    }
+
     pub fn get_broadcaster(&self) -> my_ship_update::InterShipBroadcaster {
         self.broadcaster.clone()
     }
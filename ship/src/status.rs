@@ -19,6 +19,8 @@ pub struct ShipStatus {
     pub temp_fleet_id: Option<i32>,
     pub assignment_id: Option<i64>,
     pub temp_assignment_id: Option<i64>,
+    /// the ship or its fleet is inactive, the pilot is parked until it is resumed
+    pub paused: bool,
     #[graphql(skip)]
     pub status: AssignmentStatus,
 }
@@ -44,6 +46,14 @@ pub enum AssignmentStatus {
         assignment_id: i64,
         system_symbol: String,
     },
+    Outfitting {
+        fleet_id: i32,
+        assignment_id: i64,
+        waypoint_symbol: String,
+    },
+    Maintenance {
+        waypoint_symbol: String,
+    },
     Contract {
         contract_id: Option<String>,
         run_id: Option<i32>,
@@ -65,6 +75,12 @@ pub enum AssignmentStatus {
         waiting_for_manager: bool,
         waypoint_symbol: Option<String>,
     },
+    Refining {
+        cycle: Option<i32>,
+        state: RefinerState,
+        waypoint_symbol: Option<String>,
+        refined: Option<i32>,
+    },
     #[default]
     Manuel,
 }
@@ -118,3 +134,15 @@ pub enum ExtractorState {
     #[default]
     Unknown,
 }
+
+#[derive(Debug, Default, Clone, Copy, serde::Serialize, PartialEq, Eq, Enum)]
+pub enum RefinerState {
+    InTransitToAsteroid,
+    WaitingForCargo,
+    LoadingCargo,
+    Refining,
+    InTransitToMarket,
+    SellingCargo,
+    #[default]
+    Unknown,
+}
@@ -34,18 +34,24 @@ use crate::models::{Register201ResponseData, RegisterRequest};
 use log::debug;
 use std::time::Duration;
 
-use crate::rate_limiter::PriorityRateLimiter;
+use crate::rate_limiter::{PriorityRateLimiter, RequestPriority};
 
 #[derive(Debug, Clone)]
 pub struct Api {
     configuration: Arc<Configuration>,
     limiter: Arc<PriorityRateLimiter>,
+    priority: RequestPriority,
+    /// the part of the bot using this handle, requests are counted by it
+    subsystem: &'static str,
 }
 
 macro_rules! rate_limit_retry {
     ($self:ident, $priority:expr, $name:expr, $code:expr) => {
         loop {
-            $self.limiter.until_ready($priority, $name).await;
+            $self
+                .limiter
+                .until_ready($priority, $self.subsystem, $name)
+                .await;
             let result = $code;
             if let Err(Error::ResponseError(ref response_content)) = result {
                 if response_content.status == reqwest::StatusCode::TOO_MANY_REQUESTS
@@ -75,9 +81,52 @@ impl Api {
                 ..Default::default()
             }),
             limiter: Arc::new(PriorityRateLimiter::new(quota, burst)),
+            priority: RequestPriority::default(),
+            subsystem: "general",
         }
     }
 
+    /// Returns a handle sharing configuration and rate limiter, whose requests are queued with the given priority.
+    pub fn with_priority(&self, priority: RequestPriority) -> Api {
+        Api {
+            configuration: self.configuration.clone(),
+            limiter: self.limiter.clone(),
+            priority,
+            subsystem: self.subsystem,
+        }
+    }
+
+    /// Returns a handle sharing configuration and rate limiter, whose requests are counted for the given subsystem.
+    pub fn with_subsystem(&self, subsystem: &'static str) -> Api {
+        Api {
+            configuration: self.configuration.clone(),
+            limiter: self.limiter.clone(),
+            priority: self.priority,
+            subsystem,
+        }
+    }
+
+    /// Returns a handle sharing the rate limiter, whose requests go to another server like a local mock.
+    pub fn with_base_path(&self, base_path: impl Into<String>) -> Api {
+        Api {
+            configuration: Arc::new(Configuration {
+                base_path: base_path.into(),
+                ..Configuration::clone(&self.configuration)
+            }),
+            limiter: self.limiter.clone(),
+            priority: self.priority,
+            subsystem: self.subsystem,
+        }
+    }
+
+    pub fn get_priority(&self) -> RequestPriority {
+        self.priority
+    }
+
+    pub fn get_subsystem(&self) -> &'static str {
+        self.subsystem
+    }
+
     pub fn get_configuration(&self) -> Arc<Configuration> {
         self.configuration.clone()
     }
@@ -90,7 +139,7 @@ impl Api {
     pub async fn get_status(&self) -> Result<models::GetStatus200Response, Error<GetStatusError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_status",
             crate::apis::global_api::get_status(&self.configuration).await
         )?;
@@ -108,7 +157,7 @@ impl Api {
         config.bearer_access_token = Some(account_token);
         let register_response: models::Register201Response = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "register",
             crate::apis::accounts_api::register(
                 &config,
@@ -127,7 +176,7 @@ impl Api {
     ) -> Result<models::GetSupplyChain200Response, Error<GetSupplyChainError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_exports_to_imports",
             crate::apis::data_api::get_supply_chain(&self.configuration).await
         )?;
@@ -143,7 +192,7 @@ impl Api {
         config.bearer_access_token = Some(account_token);
         let register_response = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_my_account",
             crate::apis::accounts_api::get_my_account(&config).await
         )?;
@@ -157,7 +206,7 @@ impl Api {
     ) -> Result<models::GetAgent200Response, Error<GetAgentError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_agent",
             crate::apis::agents_api::get_agent(&self.configuration, agent_symbol).await
         )?;
@@ -172,7 +221,7 @@ impl Api {
     ) -> Result<models::GetAgents200Response, Error<GetAgentsError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_agents",
             crate::apis::agents_api::get_agents(&self.configuration, page, limit).await
         )?;
@@ -219,7 +268,7 @@ impl Api {
     ) -> Result<models::GetMyAgent200Response, Error<GetMyAgentError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_my_agent",
             crate::apis::agents_api::get_my_agent(&self.configuration).await
         )?;
@@ -232,7 +281,7 @@ impl Api {
     ) -> Result<models::GetMyAgentEvents200Response, Error<GetMyAgentEventsError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_my_agent_events",
             crate::apis::agents_api::get_my_agent_events(&self.configuration).await
         )?;
@@ -249,7 +298,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "accept_contract",
             crate::apis::contracts_api::accept_contract(&self.configuration, contract_id).await
         )?;
@@ -267,7 +316,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "deliver_contract",
             crate::apis::contracts_api::deliver_contract(
                 &self.configuration,
@@ -289,7 +338,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "fulfill_contract",
             crate::apis::contracts_api::fulfill_contract(&self.configuration, contract_id).await
         )?;
@@ -306,7 +355,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_contract",
             crate::apis::contracts_api::get_contract(&self.configuration, contract_id).await
         )?;
@@ -324,7 +373,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_contracts",
             crate::apis::contracts_api::get_contracts(&self.configuration, page, limit).await
         )?;
@@ -376,7 +425,7 @@ impl Api {
     ) -> Result<models::GetFaction200Response, Error<GetFactionError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_faction",
             crate::apis::factions_api::get_faction(&self.configuration, faction_symbol).await
         )?;
@@ -391,7 +440,7 @@ impl Api {
     ) -> Result<models::GetFactions200Response, Error<GetFactionsError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_factions",
             crate::apis::factions_api::get_factions(&self.configuration, page, limit).await
         )?;
@@ -442,7 +491,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "create_chart",
             crate::apis::fleet_api::create_chart(&self.configuration, ship_symbol).await
         )?;
@@ -459,7 +508,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "create_ship_ship_scan",
             crate::apis::fleet_api::create_ship_ship_scan(&self.configuration, ship_symbol).await
         )?;
@@ -476,7 +525,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "create_ship_system_scan",
             crate::apis::fleet_api::create_ship_system_scan(&self.configuration, ship_symbol).await
         )?;
@@ -493,7 +542,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "create_ship_waypoint_scan",
             crate::apis::fleet_api::create_ship_waypoint_scan(&self.configuration, ship_symbol)
                 .await
@@ -511,7 +560,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "create_survey",
             crate::apis::fleet_api::create_survey(&self.configuration, ship_symbol).await
         )?;
@@ -528,7 +577,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "dock_ship",
             crate::apis::fleet_api::dock_ship(&self.configuration, ship_symbol).await
         )?;
@@ -545,7 +594,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "extract_resources",
             crate::apis::fleet_api::extract_resources(&self.configuration, ship_symbol).await
         )?;
@@ -563,7 +612,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "extract_resources_with_survey",
             crate::apis::fleet_api::extract_resources_with_survey(
                 &self.configuration,
@@ -585,7 +634,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_mounts",
             crate::apis::fleet_api::get_mounts(&self.configuration, ship_symbol).await
         )?;
@@ -602,7 +651,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_ship_modules",
             crate::apis::fleet_api::get_ship_modules(&self.configuration, ship_symbol).await
         )?;
@@ -619,7 +668,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_my_ship",
             crate::apis::fleet_api::get_my_ship(&self.configuration, ship_symbol).await
         )?;
@@ -636,7 +685,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_my_ship_cargo",
             crate::apis::fleet_api::get_my_ship_cargo(&self.configuration, ship_symbol).await
         )?;
@@ -654,7 +703,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_my_ships",
             crate::apis::fleet_api::get_my_ships(&self.configuration, page, limit).await
         )?;
@@ -709,7 +758,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_repair_ship",
             crate::apis::fleet_api::get_repair_ship(&self.configuration, ship_symbol).await
         )?;
@@ -726,7 +775,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_scrap_ship",
             crate::apis::fleet_api::get_scrap_ship(&self.configuration, ship_symbol).await
         )?;
@@ -743,7 +792,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_ship_cooldown",
             crate::apis::fleet_api::get_ship_cooldown(&self.configuration, ship_symbol).await
         )?;
@@ -760,7 +809,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_ship_nav",
             crate::apis::fleet_api::get_ship_nav(&self.configuration, ship_symbol).await
         )?;
@@ -778,7 +827,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "install_mount",
             crate::apis::fleet_api::install_mount(
                 &self.configuration,
@@ -801,7 +850,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "install_ship_module",
             crate::apis::fleet_api::install_ship_module(
                 &self.configuration,
@@ -824,7 +873,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "jettison",
             crate::apis::fleet_api::jettison(
                 &self.configuration,
@@ -847,7 +896,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "jump_ship",
             crate::apis::fleet_api::jump_ship(
                 &self.configuration,
@@ -870,7 +919,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "navigate_ship",
             crate::apis::fleet_api::navigate_ship(
                 &self.configuration,
@@ -895,7 +944,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "negotiate_contract",
             crate::apis::contracts_api::negotiate_contract(&self.configuration, ship_symbol).await
         )?;
@@ -912,7 +961,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "orbit_ship",
             crate::apis::fleet_api::orbit_ship(&self.configuration, ship_symbol).await
         )?;
@@ -930,7 +979,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "patch_ship_nav",
             crate::apis::fleet_api::patch_ship_nav(
                 &self.configuration,
@@ -953,7 +1002,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "purchase_cargo",
             crate::apis::fleet_api::purchase_cargo(
                 &self.configuration,
@@ -975,7 +1024,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "purchase_ship",
             crate::apis::fleet_api::purchase_ship(
                 &self.configuration,
@@ -997,7 +1046,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "refuel_ship",
             crate::apis::fleet_api::refuel_ship(
                 &self.configuration,
@@ -1020,7 +1069,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "remove_mount",
             crate::apis::fleet_api::remove_mount(
                 &self.configuration,
@@ -1043,7 +1092,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "remove_ship_module",
             crate::apis::fleet_api::remove_ship_module(
                 &self.configuration,
@@ -1065,7 +1114,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "repair_ship",
             crate::apis::fleet_api::repair_ship(&self.configuration, ship_symbol).await
         )?;
@@ -1082,7 +1131,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "scrap_ship",
             crate::apis::fleet_api::scrap_ship(&self.configuration, ship_symbol).await
         )?;
@@ -1100,7 +1149,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "sell_cargo",
             crate::apis::fleet_api::sell_cargo(
                 &self.configuration,
@@ -1123,7 +1172,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "ship_refine",
             crate::apis::fleet_api::ship_refine(
                 &self.configuration,
@@ -1145,7 +1194,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "siphon_resources",
             crate::apis::fleet_api::siphon_resources(&self.configuration, ship_symbol).await
         )?;
@@ -1163,7 +1212,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "transfer_cargo",
             crate::apis::fleet_api::transfer_cargo(
                 &self.configuration,
@@ -1186,7 +1235,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "warp_ship",
             crate::apis::fleet_api::warp_ship(
                 &self.configuration,
@@ -1206,7 +1255,7 @@ impl Api {
     ) -> Result<models::GetConstruction200Response, Error<GetConstructionError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_construction",
             crate::apis::systems_api::get_construction(
                 &self.configuration,
@@ -1226,7 +1275,7 @@ impl Api {
     ) -> Result<models::GetJumpGate200Response, Error<GetJumpGateError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_jump_gate",
             crate::apis::systems_api::get_jump_gate(
                 &self.configuration,
@@ -1246,7 +1295,7 @@ impl Api {
     ) -> Result<models::GetMarket200Response, Error<GetMarketError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_market",
             crate::apis::systems_api::get_market(
                 &self.configuration,
@@ -1266,7 +1315,7 @@ impl Api {
     ) -> Result<models::GetShipyard200Response, Error<GetShipyardError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_shipyard",
             crate::apis::systems_api::get_shipyard(
                 &self.configuration,
@@ -1285,7 +1334,7 @@ impl Api {
     ) -> Result<models::GetSystem200Response, Error<GetSystemError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_system",
             crate::apis::systems_api::get_system(&self.configuration, system_symbol).await
         )?;
@@ -1303,7 +1352,7 @@ impl Api {
     ) -> Result<models::GetSystemWaypoints200Response, Error<GetSystemWaypointsError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_system_waypoints",
             crate::apis::systems_api::get_system_waypoints(
                 &self.configuration,
@@ -1381,7 +1430,7 @@ impl Api {
     ) -> Result<models::GetSystems200Response, Error<GetSystemsError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_systems",
             crate::apis::systems_api::get_systems(&self.configuration, page, limit).await
         )?;
@@ -1431,7 +1480,7 @@ impl Api {
     ) -> Result<models::GetWaypoint200Response, Error<GetWaypointError>> {
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "get_waypoint",
             crate::apis::systems_api::get_waypoint(
                 &self.configuration,
@@ -1455,7 +1504,7 @@ impl Api {
         }
         let result = rate_limit_retry!(
             self,
-            50,
+            self.priority,
             "supply_construction",
             crate::apis::systems_api::supply_construction(
                 &self.configuration,
@@ -8,8 +8,11 @@ extern crate serde_repr;
 extern crate url;
 
 pub use crate::api::Api;
+pub use crate::rate_limiter::{RequestPriority, SentRequest};
 mod api;
 pub mod apis;
 mod middleware;
+// generated by openapi-generator, which writes manual Default impls and empty docs
+#[allow(clippy::derivable_impls, clippy::empty_docs)]
 pub mod models;
 mod rate_limiter;
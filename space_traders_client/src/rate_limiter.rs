@@ -1,4 +1,96 @@
-use std::{num::NonZeroU32, sync::atomic::AtomicI64, time::Duration};
+use std::{
+    cmp::Ordering as CmpOrdering,
+    collections::{BinaryHeap, HashMap},
+    num::NonZeroU32,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+/// Priority class of an API request.
+///
+/// Waiting requests are served highest priority first and in FIFO order within the same priority.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    async_graphql::Enum,
+)]
+pub enum RequestPriority {
+    /// Bulk crawls like fetching all systems or agents
+    Background = 0,
+    /// Market, shipyard and jump gate refreshes
+    Low = 25,
+    #[default]
+    Normal = 50,
+    /// Money-moving actions like trades and contract deliveries
+    High = 75,
+    /// Requests that must never wait behind others
+    Critical = 100,
+}
+
+impl std::fmt::Display for RequestPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Background => write!(f, "BACKGROUND"),
+            Self::Low => write!(f, "LOW"),
+            Self::Normal => write!(f, "NORMAL"),
+            Self::High => write!(f, "HIGH"),
+            Self::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+/// What a sent request is counted by
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SentRequest {
+    pub priority: RequestPriority,
+    /// the part of the bot sending the request, like a manager or a pilot
+    pub subsystem: &'static str,
+    pub endpoint: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueueEntry {
+    priority: RequestPriority,
+    ticket: u64,
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        // BinaryHeap is a max-heap: higher priority first, then lower (older) ticket first
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.ticket.cmp(&self.ticket))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    waiting: BinaryHeap<QueueEntry>,
+    /// Ticket of the request currently waiting on the rate limit bucket
+    dispatching: Option<u64>,
+}
 
 #[derive(Debug)]
 pub struct PriorityRateLimiter {
@@ -9,6 +101,11 @@ pub struct PriorityRateLimiter {
         governor::middleware::NoOpMiddleware<governor::clock::QuantaInstant>,
     >,
     pub counter: AtomicI64,
+    next_ticket: AtomicU64,
+    state: Mutex<QueueState>,
+    /// Requests let through per priority, subsystem and endpoint, retries included
+    sent: Mutex<HashMap<SentRequest, u64>>,
+    notify: tokio::sync::Notify,
 }
 
 impl PriorityRateLimiter {
@@ -23,18 +120,166 @@ impl PriorityRateLimiter {
         Self {
             limiter,
             counter: AtomicI64::new(0),
+            next_ticket: AtomicU64::new(0),
+            state: Mutex::new(QueueState::default()),
+            sent: Mutex::new(HashMap::new()),
+            notify: tokio::sync::Notify::new(),
         }
     }
 
-    pub async fn until_ready(&self, priority: u32, message: &str) {
-        self.counter
-            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    /// Waits until the request is allowed to be sent.
+    ///
+    /// Only the head of the queue waits on the rate limit bucket, everyone else waits for their turn.
+    pub async fn until_ready(
+        &self,
+        priority: RequestPriority,
+        subsystem: &'static str,
+        message: &str,
+    ) {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::SeqCst);
+        let mut guard = TicketGuard {
+            limiter: self,
+            ticket,
+            queued: true,
+            dispatching: false,
+        };
+
+        self.counter.fetch_add(1, Ordering::SeqCst);
+        self.state
+            .lock()
+            .unwrap()
+            .waiting
+            .push(QueueEntry { priority, ticket });
+
+        loop {
+            let mut notified = std::pin::pin!(self.notify.notified());
+            notified.as_mut().enable();
+
+            if self.try_dispatch(ticket) {
+                guard.queued = false;
+                guard.dispatching = true;
+                break;
+            }
+
+            notified.await;
+        }
+
+        tracing::trace!(%priority, message, ticket, "Waiting on rate limit");
         self.limiter.until_ready().await;
-        self.counter
-            .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+
+        *self
+            .sent
+            .lock()
+            .unwrap()
+            .entry(SentRequest {
+                priority,
+                subsystem,
+                endpoint: message.to_string(),
+            })
+            .or_default() += 1;
+    }
+
+    fn try_dispatch(&self, ticket: u64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.dispatching.is_some() {
+            return false;
+        }
+        match state.waiting.peek() {
+            Some(head) if head.ticket == ticket => {
+                state.waiting.pop();
+                state.dispatching = Some(ticket);
+                true
+            }
+            _ => false,
+        }
     }
 
     pub fn get_counter(&self) -> i64 {
-        self.counter.load(std::sync::atomic::Ordering::SeqCst)
+        self.counter.load(Ordering::SeqCst)
+    }
+
+    /// Number of requests sent since the limiter was created, per priority, subsystem and endpoint
+    pub fn get_sent_counts(&self) -> HashMap<SentRequest, u64> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Number of requests currently waiting with the given priority
+    pub fn get_queue_depth(&self, priority: RequestPriority) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .waiting
+            .iter()
+            .filter(|entry| entry.priority == priority)
+            .count()
+    }
+}
+
+/// Removes a request from the queue when it is done or its future got dropped.
+struct TicketGuard<'a> {
+    limiter: &'a PriorityRateLimiter,
+    ticket: u64,
+    queued: bool,
+    dispatching: bool,
+}
+
+impl Drop for TicketGuard<'_> {
+    fn drop(&mut self) {
+        {
+            let mut state = self.limiter.state.lock().unwrap();
+            if self.queued {
+                state.waiting.retain(|entry| entry.ticket != self.ticket);
+            }
+            if self.dispatching && state.dispatching == Some(self.ticket) {
+                state.dispatching = None;
+            }
+        }
+        self.limiter.counter.fetch_sub(1, Ordering::SeqCst);
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroU32, sync::Arc};
+
+    use super::{PriorityRateLimiter, RequestPriority};
+
+    #[tokio::test]
+    async fn serves_by_priority_then_fifo() {
+        let limiter = Arc::new(PriorityRateLimiter::new(20, NonZeroU32::new(1).unwrap()));
+        // drain the burst so every following request has to queue
+        limiter
+            .until_ready(RequestPriority::Normal, "test", "warmup")
+            .await;
+
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = [
+            (RequestPriority::Normal, "in_flight"),
+            (RequestPriority::Background, "background"),
+            (RequestPriority::Normal, "normal_1"),
+            (RequestPriority::High, "high"),
+            (RequestPriority::Normal, "normal_2"),
+        ];
+
+        let mut handles = Vec::new();
+        for (priority, name) in requests {
+            let limiter = limiter.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                limiter.until_ready(priority, "test", name).await;
+                order.lock().unwrap().push(name);
+            }));
+            tokio::task::yield_now().await;
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["in_flight", "high", "normal_1", "normal_2", "background"]
+        );
+        assert_eq!(limiter.get_counter(), 0);
     }
 }
@@ -0,0 +1,306 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_graphql::{
+    ServerResult, Value, Variables,
+    extensions::{
+        Extension, ExtensionContext, ExtensionFactory, NextParseQuery, NextResolve, ResolveInfo,
+    },
+    parser::types::ExecutableDocument,
+};
+use database::{ControlApiToken, ControlRole, DatabaseConnectorAsync};
+use rand::Rng;
+use warp::{Filter, Rejection};
+
+/// Where a generated operator token is written, the token itself is never logged
+const GENERATED_TOKEN_FILE: &str = "./control_api_token";
+
+/// Writes a file only readable by the owner, it holds control api tokens
+async fn write_owner_only(path: &std::path::Path, content: &[u8]) -> crate::error::Result<()> {
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let write = async {
+        let mut file = options.open(path).await?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, content).await
+    };
+    write.await.map_err(|e| {
+        crate::error::Error::General(format!("Failed to write {}: {}", path.display(), e))
+    })
+}
+
+/// The caller of a control api request, resolved from the token it sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlUser {
+    pub name: String,
+    pub role: ControlRole,
+}
+
+/// The tokens accepted by the control api, from the `CONTROL_API_TOKENS` env variable and the `control_api_tokens` configuration
+#[derive(Debug, Clone, Default)]
+pub struct ControlApiTokens {
+    tokens: HashMap<String, ControlUser>,
+}
+
+impl ControlApiTokens {
+    /// Loads all configured tokens, when there are none an operator token is generated, stored in the configuration and written to `./control_api_token`
+    ///
+    /// The stored tokens are carried across resets by [`crate::reset_priors::export_tokens`]
+    pub async fn load(database_pool: &database::DbPool) -> crate::error::Result<Self> {
+        let mut tokens = database::Configuration::get_control_api_tokens(database_pool).await?;
+
+        if let Ok(env_tokens) = std::env::var("CONTROL_API_TOKENS") {
+            tokens.extend(Self::parse_env(&env_tokens)?);
+        }
+
+        if tokens.is_empty() {
+            let token = ControlApiToken {
+                name: "generated".to_string(),
+                role: ControlRole::Operator,
+                token: rand::thread_rng()
+                    .sample_iter(&rand::distributions::Alphanumeric)
+                    .take(32)
+                    .map(char::from)
+                    .collect(),
+            };
+            database::Configuration::set_control_api_tokens(
+                database_pool,
+                std::slice::from_ref(&token),
+            )
+            .await?;
+            write_owner_only(
+                std::path::Path::new(GENERATED_TOKEN_FILE),
+                token.token.as_bytes(),
+            )
+            .await?;
+            tracing::warn!(
+                name = %token.name,
+                token_file = GENERATED_TOKEN_FILE,
+                "No control api tokens configured, generated an operator token"
+            );
+            tokens.push(token);
+        }
+
+        Ok(Self {
+            tokens: tokens
+                .into_iter()
+                .map(|t| {
+                    (
+                        t.token,
+                        ControlUser {
+                            name: t.name,
+                            role: t.role,
+                        },
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Writes the stored tokens to `path`, only readable by the owner
+    pub async fn export(
+        database_pool: &database::DbPool,
+        path: &std::path::Path,
+    ) -> crate::error::Result<usize> {
+        let tokens = database::Configuration::get_control_api_tokens(database_pool).await?;
+        let content = serde_json::to_string_pretty(&tokens)
+            .map_err(|e| crate::error::Error::General(e.to_string()))?;
+
+        write_owner_only(path, content.as_bytes()).await?;
+        Ok(tokens.len())
+    }
+
+    /// Stores the tokens of `path` in a database without tokens
+    ///
+    /// Returns how many tokens were imported, a database which already has tokens is left as it is
+    pub async fn import(
+        database_pool: &database::DbPool,
+        path: &std::path::Path,
+    ) -> crate::error::Result<usize> {
+        if !database::Configuration::get_control_api_tokens(database_pool)
+            .await?
+            .is_empty()
+        {
+            return Ok(0);
+        }
+
+        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+            crate::error::Error::General(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let tokens: Vec<ControlApiToken> = serde_json::from_str(&content)
+            .map_err(|e| crate::error::Error::General(e.to_string()))?;
+
+        database::Configuration::set_control_api_tokens(database_pool, &tokens).await?;
+        Ok(tokens.len())
+    }
+
+    /// Parses `name:role:token` entries separated by commas
+    fn parse_env(value: &str) -> crate::error::Result<Vec<ControlApiToken>> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let mut parts = entry.splitn(3, ':');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(name), Some(role), Some(token)) if !token.is_empty() => {
+                        Ok(ControlApiToken {
+                            name: name.to_string(),
+                            role: role.parse().map_err(crate::error::Error::General)?,
+                            token: token.to_string(),
+                        })
+                    }
+                    _ => Err(crate::error::Error::General(format!(
+                        "Invalid CONTROL_API_TOKENS entry for {}, expected name:role:token",
+                        entry.split(':').next().unwrap_or_default()
+                    ))),
+                }
+            })
+            .collect()
+    }
+
+    /// Accepts the plain token as well as `Bearer <token>`
+    pub fn authenticate(&self, token: &str) -> Option<ControlUser> {
+        let token = token.trim();
+        let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+        self.tokens.get(token).cloned()
+    }
+
+    /// The token of a graphql-ws `connection_init` payload, browsers can not set headers on websockets
+    pub fn authenticate_payload(&self, payload: &serde_json::Value) -> Option<ControlUser> {
+        ["Authorization", "authorization", "token"]
+            .iter()
+            .find_map(|key| payload.get(key).and_then(|v| v.as_str()))
+            .and_then(|token| self.authenticate(token))
+    }
+}
+
+#[derive(Debug)]
+pub struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Rejects requests without a valid `Authorization` header
+pub fn with_user(
+    tokens: Arc<ControlApiTokens>,
+) -> impl Filter<Extract = (ControlUser,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization").and_then(
+        move |authorization: Option<String>| {
+            let user = authorization.and_then(|a| tokens.authenticate(&a));
+            async move { user.ok_or_else(|| warp::reject::custom(Unauthorized)) }
+        },
+    )
+}
+
+/// Only lets users with at least the given role resolve a field
+pub struct RoleGuard {
+    role: ControlRole,
+}
+
+impl RoleGuard {
+    pub fn new(role: ControlRole) -> Self {
+        Self { role }
+    }
+}
+
+impl async_graphql::Guard for RoleGuard {
+    async fn check(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<()> {
+        match ctx.data_opt::<ControlUser>() {
+            Some(user) if user.role >= self.role => Ok(()),
+            Some(_) => Err(format!("Forbidden, requires the {:?} role", self.role).into()),
+            None => Err("Unauthorized".into()),
+        }
+    }
+}
+
+/// Writes every mutation, including rejected ones, to the control audit log
+pub struct AuditLog;
+
+impl ExtensionFactory for AuditLog {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AuditLogExtension::default())
+    }
+}
+
+#[derive(Default)]
+struct AuditLogExtension {
+    variables: Mutex<Variables>,
+}
+
+#[async_trait::async_trait]
+impl Extension for AuditLogExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        *self.variables.lock().unwrap() = variables.clone();
+        next.run(ctx, query, variables).await
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.parent_type != "MutationRoot" || info.path_node.parent.is_some() {
+            return next.run(ctx, info).await;
+        }
+
+        let operation = info.name.to_string();
+        let arguments = {
+            let variables = self.variables.lock().unwrap();
+            info.field
+                .arguments
+                .iter()
+                .map(|(name, value)| {
+                    let value = value
+                        .node
+                        .clone()
+                        .into_const_with(|var| variables.get(&var).cloned().ok_or(()))
+                        .ok()
+                        .and_then(|v| serde_json::to_value(v).ok())
+                        .unwrap_or_default();
+                    (name.node.to_string(), value)
+                })
+                .collect::<serde_json::Map<_, _>>()
+        };
+
+        let erg = next.run(ctx, info).await;
+
+        let user = ctx.data_opt::<ControlUser>().cloned();
+        let entry = database::ControlAuditLog {
+            id: 0,
+            token_name: user
+                .as_ref()
+                .map(|u| u.name.clone())
+                .unwrap_or("anonymous".to_string()),
+            role: user.map(|u| u.role).unwrap_or(ControlRole::ReadOnly),
+            operation,
+            arguments: serde_json::Value::Object(arguments),
+            success: erg.is_ok(),
+            error: erg.as_ref().err().map(|e| e.message.clone()),
+            created_at: chrono::Utc::now(),
+        };
+
+        match ctx.data::<database::DbPool>() {
+            Ok(database_pool) => {
+                if let Err(err) = database::ControlAuditLog::insert_new(database_pool, &entry).await
+                {
+                    tracing::error!(error = ?err, entry = ?entry, "Failed to write audit log");
+                }
+            }
+            Err(err) => {
+                tracing::error!(error = ?err, entry = ?entry, "No database for the audit log");
+            }
+        }
+
+        erg
+    }
+}
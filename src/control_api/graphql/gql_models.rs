@@ -1811,6 +1811,27 @@ paginated_gql_object!(
     GQLShipModificationTransaction
 );
 
+paginated_gql_object!(
+    GQLControlAuditLogPage,
+    "ControlAuditLogPage",
+    database::ControlAuditLog,
+    database::ControlAuditLog
+);
+
+paginated_gql_object!(
+    GQLShipCommandPage,
+    "ShipCommandPage",
+    database::ShipCommand,
+    database::ShipCommand
+);
+
+paginated_gql_object!(
+    GQLLedgerEntryPage,
+    "LedgerEntryPage",
+    database::LedgerEntry,
+    database::LedgerEntry
+);
+
 #[derive(Debug, Clone, async_graphql::SimpleObject)]
 #[graphql(name = "ShipState")]
 #[graphql(complex)]
@@ -3375,29 +3396,56 @@ impl GQLWaypoint {
         &self,
         ctx: &async_graphql::Context<'_>,
     ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
-        let context = ctx.data::<crate::utils::ConductorContext>().unwrap();
         if !(self.waypoint.has_marketplace || self.waypoint.has_shipyard) {
             return Ok(None);
         }
-        let market_trade_goods = database::MarketTradeGood::get_last_by_waypoint(
-            &context.database_pool,
-            &self.waypoint.symbol,
-            database::PaginatedQuery::unpaged(),
-        )
-        .await?
-        .items;
-        let max_update_interval = { context.config.read().await.max_update_interval };
+        let schedule_loader = ctx.data::<DataLoader<ScrapScheduleLoader>>().unwrap();
+        let schedule = schedule_loader
+            .load_one(self.waypoint.system_symbol.clone())
+            .await?
+            .unwrap_or_default();
 
-        let next_time = crate::manager::scrapping_manager::priority_calculator::get_waypoint_time(
-            market_trade_goods
-                .into_iter()
-                .map(From::from)
-                .collect::<Vec<_>>()
-                .as_slice(),
-            max_update_interval,
-        )?;
+        Ok(Some(
+            schedule
+                .get(&self.waypoint.symbol)
+                .copied()
+                .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC),
+        ))
+    }
+}
+
+/// The scrap schedule of a system, computed once for all of its waypoints in a request
+pub struct ScrapScheduleLoader(crate::utils::ConductorContext);
+
+impl ScrapScheduleLoader {
+    pub fn new(context: crate::utils::ConductorContext) -> Self {
+        Self(context)
+    }
+}
+
+impl async_graphql::dataloader::Loader<String> for ScrapScheduleLoader {
+    type Value = HashMap<String, chrono::DateTime<chrono::Utc>>;
+    type Error = std::sync::Arc<crate::error::Error>;
+
+    #[tracing::instrument(level = "trace", skip(self, keys))]
+    async fn load(
+        &self,
+        keys: &[String],
+    ) -> std::result::Result<HashMap<String, Self::Value>, Self::Error> {
+        let max_update_interval = { self.0.config.read().await.max_update_interval };
 
-        Ok(Some(next_time))
+        let mut map = HashMap::new();
+        for system_symbol in keys {
+            let schedule =
+                crate::manager::scrapping_manager::priority_calculator::get_system_schedule(
+                    &self.0.database_pool,
+                    system_symbol,
+                    max_update_interval,
+                )
+                .await?;
+            map.insert(system_symbol.clone(), schedule);
+        }
+        Ok(map)
     }
 }
 
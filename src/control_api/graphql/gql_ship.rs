@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc};
 use async_graphql::{dataloader::DataLoader, Union};
 use database::DatabaseConnectorAsync;
 use ship::{
-    status::{ExtractorState, MiningShipAssignment, ShipStatus, TransporterState},
+    status::{ExtractorState, MiningShipAssignment, RefinerState, ShipStatus, TransporterState},
     AssignmentStatus, AutopilotState, ModuleState, MountState, NavigationState, RouteState,
     ShippingStatus,
 };
@@ -194,6 +194,18 @@ pub struct TransferStatus {
     pub system_symbol: String,
 }
 
+#[derive(Debug, Clone, serde::Serialize, async_graphql::SimpleObject)]
+pub struct OutfittingStatus {
+    pub fleet_id: i32,
+    pub assignment_id: i64,
+    pub waypoint_symbol: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, async_graphql::SimpleObject)]
+pub struct MaintenanceStatus {
+    pub waypoint_symbol: String,
+}
+
 #[derive(Debug, Clone, serde::Serialize, async_graphql::SimpleObject)]
 #[graphql(complex)]
 pub struct ContractStatus {
@@ -287,6 +299,31 @@ impl ChartingStatus {
     }
 }
 
+#[derive(Debug, Clone, serde::Serialize, async_graphql::SimpleObject)]
+#[graphql(complex)]
+pub struct RefiningStatus {
+    pub cycle: Option<i32>,
+    pub state: RefinerState,
+    pub waypoint_symbol: Option<String>,
+    pub refined: Option<i32>,
+}
+
+#[async_graphql::ComplexObject]
+impl RefiningStatus {
+    async fn waypoint<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+    ) -> Result<Option<gql_models::GQLWaypoint>> {
+        let data_loader = ctx.data::<DataLoader<database::WaypointLoader>>().unwrap();
+        if let Some(waypoint_symbol) = &self.waypoint_symbol {
+            let erg = data_loader.load_one(waypoint_symbol.clone()).await?;
+            Ok(erg.map(|f| f.into()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, async_graphql::SimpleObject)]
 pub struct ManuelStatus {
     pub controlled: bool,
@@ -298,10 +335,13 @@ pub enum AssignmentStatusGQL {
     Construction(ConstructionStatus),
     Trader(TraderStatus),
     Transfer(TransferStatus),
+    Outfitting(OutfittingStatus),
+    Maintenance(MaintenanceStatus),
     Contract(ContractStatus),
     Scraper(ScraperStatus),
     Mining(MiningStatus),
     Charting(ChartingStatus),
+    Refining(RefiningStatus),
     Manuel(ManuelStatus),
 }
 
@@ -389,6 +429,18 @@ impl From<AssignmentStatus> for AssignmentStatusGQL {
                 assignment_id,
                 system_symbol,
             }),
+            AssignmentStatus::Outfitting {
+                fleet_id,
+                assignment_id,
+                waypoint_symbol,
+            } => AssignmentStatusGQL::Outfitting(OutfittingStatus {
+                fleet_id,
+                assignment_id,
+                waypoint_symbol,
+            }),
+            AssignmentStatus::Maintenance { waypoint_symbol } => {
+                AssignmentStatusGQL::Maintenance(MaintenanceStatus { waypoint_symbol })
+            }
             AssignmentStatus::Contract {
                 contract_id,
                 run_id,
@@ -425,6 +477,17 @@ impl From<AssignmentStatus> for AssignmentStatusGQL {
                 waiting_for_manager,
                 waypoint_symbol,
             }),
+            AssignmentStatus::Refining {
+                cycle,
+                state,
+                waypoint_symbol,
+                refined,
+            } => AssignmentStatusGQL::Refining(RefiningStatus {
+                cycle,
+                state,
+                waypoint_symbol,
+                refined,
+            }),
             AssignmentStatus::Manuel => {
                 AssignmentStatusGQL::Manuel(ManuelStatus { controlled: false })
             }
@@ -1,6 +1,7 @@
 mod gql_models;
 mod gql_ship;
 pub mod mutations;
+pub mod subscriptions;
 
 use async_graphql::Object;
 use database::DatabaseConnectorAsync;
@@ -9,12 +10,15 @@ use std::collections::{HashMap, HashSet};
 use strum::IntoEnumIterator;
 use utils::WaypointCan;
 
+pub use gql_models::ScrapScheduleLoader;
 pub use gql_ship::AllShipLoader;
 
 use crate::{
-    control_api::graphql::gql_models::GQLShip,
+    control_api::{auth::RoleGuard, graphql::gql_models::GQLShip},
+    manager::config_manager,
     utils::{ConductorContext, RunInfo},
 };
+use database::ControlRole;
 
 type Result<T> = std::result::Result<T, GraphiQLError>;
 
@@ -22,9 +26,38 @@ fn paginated_query(page: Option<i64>, page_size: Option<i64>) -> database::Pagin
     database::PaginatedQuery::new(page.unwrap_or(1), page_size)
 }
 
+/// Buckets are at least a minute long
+fn analytics_range(
+    bucket_seconds: Option<f64>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> (
+    f64,
+    chrono::DateTime<chrono::Utc>,
+    chrono::DateTime<chrono::Utc>,
+) {
+    let until = until.unwrap_or_else(chrono::Utc::now);
+    let since = since.unwrap_or(until - chrono::Duration::days(1));
+    (bucket_seconds.unwrap_or(3600.0).max(60.0), since, until)
+}
+
+/// Ledger ranges default to the whole current reset
+async fn ledger_range(
+    context: &ConductorContext,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> (chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>) {
+    let until = until.unwrap_or_else(chrono::Utc::now);
+    let since = match since {
+        Some(since) => since,
+        None => context.run_info.read().await.reset_date,
+    };
+    (since, until)
+}
+
 pub struct QueryRoot;
 
-#[Object]
+#[Object(guard = "RoleGuard::new(ControlRole::ReadOnly)")]
 impl QueryRoot {
     async fn run_info<'ctx>(&self, ctx: &async_graphql::Context<'ctx>) -> Result<RunInfo> {
         let context = ctx.data::<ConductorContext>()?;
@@ -32,9 +65,18 @@ impl QueryRoot {
         Ok(info)
     }
 
-    async fn api_counts<'ctx>(&self, ctx: &async_graphql::Context<'ctx>) -> Result<i64> {
+    /// Number of requests waiting on the rate limiter, optionally only those of one priority
+    async fn api_counts<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        priority: Option<space_traders_client::RequestPriority>,
+    ) -> Result<i64> {
         let context = ctx.data::<ConductorContext>()?;
-        let counter = context.api.get_limiter().get_counter();
+        let limiter = context.api.get_limiter();
+        let counter = match priority {
+            Some(priority) => limiter.get_queue_depth(priority) as i64,
+            None => limiter.get_counter(),
+        };
         Ok(counter)
     }
 
@@ -47,6 +89,49 @@ impl QueryRoot {
         Ok(config)
     }
 
+    /// Every stored config version, oldest first
+    async fn config_history<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+    ) -> Result<Vec<config_manager::ConfigVersion>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let history = config_manager::get_history(&context.database_pool)
+            .await
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        Ok(history)
+    }
+
+    /// The fields changed between two config versions, `to_version` defaults to the running config
+    async fn config_diff<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        from_version: i64,
+        to_version: Option<i64>,
+    ) -> Result<Vec<config_manager::ConfigChange>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let history = config_manager::get_history(&context.database_pool)
+            .await
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+
+        let from = history
+            .iter()
+            .find(|v| v.version == from_version)
+            .ok_or(GraphiQLError::NotFound)?
+            .config
+            .clone();
+        let to = match to_version {
+            Some(to_version) => history
+                .iter()
+                .find(|v| v.version == to_version)
+                .ok_or(GraphiQLError::NotFound)?
+                .config
+                .clone(),
+            None => context.config.read().await.clone(),
+        };
+
+        Ok(config_manager::diff(&from, &to))
+    }
+
     async fn ship<'ctx>(
         &self,
         ctx: &async_graphql::Context<'ctx>,
@@ -72,6 +157,41 @@ impl QueryRoot {
         Ok(ships.into_iter().map(|s| s.into()).collect())
     }
 
+    /// The mutations executed through the control api, newest first
+    #[graphql(guard = "RoleGuard::new(ControlRole::Operator)")]
+    async fn control_audit_log<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        page: Option<i64>,
+        page_size: Option<i64>,
+    ) -> Result<gql_models::GQLControlAuditLogPage> {
+        let context = ctx.data::<ConductorContext>()?;
+        let entries = database::ControlAuditLog::get_all(
+            &context.database_pool,
+            paginated_query(page, page_size),
+        )
+        .await?;
+        Ok(entries.into())
+    }
+
+    /// The manual commands of a ship, newest first
+    async fn ship_commands<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        ship_symbol: String,
+        page: Option<i64>,
+        page_size: Option<i64>,
+    ) -> Result<gql_models::GQLShipCommandPage> {
+        let context = ctx.data::<ConductorContext>()?;
+        let commands = database::ShipCommand::get_by_ship(
+            &context.database_pool,
+            &ship_symbol,
+            paginated_query(page, page_size),
+        )
+        .await?;
+        Ok(commands.into())
+    }
+
     async fn market_transactions<'ctx>(
         &self,
         ctx: &async_graphql::Context<'ctx>,
@@ -1140,6 +1260,150 @@ impl QueryRoot {
         Ok(market_trade_goods.into())
     }
 
+    /// Time bucketed prices, supply and activity of one good at one market, oldest first
+    ///
+    /// The range defaults to the last day, the buckets to one hour
+    async fn market_price_series<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        waypoint_symbol: String,
+        trade_symbol: models::TradeSymbol,
+        bucket_seconds: Option<f64>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<database::MarketPriceBucket>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let (bucket_seconds, since, until) = analytics_range(bucket_seconds, since, until);
+        let series = database::MarketPriceBucket::get_series(
+            &context.database_pool,
+            &waypoint_symbol,
+            &trade_symbol,
+            bucket_seconds,
+            since,
+            until,
+        )
+        .await?;
+        Ok(series)
+    }
+
+    /// The best purchase and sell market of every good in the system
+    async fn market_spreads<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        system_symbol: String,
+    ) -> Result<Vec<database::MarketSpread>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let spreads =
+            database::MarketSpread::get_by_system(&context.database_pool, &system_symbol).await?;
+        Ok(spreads)
+    }
+
+    /// The price index of all goods in the system, oldest first
+    ///
+    /// The range defaults to the last day, the buckets to one hour
+    async fn system_price_index<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        system_symbol: String,
+        bucket_seconds: Option<f64>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<database::SystemPriceIndex>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let (bucket_seconds, since, until) = analytics_range(bucket_seconds, since, until);
+        let series = database::SystemPriceIndex::get_series(
+            &context.database_pool,
+            &system_symbol,
+            bucket_seconds,
+            since,
+            until,
+        )
+        .await?;
+        Ok(series)
+    }
+
+    /// All credit movements of the reset, newest first
+    async fn ledger_entries<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+        page: Option<i64>,
+        page_size: Option<i64>,
+    ) -> Result<gql_models::GQLLedgerEntryPage> {
+        let context = ctx.data::<ConductorContext>()?;
+        let (since, until) = ledger_range(context, since, until).await;
+        let entries = database::LedgerEntry::get_by_range(
+            &context.database_pool,
+            since,
+            until,
+            paginated_query(page, page_size),
+        )
+        .await?;
+        Ok(entries.into())
+    }
+
+    /// Profit and loss per hour of the reset, oldest first
+    async fn pnl_by_hour<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<database::HourlyPnl>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let (since, until) = ledger_range(context, since, until).await;
+        let pnl = database::HourlyPnl::get_by_range(&context.database_pool, since, until).await?;
+        Ok(pnl)
+    }
+
+    /// Profit and loss per fleet of the reset
+    async fn pnl_by_fleet<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<database::FleetPnl>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let (since, until) = ledger_range(context, since, until).await;
+        let pnl = database::FleetPnl::get_by_range(&context.database_pool, since, until).await?;
+        Ok(pnl)
+    }
+
+    /// Profit and loss per activity of the reset
+    async fn pnl_by_activity<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<database::ActivityPnl>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let (since, until) = ledger_range(context, since, until).await;
+        let pnl = database::ActivityPnl::get_by_range(&context.database_pool, since, until).await?;
+        Ok(pnl)
+    }
+
+    /// Compares the credit history of the agent with the ledger, by default only the periods it can not explain
+    async fn ledger_reconciliation<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+        only_unexplained: Option<bool>,
+    ) -> Result<Vec<database::LedgerReconciliation>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let (since, until) = ledger_range(context, since, until).await;
+        let agent_symbol = context.run_info.read().await.agent_symbol.clone();
+        let reconciliation = database::LedgerReconciliation::get_by_agent(
+            &context.database_pool,
+            &agent_symbol,
+            since,
+            until,
+            only_unexplained.unwrap_or(true),
+        )
+        .await?;
+        Ok(reconciliation)
+    }
+
     async fn trade_symbol_infos(&self) -> Result<Vec<gql_models::TradeSymbolInfo>> {
         let trade_symbol_infos = models::TradeSymbol::iter().map(Into::into).collect();
         Ok(trade_symbol_infos)
@@ -1,164 +1,54 @@
 use crate::{
-    control_api::{GraphiQLError, graphql::gql_models::GQLShipInfo},
+    control_api::{GraphiQLError, auth::RoleGuard, graphql::gql_models::GQLShipInfo},
+    manager::config_manager,
     utils::ConductorContext,
 };
 use async_graphql::{Context, Object};
-use database::DatabaseConnectorAsync;
+use database::{ControlRole, DatabaseConnectorAsync};
 use space_traders_client::models;
 
 pub struct MutationRoot;
 
-#[Object]
+#[Object(guard = "RoleGuard::new(ControlRole::Operator)")]
 impl MutationRoot {
-    /// Edit the in-memory config (and return the updated config). Fields that are None are left unchanged.
+    /// Edit the config (and return the updated config). Fields that are None are left unchanged.
+    /// The edited fields are stored as a new config version and stay set over config.toml across restarts.
     async fn edit_config<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         input: InputConfig,
     ) -> super::Result<crate::utils::Config> {
         let context = ctx.data::<ConductorContext>()?;
-        {
-            let mut w = context.config.write().await;
-            let mut cfg = w.clone();
-
-            if let Some(v) = input.control_start_sleep {
-                cfg.control_start_sleep = v;
-            }
-            if let Some(v) = input.control_active {
-                cfg.control_active = v;
-            }
-
-            if let Some(v) = input.scrapper_start_sleep {
-                cfg.scrapper_start_sleep = v;
-            }
-            if let Some(v) = input.scrap_agents {
-                cfg.scrap_agents = v;
-            }
-            if let Some(v) = input.update_all_systems {
-                cfg.update_all_systems = v;
-            }
-
-            if let Some(v) = input.max_miners_per_waypoint {
-                cfg.max_miners_per_waypoint = v;
-            }
-            if let Some(v) = input.mining_eject_list {
-                cfg.mining_eject_list = v;
-            }
-            if let Some(v) = input.mining_prefer_list {
-                cfg.mining_prefer_list = v;
-            }
-            if let Some(v) = input.ignore_engineered_asteroids {
-                cfg.ignore_engineered_asteroids = v;
-            }
-            if let Some(v) = input.unstable_since_timeout {
-                cfg.unstable_since_timeout = v;
-            }
-            if let Some(v) = input.stop_all_unstable {
-                cfg.stop_all_unstable = v;
-            }
-            if let Some(v) = input.extra_mining_transporter {
-                cfg.extra_mining_transporter = v;
-            }
 
-            if let Some(v) = input.fuel_cost {
-                cfg.fuel_cost = v;
-            }
-            if let Some(v) = input.antimatter_price {
-                cfg.antimatter_price = v;
-            }
-            if let Some(v) = input.purchase_multiplier {
-                cfg.purchase_multiplier = v;
-            }
+        let changes =
+            match serde_json::to_value(&input).map_err(|e| GraphiQLError::IO(e.to_string()))? {
+                serde_json::Value::Object(map) => map
+                    .into_iter()
+                    .filter(|(_, v)| !v.is_null())
+                    .collect::<serde_json::Map<_, _>>(),
+                _ => serde_json::Map::new(),
+            };
 
-            if let Some(v) = input.market_blacklist {
-                cfg.market_blacklist = v;
-            }
-
-            if let Some(v) = input.default_purchase_price {
-                cfg.default_purchase_price = v;
-            }
-            if let Some(v) = input.default_sell_price {
-                cfg.default_sell_price = v;
-            }
-            if let Some(v) = input.default_profit {
-                cfg.default_profit = v;
-            }
-            if let Some(v) = input.max_update_interval {
-                cfg.max_update_interval = v;
-            }
-            if let Some(v) = input.markup_percentage {
-                cfg.markup_percentage = v;
-            }
-            if let Some(v) = input.margin_percentage {
-                cfg.margin_percentage = v;
-            }
-
-            if let Some(v) = input.markets_per_ship {
-                cfg.markets_per_ship = v;
-            }
-
-            if let Some(v) = input.mining_waypoints_per_system {
-                cfg.mining_waypoints_per_system = v;
-            }
-            if let Some(v) = input.mining_ships_per_waypoint {
-                cfg.mining_ships_per_waypoint = v;
-            }
-            if let Some(v) = input.transport_capacity_per_waypoint {
-                cfg.transport_capacity_per_waypoint = v;
-            }
-
-            if let Some(v) = input.trade_mode {
-                cfg.trade_mode = v;
-            }
-            if let Some(v) = input.trade_profit_threshold {
-                cfg.trade_profit_threshold = v;
-            }
-
-            if let Some(v) = input.ship_purchase_percentile {
-                cfg.ship_purchase_percentile = v;
-            }
-            if let Some(v) = input.ship_purchase_stop {
-                cfg.ship_purchase_stop = v;
-            }
-            if let Some(v) = input.expand {
-                cfg.expand = v;
-            }
-            if let Some(v) = input.ship_purchase_amount {
-                cfg.ship_purchase_amount = v;
-            }
-
-            if let Some(v) = input.use_exploration_fleet {
-                cfg.use_exploration_fleet = v;
-            }
-
-            if let Some(v) = input.iron_reserve {
-                cfg.iron_reserve = v;
-            }
-
-            *w = cfg.clone();
-        }
-
-        let old_string = tokio::fs::read_to_string("config.toml")
+        let config = config_manager::apply_overrides(context, changes, "edit_config")
             .await
-            .map_err(|e| GraphiQLError::IO(e.to_string()))?;
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
 
-        let info = { context.config.read().await.clone() };
-
-        let mut toml_edit_doc = old_string
-            .parse::<toml_edit::DocumentMut>()
-            .map_err(|e| GraphiQLError::IO(e.to_string()))?;
-
-        let config_doc: toml_edit::DocumentMut =
-            toml_edit::ser::to_document(&info).map_err(|e| GraphiQLError::IO(e.to_string()))?;
+        Ok(config)
+    }
 
-        toml_edit_doc.extend(config_doc.iter());
+    /// Remove config overrides, all of them when no fields are given, so the config.toml values apply again
+    async fn reset_config<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        fields: Option<Vec<String>>,
+    ) -> super::Result<crate::utils::Config> {
+        let context = ctx.data::<ConductorContext>()?;
 
-        let toml_string = toml_edit_doc.to_string();
-        tokio::fs::write("config.toml", toml_string)
+        let config = config_manager::remove_overrides(context, fields)
             .await
-            .map_err(|e| GraphiQLError::IO(e.to_string()))?;
+            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
 
-        Ok(info)
+        Ok(config)
     }
 
     /// Add a new fleet for a given system. Returns the created DB fleet.
@@ -201,6 +91,10 @@ impl MutationRoot {
             .await
             .map_err(|e| super::GraphiQLError::IO(e.to_string()))?;
 
+        if active.is_some() {
+            context.ship_tasks.notify_activation_changed();
+        }
+
         // Update configuration fields selectively per config variant
         if let Some(cfg) = config {
             match cfg {
@@ -281,6 +175,16 @@ impl MutationRoot {
                     .await
                     .map_err(|e| super::GraphiQLError::IO(e.to_string()))?;
                 }
+                InputFleetConfig::Refining(cfg) => {
+                    database::Fleet::update_refining_config(
+                        &context.database_pool,
+                        id,
+                        cfg.refinery_ship_count,
+                        cfg.refine_profit_threshold,
+                    )
+                    .await
+                    .map_err(|e| super::GraphiQLError::IO(e.to_string()))?;
+                }
                 InputFleetConfig::Manuel(_cfg) => {
                     // set fleet type to Manuel
                     sqlx::query!(
@@ -395,7 +299,7 @@ impl MutationRoot {
         Ok(true)
     }
 
-    /// Force assign a ship a new assignment from the fleet manager.
+    /// Force assign a ship a new assignment from the fleet manager, a parked pilot wakes up when the new fleet is active.
     async fn force_assign_ship<'ctx>(
         &self,
         ctx: &Context<'ctx>,
@@ -413,10 +317,12 @@ impl MutationRoot {
             ship.assignment_id = Some(assignment_id);
         }
         database::ShipInfo::upsert(&context.database_pool, &ship).await?;
+        // a pilot parked because of the fleet of its old assignment re-checks against the new one
+        context.ship_tasks.notify_activation_changed();
         Ok(ship.into())
     }
 
-    /// Pause a ship (set active=false in ship_info)
+    /// Pause a ship (set active=false in ship_info), its pilot finishes the current step and parks
     async fn pause_ship<'ctx>(
         &self,
         ctx: &Context<'ctx>,
@@ -428,13 +334,14 @@ impl MutationRoot {
         {
             info.active = false;
             database::ShipInfo::upsert(&context.database_pool, &info).await?;
+            context.ship_tasks.notify_activation_changed();
             Ok(true)
         } else {
             Err(super::GraphiQLError::NotFound)
         }
     }
 
-    /// Resume a ship (set active=true in ship_info)
+    /// Resume a ship (set active=true in ship_info), a parked pilot wakes up immediately
     async fn resume_ship<'ctx>(
         &self,
         ctx: &Context<'ctx>,
@@ -446,12 +353,77 @@ impl MutationRoot {
         {
             info.active = true;
             database::ShipInfo::upsert(&context.database_pool, &info).await?;
+            context.ship_tasks.notify_activation_changed();
             Ok(true)
         } else {
             Err(super::GraphiQLError::NotFound)
         }
     }
 
+    /// Queue manual commands for a ship of a Manuel fleet, they are executed in the given order after the already queued ones
+    async fn enqueue_ship_commands<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        ship_symbol: String,
+        commands: Vec<InputShipCommand>,
+    ) -> super::Result<Vec<database::ShipCommand>> {
+        let context = ctx.data::<ConductorContext>()?;
+        if context.ship_manager.get_clone(&ship_symbol).is_none() {
+            return Err(super::GraphiQLError::NotFound);
+        }
+
+        // the pilot only executes queued commands for ships of manuel fleets, like it picks the assignment the temporary one wins
+        let ship_info = database::ShipInfo::get_by_id(&context.database_pool, &ship_symbol)
+            .await?
+            .ok_or(super::GraphiQLError::NotFound)?;
+        let assignment = match ship_info.temp_assignment_id.or(ship_info.assignment_id) {
+            Some(assignment_id) => {
+                database::ShipAssignment::get_by_id(&context.database_pool, assignment_id).await?
+            }
+            None => None,
+        };
+        let fleet = match assignment {
+            Some(assignment) => {
+                database::Fleet::get_by_id(&context.database_pool, assignment.fleet_id).await?
+            }
+            None => None,
+        };
+        if fleet.is_none_or(|fleet| fleet.fleet_type != database::FleetType::Manuel) {
+            return Err(async_graphql::Error::new(format!(
+                "{} is not assigned to a Manuel fleet",
+                ship_symbol
+            ))
+            .into());
+        }
+
+        let commands = commands
+            .into_iter()
+            .map(|command| command.into_ship_command(ship_symbol.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut queued = Vec::with_capacity(commands.len());
+        for command in commands {
+            let id = database::ShipCommand::insert_new(&context.database_pool, &command).await?;
+            queued.push(database::ShipCommand { id, ..command });
+        }
+        context.ship_tasks.notify_commands_queued();
+
+        Ok(queued)
+    }
+
+    /// Cancel all pending manual commands of a ship, returns how many were cancelled
+    async fn cancel_ship_commands<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        ship_symbol: String,
+    ) -> super::Result<u64> {
+        let context = ctx.data::<ConductorContext>()?;
+        let cancelled =
+            database::ShipCommand::cancel_pending_by_ship(&context.database_pool, &ship_symbol)
+                .await?;
+        Ok(cancelled)
+    }
+
     /// Shutdown the server
     #[allow(clippy::too_many_arguments)]
     async fn shutdown<'ctx>(
@@ -512,7 +484,7 @@ enum RegenFleetBy {
     Fleet(i32),
 }
 
-#[derive(Debug, Clone, async_graphql::InputObject)]
+#[derive(Debug, Clone, serde::Serialize, async_graphql::InputObject)]
 struct InputConfig {
     pub control_start_sleep: Option<u64>,
     pub control_active: Option<bool>,
@@ -544,6 +516,8 @@ struct InputConfig {
     // Markup and margin percentages (as decimals)
     pub markup_percentage: Option<f32>,
     pub margin_percentage: Option<f32>,
+    pub price_impact_fallback: Option<f32>,
+    pub contract_profit_threshold: Option<i32>,
 
     pub markets_per_ship: Option<i64>,
 
@@ -553,13 +527,19 @@ struct InputConfig {
 
     pub trade_mode: Option<database::TradeMode>,
     pub trade_profit_threshold: Option<i32>,
+    pub trade_max_legs: Option<i32>,
 
     pub ship_purchase_percentile: Option<f32>,
     pub ship_purchase_stop: Option<bool>,
     pub expand: Option<bool>,
     pub ship_purchase_amount: Option<i32>,
+    pub ship_earnings_horizon_hours: Option<f32>,
 
     pub use_exploration_fleet: Option<bool>,
+    pub outfit_ships: Option<bool>,
+
+    pub repair_condition_threshold: Option<f32>,
+    pub scrap_integrity_threshold: Option<f32>,
 
     pub iron_reserve: Option<i64>,
 }
@@ -572,10 +552,12 @@ pub enum InputFleetConfig {
     Charting(InputChartingConfig),
     Construction(InputConstructionConfig),
     Contract(InputContractConfig),
+    Refining(InputRefiningConfig),
     Manuel(InputManuelConfig),
 }
 
 impl InputFleetConfig {
+    #[allow(dead_code)]
     pub fn into_fleet_config(self) -> Option<database::FleetConfig> {
         Some(match self {
             InputFleetConfig::Trading(cfg) => {
@@ -631,6 +613,12 @@ impl InputFleetConfig {
                     contract_ship_count: cfg.contract_ship_count?,
                 })
             }
+            InputFleetConfig::Refining(cfg) => {
+                database::FleetConfig::Refining(database::RefiningFleetConfig {
+                    refinery_ship_count: cfg.refinery_ship_count?,
+                    refine_profit_threshold: cfg.refine_profit_threshold?,
+                })
+            }
             InputFleetConfig::Manuel(_cfg) => {
                 // ManuelConfig is not exported, return default
                 database::FleetConfig::Manuel(Default::default())
@@ -692,7 +680,60 @@ pub struct InputContractConfig {
     pub contract_ship_count: Option<i32>,
 }
 
+#[derive(Debug, Clone, async_graphql::InputObject)]
+pub struct InputRefiningConfig {
+    pub refinery_ship_count: Option<i32>,
+    pub refine_profit_threshold: Option<i32>,
+}
+
 #[derive(Debug, Clone, async_graphql::InputObject)]
 pub struct InputManuelConfig {
     pub config: Option<String>,
 }
+
+#[derive(Debug, Clone, async_graphql::InputObject)]
+struct InputShipCommand {
+    pub command: database::ShipCommandType,
+    pub waypoint_symbol: Option<String>,
+    pub trade_symbol: Option<models::TradeSymbol>,
+    pub units: Option<i32>,
+    pub target_ship: Option<String>,
+    pub from_cargo: Option<bool>,
+}
+
+impl InputShipCommand {
+    fn into_ship_command(
+        self,
+        ship_symbol: String,
+    ) -> async_graphql::Result<database::ShipCommand> {
+        use database::ShipCommandType::*;
+
+        let missing = match self.command {
+            Navigate | Jump if self.waypoint_symbol.is_none() => Some("waypointSymbol"),
+            Purchase | Sell | Transfer | Jettison if self.trade_symbol.is_none() => {
+                Some("tradeSymbol")
+            }
+            Purchase | Sell | Transfer | Jettison if self.units.is_none() => Some("units"),
+            Transfer if self.target_ship.is_none() => Some("targetShip"),
+            _ => None,
+        };
+        if let Some(field) = missing {
+            return Err(async_graphql::Error::new(format!(
+                "{:?} needs {}",
+                self.command, field
+            )));
+        }
+        if self.units.is_some_and(|units| units <= 0) {
+            return Err(async_graphql::Error::new("units must be positive"));
+        }
+
+        Ok(database::ShipCommand {
+            waypoint_symbol: self.waypoint_symbol,
+            trade_symbol: self.trade_symbol,
+            units: self.units,
+            target_ship: self.target_ship,
+            from_cargo: self.from_cargo,
+            ..database::ShipCommand::new(ship_symbol, self.command)
+        })
+    }
+}
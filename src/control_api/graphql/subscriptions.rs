@@ -0,0 +1,182 @@
+use async_graphql::{Context, Subscription};
+use futures::{Stream, StreamExt};
+use tokio_stream::wrappers::BroadcastStream;
+
+use database::ControlRole;
+
+use crate::{
+    control_api::{
+        auth::RoleGuard,
+        graphql::gql_models::{GQLAgent, GQLMarketTransaction, GQLReservedFund, GQLShip},
+    },
+    utils::{ChannelInfo, ConductorContext},
+};
+
+use super::Result;
+
+/// The channel state of one manager
+#[derive(Debug, Clone, PartialEq, async_graphql::SimpleObject)]
+pub struct ManagerState {
+    pub name: String,
+    pub busy: bool,
+    pub channel_state: ChannelInfo,
+}
+
+pub struct SubscriptionRoot;
+
+#[Subscription(guard = "RoleGuard::new(ControlRole::ReadOnly)")]
+impl SubscriptionRoot {
+    /// Every ship update, optionally only those of one ship, fleet or system
+    async fn ships<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        ship_symbol: Option<String>,
+        fleet_id: Option<i32>,
+        system_symbol: Option<String>,
+    ) -> Result<impl Stream<Item = GQLShip> + use<>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let receiver = context.ship_manager.get_rx();
+
+        Ok(BroadcastStream::new(receiver).filter_map(move |ship| {
+            // a lagging subscriber skips the missed updates
+            let ship = ship.ok().filter(|ship| {
+                ship_symbol.as_ref().is_none_or(|s| &ship.symbol == s)
+                    && system_symbol
+                        .as_ref()
+                        .is_none_or(|s| &ship.nav.system_symbol == s)
+                    && fleet_id.is_none_or(|id| {
+                        ship.status.fleet_id == Some(id) || ship.status.temp_fleet_id == Some(id)
+                    })
+            });
+            futures::future::ready(ship.map(Into::into))
+        }))
+    }
+
+    /// Every change of an agent, its credits included
+    async fn agent<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        symbol: Option<String>,
+    ) -> Result<impl Stream<Item = GQLAgent> + use<>> {
+        let database_pool = ctx.data::<database::DbPool>()?;
+        let receiver = database_pool.agent_broadcast_channel.0.subscribe();
+
+        Ok(BroadcastStream::new(receiver).filter_map(move |agent| {
+            let agent = agent
+                .ok()
+                .filter(|agent| symbol.as_ref().is_none_or(|s| &agent.symbol == s));
+            futures::future::ready(agent.map(Into::into))
+        }))
+    }
+
+    /// Every reservation made, used or cancelled by the budget manager
+    async fn reservations<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> Result<impl Stream<Item = GQLReservedFund> + use<>> {
+        let context = ctx.data::<ConductorContext>()?;
+        let receiver = context.budget_manager.subscribe_reservations();
+
+        Ok(BroadcastStream::new(receiver)
+            .filter_map(|fund| futures::future::ready(fund.ok().map(Into::into))))
+    }
+
+    /// Every new transaction of our ships, optionally only those of one ship or system
+    async fn market_transactions<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        ship_symbol: Option<String>,
+        system_symbol: Option<String>,
+    ) -> Result<impl Stream<Item = GQLMarketTransaction> + use<>> {
+        let database_pool = ctx.data::<database::DbPool>()?;
+        let receiver = database_pool
+            .market_transaction_broadcast_channel
+            .0
+            .subscribe();
+
+        Ok(
+            BroadcastStream::new(receiver).filter_map(move |transaction| {
+                let transaction = transaction.ok().filter(|t| {
+                    ship_symbol.as_ref().is_none_or(|s| &t.ship_symbol == s)
+                        && system_symbol
+                            .as_ref()
+                            .is_none_or(|s| utils::get_system_symbol(&t.waypoint_symbol) == *s)
+                });
+                futures::future::ready(transaction.map(Into::into))
+            }),
+        )
+    }
+
+    /// The channel states of all managers, sampled every `interval_ms` and only sent when they changed
+    async fn manager_states<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        interval_ms: Option<u64>,
+    ) -> Result<impl Stream<Item = Vec<ManagerState>> + use<>> {
+        let context = ctx.data::<ConductorContext>()?.clone();
+        let interval = std::time::Duration::from_millis(interval_ms.unwrap_or(1000).max(100));
+
+        Ok(futures::stream::unfold(
+            (context, None),
+            move |(context, last): (ConductorContext, Option<Vec<ManagerState>>)| async move {
+                let mut last = last;
+                loop {
+                    if last.is_some() {
+                        tokio::time::sleep(interval).await;
+                    }
+                    let states = manager_states(&context);
+                    if last.as_ref() != Some(&states) {
+                        last = Some(states.clone());
+                        return Some((states, (context, last)));
+                    }
+                }
+            },
+        ))
+    }
+}
+
+fn manager_states(context: &ConductorContext) -> Vec<ManagerState> {
+    let state = |name: &str, busy: bool, channel_state: ChannelInfo| ManagerState {
+        name: name.to_string(),
+        busy,
+        channel_state,
+    };
+
+    vec![
+        state(
+            "ChartManager",
+            context.chart_manager.is_busy(),
+            context.chart_manager.get_channel_state(),
+        ),
+        state(
+            "ConstructionManager",
+            context.construction_manager.is_busy(),
+            context.construction_manager.get_channel_state(),
+        ),
+        state(
+            "ContractManager",
+            context.contract_manager.is_busy(),
+            context.contract_manager.get_channel_state(),
+        ),
+        state(
+            "FleetManager",
+            context.fleet_manager.is_busy(),
+            context.fleet_manager.get_channel_state(),
+        ),
+        state(
+            "MiningManager",
+            context.mining_manager.is_busy(),
+            context.mining_manager.get_channel_state(),
+        ),
+        state(
+            "ScrappingManager",
+            context.scrapping_manager.is_busy(),
+            context.scrapping_manager.get_channel_state(),
+        ),
+        state(
+            "TradeManager",
+            context.trade_manager.is_busy(),
+            context.trade_manager.get_channel_state(),
+        ),
+    ]
+}
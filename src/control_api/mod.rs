@@ -1,3 +1,4 @@
+pub mod auth;
 mod graphql;
 pub mod server;
 pub use graphql::GraphiQLError;
@@ -7,15 +7,21 @@ use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 
 use async_graphql::{
-    EmptySubscription, Schema,
+    Schema,
     dataloader::DataLoader,
     http::{GraphQLPlaygroundConfig, GraphiQLSource},
 };
-use async_graphql_warp::{GraphQLBadRequest, GraphQLResponse};
+use async_graphql_warp::{GraphQLBadRequest, GraphQLResponse, GraphQLWebSocket, graphql_protocol};
 use warp::{Filter, Rejection, http::Response as HttpResponse};
 
 use crate::{
-    control_api::graphql::{AllShipLoader, QueryRoot, mutations::MutationRoot},
+    control_api::{
+        auth::{AuditLog, ControlApiTokens, ControlUser, Unauthorized, with_user},
+        graphql::{
+            AllShipLoader, QueryRoot, ScrapScheduleLoader, mutations::MutationRoot,
+            subscriptions::SubscriptionRoot,
+        },
+    },
     manager::Manager,
     utils::ConductorContext,
 };
@@ -23,6 +29,7 @@ use crate::{
 pub struct ControlApiServer {
     context: ConductorContext,
     fast_cancellation_token: CancellationToken,
+    #[allow(dead_code)]
     ship_rx: Option<
         tokio::sync::broadcast::Receiver<ship::RustShip<ship::status::ShipStatus, ship::Immutable>>,
     >,
@@ -60,8 +67,9 @@ impl ControlApiServer {
         tokio::time::sleep(Duration::from_millis(config.control_start_sleep)).await;
         let context = self.context.clone();
         let database_pool = self.context.database_pool.clone();
+        let tokens = std::sync::Arc::new(ControlApiTokens::load(&database_pool).await?);
 
-        let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        let schema = Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
             .data(DataLoader::new(
                 database::WaypointSystemLoader::new(database_pool.clone()),
                 tokio::spawn,
@@ -86,32 +94,81 @@ impl ControlApiServer {
                 AssignmentsByFleetLoader::new(database_pool.clone()),
                 tokio::spawn,
             ))
+            .data(DataLoader::new(
+                ScrapScheduleLoader::new(context.clone()),
+                tokio::spawn,
+            ))
             .data(context)
             .data(database_pool)
+            .extension(AuditLog)
             .finish();
 
         tokio::fs::write("schema.graphql", schema.sdl()).await?;
 
-        let graphql_post = async_graphql_warp::graphql(schema).and_then(
-            |(schema, request): (
-                Schema<QueryRoot, MutationRoot, EmptySubscription>,
-                async_graphql::Request,
-            )| async move {
-                Ok::<_, Infallible>(GraphQLResponse::from(schema.execute(request).await))
-            },
-        );
+        // websocket upgrades on the same endpoint, the graphql-ws and graphql-transport-ws protocols are supported
+        // the token comes from the upgrade request or, for browsers, from the connection_init payload
+        let ws_schema = schema.clone();
+        let ws_tokens = tokens.clone();
+        let graphql_ws = warp::ws()
+            .and(graphql_protocol())
+            .and(warp::header::optional::<String>("authorization"))
+            .map(
+                move |ws: warp::ws::Ws, protocol, authorization: Option<String>| {
+                    let schema = ws_schema.clone();
+                    let tokens = ws_tokens.clone();
+
+                    let reply = ws.on_upgrade(move |socket| {
+                        GraphQLWebSocket::new(socket, schema, protocol)
+                            .on_connection_init(move |payload| async move {
+                                let user = authorization
+                                    .and_then(|a| tokens.authenticate(&a))
+                                    .or_else(|| tokens.authenticate_payload(&payload))
+                                    .ok_or_else(|| async_graphql::Error::new("Unauthorized"))?;
+                                let mut data = async_graphql::Data::default();
+                                data.insert(user);
+                                Ok(data)
+                            })
+                            .serve()
+                    });
+
+                    warp::reply::with_header(
+                        reply,
+                        "Sec-WebSocket-Protocol",
+                        protocol.sec_websocket_protocol(),
+                    )
+                },
+            );
+
+        let graphql_post = with_user(tokens)
+            .and(async_graphql_warp::graphql(schema))
+            .and_then(
+                |user: ControlUser,
+                 (schema, request): (
+                    Schema<QueryRoot, MutationRoot, SubscriptionRoot>,
+                    async_graphql::Request,
+                )| async move {
+                    Ok::<_, Infallible>(GraphQLResponse::from(
+                        schema.execute(request.data(user)).await,
+                    ))
+                },
+            );
 
         let graphiql = warp::path::end().and(warp::get()).map(|| {
             HttpResponse::builder()
                 .header("content-type", "text/html")
-                .body(GraphiQLSource::build().endpoint("/").finish())
+                .body(
+                    GraphiQLSource::build()
+                        .endpoint("/")
+                        .subscription_endpoint("/")
+                        .finish(),
+                )
         });
 
         let playground = warp::path("playground").and(warp::get()).map(|| {
             HttpResponse::builder()
                 .header("content-type", "text/html")
                 .body(async_graphql::http::playground_source(
-                    GraphQLPlaygroundConfig::new("/"),
+                    GraphQLPlaygroundConfig::new("/").subscription_endpoint("/"),
                 ))
         });
 
@@ -123,13 +180,24 @@ impl ControlApiServer {
                 "Accept",
                 "X-Requested-With",
                 "Content-Type",
+                "Authorization",
             ])
             .allow_methods(&[warp::http::Method::GET, warp::http::Method::POST]);
 
-        let routes = graphiql.or(playground).or(graphql_post).with(cors).recover(
-            |err: Rejection| async move {
-                if let Some(GraphQLBadRequest(err)) = err.find() {
+        let routes = graphql_ws
+            .or(graphiql)
+            .or(playground)
+            .or(graphql_post)
+            .with(cors)
+            .recover(|err: Rejection| async move {
+                if err.find::<Unauthorized>().is_some() {
                     return Ok::<_, Infallible>(warp::reply::with_status(
+                        "UNAUTHORIZED".to_string(),
+                        warp::http::StatusCode::UNAUTHORIZED,
+                    ));
+                }
+                if let Some(GraphQLBadRequest(err)) = err.find() {
+                    return Ok(warp::reply::with_status(
                         err.to_string(),
                         warp::http::StatusCode::BAD_REQUEST,
                     ));
@@ -139,8 +207,7 @@ impl ControlApiServer {
                     "INTERNAL_SERVER_ERROR".to_string(),
                     warp::http::StatusCode::INTERNAL_SERVER_ERROR,
                 ))
-            },
-        );
+            });
 
         let socket_address: std::net::SocketAddr =
             self.socket_address.parse().expect("Invalid socket address");
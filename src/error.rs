@@ -25,6 +25,7 @@ pub enum Error {
         assignment_id: Option<i64>,
     },
 
+    #[allow(clippy::enum_variant_names)]
     #[error(transparent)]
     ArcError(#[from] std::sync::Arc<Error>),
 }
@@ -7,6 +7,8 @@ mod error;
 mod manager;
 mod open_telemetry;
 mod pilot;
+mod reset_priors;
+mod reset_report;
 mod reset_runner;
 mod utils;
 
@@ -46,6 +48,8 @@ async fn main() -> anyhow::Result<()> {
     let socket_address = env::var("SOCKET_ADDRESS")
         .ok()
         .unwrap_or("0.0.0.0:8780".to_string());
+    // lets the bot run against another server like the offline mock_server
+    let api_base_path = env::var("API_BASE_PATH").ok();
 
     let global_cancel_token = CancellationToken::new();
 
@@ -71,7 +75,19 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
-        wait_for_api().await?;
+        if let Err(err) = reset_priors::import_priors(&database_pool).await {
+            error!(error = ?err, "Failed to import the reset priors, starting without them");
+        }
+
+        if let Err(err) = reset_priors::import_config(&database_pool).await {
+            error!(error = ?err, "Failed to import the config versions, starting with config.toml");
+        }
+
+        if let Err(err) = reset_priors::import_tokens(&database_pool).await {
+            error!(error = ?err, "Failed to import the control api tokens");
+        }
+
+        wait_for_api(api_base_path.as_deref()).await?;
 
         // check db if already has an agent, if not create agent
         let agent_token = {
@@ -82,8 +98,11 @@ async fn main() -> anyhow::Result<()> {
                 db_agent_token
             } else {
                 info!(%agent_symbol, "Registering new agent");
-                let account_api =
+                let mut account_api =
                     space_traders_client::Api::new(None, 500, NonZeroU32::new(2).unwrap());
+                if let Some(api_base_path) = &api_base_path {
+                    account_api = account_api.with_base_path(api_base_path);
+                }
 
                 let faction = get_random_faction();
 
@@ -107,6 +126,7 @@ async fn main() -> anyhow::Result<()> {
             database_pool.clone(),
             global_cancel_token.clone(),
             socket_address.clone(),
+            api_base_path.as_deref(),
         )
         .await?;
 
@@ -120,6 +140,28 @@ async fn main() -> anyhow::Result<()> {
             reset_info.agent_symbol
         );
 
+        if let Some(report) = &reset_info.report {
+            match report.save(&filename).await {
+                Ok((json_file, markdown_file)) => {
+                    info!(json_file, markdown_file, "Reset report written")
+                }
+                Err(err) => error!(error = ?err, "Failed to write the reset report"),
+            }
+        }
+
+        if let Err(err) = reset_priors::export_priors(&database_pool, &reset_info, &filename).await
+        {
+            error!(error = ?err, "Failed to export the reset priors");
+        }
+
+        if let Err(err) = reset_priors::export_config(&database_pool, &filename).await {
+            error!(error = ?err, "Failed to export the config versions");
+        }
+
+        if let Err(err) = reset_priors::export_tokens(&database_pool, &filename).await {
+            error!(error = ?err, "Failed to export the control api tokens");
+        }
+
         export_database(&database_url, &filename).await?;
 
         if global_cancel_token.is_cancelled() {
@@ -136,8 +178,11 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn wait_for_api() -> Result<(), anyhow::Error> {
-    let waiting_api = space_traders_client::Api::new(None, 500, NonZeroU32::new(2).unwrap());
+async fn wait_for_api(api_base_path: Option<&str>) -> Result<(), anyhow::Error> {
+    let mut waiting_api = space_traders_client::Api::new(None, 500, NonZeroU32::new(2).unwrap());
+    if let Some(api_base_path) = api_base_path {
+        waiting_api = waiting_api.with_base_path(api_base_path);
+    }
 
     loop {
         let status = waiting_api.get_status().await;
@@ -22,6 +22,8 @@ pub struct BudgetManager {
     current_funds: AtomicI64,
     reserved_funds: Mutex<HashMap<i64, ReservedFund>>,
     iron_reserve: i64,
+    /// every change of a reservation, cancelled and used ones included
+    reservation_broadcaster: tokio::sync::broadcast::Sender<ReservedFund>,
     // update_funds_fn: Option<Fn(i64) + Send + Sync>,
 }
 
@@ -31,9 +33,19 @@ impl BudgetManager {
             current_funds: AtomicI64::new(self.current_funds.load(Ordering::SeqCst)),
             reserved_funds: Mutex::new(self.reserved_funds.lock().await.clone()),
             iron_reserve: self.iron_reserve,
+            reservation_broadcaster: self.reservation_broadcaster.clone(),
         }
     }
 
+    pub fn subscribe_reservations(&self) -> tokio::sync::broadcast::Receiver<ReservedFund> {
+        self.reservation_broadcaster.subscribe()
+    }
+
+    fn broadcast_reservation(&self, reserved_fund: &ReservedFund) {
+        // no subscriber is not an error
+        let _erg = self.reservation_broadcaster.send(reserved_fund.clone());
+    }
+
     pub async fn load(
         &mut self,
         database_pool: &database::DbPool,
@@ -80,6 +92,7 @@ impl BudgetManager {
             .sum()
     }
 
+    #[allow(dead_code)]
     pub fn get_current_funds(&self) -> i64 {
         self.current_funds.load(Ordering::SeqCst)
     }
@@ -88,6 +101,7 @@ impl BudgetManager {
         self.current_funds.store(amount, Ordering::SeqCst);
     }
 
+    #[allow(dead_code)]
     pub async fn can_reserve_funds(&self, amount: i64) -> bool {
         let spendable = self.get_spendable_funds().await;
         spendable >= amount
@@ -104,6 +118,7 @@ impl BudgetManager {
             .await
     }
 
+    #[allow(dead_code)]
     pub async fn reserve_funds(
         &self,
         database_pool: &database::DbPool,
@@ -144,6 +159,7 @@ impl BudgetManager {
         funds.id = reserved_fund_id;
 
         reserved_funds.insert(funds.id, funds.clone());
+        self.broadcast_reservation(&funds);
         Ok(funds)
     }
 
@@ -173,6 +189,7 @@ impl BudgetManager {
             reserved_fund.updated_at = chrono::Utc::now();
 
             ReservedFund::upsert(database_pool, reserved_fund).await?;
+            self.broadcast_reservation(reserved_fund);
         }
 
         reserved_funds.remove(&reservation_id);
@@ -210,6 +227,7 @@ impl BudgetManager {
         reserved_fund.updated_at = chrono::Utc::now();
 
         ReservedFund::upsert(database_pool, reserved_fund).await?;
+        self.broadcast_reservation(reserved_fund);
 
         // reserved_funds.remove(&reservation_id);
 
@@ -248,6 +266,7 @@ impl BudgetManager {
         reserved_fund.updated_at = chrono::Utc::now();
 
         ReservedFund::upsert(database_pool, reserved_fund).await?;
+        self.broadcast_reservation(reserved_fund);
 
         reserved_funds.remove(&reservation_id);
 
@@ -283,6 +302,7 @@ impl BudgetManager {
         reserved_fund.updated_at = chrono::Utc::now();
 
         ReservedFund::upsert(database_pool, reserved_fund).await?;
+        self.broadcast_reservation(reserved_fund);
 
         reserved_funds.remove(&reservation_id);
 
@@ -296,6 +316,7 @@ impl Default for BudgetManager {
             current_funds: AtomicI64::new(0),
             reserved_funds: Mutex::new(HashMap::new()),
             iron_reserve: 0,
+            reservation_broadcaster: tokio::sync::broadcast::channel(100).0,
         }
     }
 }
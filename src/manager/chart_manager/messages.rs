@@ -1,3 +1,4 @@
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum ChartMessage {
     Next {
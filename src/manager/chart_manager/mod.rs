@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod chart_manager;
 mod messages;
 mod messanger;
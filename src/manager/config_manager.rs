@@ -0,0 +1,328 @@
+use std::time::{Duration, SystemTime};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    error::{Error, Result},
+    manager::Manager,
+    utils::{ConductorContext, Config},
+};
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// One stored version of the runtime config, the overrides are layered over the `config.toml` defaults
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, async_graphql::SimpleObject)]
+pub struct ConfigVersion {
+    pub version: i64,
+    /// what created the version: `edit_config`, `reset_config` or `config.toml`
+    pub source: String,
+    /// the values set at runtime, they win over `config.toml`
+    pub overrides: Value,
+    pub config: Config,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, async_graphql::SimpleObject)]
+pub struct ConfigChange {
+    pub field: String,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+fn read_defaults() -> Result<Map<String, Value>> {
+    let content = std::fs::read_to_string(CONFIG_FILE)
+        .map_err(|e| Error::General(format!("Failed to read {}: {}", CONFIG_FILE, e)))?;
+    let value: Value = toml_edit::de::from_str(&content)
+        .map_err(|e| Error::General(format!("Failed to parse {}: {}", CONFIG_FILE, e)))?;
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => Err(Error::General(format!("{} is not a table", CONFIG_FILE))),
+    }
+}
+
+/// Builds and validates the config of the defaults with the overrides on top
+fn build_config(defaults: &Map<String, Value>, overrides: &Map<String, Value>) -> Result<Config> {
+    let mut merged = defaults.clone();
+    merged.extend(overrides.clone());
+
+    let config: Config = serde_json::from_value(Value::Object(merged))
+        .map_err(|e| Error::General(format!("Invalid config: {}", e)))?;
+    config
+        .validate()
+        .map_err(|errors| Error::General(format!("Invalid config: {}", errors.join(", "))))?;
+    Ok(config)
+}
+
+fn config_map(config: &Config) -> Map<String, Value> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+pub async fn get_history(database_pool: &database::DbPool) -> Result<Vec<ConfigVersion>> {
+    let versions = database::Configuration::get_config_versions(database_pool).await?;
+    versions
+        .into_iter()
+        .map(|v| serde_json::from_value(v).map_err(|e| Error::General(e.to_string())))
+        .collect()
+}
+
+pub async fn get_latest(database_pool: &database::DbPool) -> Result<Option<ConfigVersion>> {
+    let version = database::Configuration::get_latest_config_version(database_pool).await?;
+    version
+        .map(|v| serde_json::from_value(v).map_err(|e| Error::General(e.to_string())))
+        .transpose()
+}
+
+fn overrides_of(version: &Option<ConfigVersion>) -> Map<String, Value> {
+    match version.as_ref().map(|v| &v.overrides) {
+        Some(Value::Object(map)) => map.clone(),
+        _ => Map::new(),
+    }
+}
+
+async fn store_version(
+    database_pool: &database::DbPool,
+    previous: &Option<ConfigVersion>,
+    source: &str,
+    overrides: Map<String, Value>,
+    config: &Config,
+) -> Result<ConfigVersion> {
+    let version = ConfigVersion {
+        version: previous.as_ref().map(|v| v.version).unwrap_or(0) + 1,
+        source: source.to_string(),
+        overrides: Value::Object(overrides),
+        config: config.clone(),
+        created_at: chrono::Utc::now(),
+    };
+    let value = serde_json::to_value(&version).map_err(|e| Error::General(e.to_string()))?;
+    database::Configuration::set_config_version(database_pool, version.version, value).await?;
+    Ok(version)
+}
+
+/// The config of `config.toml` with the stored overrides on top
+///
+/// Overrides which no longer fit the file, e.g. after a field was removed, are ignored
+pub async fn load_config(database_pool: &database::DbPool) -> Result<Config> {
+    let defaults = read_defaults()?;
+    let latest = get_latest(database_pool).await?;
+    let overrides = overrides_of(&latest);
+
+    match build_config(&defaults, &overrides) {
+        Ok(config) => Ok(config),
+        Err(err) if !overrides.is_empty() => {
+            tracing::error!(error = %err, "Stored config overrides are invalid, using only the config file");
+            build_config(&defaults, &Map::new())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Sets the given fields as overrides, the new config is only applied and stored when it is valid
+pub async fn apply_overrides(
+    context: &ConductorContext,
+    changes: Map<String, Value>,
+    source: &str,
+) -> Result<Config> {
+    let mut current = context.config.write().await;
+
+    let latest = get_latest(&context.database_pool).await?;
+    let mut overrides = overrides_of(&latest);
+    overrides.extend(changes);
+
+    let config = build_config(&read_defaults()?, &overrides)?;
+    store_version(&context.database_pool, &latest, source, overrides, &config).await?;
+
+    *current = config.clone();
+    Ok(config)
+}
+
+/// Removes the given overrides or all of them, so the `config.toml` values apply again
+pub async fn remove_overrides(
+    context: &ConductorContext,
+    fields: Option<Vec<String>>,
+) -> Result<Config> {
+    let mut current = context.config.write().await;
+
+    let latest = get_latest(&context.database_pool).await?;
+    let mut overrides = overrides_of(&latest);
+    match fields {
+        Some(fields) => {
+            let known = config_map(&current);
+            if let Some(unknown) = fields.iter().find(|f| !known.contains_key(*f)) {
+                return Err(Error::General(format!("Unknown config field: {}", unknown)));
+            }
+            overrides.retain(|k, _| !fields.contains(k));
+        }
+        None => overrides.clear(),
+    }
+
+    let config = build_config(&read_defaults()?, &overrides)?;
+    store_version(
+        &context.database_pool,
+        &latest,
+        "reset_config",
+        overrides,
+        &config,
+    )
+    .await?;
+
+    *current = config.clone();
+    Ok(config)
+}
+
+/// The fields which differ between two configs
+pub fn diff(old: &Config, new: &Config) -> Vec<ConfigChange> {
+    let old = config_map(old);
+    let new = config_map(new);
+
+    new.into_iter()
+        .filter_map(|(field, new_value)| {
+            let old_value = old.get(&field).cloned().unwrap_or_default();
+            (old_value != new_value).then_some(ConfigChange {
+                field,
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
+}
+
+/// Writes the config history to `path`, so it can be carried into the next reset
+pub async fn export_versions(
+    database_pool: &database::DbPool,
+    path: &std::path::Path,
+) -> Result<usize> {
+    let versions = get_history(database_pool).await?;
+    let content =
+        serde_json::to_string_pretty(&versions).map_err(|e| Error::General(e.to_string()))?;
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| Error::General(format!("Failed to write {}: {}", path.display(), e)))?;
+    Ok(versions.len())
+}
+
+/// Stores the config history of `path` in a database without config versions
+///
+/// Returns how many versions were imported, a database which already has versions is left as it is
+pub async fn import_versions(
+    database_pool: &database::DbPool,
+    path: &std::path::Path,
+) -> Result<usize> {
+    if get_latest(database_pool).await?.is_some() {
+        return Ok(0);
+    }
+
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| Error::General(format!("Failed to read {}: {}", path.display(), e)))?;
+    let versions: Vec<ConfigVersion> =
+        serde_json::from_str(&content).map_err(|e| Error::General(e.to_string()))?;
+
+    for version in versions.iter() {
+        let value = serde_json::to_value(version).map_err(|e| Error::General(e.to_string()))?;
+        database::Configuration::set_config_version(database_pool, version.version, value).await?;
+    }
+    Ok(versions.len())
+}
+
+/// Watches `config.toml` and applies changes to it while running
+///
+/// An invalid file is logged and ignored, the running config stays as it is
+pub struct ConfigManager {
+    cancel_token: tokio_util::sync::CancellationToken,
+    context: ConductorContext,
+    last_modified: Option<SystemTime>,
+}
+
+impl ConfigManager {
+    pub fn new(
+        cancel_token: tokio_util::sync::CancellationToken,
+        context: ConductorContext,
+    ) -> Self {
+        Self {
+            cancel_token,
+            context,
+            last_modified: None,
+        }
+    }
+
+    fn modified() -> Option<SystemTime> {
+        std::fs::metadata(CONFIG_FILE)
+            .and_then(|m| m.modified())
+            .ok()
+    }
+
+    #[tracing::instrument(
+        level = "info",
+        name = "spacetraders::manager::config_manager_worker",
+        skip(self),
+        err(Debug)
+    )]
+    async fn run_config_worker(&mut self) -> Result<()> {
+        self.last_modified = Self::modified();
+
+        while !self.cancel_token.is_cancelled() {
+            tokio::select! {
+                _ = self.cancel_token.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_secs(5)) => {},
+            }
+
+            let modified = Self::modified();
+            if modified == self.last_modified {
+                continue;
+            }
+            self.last_modified = modified;
+
+            if let Err(err) = self.reload().await {
+                tracing::error!(error = %err, "Failed to reload {}, keeping the current config", CONFIG_FILE);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reload(&self) -> Result<()> {
+        let defaults = read_defaults()?;
+
+        let mut current = self.context.config.write().await;
+        let latest = get_latest(&self.context.database_pool).await?;
+        let overrides = overrides_of(&latest);
+
+        let config = build_config(&defaults, &overrides)?;
+        let changes = diff(&current, &config);
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        store_version(
+            &self.context.database_pool,
+            &latest,
+            CONFIG_FILE,
+            overrides,
+            &config,
+        )
+        .await?;
+        tracing::info!(changes = ?changes, "Reloaded {}", CONFIG_FILE);
+
+        *current = config;
+        Ok(())
+    }
+}
+
+impl Manager for ConfigManager {
+    fn run(
+        &mut self,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + '_>> {
+        Box::pin(async move { self.run_config_worker().await })
+    }
+
+    fn get_name(&self) -> &str {
+        "ConfigManager"
+    }
+
+    fn get_cancel_token(&self) -> &tokio_util::sync::CancellationToken {
+        &self.cancel_token
+    }
+}
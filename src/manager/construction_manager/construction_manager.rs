@@ -165,7 +165,7 @@ impl ConstructionManager {
             } => {
                 self.fail_shipment(shipment, &error).await?;
 
-                callback.send(error).unwrap();
+                let _send = callback.send(error);
             }
             ConstructionMessage::FinishedShipment {
                 construction,
@@ -174,7 +174,7 @@ impl ConstructionManager {
                 self.finish_shipment(construction, shipment).await?;
             }
             ConstructionMessage::GetRunning { callback } => {
-                callback.send(Ok(self.running_shipments.clone())).unwrap();
+                let _send = callback.send(Ok(self.running_shipments.clone()));
             }
         }
         self.context.construction_manager.set_busy(false);
@@ -198,37 +198,45 @@ impl ConstructionManager {
             .filter(|s| s.ship_symbol == ship_clone.symbol)
             .collect::<Vec<_>>();
 
-        if !running_shipments.is_empty() {
-            let next_shipment = running_shipments.iter().min_by_key(|s| s.id).unwrap();
-
-            self.running_shipments.push((**next_shipment).clone());
+        if let Some(next_shipment) = running_shipments.iter().min_by_key(|s| s.id) {
+            if !self
+                .running_shipments
+                .iter()
+                .any(|s| s.id == next_shipment.id)
+            {
+                self.running_shipments.push((**next_shipment).clone());
+            }
 
             return Ok(super::NextShipmentResp::Shipment((**next_shipment).clone()));
         }
 
-        let construction_materials = database::ConstructionMaterial::get_unfulfilled(
+        let construction_materials = database::ConstructionMaterial::get_by_system(
             &self.context.database_pool,
+            &ship_clone.nav.system_symbol,
             PaginatedQuery::unpaged(),
         )
         .await?
-        .items;
+        .items
+        .into_iter()
+        .map(|mut c| {
+            let running = self
+                .running_shipments
+                .iter()
+                .filter(|s| s.material_id == c.id)
+                .map(|s| s.units)
+                .sum::<i32>();
 
-        let construction_materials = construction_materials
-            .into_iter()
-            .map(|mut c| {
-                let running = self
-                    .running_shipments
-                    .iter()
-                    .filter(|s| s.material_id == c.id)
-                    .map(|s| s.units)
-                    .sum::<i32>();
+            c.fulfilled = (c.fulfilled + running).min(c.required);
 
-                c.fulfilled = (c.fulfilled + running).min(c.required);
+            c
+        })
+        .collect::<Vec<_>>();
 
-                c
-            })
+        let site_progress = Self::site_progress(&construction_materials);
+
+        let construction_materials = construction_materials
+            .into_iter()
             .filter(|c| c.fulfilled < c.required)
-            .filter(|c| c.waypoint_symbol.starts_with(&ship_clone.nav.system_symbol))
             .collect::<Vec<_>>();
 
         if construction_materials.is_empty() {
@@ -236,10 +244,7 @@ impl ConstructionManager {
             return Ok(super::NextShipmentResp::ComeBackLater);
         }
 
-        // let next_material: &database::ConstructionMaterial = construction_materials
-        //     .iter()
-        //     .min_by_key(|c| ((c.fulfilled as f64 / c.required as f64) * 10000.0) as i64)
-        //     .unwrap();
+        let default_purchase_price = { self.context.config.read().await.default_purchase_price };
 
         let mut materials = Vec::new();
 
@@ -250,11 +255,18 @@ impl ConstructionManager {
                 self.calculate_purchase_volume(&ship_clone, material, &trade_symbol);
             debug!("Calculated purchase volume: {}", purchase_volume);
 
-            let purchase_symbol = self
+            let purchase_symbol = match self
                 .get_purchase_waypoint(&trade_symbol, &ship_clone.nav.system_symbol)
-                .await?;
+                .await
+            {
+                Ok(purchase_symbol) => purchase_symbol,
+                Err(err) => {
+                    debug!("No market for {}: {:?}", trade_symbol, err);
+                    continue;
+                }
+            };
             debug!("Obtained purchase waypoint: {:?}", purchase_symbol);
-            let purchase_price = purchase_symbol.1.unwrap_or(10);
+            let purchase_price = purchase_symbol.1.unwrap_or(default_purchase_price);
 
             materials.push((
                 material.clone(),
@@ -262,21 +274,31 @@ impl ConstructionManager {
                 purchase_symbol,
                 purchase_volume,
                 remaining,
-                purchase_volume * purchase_price,
+                purchase_price,
             ));
         }
 
-        let (
-            next_material,
-            trade_symbol,
-            purchase_symbol,
-            purchase_volume,
-            remaining,
-            _total_price,
-        ) = materials.into_iter().min_by_key(|m| m.4).unwrap();
-
-        let reservation = if let Some(purchase_price) = purchase_symbol.1 {
-            debug!("Calculated purchase price: {}", purchase_price);
+        // the site closest to completion goes first, within a site the material with the least units missing
+        materials.sort_by(|a, b| {
+            let progress_a = site_progress
+                .get(&a.0.waypoint_symbol)
+                .copied()
+                .unwrap_or(0.0);
+            let progress_b = site_progress
+                .get(&b.0.waypoint_symbol)
+                .copied()
+                .unwrap_or(0.0);
+            progress_b
+                .total_cmp(&progress_a)
+                .then_with(|| a.0.waypoint_symbol.cmp(&b.0.waypoint_symbol))
+                .then_with(|| a.4.cmp(&b.4))
+        });
+
+        let mut next = None;
+
+        for (material, trade_symbol, purchase_symbol, purchase_volume, remaining, purchase_price) in
+            materials
+        {
             let total_price = (purchase_price * (purchase_volume * 2).min(remaining)) as i64;
 
             let budget = self
@@ -286,28 +308,37 @@ impl ConstructionManager {
                 .await;
 
             debug!("Calculated budget: {:?}", budget);
-            if budget.is_err()
-                && let Err(e) = budget
-            {
-                if let crate::error::Error::NotEnoughFunds {
+            match budget {
+                Ok(reservation) => {
+                    next = Some((
+                        material,
+                        trade_symbol,
+                        purchase_symbol,
+                        purchase_volume,
+                        reservation,
+                    ));
+                    break;
+                }
+                Err(crate::error::Error::NotEnoughFunds {
                     remaining_funds,
                     required_funds,
-                } = e
-                {
+                }) => {
                     debug!(
-                        "Not enough budget for purchase has {} needed {}",
-                        remaining_funds, required_funds
+                        "Not enough budget for {} at {} has {} needed {}",
+                        trade_symbol, material.waypoint_symbol, remaining_funds, required_funds
                     );
-                    return Ok(super::NextShipmentResp::ComeBackLater);
-                } else {
+                }
+                Err(e) => {
                     debug!("Error reserving funds: {:?}", e);
                     return Err(e);
                 }
             }
+        }
 
-            Some(budget.unwrap())
-        } else {
-            None
+        let Some((next_material, trade_symbol, purchase_symbol, purchase_volume, reservation)) =
+            next
+        else {
+            return Ok(super::NextShipmentResp::ComeBackLater);
         };
 
         let next_shipment = database::ConstructionShipment {
@@ -321,7 +352,7 @@ impl ConstructionManager {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             status: database::ShipmentStatus::InTransit,
-            reserved_fund: reservation.map(|r| r.id),
+            reserved_fund: Some(reservation.id),
         };
 
         let id =
@@ -442,6 +473,22 @@ impl ConstructionManager {
         Ok(())
     }
 
+    /// The share of the required units of each site that is delivered or on its way
+    fn site_progress(materials: &[database::ConstructionMaterial]) -> HashMap<String, f64> {
+        let mut sites: HashMap<String, (i32, i32)> = HashMap::new();
+        for material in materials {
+            let site = sites.entry(material.waypoint_symbol.clone()).or_default();
+            site.0 += material.fulfilled;
+            site.1 += material.required;
+        }
+        sites
+            .into_iter()
+            .map(|(waypoint_symbol, (fulfilled, required))| {
+                (waypoint_symbol, fulfilled as f64 / required.max(1) as f64)
+            })
+            .collect()
+    }
+
     /// returns purchase volume and remaining volume
     fn calculate_purchase_volume(
         &self,
@@ -1,5 +1,6 @@
 use space_traders_client::models;
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum ConstructionMessage {
     RequestNextShipment {
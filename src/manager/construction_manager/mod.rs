@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod construction_manager;
 mod message;
 mod messanger;
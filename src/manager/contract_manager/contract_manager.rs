@@ -1,4 +1,8 @@
-use std::{cmp::Ordering, collections::HashMap, str::FromStr};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use chrono::{DateTime, Utc};
 use database::DatabaseConnectorAsync;
@@ -23,9 +27,13 @@ pub struct ContractManager {
     slow_cancel_token: tokio_util::sync::CancellationToken,
     context: ConductorContext,
     receiver: ContractManagerReceiver,
-    current_contract: Option<models::Contract>,
+    /// the unfulfilled contracts by id, several can be worked on at once
+    contracts: HashMap<String, models::Contract>,
+    /// the reserved funds of the contracts by contract id
+    reserved_funds: HashMap<String, database::ReservedFund>,
+    /// offered contracts not worth accepting, they are left to expire
+    declined_contracts: HashSet<String>,
     running_shipments: Vec<database::ContractShipment>,
-    reserved_funds: Option<database::ReservedFund>,
 }
 
 impl ContractManager {
@@ -48,9 +56,10 @@ impl ContractManager {
             slow_cancel_token,
             context,
             receiver,
-            current_contract: None,
+            contracts: HashMap::new(),
+            reserved_funds: HashMap::new(),
+            declined_contracts: HashSet::new(),
             running_shipments: Vec::new(),
-            reserved_funds: None,
         }
     }
 
@@ -70,33 +79,38 @@ impl ContractManager {
                 database::Contract::get_by_id(&self.context.database_pool, &contract.id).await?;
 
             if let Some(existing_contract) = in_db
-                && let Some(reserved_fund_id) = existing_contract.reserved_fund {
-                    let fund = database::ReservedFund::get_by_id(
-                        &self.context.database_pool,
-                        &reserved_fund_id,
-                    )
-                    .await?;
-                    self.reserved_funds = fund;
-                }
+                && let Some(reserved_fund_id) = existing_contract.reserved_fund
+                && let Some(fund) = database::ReservedFund::get_by_id(
+                    &self.context.database_pool,
+                    &reserved_fund_id,
+                )
+                .await?
+            {
+                self.reserved_funds.insert(contract.id.clone(), fund);
+            }
 
             database::Contract::insert_contract(
                 &self.context.database_pool,
                 contract.clone(),
-                self.reserved_funds.as_ref().map(|r| r.id),
+                self.reserved_fund_id(&contract.id),
             )
             .await?;
-        }
 
-        match contracts.len() {
-            0 => debug!("No unfulfilled contracts found"),
-            1 => {
-                self.current_contract = Some(contracts[0].clone());
-                debug!("Current contract set: {:?}", self.current_contract);
-            }
-            _ => {
-                panic!("Too many contracts");
-            }
+            self.contracts.insert(contract.id.clone(), contract.clone());
         }
+
+        self.declined_contracts.extend(
+            database::Contract::get_declined_ids(&self.context.database_pool)
+                .await?
+                .into_iter()
+                .filter(|id| self.contracts.contains_key(id)),
+        );
+
+        debug!(
+            "Tracking {} unfulfilled contracts, {} declined",
+            self.contracts.len(),
+            self.declined_contracts.len()
+        );
         debug!("Starting contract worker loop");
 
         let fast_cancel_token = self.fast_cancel_token.clone();
@@ -164,13 +178,13 @@ impl ContractManager {
             } => {
                 self.failed_shipment(shipment, &error).await?;
 
-                callback.send(Ok(error)).unwrap();
+                let _send = callback.send(Ok(error));
             }
             ContractShipmentMessage::Finished { contract, shipment } => {
                 self.finished_shipment(contract, shipment).await?;
             }
             ContractShipmentMessage::GetRunning { callback } => {
-                callback.send(Ok(self.running_shipments.clone())).unwrap();
+                let _send = callback.send(Ok(self.running_shipments.clone()));
             }
         }
         self.context.contract_manager.set_busy(false);
@@ -187,76 +201,136 @@ impl ContractManager {
 
         debug!("Current shipments {}", self.running_shipments.len());
 
-        if self.running_shipments.is_empty()
-            && self.current_contract.is_some()
-            && self
-                .current_contract
-                .as_ref()
-                .map(|c| self.can_fulfill_trade(c))
-                .unwrap_or(false)
-        {
-            let id = self
-                .current_contract
-                .as_ref()
-                .map(|c| c.id.clone())
-                .unwrap();
-            tracing::info!(contract_id = &id, "Can Fulfilled contract");
+        self.fulfill_finished_contracts().await?;
+        self.forget_expired_contracts();
 
-            let fulfill_contract_data = self.context.api.fulfill_contract(&id).await?;
+        if let Some(shipment) = self.get_in_transit_shipment(&ship_clone).await? {
+            debug!("Ship already has {:?} in transit", shipment);
+            if !self.running_shipments.iter().any(|s| s.id == shipment.id) {
+                self.running_shipments.push(shipment.clone());
+            }
+            let reserved_fund = self.reserved_fund_id(&shipment.contract_id);
+            return Ok(NextShipmentResp::Shipment(shipment, reserved_fund));
+        }
 
-            self.current_contract = None;
+        let contract_id = match self.get_workable_contract(&ship_clone) {
+            Some(contract_id) => contract_id,
+            None => {
+                let Some(contract_id) = self
+                    .get_acceptable_contract(&ship_clone, can_start_new_contract)
+                    .await?
+                else {
+                    return Ok(NextShipmentResp::ComeBackLater);
+                };
+                self.accept_contract(&contract_id).await?;
+                contract_id
+            }
+        };
 
-            database::Contract::insert_contract(
-                &self.context.database_pool,
-                *fulfill_contract_data.data.contract,
-                self.reserved_funds.as_ref().map(|r| r.id),
-            )
+        let Some(next_procurment) = self
+            .get_open_procurements(&contract_id, &ship_clone)
+            .first()
+            .cloned()
+        else {
+            debug!("No procurement tasks available");
+            return Ok(NextShipmentResp::ComeBackLater);
+        };
+        debug!("Next procurement task: {:?}", next_procurment);
+
+        let trade_symbol = models::TradeSymbol::from_str(&next_procurment.trade_symbol)
+            .map_err(|err| Error::General(err.to_string()))?;
+
+        let (purchase_volume, remaining) =
+            self.calculate_purchase_volume(&ship_clone, &next_procurment, &trade_symbol);
+        debug!("Calculated purchase volume: {}", purchase_volume);
+
+        let purchase_symbol = self
+            .get_purchase_waypoint(&trade_symbol, &ship_clone.nav.system_symbol)
             .await?;
+        debug!("Obtained purchase waypoint: {:?}", purchase_symbol);
+
+        if !self.reserved_funds.contains_key(&contract_id)
+            && let Some(purchase_price) = purchase_symbol.1
+        {
+            debug!("Calculated purchase price: {}", purchase_price);
+            let total_price = (purchase_price * remaining) as i64;
 
-            database::Agent::upsert(
+            let budget = self
+                .context
+                .budget_manager
+                .reserve_funds_with_remain(&self.context.database_pool, total_price, 30_000)
+                .await;
+
+            debug!("Calculated budget: {:?}", budget);
+            let reservation = match budget {
+                Ok(reservation) => reservation,
+                Err(crate::error::Error::NotEnoughFunds {
+                    remaining_funds,
+                    required_funds,
+                }) => {
+                    debug!(
+                        "Not enough budget for purchase has {} needed {}",
+                        remaining_funds, required_funds
+                    );
+                    return Ok(super::NextShipmentResp::ComeBackLater);
+                }
+                Err(e) => {
+                    debug!("Error reserving funds: {:?}", e);
+                    return Err(e);
+                }
+            };
+
+            database::Contract::update_reserved_fund(
                 &self.context.database_pool,
-                &database::Agent::from(*fulfill_contract_data.data.agent),
+                &contract_id,
+                Some(reservation.id),
             )
             .await?;
-        }
 
-        if self.current_contract.is_none() {
-            if can_start_new_contract {
-                let has_done = self.get_new_contract(&ship_clone).await?;
+            self.reserved_funds.insert(contract_id.clone(), reservation);
+        }
 
-                debug!("Has done: {}", has_done);
+        let mut next_shipment = database::ContractShipment {
+            contract_id: contract_id.clone(),
+            trade_symbol,
+            destination_symbol: next_procurment.destination_symbol.to_string(),
+            units: purchase_volume,
+            id: 0,
+            ship_symbol: ship_clone.symbol.to_string(),
+            purchase_symbol: purchase_symbol.0.to_owned(),
+            status: database::ShipmentStatus::InTransit,
+            ..Default::default()
+        };
 
-                if !has_done {
-                    return Ok(NextShipmentResp::ComeBackLater);
-                }
-            } else {
-                return Ok(NextShipmentResp::ComeBackLater);
-            }
-        }
-        if !self.current_contract.as_ref().unwrap().accepted {
-            let resp = self
-                .context
-                .api
-                .accept_contract(&self.current_contract.as_ref().unwrap().id)
+        let id =
+            database::ContractShipment::insert_new(&self.context.database_pool, &next_shipment)
                 .await?;
+        debug!("Inserted new shipment with id: {}", id);
 
-            self.current_contract = Some(*resp.data.contract.clone());
+        let sql_shipment =
+            database::ContractShipment::get_by_id(&self.context.database_pool, id).await?;
 
-            database::Contract::insert_contract(
-                &self.context.database_pool,
-                *resp.data.contract,
-                self.reserved_funds.as_ref().map(|r| r.id),
-            )
-            .await?;
+        next_shipment = sql_shipment;
 
-            database::Agent::upsert(
-                &self.context.database_pool,
-                &database::Agent::from(*resp.data.agent),
-            )
-            .await?;
-        }
+        self.running_shipments.push(next_shipment.clone());
+
+        Ok(NextShipmentResp::Shipment(
+            next_shipment,
+            self.reserved_fund_id(&contract_id),
+        ))
+    }
 
-        let shipments = database::ContractShipment::get_by_ship(
+    fn reserved_fund_id(&self, contract_id: &str) -> Option<i64> {
+        self.reserved_funds.get(contract_id).map(|r| r.id)
+    }
+
+    /// The in transit shipment of the ship, a ship only carries one shipment at a time.
+    /// Additional shipments come from an inconsistent state and are marked as failed.
+    async fn get_in_transit_shipment(
+        &mut self,
+        ship_clone: &ship::MyShipCopy,
+    ) -> Result<Option<database::ContractShipment>> {
+        let mut shipments = database::ContractShipment::get_by_ship(
             &self.context.database_pool,
             &ship_clone.symbol,
             database::PaginatedQuery::unpaged(),
@@ -264,32 +338,139 @@ impl ContractManager {
         .await?
         .items
         .into_iter()
-        .filter(|s| s.contract_id == self.current_contract.as_ref().unwrap().id)
         .filter(|s| s.status == database::ShipmentStatus::InTransit)
         .collect::<Vec<_>>();
 
-        match shipments.len() {
-            1 => {
-                let shipment = shipments[0].clone();
-                debug!("Ship already has {:?} in transit", shipment);
-                self.running_shipments.push(shipment.clone());
-                return Ok(NextShipmentResp::Shipment(
-                    shipment,
-                    self.reserved_funds.as_ref().map(|r| r.id),
-                ));
+        shipments.sort_by_key(|s| s.id);
+
+        let mut shipments = shipments.into_iter();
+        let mut current = None;
+
+        for shipment in shipments.by_ref() {
+            if self.contracts.contains_key(&shipment.contract_id) && current.is_none() {
+                current = Some(shipment);
+                continue;
+            }
+
+            tracing::warn!(
+                shipment_id = shipment.id,
+                contract_id = %shipment.contract_id,
+                ship_symbol = %ship_clone.symbol,
+                "Dropping superfluous in transit shipment"
+            );
+            self.failed_shipment(
+                shipment,
+                &Error::General("Superfluous in transit shipment".to_string()),
+            )
+            .await?;
+        }
+
+        Ok(current)
+    }
+
+    /// An accepted contract with open deliveries in the system of the ship, the one with the nearest deadline first
+    fn get_workable_contract(&self, ship_clone: &ship::MyShipCopy) -> Option<String> {
+        self.contracts
+            .values()
+            .filter(|c| c.accepted && !c.fulfilled)
+            .filter(|c| !self.get_open_procurements(&c.id, ship_clone).is_empty())
+            .min_by(|a, b| a.terms.deadline.cmp(&b.terms.deadline))
+            .map(|c| c.id.clone())
+    }
+
+    /// The offered contract worth accepting, a new one is negotiated when none of the known is
+    async fn get_acceptable_contract(
+        &mut self,
+        ship_clone: &ship::MyShipCopy,
+        can_start_new_contract: bool,
+    ) -> Result<Option<String>> {
+        let offered = self
+            .contracts
+            .values()
+            .filter(|c| !c.accepted && !self.declined_contracts.contains(&c.id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut best: Option<(String, i64)> = None;
+        for contract in offered.iter() {
+            match self.evaluate_contract(contract, ship_clone).await? {
+                Some(profit) if best.as_ref().is_none_or(|b| profit > b.1) => {
+                    best = Some((contract.id.clone(), profit));
+                }
+                Some(_) => {}
+                None => {
+                    self.decline_contract(&contract.id).await?;
+                }
             }
-            _ if shipments.len() > 1 => {
-                tracing::error!(length = %shipments.len(), ship_symbol = %ship_clone.symbol, "Ship already has shipments in transit");
-                panic!("Ship already has {} shipments in transit", shipments.len());
+        }
+
+        if let Some((contract_id, _)) = best {
+            return Ok(Some(contract_id));
+        }
+
+        if !can_start_new_contract {
+            return Ok(None);
+        }
+
+        let Some(contract) = self.get_new_contract(ship_clone).await? else {
+            return Ok(None);
+        };
+
+        match self.evaluate_contract(&contract, ship_clone).await? {
+            Some(_) => Ok(Some(contract.id)),
+            None => {
+                self.decline_contract(&contract.id).await?;
+                Ok(None)
             }
-            _ => {} // This arm is not necessary in this case, but it's good practice to include it
         }
+    }
 
-        let contract = self.current_contract.as_ref().unwrap();
+    /// Marks an offered contract as not worth accepting, so it is left to expire and not evaluated again after a restart
+    async fn decline_contract(&mut self, contract_id: &str) -> Result<()> {
+        tracing::info!(contract_id, "Declined contract, leaving it to expire");
+        self.declined_contracts.insert(contract_id.to_string());
+        database::Contract::mark_declined(&self.context.database_pool, contract_id).await?;
+        Ok(())
+    }
 
-        let all_procurment = contract.terms.deliver.as_ref().unwrap();
+    async fn accept_contract(&mut self, contract_id: &str) -> Result<()> {
+        let resp = self.context.api.accept_contract(contract_id).await?;
+
+        tracing::info!(contract_id, "Accepted contract");
+
+        self.contracts
+            .insert(contract_id.to_string(), *resp.data.contract.clone());
+
+        database::Contract::insert_contract(
+            &self.context.database_pool,
+            *resp.data.contract,
+            self.reserved_fund_id(contract_id),
+        )
+        .await?;
+
+        database::Agent::upsert(
+            &self.context.database_pool,
+            &database::Agent::from(*resp.data.agent),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// The deliveries of the contract in the system of the ship which are not covered by delivered or running shipments
+    fn get_open_procurements(
+        &self,
+        contract_id: &str,
+        ship_clone: &ship::MyShipCopy,
+    ) -> Vec<models::ContractDeliverGood> {
+        let Some(contract) = self.contracts.get(contract_id) else {
+            return Vec::new();
+        };
+        let Some(deliveries) = contract.terms.deliver.as_ref() else {
+            return Vec::new();
+        };
 
-        let all_procurment = all_procurment
+        deliveries
             .iter()
             .map(|p| {
                 let running = self
@@ -315,101 +496,49 @@ impl ContractManager {
                     .starts_with(&ship_clone.nav.system_symbol)
                     && c.units_fulfilled < c.units_required
             })
+            .collect::<Vec<_>>()
+    }
+
+    /// Fulfills every contract whose deliveries are complete and which has no shipment running
+    async fn fulfill_finished_contracts(&mut self) -> Result<()> {
+        let finished = self
+            .contracts
+            .values()
+            .filter(|c| c.accepted && self.can_fulfill_trade(c))
+            .filter(|c| !self.running_shipments.iter().any(|s| s.contract_id == c.id))
+            .map(|c| c.id.clone())
             .collect::<Vec<_>>();
 
-        if all_procurment.is_empty() {
-            debug!("No procurement tasks available");
-            return Ok(NextShipmentResp::ComeBackLater);
+        for contract_id in finished {
+            self.fulfill_contract(&contract_id).await?;
         }
 
-        let next_procurment = &all_procurment[0];
-        debug!("Next procurement task: {:?}", next_procurment);
-
-        let trade_symbol = models::TradeSymbol::from_str(&next_procurment.trade_symbol)
-            .map_err(|err| Error::General(err.to_string()))?;
-
-        let (purchase_volume, remaining) =
-            self.calculate_purchase_volume(&ship_clone, next_procurment, &trade_symbol);
-        debug!("Calculated purchase volume: {}", purchase_volume);
-
-        let purchase_symbol = self
-            .get_purchase_waypoint(&trade_symbol, &ship_clone.nav.system_symbol)
-            .await?;
-        debug!("Obtained purchase waypoint: {:?}", purchase_symbol);
-
-        if self.reserved_funds.is_none() {
-            let reservation = if let Some(purchase_price) = purchase_symbol.1 {
-                debug!("Calculated purchase price: {}", purchase_price);
-                let total_price = (purchase_price * remaining) as i64;
-
-                let budget = self
-                    .context
-                    .budget_manager
-                    .reserve_funds_with_remain(&self.context.database_pool, total_price, 30_000)
-                    .await;
-
-                debug!("Calculated budget: {:?}", budget);
-                if budget.is_err()
-                    && let Err(e) = budget {
-                        if let crate::error::Error::NotEnoughFunds {
-                            remaining_funds,
-                            required_funds,
-                        } = e
-                        {
-                            debug!(
-                                "Not enough budget for purchase has {} needed {}",
-                                remaining_funds, required_funds
-                            );
-                            return Ok(super::NextShipmentResp::ComeBackLater);
-                        } else {
-                            debug!("Error reserving funds: {:?}", e);
-                            return Err(e);
-                        }
-                    }
-
-                Some(budget.unwrap())
-            } else {
-                None
-            };
-
-            self.reserved_funds = reservation;
-
-            database::Contract::update_reserved_fund(
-                &self.context.database_pool,
-                &contract.id,
-                self.reserved_funds.as_ref().map(|r| r.id),
-            )
-            .await?;
-        }
+        Ok(())
+    }
 
-        let mut next_shipment = database::ContractShipment {
-            contract_id: contract.id.clone(),
-            trade_symbol,
-            destination_symbol: next_procurment.destination_symbol.to_string(),
-            units: purchase_volume,
-            id: 0,
-            ship_symbol: ship_clone.symbol.to_string(),
-            purchase_symbol: purchase_symbol.0.to_owned(),
-            status: database::ShipmentStatus::InTransit,
-            ..Default::default()
+    /// Contracts past their deadline can't be fulfilled anymore, unaccepted ones past their deadline to accept can't be accepted
+    fn forget_expired_contracts(&mut self) {
+        let now = Utc::now();
+        let is_past = |time: Option<&String>| {
+            time.and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .is_some_and(|t| t < now)
         };
+        let expired = self
+            .contracts
+            .values()
+            .filter(|c| {
+                !self.is_in_deadline(c)
+                    || (!c.accepted
+                        && is_past(c.deadline_to_accept.as_ref().or(Some(&c.expiration))))
+            })
+            .map(|c| c.id.clone())
+            .collect::<Vec<_>>();
 
-        let id =
-            database::ContractShipment::insert_new(&self.context.database_pool, &next_shipment)
-                .await?;
-        debug!("Inserted new shipment with id: {}", id);
-
-        let sql_shipment =
-            database::ContractShipment::get_by_id(&self.context.database_pool, id).await?;
-
-        next_shipment = sql_shipment;
-
-        self.running_shipments.push(next_shipment.clone());
-
-        Ok(NextShipmentResp::Shipment(
-            next_shipment,
-            self.reserved_funds.as_ref().map(|r| r.id),
-        ))
+        for contract_id in expired {
+            debug!("Contract {} expired", contract_id);
+            self.contracts.remove(&contract_id);
+            self.declined_contracts.remove(&contract_id);
+        }
     }
 
     fn calculate_purchase_volume(
@@ -457,7 +586,7 @@ impl ContractManager {
         database::Contract::insert_contract(
             &self.context.database_pool,
             contract.clone(),
-            self.reserved_funds.as_ref().map(|r| r.id),
+            self.reserved_fund_id(&contract.id),
         )
         .await?;
 
@@ -474,47 +603,63 @@ impl ContractManager {
 
         database::ContractShipment::upsert(&self.context.database_pool, &shipment).await?;
 
-        if self.can_fulfill_trade(&contract) {
-            tracing::info!(contract_id = &contract.id, "Can Fulfilled contract");
+        let contract_id = contract.id.clone();
+        self.contracts.insert(contract_id.clone(), contract);
 
-            let fulfill_contract_data = self.context.api.fulfill_contract(&contract.id).await?;
+        let has_running = self
+            .running_shipments
+            .iter()
+            .any(|s| s.contract_id == contract_id);
 
-            self.current_contract = None;
+        if !has_running
+            && self
+                .contracts
+                .get(&contract_id)
+                .is_some_and(|c| self.can_fulfill_trade(c))
+        {
+            self.fulfill_contract(&contract_id).await?;
+        }
 
-            database::Contract::insert_contract(
-                &self.context.database_pool,
-                *fulfill_contract_data.data.contract,
-                self.reserved_funds.as_ref().map(|r| r.id),
-            )
-            .await?;
+        Ok(())
+    }
+
+    async fn fulfill_contract(&mut self, contract_id: &str) -> Result<()> {
+        tracing::info!(contract_id, "Can Fulfilled contract");
+
+        let fulfill_contract_data = self.context.api.fulfill_contract(contract_id).await?;
+
+        self.contracts.remove(contract_id);
+
+        database::Contract::insert_contract(
+            &self.context.database_pool,
+            *fulfill_contract_data.data.contract,
+            self.reserved_fund_id(contract_id),
+        )
+        .await?;
 
-            database::Agent::upsert(
+        database::Agent::upsert(
+            &self.context.database_pool,
+            &database::Agent::from(*fulfill_contract_data.data.agent),
+        )
+        .await?;
+
+        if let Some(reserved_funds) = self.reserved_funds.remove(contract_id) {
+            let transactions = database::MarketTransaction::get_by_reason(
                 &self.context.database_pool,
-                &database::Agent::from(*fulfill_contract_data.data.agent),
+                database::TransactionReason::Contract(contract_id.to_string()),
+                database::PaginatedQuery::unpaged(),
             )
-            .await?;
-
-            if let Some(reserved_funds) = &self.reserved_funds {
-                let transactions = database::MarketTransaction::get_by_reason(
-                    &self.context.database_pool,
-                    database::TransactionReason::Contract(contract.id.clone()),
-                    database::PaginatedQuery::unpaged(),
-                )
-                .await?
-                .items;
-                let funds = transactions
-                    .iter()
-                    .filter(|t| t.r#type == models::market_transaction::Type::Purchase)
-                    .map(|t| t.total_price as i64)
-                    .sum();
-                self.context
-                    .budget_manager
-                    .complete_use_reservation(&self.context.database_pool, reserved_funds.id, funds)
-                    .await?;
-                self.reserved_funds = None;
-            }
-        } else {
-            self.current_contract = Some(contract);
+            .await?
+            .items;
+            let funds = transactions
+                .iter()
+                .filter(|t| t.r#type == models::market_transaction::Type::Purchase)
+                .map(|t| t.total_price as i64)
+                .sum();
+            self.context
+                .budget_manager
+                .complete_use_reservation(&self.context.database_pool, reserved_funds.id, funds)
+                .await?;
         }
 
         Ok(())
@@ -529,50 +674,113 @@ impl ContractManager {
             .collect::<Vec<_>>())
     }
 
-    async fn is_contract_viable(&self, contract: &models::Contract) -> Result<bool> {
-        debug!("Checking if contract is viable: {:?}", contract);
+    /// The expected profit of the contract, payment minus purchase cost and travel from the market data.
+    /// `None` when the contract is not worth accepting.
+    async fn evaluate_contract(
+        &self,
+        contract: &models::Contract,
+        ship_clone: &ship::MyShipCopy,
+    ) -> Result<Option<i64>> {
+        debug!("Evaluating contract: {:?}", contract);
         if !self.is_in_deadline(contract) {
-            return Ok(false);
+            return Ok(None);
         }
 
-        match contract.r#type {
-            models::contract::Type::Procurement => self.check_procurement_viability(contract).await,
-            _ => panic!("Unimplemented contract type"),
-        }
+        let profit = match contract.r#type {
+            models::contract::Type::Procurement => {
+                self.evaluate_procurement(contract, ship_clone).await?
+            }
+            _ => {
+                debug!("Unsupported contract type {:?}", contract.r#type);
+                None
+            }
+        };
+
+        let threshold = { self.context.config.read().await.contract_profit_threshold as i64 };
+
+        tracing::debug!(contract_id = %contract.id, profit = ?profit, threshold, "Evaluated contract");
+
+        Ok(profit.filter(|profit| *profit >= threshold))
     }
 
-    async fn check_procurement_viability(&self, contract: &models::Contract) -> Result<bool> {
+    async fn evaluate_procurement(
+        &self,
+        contract: &models::Contract,
+        ship_clone: &ship::MyShipCopy,
+    ) -> Result<Option<i64>> {
         let Some(deliveries) = &contract.terms.deliver else {
-            return Ok(false);
+            return Ok(None);
         };
 
-        debug!(
-            "Checking procurement viability for deliveries: {:?}",
-            deliveries
-        );
-        let market_trade_goods = database::MarketTrade::get_last(
+        let (fuel_price, default_purchase_price) = {
+            let config = self.context.config.read().await;
+            (
+                config.fuel_cost as i64,
+                config.default_purchase_price as i64,
+            )
+        };
+
+        let waypoints = database::Waypoint::get_by_system(
             &self.context.database_pool,
+            &ship_clone.nav.system_symbol,
             database::PaginatedQuery::unpaged(),
         )
         .await?
-        .items;
+        .items
+        .into_iter()
+        .map(|w| (w.symbol.clone(), w))
+        .collect::<HashMap<_, _>>();
+
+        let capacity = ship_clone.cargo.capacity.max(1) as i64;
+        let mut costs = 0;
 
         for delivery in deliveries {
             if delivery.units_required <= delivery.units_fulfilled {
                 continue;
             }
+            if !delivery
+                .destination_symbol
+                .starts_with(&ship_clone.nav.system_symbol)
+            {
+                return Ok(None);
+            }
 
             let symbol = models::TradeSymbol::from_str(&delivery.trade_symbol)
                 .map_err(|err| Error::General(err.to_string()))?;
-            if !market_trade_goods
-                .iter()
-                .any(|trade| trade.symbol == symbol)
-            {
-                return Ok(false);
-            }
+
+            let Ok((purchase_symbol, purchase_price)) = self
+                .get_purchase_waypoint(&symbol, &ship_clone.nav.system_symbol)
+                .await
+            else {
+                debug!("No market sells {}", symbol);
+                return Ok(None);
+            };
+
+            let units = (delivery.units_required - delivery.units_fulfilled) as i64;
+            let purchase_price = purchase_price
+                .map(|p| p as i64)
+                .unwrap_or(default_purchase_price);
+
+            // every trip flies from the market to the destination and back, cruising burns one fuel unit per distance
+            let distance = match (
+                waypoints.get(&purchase_symbol),
+                waypoints.get(&delivery.destination_symbol),
+            ) {
+                (Some(from), Some(to)) => {
+                    utils::distance_between_waypoints((from.x, from.y), (to.x, to.y))
+                }
+                _ => 0.0,
+            };
+            let trips = (units + capacity - 1) / capacity;
+            let fuel_units = (distance * 2.0).ceil() as i64 * trips;
+
+            costs += units * purchase_price + fuel_units * fuel_price / 100;
         }
 
-        Ok(true)
+        let payment =
+            contract.terms.payment.on_accepted as i64 + contract.terms.payment.on_fulfilled as i64;
+
+        Ok(Some(payment - costs))
     }
 
     fn is_in_deadline(&self, contract: &models::Contract) -> bool {
@@ -589,8 +797,7 @@ impl ContractManager {
         })
     }
 
-    /// Negotiates a new contract for the given ship, and starts it if a contract
-    /// is available. If a contract is already running, this function will panic.
+    /// Negotiates a new contract for the given ship and tracks it, it still has to be evaluated and accepted.
     ///
     /// Ship MUST be docked
     ///
@@ -600,39 +807,47 @@ impl ContractManager {
     ///
     /// # Return
     ///
-    /// Returns `true` if a contract was started, `false` if no contract was
-    /// available.
-    async fn get_new_contract(&mut self, ship_clone: &ship::MyShipCopy) -> Result<bool> {
+    /// Returns the negotiated contract, `None` while existing contracts block negotiating, other api errors are returned.
+    async fn get_new_contract(
+        &mut self,
+        ship_clone: &ship::MyShipCopy,
+    ) -> Result<Option<models::Contract>> {
         debug!("Negotiating new contract for ship: {:?}", ship_clone.symbol);
-        if self.current_contract.is_some() || !self.running_shipments.is_empty() {
-            panic!("Already running a contract");
-        }
 
         let current_nav = ship_clone.nav.get_status();
         if current_nav != models::ShipNavStatus::Docked {
             return Err(Error::General("Ship not docked".to_string()));
         }
 
-        let contract_resp = self
+        let contract_resp = match self
             .context
             .api
             .negotiate_contract(&ship_clone.symbol)
-            .await?;
+            .await
+        {
+            Ok(contract_resp) => contract_resp,
+            Err(space_traders_client::apis::Error::ResponseError(err))
+                if err.get_error_code() == Some(models::error_codes::EXISTING_CONTRACT_ERROR) =>
+            {
+                // the agent already has the maximum of offered and running contracts, a declined offer blocks until it expires
+                tracing::debug!(ship_symbol = %ship_clone.symbol, "Existing contracts block negotiating a new one");
+                return Ok(None);
+            }
+            Err(err) => {
+                tracing::warn!(ship_symbol = %ship_clone.symbol, error = ?err, "Failed to negotiate contract");
+                return Err(err.into());
+            }
+        };
 
         let contract = *contract_resp.data.contract;
 
-        database::Contract::insert_contract(
-            &self.context.database_pool,
-            contract.clone(),
-            self.reserved_funds.as_ref().map(|r| r.id),
-        )
-        .await?;
+        database::Contract::insert_contract(&self.context.database_pool, contract.clone(), None)
+            .await?;
 
-        let viable = self.is_contract_viable(&contract).await?;
-        self.current_contract = Some(contract);
-        debug!("New contract negotiated: {:?}", self.current_contract);
+        self.contracts.insert(contract.id.clone(), contract.clone());
+        debug!("New contract negotiated: {:?}", contract);
 
-        Ok(viable)
+        Ok(Some(contract))
     }
 
     async fn get_purchase_waypoint(
@@ -2,6 +2,7 @@ use space_traders_client::models;
 
 use crate::error::Result;
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum ContractShipmentMessage {
     RequestNext {
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod contract_manager;
 mod message;
 mod messanger;
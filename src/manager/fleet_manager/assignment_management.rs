@@ -191,6 +191,9 @@ pub async fn generate_fleet_assignments(
         database::FleetConfig::Contract(contract_config) => {
             generate_contract_fleet_assignments(fleet, &contract_config, context).await?
         }
+        database::FleetConfig::Refining(refining_config) => {
+            generate_refining_fleet_assignments(fleet, &refining_config, context).await?
+        }
         database::FleetConfig::Manuel(_) => vec![],
     };
     Ok(ships)
@@ -240,6 +243,7 @@ async fn generate_trading_fleet_assignments(
             extractor: false,
             siphon: false,
             warp_drive: false,
+            refinery: false,
         })
         .collect();
 
@@ -298,6 +302,7 @@ async fn generate_mining_fleet_assignments(
         extractor: true,
         siphon: false,
         warp_drive: false,
+        refinery: false,
     });
 
     let survey_ships = (0..survey_ships_count).map(|_i| ShipAssignment {
@@ -313,6 +318,7 @@ async fn generate_mining_fleet_assignments(
         extractor: false,
         siphon: false,
         warp_drive: false,
+        refinery: false,
     });
 
     let siphon_ships = (0..siphon_ships_count).map(|_i| ShipAssignment {
@@ -328,6 +334,7 @@ async fn generate_mining_fleet_assignments(
         extractor: false,
         siphon: true,
         warp_drive: false,
+        refinery: false,
     });
 
     let transporter_ships = (0..transporter_ships_count).map(|_i| ShipAssignment {
@@ -343,6 +350,7 @@ async fn generate_mining_fleet_assignments(
         extractor: false,
         siphon: false,
         warp_drive: false,
+        refinery: false,
     });
 
     let ships = mining_ships
@@ -392,6 +400,7 @@ async fn generate_scraping_fleet_assignments(
             extractor: false,
             siphon: false,
             warp_drive: false,
+            refinery: false,
         })
         .collect();
 
@@ -436,6 +445,7 @@ async fn generate_charting_fleet_assignments(
             extractor: false,
             siphon: false,
             warp_drive: false,
+            refinery: false,
         })
         .collect();
 
@@ -466,6 +476,7 @@ async fn generate_construction_fleet_assignments(
             extractor: false,
             siphon: false,
             warp_drive: false,
+            refinery: false,
         })
         .collect();
     Ok(ships)
@@ -497,6 +508,36 @@ async fn generate_contract_fleet_assignments(
             extractor: false,
             siphon: false,
             warp_drive: false,
+            refinery: false,
+        })
+        .collect();
+    Ok(ships)
+}
+
+async fn generate_refining_fleet_assignments(
+    fleet: &database::Fleet,
+    refining_config: &database::RefiningFleetConfig,
+    _context: &ConductorContext,
+) -> crate::error::Result<Vec<ShipAssignment>> {
+    let min_range = 300; // only travels between the asteroids and the markets of the system
+
+    let min_cargo = 60; // a refining cycle consumes 30 units of one ore
+
+    let ships = (0..refining_config.refinery_ship_count)
+        .map(|_i| ShipAssignment {
+            id: 0, // This should be set appropriately
+            fleet_id: fleet.id,
+            max_purchase_price: 3_000_000,
+            credits_threshold: 1_000_000,
+            priority: DEFAULT_PRIORITY + 20, // only worth it once the mining fleet is running
+            disabled: false,
+            range_min: min_range,
+            cargo_min: min_cargo,
+            survey: false,
+            extractor: false,
+            siphon: false,
+            warp_drive: false,
+            refinery: true,
         })
         .collect();
     Ok(ships)
@@ -6,7 +6,9 @@ use tracing::{debug, warn};
 use crate::{
     error::Result,
     manager::{
-        fleet_manager::{ship_capabilities::ShipCapabilities, ship_worth::ShipWorth},
+        fleet_manager::{
+            outfitting::OutfitPlanner, ship_capabilities::ShipCapabilities, ship_worth::ShipWorth,
+        },
         Manager,
     },
     utils::ConductorContext,
@@ -123,6 +125,15 @@ impl FleetManager {
                     crate::error::Error::General(format!("Failed to send message: {:?}", e))
                 })?;
             }
+            crate::manager::fleet_manager::message::FleetMessage::GetReplacementPrice {
+                callback,
+                assignment_id,
+            } => {
+                let erg = self.get_replacement_price(assignment_id).await?;
+                callback.send(erg).map_err(|e| {
+                    crate::error::Error::General(format!("Failed to send message: {:?}", e))
+                })?;
+            }
             crate::manager::fleet_manager::message::FleetMessage::ReGenerateAssignments {
                 callback,
             } => {
@@ -482,10 +493,20 @@ impl FleetManager {
 
         // filter assignments based on ship capabilities (e.g. required cargo space, required fuel, required equipment, ...)
 
-        let mut open_possible_assignments = open_assignments
+        let (mut open_possible_assignments, other_assignments): (Vec<_>, Vec<_>) = open_assignments
             .into_iter()
-            .filter(|assignment| ShipCapabilities::can_assign_ship(ship_clone, assignment))
-            .collect::<Vec<_>>();
+            .partition(|assignment| ShipCapabilities::can_assign_ship(ship_clone, assignment));
+
+        // when nothing fits the ship as it is, take an assignment it can be outfitted for at a shipyard instead of idling
+
+        let outfit_ships = { self.context.config.read().await.outfit_ships };
+        if open_possible_assignments.is_empty() && outfit_ships && !temp {
+            let planner = OutfitPlanner::load(&self.context.database_pool, ship_clone).await?;
+            open_possible_assignments = other_assignments
+                .into_iter()
+                .filter(|assignment| planner.plan(ship_clone, assignment).is_some())
+                .collect();
+        }
 
         // get fleets from the database and calculate the distance from the ship_system to the fleet system
 
@@ -567,6 +588,104 @@ impl FleetManager {
         }
     }
 
+    /// the total price of the cheapest ship which could be bought for the assignment, including the travel to the fleet
+    async fn get_replacement_price(&mut self, assignment_id: i64) -> Result<Option<i64>> {
+        let Some(assignment) =
+            database::ShipAssignment::get_by_id(&self.context.database_pool, assignment_id).await?
+        else {
+            return Ok(None);
+        };
+        let Some(fleet) =
+            database::Fleet::get_by_id(&self.context.database_pool, assignment.fleet_id).await?
+        else {
+            return Ok(None);
+        };
+
+        let ship_frames = database::FrameInfo::get_all(
+            &self.context.database_pool,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items
+        .into_iter()
+        .map(|f| (f.symbol, f))
+        .collect::<HashMap<_, _>>();
+
+        let shipyard_ships = database::ShipyardShip::get_last_paginated(
+            &self.context.database_pool,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items;
+
+        let antimatter_price = { self.context.config.read().await.antimatter_price as i64 };
+
+        let jump_gate = self.get_jump_navigator().await?;
+
+        let price = shipyard_ships
+            .iter()
+            .filter(|shipyard_ship| {
+                ship_frames
+                    .get(&shipyard_ship.frame_type)
+                    .map(|frame| {
+                        ShipCapabilities::get_shipyard_ship_capabilities(shipyard_ship, frame)
+                            .capable(&assignment)
+                    })
+                    .unwrap_or(false)
+            })
+            .map(|shipyard_ship| {
+                ShipWorth::new(
+                    &assignment,
+                    shipyard_ship,
+                    &fleet,
+                    jump_gate,
+                    antimatter_price,
+                    0,
+                )
+                .total_price
+            })
+            .min();
+
+        Ok(price)
+    }
+
+    /// What ships earned per hour by ship type and fleet type
+    ///
+    /// Ships bought in the current reset take precedence, the priors of the previous reset fill the gaps until then
+    async fn get_earnings_per_hour(
+        &self,
+    ) -> Result<HashMap<(String, Option<database::FleetType>), f64>> {
+        let (agent_symbol, reset_date) = {
+            let run_info = self.context.run_info.read().await;
+            (run_info.agent_symbol.clone(), run_info.reset_date)
+        };
+
+        let priors =
+            database::ResetPriors::get_latest_before(&self.context.database_pool, reset_date)
+                .await?
+                .map(|priors| priors.ship_types)
+                .unwrap_or_default();
+
+        let current = database::ShipTypePrior::get_by_agent(
+            &self.context.database_pool,
+            &agent_symbol,
+            reset_date,
+            chrono::Utc::now(),
+        )
+        .await?;
+
+        Ok(priors
+            .into_iter()
+            .chain(current)
+            .map(|ship_type| {
+                (
+                    (ship_type.ship_type, ship_type.fleet_type),
+                    ship_type.avg_earned_per_hour,
+                )
+            })
+            .collect())
+    }
+
     async fn get_jump_navigator(
         &mut self,
     ) -> Result<&mut ship::autopilot::jump_gate_nav::JumpPathfinder> {
@@ -712,7 +831,15 @@ impl FleetManager {
 
         let antimatter_price = { self.context.config.read().await.antimatter_price as i64 };
 
-        let percentile = { self.context.config.read().await.ship_purchase_percentile };
+        let (percentile, earnings_horizon) = {
+            let config = self.context.config.read().await;
+            (
+                config.ship_purchase_percentile,
+                config.ship_earnings_horizon_hours as f64,
+            )
+        };
+
+        let earnings_per_hour = self.get_earnings_per_hour().await?;
 
         let jump_gate = self.get_jump_navigator().await?;
 
@@ -724,12 +851,18 @@ impl FleetManager {
                     .filter(|(_shipyard_ship, capability)| capability.capable(assignment))
                     .map(|(shipyard_ship, _)| shipyard_ship)
                     .filter_map(|shipyard_ship| {
+                        let fleet = fleets.get(&assignment.fleet_id)?;
+                        let expected_earnings = earnings_per_hour
+                            .get(&(shipyard_ship.ship_type.to_string(), Some(fleet.fleet_type)))
+                            .map(|per_hour| (per_hour * earnings_horizon) as i64)
+                            .unwrap_or(0);
                         Some(ShipWorth::new(
                             assignment,
                             shipyard_ship,
-                            fleets.get(&assignment.fleet_id)?,
+                            fleet,
                             jump_gate,
                             antimatter_price,
+                            expected_earnings,
                         ))
                     })
                     .filter(|sh| {
@@ -763,6 +896,7 @@ impl FleetManager {
 #[derive(Debug, Clone)]
 struct AssignmentPriceComparison<'a> {
     assignment: &'a database::ShipAssignment,
+    #[allow(dead_code)]
     global_price: BTreeSet<ShipWorth<'a>>,
     purchasable_subset: Vec<ShipWorth<'a>>,
     waypoint_symbol: &'a str,
@@ -1,5 +1,4 @@
-use core::panic;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use database::DatabaseConnectorAsync;
 use space_traders_client::models;
@@ -12,7 +11,7 @@ struct SystemFleets {
     trading_fleet: Option<database::Fleet>, // exists if there are >1 marketplaces, and there are no uncharted marketplace waypoints
     market_balance_fleet: Option<database::Fleet>, // exists if a construction fleet exists
     mining_fleet: Option<database::Fleet>,  // exists if a construction fleet exists
-    construction_fleet: Option<database::Fleet>, // exists if it's the main system and in that system there is an unfinished construction site
+    construction_fleet: Option<database::Fleet>, // exists if in that system there is at least one unfinished construction site
     contract_fleet: Option<database::Fleet>, // exists if it's the main system and there is no other fleet present anywhere
 }
 
@@ -216,23 +215,19 @@ async fn generate_system_fleets(
         .filter(|c| c.required != c.fulfilled)
         .collect::<Vec<_>>();
 
-    let open_construction_waypoints = open_construction_site
-        .iter()
-        .map(|c| c.waypoint_symbol.clone())
-        .collect::<HashSet<_>>();
-
-    if open_construction_waypoints.len() > 1 {
-        log::error!(
-            "System {} has multiple open construction sites: {:?}",
-            system_symbol,
-            open_construction_waypoints
-        );
-        panic!("System has multiple open construction sites");
+    // the site with the most units still missing is the main one, the construction manager serves all of them
+    let mut open_construction_waypoints = HashMap::new();
+    for material in open_construction_site.iter() {
+        *open_construction_waypoints
+            .entry(material.waypoint_symbol.clone())
+            .or_insert(0) += material.required - material.fulfilled;
     }
+    let construction_site_count = open_construction_waypoints.len() as i32;
 
-    let construction_waypoint = open_construction_waypoints.into_iter().next();
-
-    let has_open_construction_site = !open_construction_site.is_empty();
+    let construction_waypoint = open_construction_waypoints
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(waypoint_symbol, _)| waypoint_symbol);
 
     let market_count = waypoints.iter().filter(|w| w.is_marketplace()).count();
 
@@ -272,27 +267,32 @@ async fn generate_system_fleets(
         );
     }
 
-    // construction fleet
-    if is_headquarters_system
-        && has_open_construction_site
-        && let Some(construction_waypoint) = construction_waypoint
-    {
+    // construction fleet, one ship per open site
+    if let Some(construction_waypoint) = construction_waypoint {
         system_fleets.construction_fleet = Some(
             database::Fleet::new(system_symbol.to_string(), true).with_config(
                 database::FleetConfig::Construction(database::ConstructionFleetConfig {
-                    construction_ship_count: 1,
+                    construction_ship_count: construction_site_count,
                     construction_waypoint,
                 }),
             ),
         );
     }
 
+    // the supporting trade and mining fleets are only worth it for the main system
+    let supports_construction =
+        is_headquarters_system && system_fleets.construction_fleet.is_some();
+
     // scrapping fleet
     if market_count > 1 {
+        // on average every market is scraped twice per max_update_interval
+        let max_update_interval = { context.config.read().await.max_update_interval.max(1) };
+        let allowed_requests = (market_count as i32 * 2 * 3600 / max_update_interval).max(1);
+
         system_fleets.scrapping_fleet = Some(
             database::Fleet::new(system_symbol.to_string(), true).with_config(
                 database::FleetConfig::Scraping(database::ScrapingFleetConfig {
-                    allowed_requests: 1, // todo do right calculations
+                    allowed_requests,
                     notify_on_shipyard: true,
                     ship_market_ratio: 1.0,
                 }),
@@ -304,19 +304,15 @@ async fn generate_system_fleets(
     if market_count > 1 && !has_uncharted_marketplace_waypoints {
         let ship_market_ratio = 0.1;
 
-        let trade_mode = if system_fleets.construction_fleet.is_some() {
+        let trade_mode = if supports_construction {
             database::TradeMode::ProfitPerHour
         } else {
             database::TradeMode::ProfitPerTrip
         };
 
-        let trade_profit_threshold = if system_fleets.construction_fleet.is_some() {
-            200
-        } else {
-            2000
-        };
+        let trade_profit_threshold = if supports_construction { 200 } else { 2000 };
 
-        let market_blacklist = if system_fleets.construction_fleet.is_some() {
+        let market_blacklist = if supports_construction {
             let goods = open_construction_site
                 .iter()
                 .map(|f| f.trade_symbol)
@@ -342,17 +338,14 @@ async fn generate_system_fleets(
     }
 
     // market balance fleet
-    if market_count > 1
-        && !has_uncharted_marketplace_waypoints
-        && system_fleets.construction_fleet.is_some()
-    {
+    if market_count > 1 && !has_uncharted_marketplace_waypoints && supports_construction {
         let ship_market_ratio = 0.1;
 
         let trade_mode = database::TradeMode::MarketBalanced;
 
         let trade_profit_threshold = -2000;
 
-        let market_blacklist = if system_fleets.construction_fleet.is_some() {
+        let market_blacklist = if supports_construction {
             let goods = open_construction_site
                 .iter()
                 .map(|f| f.trade_symbol)
@@ -378,7 +371,7 @@ async fn generate_system_fleets(
     }
 
     // mining fleet
-    if system_fleets.construction_fleet.is_some() {
+    if supports_construction {
         let mining_prefer_list = vec![
             // todo calculate based on construction needs
             models::TradeSymbol::SiliconCrystals,
@@ -452,9 +445,12 @@ async fn update_fleet(
         }
         (None, None) => {
             // do nothing
+            return Ok(());
         }
     }
 
+    context.ship_tasks.notify_activation_changed();
+
     Ok(())
 }
 
@@ -1,3 +1,4 @@
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum FleetMessage {
     ScrapperAtShipyard {
@@ -10,6 +11,10 @@ pub enum FleetMessage {
         ship_clone: ship::MyShipCopy,
         temp: bool,
     },
+    GetReplacementPrice {
+        callback: tokio::sync::oneshot::Sender<Option<i64>>,
+        assignment_id: i64,
+    },
     ReGenerateAssignments {
         callback: tokio::sync::oneshot::Sender<()>,
     },
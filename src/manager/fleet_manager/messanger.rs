@@ -91,6 +91,26 @@ impl FleetManagerMessanger {
         Ok(erg)
     }
 
+    /// gets the total price of the cheapest ship which could be bought to take over the assignment
+    #[tracing::instrument(skip(self), name = "FleetManagerMessanger::get_replacement_price")]
+    pub async fn get_replacement_price(
+        &self,
+        assignment_id: i64,
+    ) -> Result<Option<i64>, crate::error::Error> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(FleetManagerMessage::GetReplacementPrice {
+                callback: sender,
+                assignment_id,
+            })
+            .await
+            .map_err(|e| crate::error::Error::General(e.to_string()))?;
+        let erg = receiver
+            .await
+            .map_err(|e| crate::error::Error::General(e.to_string()))?;
+        Ok(erg)
+    }
+
     /// Ask the fleet manager to re-generate assignments for all fleets.
     pub async fn regenerate_all_assignments(&self) -> Result<(), crate::error::Error> {
         let (sender, receiver) = tokio::sync::oneshot::channel();
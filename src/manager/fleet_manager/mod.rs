@@ -1,10 +1,15 @@
 pub mod assignment_management;
+#[allow(clippy::module_inception)]
 mod fleet_manager;
 pub mod fleet_population;
 pub mod message;
 mod messanger;
+mod outfitting;
 mod ship_capabilities;
 mod ship_worth;
 pub use fleet_manager::FleetManager;
 pub use fleet_manager::FleetManagerReceiver;
 pub use messanger::FleetManagerMessanger;
+pub use outfitting::OutfitPlan;
+pub use outfitting::OutfitPlanner;
+pub use outfitting::OutfitStep;
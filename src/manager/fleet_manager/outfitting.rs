@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+
+use database::DatabaseConnectorAsync;
+use space_traders_client::models;
+
+use super::ship_capabilities::ShipCapabilities;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutfitStep {
+    RemoveMount(models::ship_mount::Symbol),
+    RemoveModule(models::ship_module::Symbol),
+    InstallMount(models::ship_mount::Symbol),
+    InstallModule(models::ship_module::Symbol),
+}
+
+impl OutfitStep {
+    /// the trade symbol of the part moved in or out of the cargo
+    pub fn trade_symbol(&self) -> Option<models::TradeSymbol> {
+        // mounts and modules are traded under the same symbol
+        let symbol = match self {
+            OutfitStep::RemoveMount(symbol) | OutfitStep::InstallMount(symbol) => {
+                serde_json::to_value(symbol)
+            }
+            OutfitStep::RemoveModule(symbol) | OutfitStep::InstallModule(symbol) => {
+                serde_json::to_value(symbol)
+            }
+        };
+        symbol
+            .ok()
+            .and_then(|symbol| serde_json::from_value(symbol).ok())
+    }
+
+    pub fn is_install(&self) -> bool {
+        matches!(
+            self,
+            OutfitStep::InstallMount(_) | OutfitStep::InstallModule(_)
+        )
+    }
+}
+
+/// The mounts and modules to remove and install so a ship fulfills the requirements of an assignment.
+/// Removals come first, parts the assignment doesn't need are removed, cargo holds only when their slot is needed for a new part.
+#[derive(Debug, Clone, Default)]
+pub struct OutfitPlan {
+    pub steps: Vec<OutfitStep>,
+}
+
+/// Plans the outfitting of one ship, holds the frame, reactor, engine and part infos needed to check the free slots, power and crew
+pub struct OutfitPlanner {
+    frame: database::FrameInfo,
+    reactor: database::ReactorInfo,
+    engine: database::EngineInfo,
+    mounts: HashMap<models::ship_mount::Symbol, database::MountInfo>,
+    modules: HashMap<models::ship_module::Symbol, database::ModuleInfo>,
+}
+
+impl OutfitPlanner {
+    pub async fn load(
+        database_pool: &database::DbPool,
+        ship_clone: &ship::MyShipCopy,
+    ) -> crate::error::Result<OutfitPlanner> {
+        let frame = database::FrameInfo::get_by_symbol(database_pool, &ship_clone.frame).await?;
+        let reactor =
+            database::ReactorInfo::get_by_symbol(database_pool, &ship_clone.reactor).await?;
+        let engine = database::EngineInfo::get_by_symbol(database_pool, &ship_clone.engine).await?;
+        let mounts =
+            database::MountInfo::get_all(database_pool, database::PaginatedQuery::unpaged())
+                .await?
+                .items
+                .into_iter()
+                .map(|mount| (mount.symbol, mount))
+                .collect();
+        let modules =
+            database::ModuleInfo::get_all(database_pool, database::PaginatedQuery::unpaged())
+                .await?
+                .items
+                .into_iter()
+                .map(|module| (module.symbol, module))
+                .collect();
+
+        Ok(OutfitPlanner::new(frame, reactor, engine, mounts, modules))
+    }
+
+    pub fn new(
+        frame: database::FrameInfo,
+        reactor: database::ReactorInfo,
+        engine: database::EngineInfo,
+        mounts: HashMap<models::ship_mount::Symbol, database::MountInfo>,
+        modules: HashMap<models::ship_module::Symbol, database::ModuleInfo>,
+    ) -> OutfitPlanner {
+        OutfitPlanner {
+            frame,
+            reactor,
+            engine,
+            mounts,
+            modules,
+        }
+    }
+
+    /// whether the planner was loaded for the frame, reactor and engine of the ship
+    pub fn is_for(&self, ship_clone: &ship::MyShipCopy) -> bool {
+        self.frame.symbol == ship_clone.frame
+            && self.reactor.symbol == ship_clone.reactor
+            && self.engine.symbol == ship_clone.engine
+    }
+
+    /// Returns `None` when the assignment can't be fulfilled by swapping parts, e.g. when the frame has not enough fuel capacity
+    pub fn plan(
+        &self,
+        ship_clone: &ship::MyShipCopy,
+        assignment: &database::ShipAssignment,
+    ) -> Option<OutfitPlan> {
+        OutfitPlan::plan(
+            &ship_clone.mounts.mounts,
+            &ship_clone.modules.modules,
+            ship_clone.fuel.capacity,
+            assignment,
+            self,
+        )
+    }
+
+    /// the slots required by a module, modules without info take one slot
+    fn slots_of(&self, module: &models::ship_module::Symbol) -> i32 {
+        self.modules
+            .get(module)
+            .and_then(|module| module.slots_required)
+            .unwrap_or(1)
+    }
+
+    fn power_required(
+        &self,
+        mounts: &[models::ship_mount::Symbol],
+        modules: &[models::ship_module::Symbol],
+    ) -> i32 {
+        self.frame.power_required.unwrap_or(0)
+            + self.engine.power_required.unwrap_or(0)
+            + mounts
+                .iter()
+                .filter_map(|mount| self.mounts.get(mount)?.power_required)
+                .sum::<i32>()
+            + modules
+                .iter()
+                .filter_map(|module| self.modules.get(module)?.power_required)
+                .sum::<i32>()
+    }
+
+    fn crew_required(
+        &self,
+        mounts: &[models::ship_mount::Symbol],
+        modules: &[models::ship_module::Symbol],
+    ) -> i32 {
+        self.frame.crew_required.unwrap_or(0)
+            + self.reactor.crew_required.unwrap_or(0)
+            + self.engine.crew_required.unwrap_or(0)
+            + mounts
+                .iter()
+                .filter_map(|mount| self.mounts.get(mount)?.crew_required)
+                .sum::<i32>()
+            + modules
+                .iter()
+                .filter_map(|module| self.modules.get(module)?.crew_required)
+                .sum::<i32>()
+    }
+
+    fn crew_capacity(&self, modules: &[models::ship_module::Symbol]) -> i32 {
+        modules
+            .iter()
+            .filter(|module| **module == models::ship_module::Symbol::CrewQuartersI)
+            .filter_map(|module| self.modules.get(module)?.capacity)
+            .sum()
+    }
+
+    /// The reactor has to power the new parts and the crew quarters have to house their crew.
+    /// Ships which already exceed a limit by the infos we have may still swap parts as long as the need doesn't grow.
+    fn supports(
+        &self,
+        current_mounts: &[models::ship_mount::Symbol],
+        current_modules: &[models::ship_module::Symbol],
+        mounts: &[models::ship_mount::Symbol],
+        modules: &[models::ship_module::Symbol],
+    ) -> bool {
+        let power = self.power_required(mounts, modules);
+        let power_ok = power <= self.reactor.power_output
+            || power <= self.power_required(current_mounts, current_modules);
+
+        let crew = self.crew_required(mounts, modules);
+        let crew_ok = crew <= self.crew_capacity(modules)
+            || crew <= self.crew_required(current_mounts, current_modules);
+
+        power_ok && crew_ok
+    }
+}
+
+impl OutfitPlan {
+    pub fn plan(
+        current_mounts: &[models::ship_mount::Symbol],
+        current_modules: &[models::ship_module::Symbol],
+        fuel_capacity: i32,
+        assignment: &database::ShipAssignment,
+        planner: &OutfitPlanner,
+    ) -> Option<OutfitPlan> {
+        let slots_of = |module: &models::ship_module::Symbol| planner.slots_of(module);
+
+        let mut mounts = current_mounts.to_vec();
+        let mut modules = current_modules.to_vec();
+
+        let mount_installs = Self::required_mounts(&mounts, assignment);
+        let module_installs = Self::required_modules(&modules, assignment);
+
+        let mut steps = Vec::new();
+
+        // parts of an earlier role
+        mounts.retain(|mount| {
+            let surplus = Self::is_removable_mount(mount, assignment);
+            if surplus {
+                steps.push(OutfitStep::RemoveMount(*mount));
+            }
+            !surplus
+        });
+        modules.retain(|module| {
+            let surplus = Self::is_surplus_module(module, assignment);
+            if surplus {
+                steps.push(OutfitStep::RemoveModule(*module));
+            }
+            !surplus
+        });
+
+        // free mounting points
+        let mut free_points = planner.frame.mounting_points - mounts.len() as i32;
+        while free_points < mount_installs.len() as i32 {
+            let index = mounts
+                .iter()
+                .position(|mount| Self::is_removable_mount(mount, assignment))?;
+            let removed = mounts.remove(index);
+            steps.push(OutfitStep::RemoveMount(removed));
+            free_points += 1;
+        }
+
+        // free module slots
+        let needed_slots = module_installs.iter().map(slots_of).sum::<i32>();
+        let installed_cargo = module_installs
+            .iter()
+            .map(|module| ShipCapabilities::cargo_from_modules(&[*module]))
+            .sum::<i32>();
+        let mut free_slots = planner.frame.module_slots - modules.iter().map(slots_of).sum::<i32>();
+        while free_slots < needed_slots {
+            let cargo = ShipCapabilities::cargo_from_modules(&modules) + installed_cargo;
+            let index = modules
+                .iter()
+                .position(|module| Self::is_removable_module(module, assignment, cargo))?;
+            let removed = modules.remove(index);
+            steps.push(OutfitStep::RemoveModule(removed));
+            free_slots += slots_of(&removed);
+        }
+
+        mounts.extend(mount_installs.iter().copied());
+        modules.extend(module_installs.iter().copied());
+
+        if !planner.supports(current_mounts, current_modules, &mounts, &modules) {
+            return None;
+        }
+
+        steps.extend(mount_installs.into_iter().map(OutfitStep::InstallMount));
+        steps.extend(module_installs.into_iter().map(OutfitStep::InstallModule));
+
+        let capabilities =
+            ShipCapabilities::from_mounts_and_modules(&modules, &mounts, fuel_capacity);
+        if !capabilities.capable(assignment) {
+            return None;
+        }
+
+        Some(OutfitPlan { steps })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// the parts which have to be bought before they can be installed
+    pub fn parts_to_buy(&self) -> Vec<models::TradeSymbol> {
+        self.steps
+            .iter()
+            .filter(|step| step.is_install())
+            .filter_map(|step| step.trade_symbol())
+            .collect()
+    }
+
+    fn required_mounts(
+        mounts: &[models::ship_mount::Symbol],
+        assignment: &database::ShipAssignment,
+    ) -> Vec<models::ship_mount::Symbol> {
+        let has = |candidates: &[models::ship_mount::Symbol]| {
+            mounts.iter().any(|mount| candidates.contains(mount))
+        };
+        let mut required = Vec::new();
+
+        if assignment.extractor && !has(&MINING_LASERS) {
+            required.push(models::ship_mount::Symbol::MiningLaserI);
+        }
+        if assignment.siphon && !has(&GAS_SIPHONS) {
+            required.push(models::ship_mount::Symbol::GasSiphonI);
+        }
+        if assignment.survey && !has(&SURVEYORS) {
+            required.push(models::ship_mount::Symbol::SurveyorI);
+        }
+
+        required
+    }
+
+    fn required_modules(
+        modules: &[models::ship_module::Symbol],
+        assignment: &database::ShipAssignment,
+    ) -> Vec<models::ship_module::Symbol> {
+        let mut required = Vec::new();
+
+        if assignment.extractor
+            && !modules.contains(&models::ship_module::Symbol::MineralProcessorI)
+        {
+            required.push(models::ship_module::Symbol::MineralProcessorI);
+        }
+        if assignment.siphon && !modules.contains(&models::ship_module::Symbol::GasProcessorI) {
+            required.push(models::ship_module::Symbol::GasProcessorI);
+        }
+        if assignment.refinery && !modules.contains(&models::ship_module::Symbol::OreRefineryI) {
+            required.push(models::ship_module::Symbol::OreRefineryI);
+        }
+        if assignment.warp_drive && !modules.iter().any(|module| WARP_DRIVES.contains(module)) {
+            required.push(models::ship_module::Symbol::WarpDriveI);
+        }
+
+        let mut missing_cargo =
+            assignment.cargo_min - ShipCapabilities::cargo_from_modules(modules);
+        while missing_cargo > 0 {
+            let hold = if missing_cargo > 15 {
+                models::ship_module::Symbol::CargoHoldIi
+            } else {
+                models::ship_module::Symbol::CargoHoldI
+            };
+            missing_cargo -= ShipCapabilities::cargo_from_modules(&[hold]);
+            required.push(hold);
+        }
+
+        required
+    }
+
+    fn is_removable_mount(
+        mount: &models::ship_mount::Symbol,
+        assignment: &database::ShipAssignment,
+    ) -> bool {
+        if MINING_LASERS.contains(mount) {
+            !assignment.extractor
+        } else if GAS_SIPHONS.contains(mount) {
+            !assignment.siphon
+        } else if SURVEYORS.contains(mount) {
+            !assignment.survey
+        } else {
+            true
+        }
+    }
+
+    /// cargo holds above the minimum are only removed for a new part, more cargo space still helps
+    fn is_removable_module(
+        module: &models::ship_module::Symbol,
+        assignment: &database::ShipAssignment,
+        cargo: i32,
+    ) -> bool {
+        match module {
+            models::ship_module::Symbol::CargoHoldI
+            | models::ship_module::Symbol::CargoHoldIi
+            | models::ship_module::Symbol::CargoHoldIii => {
+                cargo - ShipCapabilities::cargo_from_modules(&[*module]) >= assignment.cargo_min
+            }
+            _ => Self::is_surplus_module(module, assignment),
+        }
+    }
+
+    /// modules the assignment doesn't need, crew quarters, reactors and other support modules are never removed, the ship might not work without them
+    fn is_surplus_module(
+        module: &models::ship_module::Symbol,
+        assignment: &database::ShipAssignment,
+    ) -> bool {
+        match module {
+            models::ship_module::Symbol::MineralProcessorI => !assignment.extractor,
+            models::ship_module::Symbol::GasProcessorI => !assignment.siphon,
+            models::ship_module::Symbol::OreRefineryI => !assignment.refinery,
+            module if WARP_DRIVES.contains(module) => !assignment.warp_drive,
+            _ => false,
+        }
+    }
+}
+
+const MINING_LASERS: [models::ship_mount::Symbol; 3] = [
+    models::ship_mount::Symbol::MiningLaserI,
+    models::ship_mount::Symbol::MiningLaserIi,
+    models::ship_mount::Symbol::MiningLaserIii,
+];
+
+const GAS_SIPHONS: [models::ship_mount::Symbol; 3] = [
+    models::ship_mount::Symbol::GasSiphonI,
+    models::ship_mount::Symbol::GasSiphonIi,
+    models::ship_mount::Symbol::GasSiphonIii,
+];
+
+const SURVEYORS: [models::ship_mount::Symbol; 3] = [
+    models::ship_mount::Symbol::SurveyorI,
+    models::ship_mount::Symbol::SurveyorIi,
+    models::ship_mount::Symbol::SurveyorIii,
+];
+
+const WARP_DRIVES: [models::ship_module::Symbol; 3] = [
+    models::ship_module::Symbol::WarpDriveI,
+    models::ship_module::Symbol::WarpDriveIi,
+    models::ship_module::Symbol::WarpDriveIii,
+];
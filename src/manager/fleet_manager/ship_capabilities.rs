@@ -6,10 +6,12 @@ pub struct ShipCapabilities {
     cargo: i32,
     fuel: i32, // if it's 0 that means infinite range
     survey: i32,
+    #[allow(dead_code)]
     sensor: i32,
     extractor: i32,
     siphon: i32,
     warp_drive: i32,
+    refinery: i32,
 }
 
 impl ShipCapabilities {
@@ -34,6 +36,7 @@ impl ShipCapabilities {
         let extractor = Self::extractor_from_mounts_and_modules(modules, mounts);
         let siphon = Self::siphon_from_mounts_and_modules(modules, mounts);
         let warp_drive = Self::warp_drive_from_modules(modules);
+        let refinery = Self::refinery_from_modules(modules);
         let sensor = Self::sensor_from_mounts(mounts);
 
         ShipCapabilities {
@@ -43,6 +46,7 @@ impl ShipCapabilities {
             extractor,
             siphon,
             warp_drive,
+            refinery,
             sensor,
         }
     }
@@ -60,22 +64,29 @@ impl ShipCapabilities {
             && self.extractor >= if assignment.extractor { 1 } else { 0 }
             && self.siphon >= if assignment.siphon { 1 } else { 0 }
             && self.warp_drive >= if assignment.warp_drive { 1 } else { 0 }
+            && self.refinery >= if assignment.refinery { 1 } else { 0 }
     }
 
     fn get_ship_capabilities(ship_clone: &ship::MyShipCopy) -> ShipCapabilities {
-        let cargo = Self::cargo_from_modules(&ship_clone.modules.modules);
-        let fuel = ship_clone.fuel.capacity;
-        let survey = Self::survey_from_mounts(&ship_clone.mounts.mounts);
-        let extractor = Self::extractor_from_mounts_and_modules(
+        Self::from_mounts_and_modules(
             &ship_clone.modules.modules,
             &ship_clone.mounts.mounts,
-        );
-        let siphon = Self::siphon_from_mounts_and_modules(
-            &ship_clone.modules.modules,
-            &ship_clone.mounts.mounts,
-        );
-        let warp_drive = Self::warp_drive_from_modules(&ship_clone.modules.modules);
-        let sensor = Self::sensor_from_mounts(&ship_clone.mounts.mounts);
+            ship_clone.fuel.capacity,
+        )
+    }
+
+    pub fn from_mounts_and_modules(
+        modules: &[models::ship_module::Symbol],
+        mounts: &[models::ship_mount::Symbol],
+        fuel: i32,
+    ) -> ShipCapabilities {
+        let cargo = Self::cargo_from_modules(modules);
+        let survey = Self::survey_from_mounts(mounts);
+        let extractor = Self::extractor_from_mounts_and_modules(modules, mounts);
+        let siphon = Self::siphon_from_mounts_and_modules(modules, mounts);
+        let warp_drive = Self::warp_drive_from_modules(modules);
+        let refinery = Self::refinery_from_modules(modules);
+        let sensor = Self::sensor_from_mounts(mounts);
 
         ShipCapabilities {
             cargo,
@@ -84,11 +95,12 @@ impl ShipCapabilities {
             extractor,
             siphon,
             warp_drive,
+            refinery,
             sensor,
         }
     }
 
-    fn cargo_from_modules(modules: &[models::ship_module::Symbol]) -> i32 {
+    pub fn cargo_from_modules(modules: &[models::ship_module::Symbol]) -> i32 {
         modules
             .iter()
             .map(|f| match f {
@@ -188,4 +200,11 @@ impl ShipCapabilities {
             .max()
             .unwrap_or(0)
     }
+
+    fn refinery_from_modules(modules: &[models::ship_module::Symbol]) -> i32 {
+        modules
+            .iter()
+            .filter(|f| **f == models::ship_module::Symbol::OreRefineryI)
+            .count() as i32
+    }
 }
@@ -8,6 +8,8 @@ pub struct ShipWorth<'a> {
     pub total_jumps: i32,
     pub total_distance: f64,
     pub total_price: i64,
+    /// what a ship of this type earned in this kind of fleet over the configured horizon
+    pub expected_earnings: i64,
 }
 
 impl ShipWorth<'_> {
@@ -17,6 +19,7 @@ impl ShipWorth<'_> {
         fleet: &'a database::Fleet,
         jump_gate: &mut ship::autopilot::jump_gate_nav::JumpPathfinder,
         antimatter_cost: i64,
+        expected_earnings: i64,
     ) -> ShipWorth<'a> {
         let shipyard_system = get_system_symbol(&shipyard_ship.waypoint_symbol);
         let route = jump_gate.find_cached_route(&shipyard_system, &fleet.system_symbol);
@@ -32,8 +35,14 @@ impl ShipWorth<'_> {
             total_jumps,
             total_distance,
             total_price,
+            expected_earnings,
         }
     }
+
+    /// the price left after the expected earnings, lower is better
+    pub fn net_price(&self) -> i64 {
+        self.total_price - self.expected_earnings
+    }
 }
 
 impl Eq for ShipWorth<'_> {}
@@ -43,6 +52,7 @@ impl Ord for ShipWorth<'_> {
         self.assignment
             .priority
             .cmp(&other.assignment.priority)
+            .then_with(|| self.net_price().cmp(&other.net_price()))
             .then_with(|| self.total_price.cmp(&other.total_price))
     }
 }
@@ -1,10 +1,11 @@
 use crate::{
     control_api,
     manager::{
-        self, chart_manager::ChartManager, construction_manager::ConstructionManager,
-        contract_manager::ContractManager, fleet_manager::FleetManager,
-        mining_manager::MiningManager, scrapping_manager::ScrappingManager,
-        ship_task::ShipTaskHandler, trade_manager::TradeManager,
+        self, chart_manager::ChartManager, config_manager::ConfigManager,
+        construction_manager::ConstructionManager, contract_manager::ContractManager,
+        fleet_manager::FleetManager, mining_manager::MiningManager,
+        scrapping_manager::ScrappingManager, ship_task::ShipTaskHandler,
+        trade_manager::TradeManager,
     },
 };
 
@@ -17,6 +18,7 @@ pub struct ManagerManager {
     fleet_manager: FleetManager,
     chart_manager: ChartManager,
     ship_task_handler: ShipTaskHandler,
+    config_manager: ConfigManager,
     control_api: control_api::server::ControlApiServer,
 }
 
@@ -31,6 +33,7 @@ impl ManagerManager {
         fleet_manager: FleetManager,
         chart_manager: ChartManager,
         ship_task_handler: ShipTaskHandler,
+        config_manager: ConfigManager,
         control_api: control_api::server::ControlApiServer,
     ) -> Self {
         ManagerManager {
@@ -42,6 +45,7 @@ impl ManagerManager {
             fleet_manager,
             chart_manager,
             ship_task_handler,
+            config_manager,
             control_api,
         }
     }
@@ -56,6 +60,7 @@ impl ManagerManager {
             fleet_manager: ManagersHandle::init(self.fleet_manager),
             chart_manager: ManagersHandle::init(self.chart_manager),
             ship_task_handler: ManagersHandle::init(self.ship_task_handler),
+            config_manager: ManagersHandle::init(self.config_manager),
             control_api: ManagersHandle::init(self.control_api),
         }
     }
@@ -128,6 +133,7 @@ pub struct ManagerHandels {
     fleet_manager: ManagersHandle<FleetManager>,
     chart_manager: ManagersHandle<ChartManager>,
     ship_task_handler: ManagersHandle<ShipTaskHandler>,
+    config_manager: ManagersHandle<ConfigManager>,
     control_api: ManagersHandle<control_api::server::ControlApiServer>,
 }
 
@@ -154,6 +160,8 @@ impl ManagerHandels {
                 .wait(global_cancel_token, run_cancel_token),
             self.ship_task_handler
                 .wait(global_cancel_token, run_cancel_token),
+            self.config_manager
+                .wait(global_cancel_token, run_cancel_token),
             self.control_api.wait(global_cancel_token, run_cancel_token),
         );
 
@@ -166,6 +174,7 @@ impl ManagerHandels {
             Some(fleet_manager),
             Some(chart_manager),
             Some(ship_task_handler),
+            Some(config_manager),
             Some(control_api),
         ) = erg
         {
@@ -178,6 +187,7 @@ impl ManagerHandels {
                 fleet_manager.0,
                 chart_manager.0,
                 ship_task_handler.0,
+                config_manager.0,
                 control_api.0,
             ))
         } else {
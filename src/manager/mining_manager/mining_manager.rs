@@ -29,6 +29,8 @@ pub struct MiningManager {
     transfer_manager: Arc<TransferManager>,
     inventory_manager: ShipInventoryManager,
     waypoint_manager: WaypointManager,
+    /// transfers which were interrupted by a restart before the cargo moved, run again once both ships are back
+    resumed_transfers: Vec<database::MiningTransfer>,
 }
 
 impl MiningManager {
@@ -64,6 +66,7 @@ impl MiningManager {
             transfer_manager,
             inventory_manager: ShipInventoryManager::new(),
             waypoint_manager: WaypointManager::new(context.clone(), max_miners_per_waypoint),
+            resumed_transfers: Vec::new(),
             context,
         }
     }
@@ -76,6 +79,14 @@ impl MiningManager {
     )]
     async fn run_mining_worker(&mut self) -> Result<()> {
         tracing::debug!("Starting MiningManager worker");
+
+        if let Err(err) = self.waypoint_manager.restore().await {
+            tracing::error!(error = ?err, "Failed to restore the mining assignments");
+        }
+        if let Err(err) = self.recover_transfers().await {
+            tracing::error!(error = ?err, "Failed to recover the pending transfers");
+        }
+
         let fast_cancel_token = self.fast_cancel_token.clone();
         tokio::select! {
             _ = fast_cancel_token.cancelled() => {
@@ -89,6 +100,113 @@ impl MiningManager {
         Ok(())
     }
 
+    /// Settles the transfers which were running when the bot stopped, by the cargo the ships have now
+    ///
+    /// Transfers which did not happen are handed back to the ships, they are run again
+    /// once the extractor and the transporter are at the waypoint. When the extractor moved on
+    /// the cargo stays in its hold and the transfer is rolled back.
+    async fn recover_transfers(&mut self) -> Result<()> {
+        let pending = database::MiningTransfer::get_pending(&self.context.database_pool).await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let ships = self.context.ship_manager.get_all_clone().await;
+        for transfer in pending {
+            let status = transfer.outcome(
+                ships
+                    .get(&transfer.from_ship)
+                    .map(|ship| ship.cargo.get_amount(&transfer.trade_symbol)),
+                ships
+                    .get(&transfer.to_ship)
+                    .map(|ship| ship.cargo.get_amount(&transfer.trade_symbol)),
+            );
+            let status = match status {
+                database::MiningTransferStatus::RolledBack
+                    if self
+                        .waypoint_manager
+                        .is_assigned(&transfer.from_ship, &transfer.waypoint_symbol) =>
+                {
+                    tracing::info!(transfer_id = transfer.id, from_ship = %transfer.from_ship, to_ship = %transfer.to_ship, "Resuming pending transfer");
+                    self.resumed_transfers.push(transfer);
+                    continue;
+                }
+                status => status,
+            };
+            tracing::info!(transfer_id = transfer.id, from_ship = %transfer.from_ship, to_ship = %transfer.to_ship, status = ?status, "Recovered pending transfer");
+            database::MiningTransfer::update_status(
+                &self.context.database_pool,
+                transfer.id,
+                status,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs the resumed transfers of the waypoint whose ships are both waiting there again
+    async fn resume_transfers(&mut self, waypoint_symbol: &str) -> Result<()> {
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.resumed_transfers)
+            .into_iter()
+            .partition(|transfer| {
+                transfer.waypoint_symbol == waypoint_symbol
+                    && self
+                        .transfer_manager
+                        .viable(&transfer.from_ship, &transfer.to_ship)
+            });
+        self.resumed_transfers = waiting;
+
+        for transfer in ready {
+            let from_units = self
+                .context
+                .ship_manager
+                .get_clone(&transfer.from_ship)
+                .map(|ship| ship.cargo.get_amount(&transfer.trade_symbol))
+                .unwrap_or_default();
+            let to_space = self
+                .context
+                .ship_manager
+                .get_clone(&transfer.to_ship)
+                .map(|ship| ship.cargo.capacity - ship.cargo.units)
+                .unwrap_or_default();
+            let units = transfer.units.min(from_units).min(to_space);
+
+            let status = if units <= 0 {
+                database::MiningTransferStatus::RolledBack
+            } else {
+                match self
+                    .transfer_manager
+                    .process_transfer(
+                        &transfer.from_ship,
+                        &transfer.to_ship,
+                        transfer.trade_symbol,
+                        units,
+                    )
+                    .await
+                {
+                    Ok(_) => database::MiningTransferStatus::Done,
+                    Err(_) => {
+                        tracing::warn!(
+                            transfer_id = transfer.id,
+                            "Resumed transfer failed, a ship dropped out"
+                        );
+                        database::MiningTransferStatus::RolledBack
+                    }
+                }
+            };
+            tracing::info!(transfer_id = transfer.id, units, status = ?status, "Resumed transfer");
+            database::MiningTransfer::update_status(
+                &self.context.database_pool,
+                transfer.id,
+                status,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
     async fn run_mining_worker_loop(&mut self) -> Result<()> {
         while !self.slow_cancel_token.is_cancelled() {
             let message: Option<MiningMessage> = tokio::select! {
@@ -240,6 +358,8 @@ impl MiningManager {
             "Processing possible transfers at waypoint: {:?}",
             waypoint_symbol
         );
+        self.resume_transfers(waypoint_symbol).await?;
+
         let mut current_trade_symbol = None;
 
         let mut count = 10;
@@ -331,7 +451,12 @@ impl MiningManager {
             .into_iter()
             .filter(|f| f.1.nav.waypoint_symbol == waypoint_symbol)
             .filter(|f| !f.1.nav.is_in_transit())
-            .filter(|f| matches!(&f.1.status.status, ship::AssignmentStatus::Mining { .. }))
+            .filter(|f| {
+                matches!(
+                    &f.1.status.status,
+                    ship::AssignmentStatus::Mining { .. } | ship::AssignmentStatus::Refining { .. }
+                )
+            })
             .map(|f| f.1)
             .collect())
     }
@@ -410,6 +535,28 @@ impl MiningManager {
                 transporter_ship_symbol = transporter.ship_symbol,
                 "Processing transfer",
             );
+            let to_units_before = transport_ships
+                .iter()
+                .find(|ship| ship.symbol == transporter.ship_symbol)
+                .map(|ship| ship.cargo.get_amount(trade_symbol))
+                .unwrap_or_default();
+            let mut transfer = database::MiningTransfer::new(
+                extraction_ships
+                    .iter()
+                    .find(|ship| ship.symbol == extractor.ship_symbol)
+                    .map(|ship| ship.nav.waypoint_symbol.clone())
+                    .unwrap_or_default(),
+                extractor.ship_symbol.clone(),
+                transporter.ship_symbol.clone(),
+                *trade_symbol,
+                transfer_amount,
+                extractor.amount,
+                to_units_before,
+            );
+            transfer.id =
+                database::MiningTransfer::insert_new(&self.context.database_pool, &transfer)
+                    .await?;
+
             let erg = self
                 .transfer_manager
                 .process_transfer(
@@ -420,6 +567,23 @@ impl MiningManager {
                 )
                 .await;
 
+            let status = transfer.outcome(
+                self.context
+                    .ship_manager
+                    .get_clone(&transfer.from_ship)
+                    .map(|ship| ship.cargo.get_amount(trade_symbol)),
+                self.context
+                    .ship_manager
+                    .get_clone(&transfer.to_ship)
+                    .map(|ship| ship.cargo.get_amount(trade_symbol)),
+            );
+            database::MiningTransfer::update_status(
+                &self.context.database_pool,
+                transfer.id,
+                status,
+            )
+            .await?;
+
             match erg {
                 Ok(_) => {}
                 Err(err) => match err {
@@ -82,6 +82,7 @@ impl std::fmt::Display for AssignWaypointMessage {
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum ExtractionNotification {
     GetNextWaypoint {
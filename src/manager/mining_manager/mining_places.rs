@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct WaypointInfo {
@@ -8,6 +8,7 @@ pub struct WaypointInfo {
 }
 
 impl WaypointInfo {
+    #[allow(dead_code)]
     pub fn get_ship_level(&self, ship_symbol: &str) -> Option<AssignLevel> {
         self.assigned_ships.get(ship_symbol).cloned()
     }
@@ -16,6 +17,7 @@ impl WaypointInfo {
         self.assigned_ships.len()
     }
 
+    #[allow(dead_code)]
     pub fn get_count_on_way(&self) -> usize {
         self.assigned_ships
             .iter()
@@ -23,6 +25,7 @@ impl WaypointInfo {
             .count()
     }
 
+    #[allow(dead_code)]
     pub fn get_count_active(&self) -> usize {
         self.assigned_ships
             .iter()
@@ -30,6 +33,7 @@ impl WaypointInfo {
             .count()
     }
 
+    #[allow(dead_code)]
     pub fn get_count_inactive(&self) -> usize {
         self.assigned_ships
             .iter()
@@ -69,6 +73,26 @@ pub enum AssignLevel {
     Active,
 }
 
+impl From<AssignLevel> for database::MiningAssignLevel {
+    fn from(level: AssignLevel) -> Self {
+        match level {
+            AssignLevel::Inactive => database::MiningAssignLevel::Inactive,
+            AssignLevel::OnTheWay => database::MiningAssignLevel::OnTheWay,
+            AssignLevel::Active => database::MiningAssignLevel::Active,
+        }
+    }
+}
+
+impl From<database::MiningAssignLevel> for AssignLevel {
+    fn from(level: database::MiningAssignLevel) -> Self {
+        match level {
+            database::MiningAssignLevel::Inactive => AssignLevel::Inactive,
+            database::MiningAssignLevel::OnTheWay => AssignLevel::OnTheWay,
+            database::MiningAssignLevel::Active => AssignLevel::Active,
+        }
+    }
+}
+
 impl MiningPlaces {
     pub fn new(max_miners_per_waypoint: u32) -> MiningPlaces {
         MiningPlaces {
@@ -203,6 +227,52 @@ impl MiningPlaces {
         }
     }
 
+    /// Puts back an assignment from before a restart, ignoring the capacity of the waypoint
+    pub fn restore(&mut self, ship_symbol: &str, waypoint: &str, level: AssignLevel) {
+        self.mining_places
+            .entry(waypoint.to_string())
+            .or_insert_with(|| WaypointInfo {
+                waypoint_symbol: waypoint.to_string(),
+                assigned_ships: HashMap::new(),
+                last_updated: chrono::DateTime::<chrono::Utc>::MIN_UTC, //never been updated
+            })
+            .assigned_ships
+            .insert(ship_symbol.to_string(), level);
+    }
+
+    /// Puts back the saved assignments of the ships which are still in a mining fleet
+    ///
+    /// `positions` holds the waypoint of every ship and whether it is in transit there,
+    /// an assignment is only kept when the ship is at its waypoint or on its way, ships in transit count as on the way.
+    ///
+    /// Returns the assignments which were dropped.
+    pub fn restore_all(
+        &mut self,
+        assignments: Vec<database::MiningAssignment>,
+        mining_ships: &HashSet<String>,
+        positions: &HashMap<String, (String, bool)>,
+    ) -> Vec<database::MiningAssignment> {
+        let mut dropped = Vec::new();
+        for assignment in assignments {
+            let level = positions
+                .get(&assignment.ship_symbol)
+                .filter(|_| mining_ships.contains(&assignment.ship_symbol))
+                .filter(|(waypoint_symbol, _)| *waypoint_symbol == assignment.waypoint_symbol)
+                .map(|(_, in_transit)| match in_transit {
+                    true => AssignLevel::OnTheWay,
+                    false => assignment.level.into(),
+                });
+
+            match level {
+                Some(level) => {
+                    self.restore(&assignment.ship_symbol, &assignment.waypoint_symbol, level)
+                }
+                None => dropped.push(assignment),
+            }
+        }
+        dropped
+    }
+
     // fn get_all(&self) -> HashMap<String, WaypointInfo> {
     //     self.mining_places.clone()
     // }
@@ -1,7 +1,8 @@
+#[allow(clippy::module_inception)]
 mod mining_manager;
 mod mining_manager_messanger;
 mod mining_messages;
-mod mining_places;
+pub mod mining_places;
 mod place_finder;
 mod ship_inventory_manager;
 mod transfer_manager;
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use space_traders_client::models;
 use utils::WaypointCan;
 
 use crate::utils::ConductorContext;
@@ -28,9 +31,37 @@ impl ActionType {
     }
 }
 
+/// how many of the latest extractions of a waypoint are compared to all of them to notice depletion
+const RECENT_EXTRACTIONS: i64 = 20;
+/// a waypoint whose recent extractions are worth less than this share of all its extractions is depleting
+const DEPLETION_RATIO: f64 = 0.6;
+/// the value of an unstable waypoint is multiplied by this until `unstable_since_timeout` passed
+const UNSTABLE_FACTOR: f64 = 0.5;
+/// credits a unit of distance between the waypoint and the nearest market costs per extraction
+const DISTANCE_COST: f64 = 2.0;
+
+/// What the extractions at a waypoint are worth at the current sell prices in the system
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WaypointValue {
+    pub extractions: i64,
+    pub value_per_extraction: f64,
+    /// only counting the latest extractions
+    pub recent_value_per_extraction: f64,
+}
+
+impl WaypointValue {
+    pub fn is_depleting(&self) -> bool {
+        self.extractions >= RECENT_EXTRACTIONS * 2
+            && self.recent_value_per_extraction < self.value_per_extraction * DEPLETION_RATIO
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct FoundWaypointInfo {
     pub waypoint: database::Waypoint,
     pub distance: i32,
+    /// the expected value per extraction minus the transport to the nearest market
+    pub score: f64,
     #[allow(dead_code)]
     pub next: String,
 }
@@ -45,12 +76,29 @@ impl PlaceFinder {
         Self { context }
     }
 
-    pub async fn find(
-        &self,
-        ship_clone: ship::MyShipCopy,
-        filter_fn: impl Fn(&database::Waypoint) -> bool,
+    /// The ranked waypoints with room for more miners, the most valuable first
+    pub fn find(
+        ranked: &[FoundWaypointInfo],
         mining_places: &MiningPlaces,
         max_miners: usize,
+    ) -> Vec<FoundWaypointInfo> {
+        ranked
+            .iter()
+            .filter(|wp| {
+                let count = mining_places.get_count(&wp.waypoint.symbol);
+                count < max_miners
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    }
+
+    /// All waypoints passing the filter, the most valuable first
+    pub async fn rank(
+        &self,
+        ship_clone: &ship::MyShipCopy,
+        filter_fn: impl Fn(&database::Waypoint) -> bool,
+        values: &HashMap<String, WaypointValue>,
+        unstable_since_timeout: i64,
     ) -> Result<Vec<FoundWaypointInfo>, crate::error::Error> {
         let sql_waypoints = database::Waypoint::get_by_system(
             &self.context.database_pool,
@@ -59,23 +107,69 @@ impl PlaceFinder {
         )
         .await?
         .items;
-        let waypoints: Vec<FoundWaypointInfo> = self.get_best_waypoints(&sql_waypoints, filter_fn);
+        Ok(self.get_best_waypoints(&sql_waypoints, filter_fn, values, unstable_since_timeout))
+    }
 
-        let possible_waypoints: Vec<FoundWaypointInfo> = waypoints
-            .into_iter()
-            .filter(|wp| {
-                let count = mining_places.get_count(&wp.waypoint.symbol);
-                count < max_miners
-            })
-            .collect::<Vec<_>>();
+    /// The value of the extractions of every waypoint in the system
+    pub async fn get_values(
+        &self,
+        system_symbol: &str,
+    ) -> Result<HashMap<String, WaypointValue>, crate::error::Error> {
+        let mut sell_prices: HashMap<models::TradeSymbol, i32> = HashMap::new();
+        for good in database::MarketTradeGood::get_last_by_system(
+            &self.context.database_pool,
+            system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items
+        {
+            let price = sell_prices.entry(good.symbol).or_default();
+            *price = (*price).max(good.sell_price);
+        }
 
-        Ok(possible_waypoints)
+        let mut values: HashMap<String, (i64, i64, f64, f64)> = HashMap::new();
+        for yielded in database::ExtractionYield::get_by_system(
+            &self.context.database_pool,
+            system_symbol,
+            RECENT_EXTRACTIONS,
+        )
+        .await?
+        {
+            let price = sell_prices
+                .get(&yielded.yield_symbol)
+                .copied()
+                .unwrap_or_default() as f64;
+            let entry = values.entry(yielded.waypoint_symbol).or_default();
+            entry.0 += yielded.extractions;
+            entry.1 += yielded.recent_extractions;
+            entry.2 += yielded.units as f64 * price;
+            entry.3 += yielded.recent_units as f64 * price;
+        }
+
+        Ok(values
+            .into_iter()
+            .map(
+                |(waypoint_symbol, (extractions, recent, value, recent_value))| {
+                    (
+                        waypoint_symbol,
+                        WaypointValue {
+                            extractions,
+                            value_per_extraction: value / extractions.max(1) as f64,
+                            recent_value_per_extraction: recent_value / recent.max(1) as f64,
+                        },
+                    )
+                },
+            )
+            .collect())
     }
 
     fn get_best_waypoints(
         &self,
         system_waypoints: &[database::Waypoint],
         filter: impl Fn(&database::Waypoint) -> bool,
+        values: &HashMap<String, WaypointValue>,
+        unstable_since_timeout: i64,
     ) -> Vec<FoundWaypointInfo> {
         let points = system_waypoints
             .iter()
@@ -87,6 +181,16 @@ impl PlaceFinder {
             .filter(|w| w.is_marketplace())
             .collect::<Vec<_>>();
 
+        // waypoints nobody extracted at yet are expected to be as good as the others of their type
+        let mut type_values: HashMap<models::WaypointType, (f64, usize)> = HashMap::new();
+        for wp in system_waypoints.iter() {
+            if let Some(value) = values.get(&wp.symbol) {
+                let entry = type_values.entry(wp.waypoint_type).or_default();
+                entry.0 += value.recent_value_per_extraction;
+                entry.1 += 1;
+            }
+        }
+
         let mut d_points: Vec<FoundWaypointInfo> = points
             .iter()
             .map(|wp| {
@@ -100,15 +204,39 @@ impl PlaceFinder {
 
                 let dis = dis.unwrap();
 
+                let value = values
+                    .get(&wp.symbol)
+                    .map(|value| value.recent_value_per_extraction)
+                    .or_else(|| {
+                        type_values
+                            .get(&wp.waypoint_type)
+                            .map(|(sum, count)| sum / *count as f64)
+                    })
+                    .unwrap_or_default();
+
+                let unstable = wp.unstable_since.is_some_and(|since| {
+                    since + chrono::Duration::seconds(unstable_since_timeout) > chrono::Utc::now()
+                });
+                let value = if unstable {
+                    value * UNSTABLE_FACTOR
+                } else {
+                    value
+                };
+
                 FoundWaypointInfo {
                     waypoint: (*wp).clone(),
                     distance: dis.1,
+                    score: value - (dis.1 as f64).sqrt() * DISTANCE_COST,
                     next: dis.0.clone(),
                 }
             })
             .collect::<Vec<_>>();
 
-        d_points.sort_by_key(|a| a.distance);
+        d_points.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.distance.cmp(&b.distance))
+        });
 
         d_points
     }
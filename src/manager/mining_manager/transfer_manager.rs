@@ -15,6 +15,7 @@ pub struct TransportTransferRequest {
 
 #[derive(Debug)]
 pub struct ExtractorTransferRequest {
+    #[allow(dead_code)]
     pub from_symbol: String,
     pub to_symbol: String,
     pub amount: i32,
@@ -1,4 +1,5 @@
-use database::DatabaseConnectorAsync;
+use std::collections::HashMap;
+
 use space_traders_client::models;
 use utils::WaypointCan;
 
@@ -12,11 +13,24 @@ use super::{
 
 use crate::error::Result;
 
+/// seconds the measured values of the waypoints of a system are reused
+const WAYPOINT_VALUES_TTL: i64 = 10 * 60;
+/// another waypoint has to score this many times the current one before a miner is moved
+const SWITCH_RATIO: f64 = 1.5;
+
 #[derive(Debug)]
 pub struct WaypointManager {
     places: MiningPlaces,
     finder: PlaceFinder,
     context: ConductorContext,
+    /// the waypoint values of every system and when they were loaded
+    values: HashMap<
+        String,
+        (
+            chrono::DateTime<chrono::Utc>,
+            HashMap<String, place_finder::WaypointValue>,
+        ),
+    >,
 }
 
 impl WaypointManager {
@@ -25,6 +39,7 @@ impl WaypointManager {
             places: MiningPlaces::new(max_miners),
             finder: PlaceFinder::new(context.clone()),
             context,
+            values: HashMap::new(),
         }
     }
 
@@ -44,7 +59,9 @@ impl WaypointManager {
             ActionType::Extract
         };
 
-        self.assign_waypoint(&ship_clone, action).await
+        let erg = self.assign_waypoint(&ship_clone, action).await;
+        self.persist(&ship_clone.symbol).await;
+        erg
     }
     pub async fn assign_waypoint(
         &mut self,
@@ -60,26 +77,35 @@ impl WaypointManager {
             )
         };
 
+        let usable = |waypoint: &database::Waypoint| match action {
+            ActionType::Extract => {
+                waypoint.is_minable()
+                    && (waypoint.waypoint_type != models::WaypointType::EngineeredAsteroid
+                        || ignore_engineered_asteroids)
+                    && waypoint
+                        .unstable_since
+                        .map(|last| {
+                            (last + chrono::Duration::seconds(unstable_since_timeout)
+                                < chrono::Utc::now())
+                                || !stop_all_unstable
+                        })
+                        .unwrap_or(true)
+            }
+            ActionType::Siphon => waypoint.is_sipherable(),
+        };
+
+        let values = self.get_values(&ship.nav.system_symbol).await?;
+        let ranked = self
+            .finder
+            .rank(ship, usable, &values, unstable_since_timeout)
+            .await?;
+
+        let counts = self.places.get_max_miners_per_waypoint()
+            + (((action == ActionType::Siphon) as u32) * 10000);
+        let waypoints = PlaceFinder::find(&ranked, &self.places, counts as usize);
+
         if let Some((waypoint_symbol, _)) = self.places.get_ship(&ship.symbol) {
-            let waypoint =
-                database::Waypoint::get_by_id(&self.context.database_pool, &waypoint_symbol)
-                    .await?;
-
-            if waypoint
-                .map(|waypoint| {
-                    waypoint.is_minable()
-                        && (waypoint.waypoint_type != models::WaypointType::EngineeredAsteroid
-                            || ignore_engineered_asteroids)
-                        && waypoint
-                            .unstable_since
-                            .map(|last| {
-                                (last + chrono::Duration::seconds(unstable_since_timeout)
-                                    < chrono::Utc::now())
-                                    || !stop_all_unstable
-                            })
-                            .unwrap_or(true)
-                })
-                .unwrap_or(false)
+            if self.worth_staying(&waypoint_symbol, &ranked, &waypoints, &values)
                 && self.places.try_assign_on_way(
                     &ship.symbol,
                     &waypoint_symbol,
@@ -88,27 +114,14 @@ impl WaypointManager {
             {
                 return Ok(waypoint_symbol.to_string());
             }
+
+            tracing::info!(ship_symbol = %ship.symbol, waypoint_symbol = %waypoint_symbol, "Moving miner off its waypoint");
+            self.places.try_unassign(&ship.symbol, &waypoint_symbol);
         }
 
         let waypoint_symbol = ship.nav.waypoint_symbol.clone();
 
-        let waypoint =
-            database::Waypoint::get_by_id(&self.context.database_pool, &waypoint_symbol).await?;
-        if waypoint
-            .map(|waypoint| {
-                waypoint.is_minable()
-                    && (waypoint.waypoint_type != models::WaypointType::EngineeredAsteroid
-                        || ignore_engineered_asteroids)
-                    && waypoint
-                        .unstable_since
-                        .map(|last| {
-                            (last + chrono::Duration::seconds(unstable_since_timeout)
-                                < chrono::Utc::now())
-                                || !stop_all_unstable
-                        })
-                        .unwrap_or(true)
-            })
-            .unwrap_or(false)
+        if self.worth_staying(&waypoint_symbol, &ranked, &waypoints, &values)
             && self.places.try_assign_on_way(
                 &ship.symbol,
                 &waypoint_symbol,
@@ -118,35 +131,54 @@ impl WaypointManager {
             return Ok(waypoint_symbol.to_string());
         }
 
-        let counts = self.places.get_max_miners_per_waypoint()
-            + (((action == ActionType::Siphon) as u32) * 10000);
+        self.assign_to_available_waypoint(ship, waypoints, action)
+    }
 
-        let waypoints: Vec<place_finder::FoundWaypointInfo> = self
-            .finder
-            .find(
-                ship.clone(),
-                |waypoint| match action {
-                    ActionType::Extract => {
-                        waypoint.is_minable()
-                            && (waypoint.waypoint_type != models::WaypointType::EngineeredAsteroid
-                                || ignore_engineered_asteroids)
-                            && waypoint
-                                .unstable_since
-                                .map(|last| {
-                                    (last + chrono::Duration::seconds(unstable_since_timeout)
-                                        < chrono::Utc::now())
-                                        || !stop_all_unstable
-                                })
-                                .unwrap_or(true)
-                    }
-                    ActionType::Siphon => waypoint.is_sipherable(),
-                },
-                &self.places,
-                counts as usize,
-            )
-            .await?;
+    /// A usable waypoint is kept unless it is depleting or another one with room is worth clearly more
+    fn worth_staying(
+        &self,
+        waypoint_symbol: &str,
+        ranked: &[place_finder::FoundWaypointInfo],
+        available: &[place_finder::FoundWaypointInfo],
+        values: &HashMap<String, place_finder::WaypointValue>,
+    ) -> bool {
+        let Some(current) = ranked
+            .iter()
+            .find(|wp| wp.waypoint.symbol == waypoint_symbol)
+        else {
+            return false;
+        };
 
-        self.assign_to_available_waypoint(ship, waypoints, action)
+        if values
+            .get(waypoint_symbol)
+            .is_some_and(|value| value.is_depleting())
+        {
+            return false;
+        }
+
+        let threshold = current.score + current.score.abs() * (SWITCH_RATIO - 1.0);
+        !available
+            .iter()
+            .filter(|wp| wp.waypoint.symbol != waypoint_symbol)
+            .any(|wp| wp.score > threshold)
+    }
+
+    async fn get_values(
+        &mut self,
+        system_symbol: &str,
+    ) -> Result<HashMap<String, place_finder::WaypointValue>> {
+        if let Some((loaded, values)) = self.values.get(system_symbol)
+            && (chrono::Utc::now() - *loaded).num_seconds() < WAYPOINT_VALUES_TTL
+        {
+            return Ok(values.clone());
+        }
+
+        let values = self.finder.get_values(system_symbol).await?;
+        self.values.insert(
+            system_symbol.to_string(),
+            (chrono::Utc::now(), values.clone()),
+        );
+        Ok(values)
     }
 
     fn assign_to_available_waypoint(
@@ -181,6 +213,7 @@ impl WaypointManager {
             &waypoint_symbol,
             action == ActionType::Siphon,
         );
+        self.persist(&ship_clone.symbol).await;
 
         if wp {
             Ok(waypoint_symbol)
@@ -198,6 +231,7 @@ impl WaypointManager {
         let wp = self
             .places
             .try_assign_inactive(&ship_clone.symbol, &waypoint_symbol);
+        self.persist(&ship_clone.symbol).await;
 
         if wp {
             Ok(waypoint_symbol)
@@ -215,6 +249,7 @@ impl WaypointManager {
         let wp = self
             .places
             .try_unassign(&ship_clone.symbol, &waypoint_symbol);
+        self.persist(&ship_clone.symbol).await;
 
         if wp {
             Ok(waypoint_symbol)
@@ -223,6 +258,84 @@ impl WaypointManager {
         }
     }
 
+    /// Stores the assignment of the ship, so it survives a restart
+    async fn persist(&self, ship_symbol: &str) {
+        let erg = match self.places.get_ship(ship_symbol) {
+            Some((waypoint_symbol, level)) => {
+                database::MiningAssignment::upsert(
+                    &self.context.database_pool,
+                    ship_symbol,
+                    &waypoint_symbol,
+                    level.into(),
+                )
+                .await
+            }
+            None => {
+                database::MiningAssignment::delete_by_ship(&self.context.database_pool, ship_symbol)
+                    .await
+            }
+        };
+
+        if let Err(err) = erg {
+            tracing::warn!(ship_symbol, error = ?err, "Failed to persist the mining assignment");
+        }
+    }
+
+    /// Restores the assignments from before a restart
+    ///
+    /// The fleets of the ships come from their assignments in the database,
+    /// the pilots only set them on the ships once they start, after the managers.
+    pub async fn restore(&mut self) -> Result<()> {
+        let assignments = database::MiningAssignment::get_all(&self.context.database_pool).await?;
+        if assignments.is_empty() {
+            return Ok(());
+        }
+
+        let mining_ships = database::ShipInfo::get_by_fleet_type(
+            &self.context.database_pool,
+            database::FleetType::Mining,
+        )
+        .await?
+        .into_iter()
+        .map(|ship_info| ship_info.symbol)
+        .collect::<std::collections::HashSet<_>>();
+        let positions = self
+            .context
+            .ship_manager
+            .get_all_clone()
+            .await
+            .into_iter()
+            .map(|(symbol, ship)| {
+                let in_transit = ship.nav.is_in_transit();
+                (symbol, (ship.nav.waypoint_symbol, in_transit))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let total = assignments.len();
+        let dropped = self
+            .places
+            .restore_all(assignments, &mining_ships, &positions);
+        for assignment in dropped.iter() {
+            tracing::debug!(ship_symbol = %assignment.ship_symbol, waypoint_symbol = %assignment.waypoint_symbol, "Dropping mining assignment of a ship which moved on");
+            database::MiningAssignment::delete_by_ship(
+                &self.context.database_pool,
+                &assignment.ship_symbol,
+            )
+            .await?;
+        }
+
+        tracing::info!(
+            restored = total - dropped.len(),
+            "Restored mining assignments"
+        );
+        Ok(())
+    }
+
+    /// Whether the ship holds an assignment to the waypoint
+    pub fn is_assigned(&self, ship_symbol: &str, waypoint_symbol: &str) -> bool {
+        self.places.has_ship(ship_symbol, waypoint_symbol)
+    }
+
     pub fn up_date(&mut self, waypoint: &str) {
         self.places.up_date(waypoint);
     }
@@ -242,6 +355,10 @@ impl WaypointManager {
             .places
             .iter()
             .map(|wp| Self::calculate_waypoint_urgency(wp.1, the_ships))
+            .map(|(waypoint_symbol, urgency)| {
+                let weight = self.value_weight(&waypoint_symbol);
+                (waypoint_symbol, (urgency as f64 * weight) as i64)
+            })
             .collect::<Vec<_>>();
 
         erg.sort_by_key(|b| std::cmp::Reverse(b.1));
@@ -249,6 +366,29 @@ impl WaypointManager {
         erg
     }
 
+    /// the value of the waypoint relative to the mean of all measured waypoints, 1 when unknown
+    fn value_weight(&self, waypoint_symbol: &str) -> f64 {
+        let Some(values) = self
+            .values
+            .values()
+            .map(|(_, values)| values)
+            .find(|values| values.contains_key(waypoint_symbol))
+        else {
+            return 1.0;
+        };
+
+        let mean = values
+            .values()
+            .map(|value| value.recent_value_per_extraction)
+            .sum::<f64>()
+            / values.len() as f64;
+        if mean <= 0.0 {
+            return 1.0;
+        }
+
+        values[waypoint_symbol].recent_value_per_extraction / mean
+    }
+
     // where do I put you?
     fn calculate_waypoint_urgency(
         wp: &super::mining_places::WaypointInfo,
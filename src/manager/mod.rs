@@ -3,6 +3,7 @@ use tokio_util::sync::CancellationToken;
 
 pub mod budget_manager;
 pub mod chart_manager;
+pub mod config_manager;
 pub mod construction_manager;
 pub mod contract_manager;
 pub mod fleet_manager;
@@ -17,5 +18,6 @@ pub trait Manager: Send + Sync {
         &mut self,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + '_>>;
     fn get_name(&self) -> &str;
+    #[allow(dead_code)]
     fn get_cancel_token(&self) -> &CancellationToken;
 }
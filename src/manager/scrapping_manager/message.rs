@@ -4,6 +4,7 @@ the ship requests what to scrap next in the current system
 if all markets have a scrapper the manager tells him to stay at the waypoint and a date to wait and scrap.
 the scrap date is calculated since the last time it updated plus a interval constant and the waypoints importance
 importance is calculated based on the amount of item the marketplace sells, what type they are(EXCHANGE being the worst)
+how often and how much its prices changed and the running trade routes depending on it, the allowed requests of the fleet are shared by that importance
 if the ship isn't at a market and all markets are already taken the ship will be unassigned from scrapping
 if the waypoint also contains a shipyard the shipyard will be scrapped at the same time
 
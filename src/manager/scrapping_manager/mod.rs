@@ -1,6 +1,7 @@
 mod message;
 mod messanger;
 pub mod priority_calculator;
+#[allow(clippy::module_inception)]
 mod scrapping_manager;
 pub mod utils;
 
@@ -6,9 +6,13 @@ imports should be logarythmic, a few will have a high impact but the more their
 exports should be somewhat linear, the more exports their are the better
 
 less valuable=longer time until next scrap
+
+on top of that the measured price changes of a market and the running trade routes depending on it decide how much
+of the allowed requests it gets
      */
 
-use chrono::DateTime;
+use std::collections::HashMap;
+
 use space_traders_client::models;
 
 use crate::error::Result;
@@ -17,36 +21,154 @@ const EXPORT_WEIGHT: f64 = 0.15;
 const IMPORT_WEIGHT: f64 = 0.15;
 const EXCHANGE_WEIGHT: f64 = 0.3;
 
-pub fn get_waypoint_time(
-    // waypoint: &database::Waypoint,
-    market_trade: &[database::MarketTrade],
-    // market_trade_goods: &[database::MarketTradeGood],
+/// how far back the price changes of a market are measured (in hours)
+const VOLATILITY_WINDOW: i64 = 12;
+/// the shortest time between two scrapes of the same market (in seconds)
+const MIN_UPDATE_INTERVAL: f64 = 60.0;
+/// the drift assumed for markets which were not scraped twice yet, so no market is starved
+const MIN_DRIFT_PER_HOUR: f64 = 0.01;
+/// the credits depending on a market without running trade routes, scaled by its goods
+const BASE_STAKE: f64 = 10_000.0;
+
+/// What outdated prices of one market cost
+#[derive(Debug, Clone)]
+pub struct MarketScrapValue {
+    pub waypoint_symbol: String,
+    pub last_scrape: chrono::DateTime<chrono::Utc>,
+    /// how much the exports, imports and exchanges of the market are worth knowing
+    pub structural_weight: f64,
+    pub volatility: Option<database::MarketVolatility>,
+    /// the credits of the running trade routes buying or selling at the market
+    pub route_stake: i64,
+}
+
+impl MarketScrapValue {
+    /// the credits expected to be lost per hour the prices are not updated
+    pub fn cost_per_hour(&self) -> f64 {
+        let drift = self
+            .volatility
+            .as_ref()
+            .map(|v| v.drift_per_hour)
+            .unwrap_or_default()
+            .max(MIN_DRIFT_PER_HOUR);
+        drift * (BASE_STAKE * self.structural_weight + self.route_stake as f64)
+    }
+}
+
+/// The next scrap date of every market in the system with known goods
+///
+/// The scrapes per hour the active scraping fleets allow are shared by the markets by what their outdated prices cost.
+/// Without a scraping fleet every market gets one scrape per `max_update_interval`.
+pub async fn get_system_schedule(
+    database_pool: &database::DbPool,
+    system_symbol: &str,
     max_update_interval: i32, // in seconds
-) -> Result<chrono::DateTime<chrono::Utc>> {
-    get_waypoint_time_with_weights(
-        market_trade,
-        max_update_interval,
-        EXPORT_WEIGHT,
-        IMPORT_WEIGHT,
-        EXCHANGE_WEIGHT,
+) -> Result<HashMap<String, chrono::DateTime<chrono::Utc>>> {
+    let values = get_market_values(database_pool, system_symbol).await?;
+
+    let allowed_requests = database::Fleet::get_by_system(
+        database_pool,
+        system_symbol,
+        database::PaginatedQuery::unpaged(),
     )
+    .await?
+    .items
+    .into_iter()
+    .filter(|fleet| fleet.active)
+    .filter_map(|fleet| fleet.get_scraping_config())
+    .map(|config| config.allowed_requests.max(0) as f64)
+    .sum::<f64>();
+
+    Ok(schedule(&values, allowed_requests, max_update_interval))
 }
 
-pub fn get_waypoint_time_with_weights(
-    // waypoint: &database::Waypoint,
-    market_trade: &[database::MarketTrade],
-    // market_trade_goods: &[database::MarketTradeGood],
-    max_update_interval: i32, // in seconds
-    export_weight: f64,
-    import_weight: f64,
-    exchange_weight: f64,
-) -> Result<chrono::DateTime<chrono::Utc>> {
-    let last_date = market_trade
+async fn get_market_values(
+    database_pool: &database::DbPool,
+    system_symbol: &str,
+) -> Result<Vec<MarketScrapValue>> {
+    let mut market_trades: HashMap<String, Vec<database::MarketTrade>> = HashMap::new();
+    for trade_good in database::MarketTradeGood::get_last_by_system(
+        database_pool,
+        system_symbol,
+        database::PaginatedQuery::unpaged(),
+    )
+    .await?
+    .items
+    {
+        market_trades
+            .entry(trade_good.waypoint_symbol.clone())
+            .or_default()
+            .push(trade_good.into());
+    }
+
+    let mut volatilities = database::MarketVolatility::get_by_system(
+        database_pool,
+        system_symbol,
+        chrono::Utc::now() - chrono::Duration::hours(VOLATILITY_WINDOW),
+    )
+    .await?
+    .into_iter()
+    .map(|v| (v.waypoint_symbol.clone(), v))
+    .collect::<HashMap<_, _>>();
+
+    let mut stakes: HashMap<String, i64> = HashMap::new();
+    for route in
+        database::TradeRoute::get_unfinished(database_pool, database::PaginatedQuery::unpaged())
+            .await?
+            .items
+    {
+        *stakes.entry(route.purchase_waypoint).or_default() +=
+            route.trade_volume as i64 * route.predicted_purchase_price as i64;
+        *stakes.entry(route.sell_waypoint).or_default() +=
+            route.trade_volume as i64 * route.predicted_sell_price as i64;
+    }
+
+    let values = market_trades
+        .into_iter()
+        .map(|(waypoint_symbol, market_trade)| MarketScrapValue {
+            last_scrape: market_trade
+                .iter()
+                .map(|f| f.created_at)
+                .max()
+                .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC),
+            structural_weight: get_structural_weight(&market_trade),
+            volatility: volatilities.remove(&waypoint_symbol),
+            route_stake: stakes.get(&waypoint_symbol).copied().unwrap_or_default(),
+            waypoint_symbol,
+        })
+        .collect();
+
+    Ok(values)
+}
+
+fn schedule(
+    values: &[MarketScrapValue],
+    allowed_requests: f64, // scrapes per hour
+    max_update_interval: i32,
+) -> HashMap<String, chrono::DateTime<chrono::Utc>> {
+    let max_update_interval = (max_update_interval as f64).max(MIN_UPDATE_INTERVAL);
+    let total_cost = values.iter().map(|v| v.cost_per_hour()).sum::<f64>();
+
+    values
         .iter()
-        .max_by(|a, b| a.created_at.cmp(&b.created_at))
-        .map(|f| f.created_at)
-        .unwrap_or(DateTime::<chrono::Utc>::MIN_UTC);
+        .map(|value| {
+            let interval = if allowed_requests > 0.0 && total_cost > 0.0 {
+                let scrapes_per_hour = allowed_requests * value.cost_per_hour() / total_cost;
+                (3600.0 / scrapes_per_hour).clamp(MIN_UPDATE_INTERVAL, max_update_interval)
+            } else {
+                max_update_interval
+            };
+
+            (
+                value.waypoint_symbol.clone(),
+                value.last_scrape + chrono::Duration::seconds(interval as i64),
+            )
+        })
+        .collect()
+}
 
+/// 1 for a market without goods, growing with the exports, imports and exchanges
+fn get_structural_weight(market_trade: &[database::MarketTrade]) -> f64 {
     let num_exports = market_trade
         .iter()
         .filter(|f| f.r#type == models::market_trade_good::Type::Export)
@@ -62,29 +184,14 @@ pub fn get_waypoint_time_with_weights(
         .filter(|f| f.r#type == models::market_trade_good::Type::Exchange)
         .count() as f64;
 
-    // let interval_percent = calc_interval_c(
-    //     export_weight,
-    //     import_weight,
-    //     exchange_weight,
-    //     num_exports,
-    //     num_imports,
-    //     num_exchanges,
-    // );
-    let interval_percent = calc_interval_a(
-        export_weight,
-        import_weight,
-        exchange_weight,
+    1.0 / calc_interval_a(
+        EXPORT_WEIGHT,
+        IMPORT_WEIGHT,
+        EXCHANGE_WEIGHT,
         num_exports,
         num_imports,
         num_exchanges,
-    );
-
-    let interval_duration =
-        chrono::Duration::seconds((interval_percent * max_update_interval as f64) as i64);
-
-    let nextscrap = last_date + interval_duration;
-
-    Ok(nextscrap)
+    )
 }
 
 fn calc_interval_a(
@@ -110,6 +217,7 @@ fn calc_interval_a(
     1.0 / interval_modifier
 }
 
+#[allow(dead_code)]
 fn calc_interval_c(
     export_weight: f64,   // Weight for exports
     import_weight: f64,   // Weight for imports
@@ -107,7 +107,10 @@ impl ScrappingManager {
     )> {
         let erg = { self.context.config.read().await.scrap_agents };
         let agent_join_handle = if erg {
-            let api = self.context.api.clone();
+            let api = self
+                .context
+                .api
+                .with_priority(space_traders_client::RequestPriority::Background);
             let database_pool = self.context.database_pool.clone();
             let slow_cancel_token = self.slow_cancel_token.child_token();
             let fast_cancel_token = self.fast_cancel_token.child_token();
@@ -130,7 +133,10 @@ impl ScrappingManager {
         let system_join_handle: tokio::task::JoinHandle<
             std::result::Result<(), crate::error::Error>,
         > = if erg {
-            let api = self.context.api.clone();
+            let api = self
+                .context
+                .api
+                .with_priority(space_traders_client::RequestPriority::Background);
             let database_pool = self.context.database_pool.clone();
             let fast_cancel_token = self.fast_cancel_token.child_token();
 
@@ -295,32 +301,23 @@ impl ScrappingManager {
             .filter(|w| !self.scrap_waypoints.contains_key(&w.symbol))
             .collect::<Vec<_>>();
 
-        let mut waypoints: Vec<(database::Waypoint, chrono::DateTime<chrono::Utc>)> = vec![];
+        let max_update_interval = { self.context.config.read().await.max_update_interval };
 
-        for wp in wps {
-            let market_trade_goods = database::MarketTradeGood::get_last_by_waypoint(
-                &self.context.database_pool,
-                &wp.symbol,
-                database::PaginatedQuery::unpaged(),
-            )
-            .await?
-            .items;
-
-            if !wp.is_charted() || market_trade_goods.is_empty() {
-                waypoints.push((wp, chrono::DateTime::<chrono::Utc>::MIN_UTC));
-                continue;
-            }
+        let schedule = priority_calculator::get_system_schedule(
+            &self.context.database_pool,
+            &system_symbol,
+            max_update_interval,
+        )
+        .await?;
 
-            let max_update_interval = { self.context.config.read().await.max_update_interval };
+        let mut waypoints: Vec<(database::Waypoint, chrono::DateTime<chrono::Utc>)> = vec![];
 
-            let next_time = priority_calculator::get_waypoint_time(
-                market_trade_goods
-                    .into_iter()
-                    .map(From::from)
-                    .collect::<Vec<_>>()
-                    .as_slice(),
-                max_update_interval,
-            )?;
+        for wp in wps {
+            let next_time = schedule
+                .get(&wp.symbol)
+                .filter(|_| wp.is_charted())
+                .copied()
+                .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
 
             waypoints.push((wp, next_time));
         }
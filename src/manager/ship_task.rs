@@ -12,6 +12,7 @@ pub struct ShipTaskHandler {
     slow_ship_cancel_token: tokio_util::sync::CancellationToken,
     fast_ship_cancel_token: tokio_util::sync::CancellationToken,
     slow_manager_cancel_token: tokio_util::sync::CancellationToken,
+    #[allow(dead_code)]
     slow_cancel_token: tokio_util::sync::CancellationToken,
     fast_cancel_token: tokio_util::sync::CancellationToken,
     context: ConductorContext,
@@ -22,6 +23,10 @@ type ShipFuture = ();
 #[derive(Debug, Clone)]
 pub struct ShipTaskMessanger {
     sender: tokio::sync::mpsc::Sender<database::ShipInfo>,
+    /// wakes paused pilots whenever a ship or fleet is (de)activated
+    activation: std::sync::Arc<tokio::sync::Notify>,
+    /// wakes idle Manuel pilots whenever commands are queued
+    commands: std::sync::Arc<tokio::sync::Notify>,
 }
 
 impl ShipTaskMessanger {
@@ -29,12 +34,39 @@ impl ShipTaskMessanger {
         tracing::debug!(ship_names = ?ship_names, "Starting ship");
         let _erg = self.sender.send(ship_names).await;
     }
+
+    /// Tells every paused pilot to re-check whether it is active again
+    pub fn notify_activation_changed(&self) {
+        tracing::debug!("Ship activation changed");
+        self.activation.notify_waiters();
+    }
+
+    /// Resolves on the next `notify_activation_changed`, enable it before checking the state to not miss a change
+    pub fn activation_changed(&self) -> tokio::sync::futures::Notified<'_> {
+        self.activation.notified()
+    }
+
+    pub fn notify_commands_queued(&self) {
+        self.commands.notify_waiters();
+    }
+
+    /// Resolves on the next `notify_commands_queued`, enable it before checking the queue to not miss a command
+    pub fn commands_queued(&self) -> tokio::sync::futures::Notified<'_> {
+        self.commands.notified()
+    }
 }
 
 impl ShipTaskHandler {
     pub fn create() -> (ShipTaskHandlerReceiver, ShipTaskMessanger) {
         let (sender, receiver) = tokio::sync::mpsc::channel(1024); // may become a problem if we have to many ships, set to 8192 if needed
-        (receiver, ShipTaskMessanger { sender })
+        (
+            receiver,
+            ShipTaskMessanger {
+                sender,
+                activation: std::sync::Arc::new(tokio::sync::Notify::new()),
+                commands: std::sync::Arc::new(tokio::sync::Notify::new()),
+            },
+        )
     }
     pub fn new(
         fast_ship_cancel_token: tokio_util::sync::CancellationToken,
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+
+use database::DatabaseConnectorAsync;
+use space_traders_client::models;
+
+use crate::error::Result;
+
+use super::routes::ConcreteTradeRoute;
+
+/// how many full trade volumes move the supply of a market by one level
+const BATCHES_PER_LEVEL: f64 = 4.0;
+/// supply levels a market recovers per hour towards its own level after our trades
+const RECOVERY_LEVELS_PER_HOUR: f64 = 0.25;
+/// the score of one squared supply level, the scores are integers
+const SCORE_SCALE: f64 = 100.0;
+
+type MarketKey = (String, models::TradeSymbol);
+
+/// Scores trade routes by how they move the supply of both markets towards healthy levels, used by `TradeMode::MarketBalanced`
+///
+/// Exports should not be drained below moderate supply, imports should be filled up to moderate supply.
+/// Imports which an export of the same market is produced from are filled up to high supply, so the production grows,
+/// the more when the export is restricted or weak for the lack of them.
+/// The volume of unfinished trade routes is already applied to the markets.
+#[derive(Debug, Clone, Default)]
+pub struct MarketBalance {
+    /// the exports of every market and their activity
+    exports: HashMap<String, Vec<(models::TradeSymbol, Option<models::ActivityLevel>)>>,
+    /// (export, import) pairs of the supply chain
+    supply_chain: HashSet<(models::TradeSymbol, models::TradeSymbol)>,
+    /// supply levels already moved by unfinished trade routes, negative when drained
+    pending: HashMap<MarketKey, f64>,
+}
+
+impl MarketBalance {
+    pub async fn load(
+        database_pool: &database::DbPool,
+        trade_goods: &[database::MarketTradeGood],
+    ) -> Result<MarketBalance> {
+        let supply_chain = database::ExportImportMapping::get_all(
+            database_pool,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items
+        .into_iter()
+        .map(|m| (m.export_symbol, m.import_symbol))
+        .collect::<HashSet<_>>();
+
+        let mut exports: HashMap<String, Vec<_>> = HashMap::new();
+        for good in trade_goods
+            .iter()
+            .filter(|g| g.r#type == models::market_trade_good::Type::Export)
+        {
+            exports
+                .entry(good.waypoint_symbol.clone())
+                .or_default()
+                .push((good.symbol, good.activity));
+        }
+
+        let trade_volumes = trade_goods
+            .iter()
+            .map(|g| ((g.waypoint_symbol.clone(), g.symbol), g.trade_volume))
+            .collect::<HashMap<_, _>>();
+
+        let mut pending: HashMap<MarketKey, f64> = HashMap::new();
+        for route in
+            database::TradeRoute::get_unfinished(database_pool, database::PaginatedQuery::unpaged())
+                .await?
+                .items
+        {
+            let purchase = (route.purchase_waypoint, route.symbol);
+            let sell = (route.sell_waypoint, route.symbol);
+            if let Some(trade_volume) = trade_volumes.get(&purchase) {
+                *pending.entry(purchase).or_default() -=
+                    level_shift(route.trade_volume, *trade_volume);
+            }
+            if let Some(trade_volume) = trade_volumes.get(&sell) {
+                *pending.entry(sell).or_default() += level_shift(route.trade_volume, *trade_volume);
+            }
+        }
+
+        Ok(MarketBalance {
+            exports,
+            supply_chain,
+            pending,
+        })
+    }
+
+    /// positive when the trip moves both markets closer to healthy supply, markets without known supply count as healthy
+    pub fn score(&self, route: &ConcreteTradeRoute) -> i32 {
+        let volume = route.trip.volume;
+        let trip_hours = route.trip.time / 3600.0;
+
+        let purchase_gain = route
+            .route
+            .purchase_good
+            .as_ref()
+            .map(|good| {
+                let level = self.level(good);
+                // on recurring trips the market recovers while the ship is away
+                let drain = (level_shift(volume, good.trade_volume)
+                    - RECOVERY_LEVELS_PER_HOUR * trip_hours)
+                    .max(0.0);
+                health(level - drain, self.target(good)) - health(level, self.target(good))
+            })
+            .unwrap_or_default();
+
+        let sell_gain = route
+            .route
+            .sell_good
+            .as_ref()
+            .map(|good| {
+                let level = self.level(good);
+                let fill = level_shift(volume, good.trade_volume);
+                let gain =
+                    health(level + fill, self.target(good)) - health(level, self.target(good));
+                gain * (1.0 + self.feed_weight(good))
+            })
+            .unwrap_or_default();
+
+        ((purchase_gain + sell_gain) * SCORE_SCALE).round() as i32
+    }
+
+    fn level(&self, good: &database::MarketTradeGood) -> f64 {
+        good.supply as i32 as f64
+            + self
+                .pending
+                .get(&(good.waypoint_symbol.clone(), good.symbol))
+                .copied()
+                .unwrap_or_default()
+    }
+
+    /// the supply level a market should be kept at
+    fn target(&self, good: &database::MarketTradeGood) -> f64 {
+        let feeds_production =
+            good.r#type == models::market_trade_good::Type::Import && self.feed_weight(good) > 0.0;
+        if feeds_production {
+            models::SupplyLevel::High as i32 as f64
+        } else {
+            models::SupplyLevel::Moderate as i32 as f64
+        }
+    }
+
+    /// how much the exports of the market depend on the good, starving exports weigh more
+    fn feed_weight(&self, good: &database::MarketTradeGood) -> f64 {
+        if good.r#type != models::market_trade_good::Type::Import {
+            return 0.0;
+        }
+        self.exports
+            .get(&good.waypoint_symbol)
+            .map(|exports| {
+                exports
+                    .iter()
+                    .filter(|(export, _)| self.supply_chain.contains(&(*export, good.symbol)))
+                    .map(|(_, activity)| match activity {
+                        Some(models::ActivityLevel::Restricted) => 2.0,
+                        Some(models::ActivityLevel::Weak) => 1.5,
+                        Some(models::ActivityLevel::Growing) | None => 1.0,
+                        Some(models::ActivityLevel::Strong) => 0.5,
+                    })
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// the supply levels `units` move a market with the given trade volume
+fn level_shift(units: i32, trade_volume: i32) -> f64 {
+    units.max(0) as f64 / (trade_volume.max(1) as f64 * BATCHES_PER_LEVEL)
+}
+
+/// 0 at the target, falling with the squared distance so scarce markets are filled up first
+fn health(level: f64, target: f64) -> f64 {
+    let level = level.clamp(
+        models::SupplyLevel::Scarce as i32 as f64,
+        models::SupplyLevel::Abundant as i32 as f64,
+    );
+    -(level - target).powi(2)
+}
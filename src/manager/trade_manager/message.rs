@@ -7,6 +7,23 @@ pub enum TradeMessage {
         trading_config: database::TradingFleetConfig,
         callback: tokio::sync::oneshot::Sender<Result<Option<database::TradeRoute>>>,
     },
+    RequestNextTradePlan {
+        ship_clone: ship::MyShipCopy,
+        trading_config: database::TradingFleetConfig,
+        callback: tokio::sync::oneshot::Sender<Result<Option<database::TradePlan>>>,
+    },
+    CompleteTradePlan {
+        trade_plan: database::TradePlan,
+        callback: tokio::sync::oneshot::Sender<Result<database::TradePlan>>,
+    },
+    StartTradeLeg {
+        trade_routes: Vec<database::TradeRoute>,
+        callback: tokio::sync::oneshot::Sender<Result<Vec<database::TradeRoute>>>,
+    },
+    AbortTradePlan {
+        trade_plan: database::TradePlan,
+        callback: tokio::sync::oneshot::Sender<Result<database::TradePlan>>,
+    },
     CompleteTradeRoute {
         trade_route: database::TradeRoute,
         callback: tokio::sync::oneshot::Sender<Result<database::TradeRoute>>,
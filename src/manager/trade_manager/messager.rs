@@ -74,6 +74,110 @@ impl TradeManagerMessanger {
         resp
     }
 
+    pub async fn get_plan(
+        &self,
+        ship_clone: ship::MyShipCopy,
+        trading_config: database::TradingFleetConfig,
+    ) -> Result<Option<database::TradePlan>, Error> {
+        tracing::debug!(ship_symbol = %ship_clone.symbol, "Requesting next trade plan for ship");
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+
+        let message = TradeManagerMessage::RequestNextTradePlan {
+            ship_clone,
+            trading_config,
+            callback: sender,
+        };
+
+        self.sender
+            .send(message)
+            .await
+            .map_err(|e| Error::General(format!("Failed to send message: {}", e)))?;
+
+        let resp = receiver
+            .await
+            .map_err(|e| Error::General(format!("Failed to get trade plan message: {}", e)))?;
+
+        tracing::debug!(resp = ?resp, "Received trade plan for ship");
+        resp
+    }
+
+    pub async fn complete_plan(
+        &self,
+        trade_plan: &database::TradePlan,
+    ) -> Result<database::TradePlan, Error> {
+        tracing::debug!(trade_plan_id = %trade_plan.id, "Completing trade plan");
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+
+        let message = TradeManagerMessage::CompleteTradePlan {
+            trade_plan: trade_plan.clone(),
+            callback: sender,
+        };
+
+        self.sender
+            .send(message)
+            .await
+            .map_err(|e| Error::General(format!("Failed to send message: {}", e)))?;
+
+        let resp = receiver.await.map_err(|e| {
+            Error::General(format!("Failed to get trade plan complete message: {}", e))
+        })?;
+
+        tracing::debug!(resp = ?resp, "Completed trade plan");
+        resp
+    }
+
+    /// Reserves the funds for the routes of a plan leg which are not reserved yet
+    pub async fn start_leg(
+        &self,
+        trade_routes: Vec<database::TradeRoute>,
+    ) -> Result<Vec<database::TradeRoute>, Error> {
+        tracing::debug!(trade_routes = trade_routes.len(), "Starting trade plan leg");
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+
+        let message = TradeManagerMessage::StartTradeLeg {
+            trade_routes,
+            callback: sender,
+        };
+
+        self.sender
+            .send(message)
+            .await
+            .map_err(|e| Error::General(format!("Failed to send message: {}", e)))?;
+
+        let resp = receiver
+            .await
+            .map_err(|e| Error::General(format!("Failed to get trade leg start message: {}", e)))?;
+
+        tracing::debug!(resp = ?resp, "Started trade plan leg");
+        resp
+    }
+
+    /// Fails the open routes of a plan and releases their locks and reservations
+    pub async fn abort_plan(
+        &self,
+        trade_plan: &database::TradePlan,
+    ) -> Result<database::TradePlan, Error> {
+        tracing::debug!(trade_plan_id = %trade_plan.id, "Aborting trade plan");
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+
+        let message = TradeManagerMessage::AbortTradePlan {
+            trade_plan: trade_plan.clone(),
+            callback: sender,
+        };
+
+        self.sender
+            .send(message)
+            .await
+            .map_err(|e| Error::General(format!("Failed to send message: {}", e)))?;
+
+        let resp = receiver.await.map_err(|e| {
+            Error::General(format!("Failed to get trade plan abort message: {}", e))
+        })?;
+
+        tracing::debug!(resp = ?resp, "Aborted trade plan");
+        resp
+    }
+
     pub fn is_busy(&self) -> bool {
         self.busy.load(std::sync::atomic::Ordering::Relaxed)
     }
@@ -1,13 +1,18 @@
+mod market_balance;
 mod message;
 mod messager;
+mod price_model;
 mod route_calculator_concrete;
 mod routes;
 mod routes_calculator;
 mod routes_tracker;
+#[allow(clippy::module_inception)]
 mod trade_manager;
+mod trade_planner;
 
 pub use message::TradeManagerMessage;
 pub use messager::TradeManagerMessanger;
+pub use price_model::{PRICE_MODEL_TTL, PriceModel};
 pub use trade_manager::TradeManager;
 pub use trade_manager::TradeManagerReceiver;
 // pub use trade_manager::TradeMessage;
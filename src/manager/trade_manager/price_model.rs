@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use space_traders_client::models;
+
+use crate::error::Result;
+
+/// transactions closer together than this are treated as batches of the same trade
+const BATCH_WINDOW_SECONDS: i64 = 10 * 60;
+/// how far back transactions and market snapshots are used to learn the model
+const HISTORY_HOURS: i64 = 48;
+/// how long a learned price model is reused before it is learned again (in seconds)
+pub const PRICE_MODEL_TTL: i64 = 10 * 60;
+
+type ImpactKey = (
+    String,
+    models::TradeSymbol,
+    models::market_transaction::Type,
+);
+
+/// How the price of one good at one market reacts to our trades
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PriceImpact {
+    /// relative price change per unit traded, positive for purchases, negative for sales
+    pub per_unit: f64,
+    /// share of a price displacement recovered per hour
+    pub recovery_per_hour: f64,
+    /// the price the market recovers to
+    pub baseline: Option<f64>,
+}
+
+impl PriceImpact {
+    /// the price after `units` have been traded in batches of `trade_volume`, the price only moves between batches
+    pub fn price_after(&self, price: i32, units: i32, trade_volume: i32) -> f64 {
+        let batches = units / trade_volume.max(1);
+        price as f64 * self.batch_factor(trade_volume).powi(batches)
+    }
+
+    /// the total price of `units` traded in batches of `trade_volume`, starting at `price`
+    pub fn total_price(&self, price: i32, units: i32, trade_volume: i32) -> i64 {
+        let trade_volume = trade_volume.max(1);
+        let factor = self.batch_factor(trade_volume);
+
+        let mut total = 0.0;
+        let mut batch_price = price as f64;
+        let mut remaining = units;
+        while remaining > 0 {
+            let batch = remaining.min(trade_volume);
+            total += batch_price.round() * batch as f64;
+            batch_price *= factor;
+            remaining -= batch;
+        }
+        total as i64
+    }
+
+    /// the units that can be traded before the price passes `limit`, at most `max_units`
+    pub fn units_until(&self, price: i32, trade_volume: i32, limit: f64, max_units: i32) -> i32 {
+        let trade_volume = trade_volume.max(1);
+        let factor = self.batch_factor(trade_volume);
+        let rising = factor >= 1.0;
+
+        let mut units = 0;
+        let mut batch_price = price as f64;
+        while units < max_units {
+            if (rising && batch_price > limit) || (!rising && batch_price < limit) {
+                break;
+            }
+            units += trade_volume.min(max_units - units);
+            batch_price *= factor;
+        }
+        units
+    }
+
+    /// the expected price after `hours` without trades, a displaced price moves back to the baseline
+    pub fn recovered_price(&self, price: i32, hours: f64) -> i32 {
+        let Some(baseline) = self.baseline else {
+            return price;
+        };
+        let remaining = (1.0 - self.recovery_per_hour).powf(hours.max(0.0));
+        (baseline + (price as f64 - baseline) * remaining).round() as i32
+    }
+
+    fn batch_factor(&self, trade_volume: i32) -> f64 {
+        (1.0 + self.per_unit * trade_volume as f64).max(0.0)
+    }
+}
+
+/// Per market and good price response learned from our `MarketTransaction`s and successive `MarketTradeGood` snapshots.
+/// Markets without history fall back to the configured impact and don't recover.
+#[derive(Debug, Clone, Default)]
+pub struct PriceModel {
+    impacts: HashMap<ImpactKey, PriceImpact>,
+    fallback_per_unit: f64,
+}
+
+impl PriceModel {
+    pub async fn load(
+        database_pool: &database::DbPool,
+        system_symbol: &str,
+        fallback_per_unit: f64,
+    ) -> Result<PriceModel> {
+        let since = chrono::Utc::now() - chrono::TimeDelta::hours(HISTORY_HOURS);
+        let transactions =
+            database::MarketTransaction::get_by_system_since(database_pool, system_symbol, since)
+                .await?;
+        let snapshots = database::MarketTradeGood::get_history_by_system_since(
+            database_pool,
+            system_symbol,
+            since,
+        )
+        .await?;
+
+        Ok(Self::learn(&transactions, &snapshots, fallback_per_unit))
+    }
+
+    /// `transactions` and `snapshots` have to be ordered by time
+    pub fn learn(
+        transactions: &[database::MarketTransaction],
+        snapshots: &[database::MarketTradeGood],
+        fallback_per_unit: f64,
+    ) -> PriceModel {
+        let mut by_market: HashMap<ImpactKey, Vec<&database::MarketTransaction>> = HashMap::new();
+        for transaction in transactions {
+            by_market
+                .entry((
+                    transaction.waypoint_symbol.clone(),
+                    transaction.trade_symbol,
+                    transaction.r#type,
+                ))
+                .or_default()
+                .push(transaction);
+        }
+
+        let mut impacts: HashMap<ImpactKey, PriceImpact> = HashMap::new();
+        for (key, transactions) in by_market {
+            let samples = transactions
+                .windows(2)
+                .filter(|w| {
+                    w[0].ship_symbol == w[1].ship_symbol
+                        && (w[1].timestamp - w[0].timestamp).num_seconds() <= BATCH_WINDOW_SECONDS
+                        && w[0].price_per_unit > 0
+                        && w[0].units > 0
+                })
+                .map(|w| {
+                    (w[1].price_per_unit - w[0].price_per_unit) as f64
+                        / w[0].price_per_unit as f64
+                        / w[0].units as f64
+                })
+                .collect::<Vec<_>>();
+            if samples.is_empty() {
+                continue;
+            }
+            let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+            // noise must not turn purchases cheaper or sales more expensive
+            let per_unit = match key.2 {
+                models::market_transaction::Type::Purchase => mean.max(0.0),
+                models::market_transaction::Type::Sell => mean.min(0.0),
+            };
+            impacts.insert(
+                key,
+                PriceImpact {
+                    per_unit,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut history: HashMap<(String, models::TradeSymbol), Vec<&database::MarketTradeGood>> =
+            HashMap::new();
+        for snapshot in snapshots {
+            history
+                .entry((snapshot.waypoint_symbol.clone(), snapshot.symbol))
+                .or_default()
+                .push(snapshot);
+        }
+
+        for ((waypoint_symbol, symbol), snapshots) in history {
+            for r#type in [
+                models::market_transaction::Type::Purchase,
+                models::market_transaction::Type::Sell,
+            ] {
+                let prices = snapshots
+                    .iter()
+                    .map(|s| {
+                        let price = match r#type {
+                            models::market_transaction::Type::Purchase => s.purchase_price,
+                            models::market_transaction::Type::Sell => s.sell_price,
+                        };
+                        (s.created, price as f64)
+                    })
+                    .collect::<Vec<_>>();
+                let Some((baseline, recovery)) = Self::learn_recovery(&prices) else {
+                    continue;
+                };
+                let impact = impacts
+                    .entry((waypoint_symbol.clone(), symbol, r#type))
+                    .or_insert(PriceImpact {
+                        per_unit: match r#type {
+                            models::market_transaction::Type::Purchase => fallback_per_unit,
+                            models::market_transaction::Type::Sell => -fallback_per_unit,
+                        },
+                        ..Default::default()
+                    });
+                impact.baseline = Some(baseline);
+                impact.recovery_per_hour = recovery;
+            }
+        }
+
+        PriceModel {
+            impacts,
+            fallback_per_unit,
+        }
+    }
+
+    /// The median price and the mean share of a displacement from it recovered per hour
+    fn learn_recovery(prices: &[(chrono::DateTime<chrono::Utc>, f64)]) -> Option<(f64, f64)> {
+        if prices.len() < 3 {
+            return None;
+        }
+        let mut sorted = prices.iter().map(|(_, p)| *p).collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let baseline = sorted[sorted.len() / 2];
+
+        let rates = prices
+            .windows(2)
+            .filter_map(|w| {
+                let hours = (w[1].0 - w[0].0).num_seconds() as f64 / 3600.0;
+                let before = w[0].1 - baseline;
+                let after = w[1].1 - baseline;
+                // only steps moving back towards the baseline without crossing it tell the speed
+                if hours <= 0.0 || before == 0.0 || before.signum() != after.signum() {
+                    return None;
+                }
+                if after.abs() >= before.abs() {
+                    return None;
+                }
+                Some((before.abs() / after.abs()).ln() / hours)
+            })
+            .collect::<Vec<_>>();
+        if rates.is_empty() {
+            return None;
+        }
+        let rate = rates.iter().sum::<f64>() / rates.len() as f64;
+        Some((baseline, 1.0 - (-rate).exp()))
+    }
+
+    pub fn purchase_impact(
+        &self,
+        waypoint_symbol: &str,
+        symbol: models::TradeSymbol,
+    ) -> PriceImpact {
+        self.impact(
+            waypoint_symbol,
+            symbol,
+            models::market_transaction::Type::Purchase,
+        )
+    }
+
+    pub fn sell_impact(&self, waypoint_symbol: &str, symbol: models::TradeSymbol) -> PriceImpact {
+        self.impact(
+            waypoint_symbol,
+            symbol,
+            models::market_transaction::Type::Sell,
+        )
+    }
+
+    fn impact(
+        &self,
+        waypoint_symbol: &str,
+        symbol: models::TradeSymbol,
+        r#type: models::market_transaction::Type,
+    ) -> PriceImpact {
+        self.impacts
+            .get(&(waypoint_symbol.to_string(), symbol, r#type))
+            .copied()
+            .unwrap_or(PriceImpact {
+                per_unit: match r#type {
+                    models::market_transaction::Type::Purchase => self.fallback_per_unit,
+                    models::market_transaction::Type::Sell => -self.fallback_per_unit,
+                },
+                ..Default::default()
+            })
+    }
+
+    /// The units to buy and sell on a route, batches are added as long as the next one still makes a profit
+    #[allow(clippy::too_many_arguments)]
+    pub fn route_volume(
+        purchase: &PriceImpact,
+        purchase_price: i32,
+        purchase_trade_volume: i32,
+        sell: &PriceImpact,
+        sell_price: i32,
+        sell_trade_volume: i32,
+        max_units: i32,
+    ) -> i32 {
+        let step = purchase_trade_volume.max(1).min(sell_trade_volume.max(1));
+        let mut units = 0;
+        while units < max_units {
+            let purchase_next = purchase.price_after(purchase_price, units, purchase_trade_volume);
+            let sell_next = sell.price_after(sell_price, units, sell_trade_volume);
+            if sell_next <= purchase_next {
+                break;
+            }
+            units += step.min(max_units - units);
+        }
+        units
+    }
+}
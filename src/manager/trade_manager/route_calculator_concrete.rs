@@ -2,7 +2,13 @@ use std::collections::HashMap;
 
 use crate::utils::ConductorContext;
 
-use super::routes::{ConcreteTradeRoute, ExtrapolatedTradeRoute, TripStats};
+use super::{
+    price_model::PriceModel,
+    routes::{
+        ConcreteTradeRoute, ExtrapolatedTradeRoute, MARKET_VISIT_REQUESTS, RouteStats, TripStats,
+        trade_batches,
+    },
+};
 
 #[derive(Debug)]
 pub struct ConcreteRouteCalculator {
@@ -13,6 +19,7 @@ impl ConcreteRouteCalculator {
     pub fn new(context: ConductorContext) -> Self {
         Self { context }
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn calc(
         &mut self,
         ship: &ship::MyShipCopy,
@@ -21,6 +28,7 @@ impl ConcreteRouteCalculator {
         fuel_cost: i32,
         antimatter_price: i32,
         purchase_multiplier: f64,
+        price_model: &PriceModel,
     ) -> ConcreteTradeRoute {
         let max_transport = ship.cargo.capacity;
 
@@ -35,21 +43,42 @@ impl ConcreteRouteCalculator {
             antimatter_price,
         );
 
+        let max_volume = ship
+            .cargo
+            .capacity
+            .min((trade_route.data.min_trade_volume as f64 * purchase_multiplier) as i32);
+        let totals = self.calculate_trade_totals(
+            &trade_route,
+            price_model,
+            max_volume,
+            route_stats.travel_time,
+        );
+        if totals.volume > 0 {
+            // the route is priced with the average prices of the whole volume
+            trade_route.data.purchase_price = (totals.purchase / totals.volume as i64) as i32;
+            trade_route.data.sell_price = (totals.sale / totals.volume as i64) as i32;
+            trade_route.data.profit = trade_route.data.sell_price - trade_route.data.purchase_price;
+        }
+
+        // a visit at both markets, the trades are split into batches of the market trade volumes
+        let trade_requests = 2 * MARKET_VISIT_REQUESTS
+            + trade_batches(totals.volume, totals.purchase_trade_volume)
+            + trade_batches(totals.volume, totals.sell_trade_volume);
+
         let trip_stats = if true {
             self.calculate_reoccurring_trip_stats(
                 ship,
-                &trade_route,
+                &totals,
                 route_stats.distance,
                 route_stats.fuel_cost,
                 route_stats.travel_time,
                 fuel_cost,
-                purchase_multiplier,
-                route_stats.api_requests,
+                route_stats.api_requests * 2 + trade_requests,
             )
         } else {
             self.calculate_trip_stats(
                 ship,
-                &trade_route,
+                &totals,
                 route_stats.distance,
                 route_stats.fuel_cost,
                 route_stats.travel_time,
@@ -57,8 +86,7 @@ impl ConcreteRouteCalculator {
                 route_to_stats.fuel_cost,
                 route_to_stats.travel_time,
                 fuel_cost,
-                purchase_multiplier,
-                route_stats.api_requests,
+                route_stats.api_requests + route_to_stats.api_requests + trade_requests,
             )
         };
 
@@ -102,6 +130,34 @@ impl ConcreteRouteCalculator {
         )
     }
 
+    /// The travel between two waypoints in the system, `None` when there is no route
+    pub fn travel_stats(
+        &mut self,
+        ship: &ship::MyShipCopy,
+        waypoints: &HashMap<String, database::Waypoint>,
+        from_wp_symbol: &str,
+        to_wp_symbol: &str,
+        fuel_cost: i32,
+        antimatter_price: i32,
+    ) -> Option<RouteStats> {
+        if from_wp_symbol == to_wp_symbol {
+            return Some(RouteStats::default());
+        }
+        let route = self
+            .find_route(ship, waypoints, from_wp_symbol, to_wp_symbol)
+            .ok()?;
+        let route = ship
+            .assemble_simple_route(&route, fuel_cost, antimatter_price)
+            .ok()?;
+
+        Some(RouteStats {
+            fuel_cost: route.total_fuel_cost as i32,
+            travel_time: route.total_travel_time,
+            distance: route.total_distance,
+            api_requests: route.total_api_requests,
+        })
+    }
+
     fn create_concrete_route(
         &self,
         trade_route: ExtrapolatedTradeRoute,
@@ -111,6 +167,7 @@ impl ConcreteRouteCalculator {
             route: trade_route.route,
             data: trade_route.data,
             trip: trip_stats,
+            balance: 0,
         }
     }
 
@@ -129,33 +186,30 @@ impl ConcreteRouteCalculator {
         Ok(route)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn calculate_reoccurring_trip_stats(
         &self,
         ship: &ship::MyShipCopy,
-        trade_route: &ExtrapolatedTradeRoute,
+        totals: &TradeTotals,
         total_distance: f64,
         total_fuel_cost: i32,
         total_travel_time: f64,
         fuel_cost: i32,
-        purchase_multiplier: f64,
         api_requests: i32,
     ) -> TripStats {
         let trip_fuel_cost = (total_fuel_cost * 2) / 100 * fuel_cost;
 
-        let trip_volume = ship
-            .cargo
-            .capacity
-            .min((trade_route.data.min_trade_volume as f64 * purchase_multiplier) as i32);
+        let trip_volume = totals.volume;
 
-        let trip_total_cost = trade_route.data.purchase_price * trip_volume + trip_fuel_cost;
-        let trip_total_profit = trade_route.data.sell_price * trip_volume - trip_total_cost;
+        let trip_total_cost = totals.purchase + trip_fuel_cost as i64;
+        let trip_total_profit = totals.sale - trip_total_cost;
 
         let trip_per_hour = (total_travel_time * 1000.0 * 2.0)
             / (chrono::TimeDelta::hours(1).num_milliseconds()) as f64;
 
         let profit_per_hour = trip_total_profit as f64 / trip_per_hour;
 
-        let profit_per_api_request = trip_total_profit / api_requests;
+        let profit_per_api_request = trip_total_profit / api_requests.max(1) as i64;
 
         TripStats {
             time: total_travel_time * 2.0,
@@ -166,17 +220,18 @@ impl ConcreteRouteCalculator {
             total_cost: trip_total_cost,
             total_profit: trip_total_profit,
             trips_per_hour: trip_per_hour as f32,
-            profit_per_hour: profit_per_hour as i32,
+            profit_per_hour: profit_per_hour as i64,
             ship_symbol: ship.symbol.clone(),
             distance: total_distance,
             profit_per_api_request,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn calculate_trip_stats(
         &self,
         ship: &ship::MyShipCopy,
-        trade_route: &ExtrapolatedTradeRoute,
+        totals: &TradeTotals,
         total_distance: f64,
         total_fuel_cost: i32,
         total_travel_time: f64,
@@ -184,30 +239,26 @@ impl ConcreteRouteCalculator {
         total_fuel_cost_to: i32,
         total_travel_time_to: f64,
         fuel_cost: i32,
-        purchase_multiplier: f64,
         api_requests: i32,
     ) -> TripStats {
         let trip_fuel_cost = (total_fuel_cost * total_fuel_cost_to) / 100 * fuel_cost;
 
-        let trip_volume = ship
-            .cargo
-            .capacity
-            .min((trade_route.data.min_trade_volume as f64 * purchase_multiplier) as i32);
+        let trip_volume = totals.volume;
 
-        let trip_total_cost = trade_route.data.purchase_price * trip_volume + trip_fuel_cost;
-        let trip_total_profit = trade_route.data.sell_price * trip_volume - trip_total_cost;
+        let trip_total_cost = totals.purchase + trip_fuel_cost as i64;
+        let trip_total_profit = totals.sale - trip_total_cost;
 
         let trip_per_hour = (total_travel_time * 1000.0 + total_travel_time_to * 1000.0)
             / (chrono::TimeDelta::hours(1).num_milliseconds()) as f64;
 
         let profit_per_hour = trip_total_profit as f64 / trip_per_hour;
 
-        let profit_per_api_request = trip_total_profit / api_requests;
+        let profit_per_api_request = trip_total_profit / api_requests.max(1) as i64;
 
         TripStats {
             ship_symbol: ship.symbol.clone(),
             trips_per_hour: trip_per_hour as f32,
-            profit_per_hour: profit_per_hour as i32,
+            profit_per_hour: profit_per_hour as i64,
             fuel_units: total_fuel_cost + total_fuel_cost_to,
             fuel_cost: trip_fuel_cost,
             api_requests,
@@ -220,6 +271,67 @@ impl ConcreteRouteCalculator {
         }
     }
 
+    /// The units worth trading on one trip and what buying and selling them costs and earns.
+    /// Displaced prices recover until the ship arrives, every batch moves the price further.
+    fn calculate_trade_totals(
+        &self,
+        trade_route: &ExtrapolatedTradeRoute,
+        price_model: &PriceModel,
+        max_volume: i32,
+        travel_time: f64,
+    ) -> TradeTotals {
+        let route = &trade_route.route;
+        let purchase = price_model.purchase_impact(&route.purchase.waypoint_symbol, route.symbol);
+        let sell = price_model.sell_impact(&route.sell.waypoint_symbol, route.symbol);
+
+        let now = chrono::Utc::now();
+        let hours_since =
+            |created: chrono::DateTime<chrono::Utc>| (now - created).num_seconds() as f64 / 3600.0;
+
+        let (purchase_price, purchase_trade_volume) = match route.purchase_good.as_ref() {
+            Some(good) => (
+                purchase
+                    .recovered_price(trade_route.data.purchase_price, hours_since(good.created)),
+                good.trade_volume,
+            ),
+            None => (
+                trade_route.data.purchase_price,
+                trade_route.data.min_trade_volume,
+            ),
+        };
+        let (sell_price, sell_trade_volume) = match route.sell_good.as_ref() {
+            Some(good) => (
+                sell.recovered_price(
+                    trade_route.data.sell_price,
+                    hours_since(good.created) + travel_time / 3600.0,
+                ),
+                good.trade_volume,
+            ),
+            None => (
+                trade_route.data.sell_price,
+                trade_route.data.min_trade_volume,
+            ),
+        };
+
+        let volume = PriceModel::route_volume(
+            &purchase,
+            purchase_price,
+            purchase_trade_volume,
+            &sell,
+            sell_price,
+            sell_trade_volume,
+            max_volume,
+        );
+
+        TradeTotals {
+            volume,
+            purchase_trade_volume,
+            sell_trade_volume,
+            purchase: purchase.total_price(purchase_price, volume, purchase_trade_volume),
+            sale: sell.total_price(sell_price, volume, sell_trade_volume),
+        }
+    }
+
     fn calculate_single_route_stats(
         &self,
         ship: &ship::MyShipCopy,
@@ -241,9 +353,10 @@ impl ConcreteRouteCalculator {
 }
 
 #[derive(Debug)]
-struct RouteStats {
-    fuel_cost: i32,
-    travel_time: f64,
-    distance: f64,
-    api_requests: i32,
+struct TradeTotals {
+    volume: i32,
+    purchase_trade_volume: i32,
+    sell_trade_volume: i32,
+    purchase: i64,
+    sale: i64,
 }
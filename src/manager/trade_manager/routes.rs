@@ -118,6 +118,17 @@ impl fmt::Display for ExtrapolatedTradeRoute {
     }
 }
 
+/// the requests of one market visit besides the trades, docking and refreshing the market
+pub const MARKET_VISIT_REQUESTS: i32 = 2;
+
+/// the purchase or sell requests needed for `volume` units, a market trades at most `trade_volume` units per request
+pub fn trade_batches(volume: i32, trade_volume: i32) -> i32 {
+    if volume <= 0 {
+        return 0;
+    }
+    (volume as u32).div_ceil(trade_volume.max(1) as u32) as i32
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct TripStats {
     pub ship_symbol: String,
@@ -130,12 +141,12 @@ pub struct TripStats {
     pub volume: i32,
 
     pub fuel_cost: i32,
-    pub total_cost: i32,
-    pub total_profit: i32,
+    pub total_cost: i64,
+    pub total_profit: i64,
 
     pub trips_per_hour: f32,
-    pub profit_per_hour: i32,
-    pub profit_per_api_request: i32,
+    pub profit_per_hour: i64,
+    pub profit_per_api_request: i64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -145,44 +156,36 @@ pub struct ConcreteTradeRoute {
     pub data: RouteData,
 
     pub trip: TripStats,
+
+    /// how the trip moves the supply of both markets towards healthy levels, used by `TradeMode::MarketBalanced`
+    pub balance: i32,
 }
 impl ConcreteTradeRoute {
     pub(crate) fn compare(
         &self,
         other: &ConcreteTradeRoute,
         mode: database::TradeMode,
-    ) -> Option<std::cmp::Ordering> {
+    ) -> std::cmp::Ordering {
         let cmp = self.route.cmp(&other.route).reverse();
         if cmp != Ordering::Equal {
-            Some(cmp)
+            cmp
         } else {
             match mode {
                 database::TradeMode::ProfitPerHour => {
-                    Some(self.trip.profit_per_hour.cmp(&other.trip.profit_per_hour))
+                    self.trip.profit_per_hour.cmp(&other.trip.profit_per_hour)
                 }
-                database::TradeMode::ProfitPerAPIRequest => None,
+                database::TradeMode::ProfitPerAPIRequest => self
+                    .trip
+                    .profit_per_api_request
+                    .cmp(&other.trip.profit_per_api_request)
+                    .then(self.trip.profit_per_hour.cmp(&other.trip.profit_per_hour)),
                 database::TradeMode::ProfitPerTrip => {
-                    Some(self.trip.total_profit.cmp(&other.trip.total_profit))
-                }
-                database::TradeMode::MarketBalanced => {
-                    // TODO
-                    // prefer trades that trade from high supply to low supply the higher difference the better
-
-                    // better if sell pw exporting
-                    // better if buy pw importing
-
-                    let purchase_good_a = self.route.purchase_good.as_ref().unwrap();
-                    let purchase_good_b = other.route.purchase_good.as_ref().unwrap();
-                    let sell_good_a = self.route.sell_good.as_ref().unwrap();
-                    let sell_good_b = other.route.sell_good.as_ref().unwrap();
-
-                    // todo calculate divv via the lenght not of a linear function but a curve to make it better to fill up scarce markets
-                    let route_a_diff =
-                        (purchase_good_a.supply as i32) - (sell_good_a.supply as i32);
-                    let route_b_diff =
-                        (purchase_good_b.supply as i32) - (sell_good_b.supply as i32);
-                    Some(route_a_diff.cmp(&route_b_diff))
+                    self.trip.total_profit.cmp(&other.trip.total_profit)
                 }
+                database::TradeMode::MarketBalanced => self
+                    .balance
+                    .cmp(&other.balance)
+                    .then(self.trip.profit_per_hour.cmp(&other.trip.profit_per_hour)),
             }
         }
     }
@@ -196,7 +199,7 @@ impl From<ConcreteTradeRoute> for database::TradeRoute {
             ship_symbol: value.trip.ship_symbol,
             predicted_purchase_price: value.data.purchase_price,
             predicted_sell_price: value.data.sell_price,
-            trade_volume: value.data.max_trade_volume,
+            trade_volume: value.trip.volume,
             purchase_waypoint: value.route.purchase.waypoint_symbol,
             sell_waypoint: value.route.sell.waypoint_symbol,
             ..Default::default()
@@ -228,3 +231,132 @@ impl fmt::Display for ConcreteTradeRoute {
         )
     }
 }
+
+/// The travel between two waypoints, fuel is in units
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RouteStats {
+    pub fuel_cost: i32,
+    pub travel_time: f64,
+    pub distance: f64,
+    pub api_requests: i32,
+}
+
+/// A good carried on one leg of a trade plan and the units planned for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedGood {
+    pub route: ConcreteTradeRoute,
+    pub volume: i32,
+}
+
+/// One leg of a trade plan, several goods bought at one market and sold at the next
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeLeg {
+    pub purchase_wp_symbol: String,
+    pub sell_wp_symbol: String,
+    pub goods: Vec<PlannedGood>,
+    pub travel: RouteStats,
+}
+
+impl TradeLeg {
+    /// the profit of the goods without the fuel
+    pub fn goods_profit(&self) -> i32 {
+        self.goods
+            .iter()
+            .map(|g| g.route.data.profit * g.volume)
+            .sum()
+    }
+
+    pub fn min_routes(&self) -> Vec<MinTradeRoute> {
+        self.goods.iter().map(|g| g.route.clone().into()).collect()
+    }
+}
+
+/// A chain of legs A -> B -> C -> ..., the ship first flies empty to the start of the first leg
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConcreteTradePlan {
+    pub legs: Vec<TradeLeg>,
+    pub trip: TripStats,
+    /// the summed market balance of all goods for their planned volumes, used by `TradeMode::MarketBalanced`
+    pub balance: i32,
+}
+
+impl ConcreteTradePlan {
+    pub(crate) fn compare(
+        &self,
+        other: &ConcreteTradePlan,
+        mode: database::TradeMode,
+    ) -> std::cmp::Ordering {
+        match mode {
+            database::TradeMode::ProfitPerHour => {
+                self.trip.profit_per_hour.cmp(&other.trip.profit_per_hour)
+            }
+            database::TradeMode::ProfitPerAPIRequest => self
+                .trip
+                .profit_per_api_request
+                .cmp(&other.trip.profit_per_api_request),
+            database::TradeMode::ProfitPerTrip => {
+                self.trip.total_profit.cmp(&other.trip.total_profit)
+            }
+            database::TradeMode::MarketBalanced => self
+                .balance
+                .cmp(&other.balance)
+                .then(self.trip.profit_per_hour.cmp(&other.trip.profit_per_hour)),
+        }
+    }
+
+    pub fn min_routes(&self) -> Vec<MinTradeRoute> {
+        self.legs.iter().flat_map(|l| l.min_routes()).collect()
+    }
+
+    /// the trade routes of all goods together with the index of their leg
+    pub fn trade_routes(&self) -> Vec<(i32, database::TradeRoute)> {
+        self.legs
+            .iter()
+            .enumerate()
+            .flat_map(|(index, leg)| {
+                leg.goods.iter().map(move |good| {
+                    let route = database::TradeRoute {
+                        trade_volume: good.volume,
+                        ..good.route.clone().into()
+                    };
+                    (index as i32, route)
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<&ConcreteTradePlan> for database::TradePlan {
+    fn from(value: &ConcreteTradePlan) -> Self {
+        database::TradePlan {
+            ship_symbol: value.trip.ship_symbol.clone(),
+            status: database::ShipmentStatus::InTransit,
+            // the database column is an i32, a profit beyond it is stored as the limit
+            predicted_profit: value
+                .trip
+                .total_profit
+                .clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for ConcreteTradePlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.trip.ship_symbol)?;
+        for leg in &self.legs {
+            write!(
+                f,
+                " {} -> {} ({})",
+                leg.purchase_wp_symbol,
+                leg.sell_wp_symbol,
+                leg.goods
+                    .iter()
+                    .map(|g| format!("{} {}", g.volume, g.route.route.symbol))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        write!(f, " {}/h", self.trip.profit_per_hour)
+    }
+}
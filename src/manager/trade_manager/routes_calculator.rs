@@ -1,19 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use space_traders_client::models;
 
 use crate::{error::Error, utils::ConductorContext};
 
 use super::{
+    market_balance::MarketBalance,
+    price_model::{PRICE_MODEL_TTL, PriceModel},
     route_calculator_concrete::ConcreteRouteCalculator,
-    routes::{ExtrapolatedTradeRoute, PossibleTradeRoute, RouteData},
+    routes::{
+        ConcreteTradePlan, ConcreteTradeRoute, ExtrapolatedTradeRoute, PossibleTradeRoute,
+        RouteData,
+    },
     routes_tracker::RoutesTracker,
+    trade_planner::TradePlanner,
 };
 
+type GoodPricePriors =
+    HashMap<(models::TradeSymbol, models::market_trade_good::Type), database::GoodPricePrior>;
+
 #[derive(Debug)]
 pub struct RouteCalculator {
     context: ConductorContext,
     concrete: ConcreteRouteCalculator,
+    /// the last learned price model per system and when it was learned
+    price_models: HashMap<String, (chrono::DateTime<chrono::Utc>, PriceModel)>,
+    /// the average prices of the previous reset, loaded once
+    price_priors: Option<GoodPricePriors>,
 }
 
 impl RouteCalculator {
@@ -21,6 +34,8 @@ impl RouteCalculator {
         Self {
             context: context.clone(),
             concrete: ConcreteRouteCalculator::new(context.clone()),
+            price_models: HashMap::new(),
+            price_priors: None,
         }
     }
 
@@ -37,10 +52,127 @@ impl RouteCalculator {
         trading_config: database::TradingFleetConfig,
     ) -> Result<Option<database::TradeRoute>, Error> {
         tracing::debug!("Getting new best route");
+        let routes = self
+            .get_concrete_routes(ship, &trading_config)
+            .await?
+            .into_iter()
+            .filter(|route| route.trip.total_profit > trading_config.trade_profit_threshold as i64)
+            .collect::<Vec<_>>();
+
+        tracing::debug!(routes_len = %routes.len(), "Calculated routes");
+        // tracing::debug!(routes = ?routes, "Routes detail");
+
+        let route = routes
+            .into_iter()
+            .filter(|route| !running_routes.is_locked(&(*route).clone().into()))
+            .max_by(|a, b| a.compare(b, trading_config.trade_mode));
+
+        Ok(route.map(|route| route.into()))
+    }
+
+    /// The best chain of up to `max_legs` legs with several goods each, only markets with known prices are used
+    pub async fn get_best_plan(
+        &mut self,
+        ship: &ship::MyShipCopy,
+        running_routes: &RoutesTracker,
+        trading_config: database::TradingFleetConfig,
+        max_legs: usize,
+    ) -> Result<Option<ConcreteTradePlan>, Error> {
+        tracing::debug!("Getting new best trade plan");
+        let routes = self
+            .get_concrete_routes(ship, &trading_config)
+            .await?
+            .into_iter()
+            .filter(|route| {
+                route.route.purchase_good.is_some()
+                    && route.route.sell_good.is_some()
+                    && route.trip.volume > 0
+                    && !running_routes.is_locked(&route.clone().into())
+            })
+            .collect::<Vec<_>>();
+
+        let waypoints = database::Waypoint::get_by_system(
+            &self.context.database_pool,
+            &ship.nav.system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items
+        .into_iter()
+        .map(|w| (w.symbol.clone(), w))
+        .collect::<HashMap<_, _>>();
+
+        let config = { self.context.config.read().await.clone() };
+
+        let legs = TradePlanner::build_legs(routes, ship.cargo.capacity, |from, to| {
+            self.concrete.travel_stats(
+                ship,
+                &waypoints,
+                from,
+                to,
+                config.fuel_cost,
+                config.antimatter_price,
+            )
+        });
+
+        let approach = legs
+            .keys()
+            .filter_map(|start| {
+                self.concrete
+                    .travel_stats(
+                        ship,
+                        &waypoints,
+                        &ship.nav.waypoint_symbol,
+                        start,
+                        config.fuel_cost,
+                        config.antimatter_price,
+                    )
+                    .map(|stats| (start.clone(), stats))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let planner = TradePlanner::new(
+            ship.symbol.clone(),
+            max_legs,
+            config.fuel_cost,
+            trading_config.trade_mode,
+        );
+
+        let plan = planner
+            .best_plan(&legs, &approach)
+            .filter(|plan| plan.trip.total_profit > trading_config.trade_profit_threshold as i64);
+
+        tracing::debug!(plan = ?plan.as_ref().map(|p| p.to_string()), "Calculated trade plan");
+
+        Ok(plan)
+    }
+
+    /// All single good routes in the system of the ship, extrapolated where prices are unknown
+    async fn get_concrete_routes(
+        &mut self,
+        ship: &ship::MyShipCopy,
+        trading_config: &database::TradingFleetConfig,
+    ) -> Result<Vec<ConcreteTradeRoute>, Error> {
         let (trade_goods, market_trade) = self.fetch_market_data(&ship.nav.system_symbol).await?;
 
         let possible_trades = self.gen_all_possible_trades(&trade_goods, &market_trade);
 
+        // no pair of markets with known prices can be traded with a profit for these goods,
+        // balancing markets trades at a loss on purpose and keeps them
+        let unprofitable = if trading_config.trade_mode == database::TradeMode::MarketBalanced {
+            HashSet::new()
+        } else {
+            Self::unprofitable_goods(&trade_goods)
+        };
+        let possible_trades = possible_trades
+            .into_iter()
+            .filter(|route| {
+                !(unprofitable.contains(&route.symbol)
+                    && route.purchase_good.is_some()
+                    && route.sell_good.is_some())
+            })
+            .collect::<Vec<_>>();
+
         let waypoints = database::Waypoint::get_by_system(
             &self.context.database_pool,
             &ship.nav.system_symbol,
@@ -51,11 +183,24 @@ impl RouteCalculator {
 
         let config = { self.context.config.read().await.clone() };
 
+        let price_model = self
+            .get_price_model(&ship.nav.system_symbol, config.price_impact_fallback as f64)
+            .await?;
+
+        let price_priors = self.get_price_priors().await?.clone();
+
+        let balance = if trading_config.trade_mode == database::TradeMode::MarketBalanced {
+            MarketBalance::load(&self.context.database_pool, &trade_goods).await?
+        } else {
+            MarketBalance::default()
+        };
+
         let routes = possible_trades
             .into_iter()
             .map(|route| {
                 self.extrapolate_trade_route(
                     route,
+                    &price_priors,
                     config.markup_percentage,
                     config.margin_percentage,
                     config.default_purchase_price,
@@ -74,20 +219,79 @@ impl RouteCalculator {
                     config.fuel_cost,
                     config.antimatter_price,
                     trading_config.purchase_multiplier,
+                    &price_model,
                 )
             })
-            .filter(|route| route.trip.total_profit > trading_config.trade_profit_threshold)
+            .map(|mut route| {
+                route.balance = balance.score(&route);
+                route
+            })
             .collect::<Vec<_>>();
 
-        tracing::debug!(routes_len = %routes.len(), "Calculated routes");
-        // tracing::debug!(routes = ?routes, "Routes detail");
+        Ok(routes)
+    }
 
-        let route = routes
-            .into_iter()
-            .filter(|route| !running_routes.is_locked(&(*route).clone().into()))
-            .max_by(|a, b| a.compare(b, trading_config.trade_mode).unwrap());
+    async fn get_price_model(
+        &mut self,
+        system_symbol: &str,
+        fallback_per_unit: f64,
+    ) -> Result<PriceModel, Error> {
+        let now = chrono::Utc::now();
+        if let Some((learned_at, model)) = self.price_models.get(system_symbol)
+            && (now - *learned_at).num_seconds() < PRICE_MODEL_TTL
+        {
+            return Ok(model.clone());
+        }
 
-        Ok(route.map(|route| route.into()))
+        let model = PriceModel::load(
+            &self.context.database_pool,
+            system_symbol,
+            fallback_per_unit,
+        )
+        .await?;
+        self.price_models
+            .insert(system_symbol.to_string(), (now, model.clone()));
+        Ok(model)
+    }
+
+    async fn get_price_priors(&mut self) -> Result<&GoodPricePriors, Error> {
+        if self.price_priors.is_none() {
+            let reset_date = { self.context.run_info.read().await.reset_date };
+            let priors =
+                database::ResetPriors::get_latest_before(&self.context.database_pool, reset_date)
+                    .await?
+                    .map(|priors| priors.good_prices)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|prior| ((prior.symbol, prior.trade_type), prior))
+                    .collect::<GoodPricePriors>();
+            tracing::debug!(
+                priors = priors.len(),
+                "Loaded price priors of the previous reset"
+            );
+            self.price_priors = Some(priors);
+        }
+
+        Ok(self.price_priors.get_or_insert_default())
+    }
+
+    /// The goods whose best sell price in the system is not above their best purchase price
+    fn unprofitable_goods(
+        trade_goods: &[database::MarketTradeGood],
+    ) -> HashSet<models::TradeSymbol> {
+        let mut spreads: HashMap<models::TradeSymbol, (i32, i32)> = HashMap::new();
+        for good in trade_goods {
+            let (purchase, sell) = spreads
+                .entry(good.symbol)
+                .or_insert((good.purchase_price, good.sell_price));
+            *purchase = (*purchase).min(good.purchase_price);
+            *sell = (*sell).max(good.sell_price);
+        }
+        spreads
+            .into_iter()
+            .filter(|(_, (purchase, sell))| sell <= purchase)
+            .map(|(symbol, _)| symbol)
+            .collect()
     }
 
     async fn fetch_market_data(
@@ -147,41 +351,40 @@ impl RouteCalculator {
             .collect::<Vec<_>>()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn extrapolate_trade_route(
         &self,
         route: PossibleTradeRoute,
+        price_priors: &GoodPricePriors,
         markup_percentage: f32,
         margin_percentage: f32,
         default_purchase_price: i32,
         default_sell_price: i32,
         default_profit: i32,
     ) -> ExtrapolatedTradeRoute {
+        // markets without fresh data fall back to what the same kind of market did in the previous reset
+        let purchase_prior = price_priors.get(&(route.symbol, route.purchase.r#type));
+        let sell_prior = price_priors.get(&(route.symbol, route.sell.r#type));
+
+        let purchase_volume = route
+            .purchase_good
+            .as_ref()
+            .map(|t| t.trade_volume)
+            .or(purchase_prior.map(|p| p.avg_trade_volume.round() as i32));
+        let sell_volume = route
+            .sell_good
+            .as_ref()
+            .map(|t| t.trade_volume)
+            .or(sell_prior.map(|p| p.avg_trade_volume.round() as i32));
+
         let (min_trade_volume, max_trade_volume) = {
-            let min_volume = route
-                .purchase_good
-                .as_ref()
-                .map(|t| t.trade_volume)
+            let min_volume = purchase_volume
                 .unwrap_or(i32::MAX)
-                .min(
-                    route
-                        .sell_good
-                        .as_ref()
-                        .map(|t| t.trade_volume)
-                        .unwrap_or(i32::MAX),
-                );
+                .min(sell_volume.unwrap_or(i32::MAX));
 
-            let max_volume = route
-                .purchase_good
-                .as_ref()
-                .map(|t| t.trade_volume)
+            let max_volume = purchase_volume
                 .unwrap_or(i32::MIN)
-                .max(
-                    route
-                        .sell_good
-                        .as_ref()
-                        .map(|t| t.trade_volume)
-                        .unwrap_or(i32::MIN),
-                );
+                .max(sell_volume.unwrap_or(i32::MIN));
 
             (
                 if min_volume == i32::MAX {
@@ -199,8 +402,16 @@ impl RouteCalculator {
 
         // Constants for price calculations
 
-        let purchase_price: Option<i32> = route.purchase_good.as_ref().map(|t| t.purchase_price);
-        let sell_price: Option<i32> = route.sell_good.as_ref().map(|t| t.sell_price);
+        let purchase_price: Option<i32> = route
+            .purchase_good
+            .as_ref()
+            .map(|t| t.purchase_price)
+            .or(purchase_prior.map(|p| p.avg_purchase_price.round() as i32));
+        let sell_price: Option<i32> = route
+            .sell_good
+            .as_ref()
+            .map(|t| t.sell_price)
+            .or(sell_prior.map(|p| p.avg_sell_price.round() as i32));
 
         let (final_purchase_price, final_sell_price, final_profit) =
             match (purchase_price, sell_price) {
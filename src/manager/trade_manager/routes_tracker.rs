@@ -21,7 +21,7 @@ pub struct RoutesTracker {
 impl RoutesTracker {
     pub fn lock(&mut self, route: &MinTradeRoute) -> bool {
         let start: RouteLock = (route.symbol, route.purchase_wp_symbol.clone(), false);
-        let end: RouteLock = (route.symbol, route.purchase_wp_symbol.clone(), true);
+        let end: RouteLock = (route.symbol, route.sell_wp_symbol.clone(), true);
 
         let start_val = *self.routes.get(&start).unwrap_or(&false);
         let end_val = *self.routes.get(&end).unwrap_or(&false);
@@ -35,9 +35,22 @@ impl RoutesTracker {
         false
     }
 
+    /// Locks every leg of a plan on its own, either all of them get locked or none
+    pub fn lock_all(&mut self, routes: &[MinTradeRoute]) -> bool {
+        if routes.iter().any(|route| self.is_locked(route)) {
+            return false;
+        }
+
+        for route in routes {
+            self.lock(route);
+        }
+
+        true
+    }
+
     pub fn unlock(&mut self, route: &MinTradeRoute) {
         let start: RouteLock = (route.symbol, route.purchase_wp_symbol.clone(), false);
-        let end: RouteLock = (route.symbol, route.purchase_wp_symbol.clone(), true);
+        let end: RouteLock = (route.symbol, route.sell_wp_symbol.clone(), true);
 
         self.routes.insert(start, false);
         self.routes.insert(end, false);
@@ -57,7 +70,7 @@ impl RoutesTracker {
     }
 
     fn is_end_locked(&self, route: &MinTradeRoute) -> bool {
-        let end: RouteLock = (route.symbol, route.purchase_wp_symbol.clone(), true);
+        let end: RouteLock = (route.symbol, route.sell_wp_symbol.clone(), true);
         *self.routes.get(&end).unwrap_or(&false)
     }
 }
@@ -125,6 +125,41 @@ impl TradeManager {
                 debug!("Sending route: {:?}", route);
                 let _send = callback.send(route);
             }
+            TradeMessage::RequestNextTradePlan {
+                ship_clone,
+                trading_config,
+                callback,
+            } => {
+                let plan = self
+                    .request_next_trade_plan(ship_clone, trading_config)
+                    .await;
+                debug!("Sending plan: {:?}", plan);
+                let _send = callback.send(plan);
+            }
+            TradeMessage::CompleteTradePlan {
+                trade_plan,
+                callback,
+            } => {
+                let plan = self.complete_trade_plan(trade_plan).await;
+                debug!("Sending plan: {:?}", plan);
+                let _send = callback.send(plan);
+            }
+            TradeMessage::StartTradeLeg {
+                trade_routes,
+                callback,
+            } => {
+                let routes = self.start_trade_leg(trade_routes).await;
+                debug!("Sending routes: {:?}", routes);
+                let _send = callback.send(routes);
+            }
+            TradeMessage::AbortTradePlan {
+                trade_plan,
+                callback,
+            } => {
+                let plan = self.abort_trade_plan(trade_plan).await;
+                debug!("Sending plan: {:?}", plan);
+                let _send = callback.send(plan);
+            }
             TradeMessage::CompleteTradeRoute {
                 trade_route,
                 callback,
@@ -199,6 +234,192 @@ impl TradeManager {
         Ok(Some(next_route))
     }
 
+    async fn request_next_trade_plan(
+        &mut self,
+        ship_clone: ship::MyShipCopy,
+        trading_config: database::TradingFleetConfig,
+    ) -> Result<Option<database::TradePlan>> {
+        let unfinished_plans = database::TradePlan::get_unfinished_by_ship(
+            &self.context.database_pool,
+            &ship_clone.symbol,
+        )
+        .await?;
+
+        if let Some(plan) = unfinished_plans.into_iter().next() {
+            let routes =
+                database::TradePlan::get_routes(&self.context.database_pool, plan.id).await?;
+            for (_, route) in routes
+                .iter()
+                .filter(|(_, r)| r.status == database::ShipmentStatus::InTransit)
+            {
+                if !self.routes_tracker.lock(&route.clone().into()) {
+                    tracing::warn!("Route was already locked, continuing");
+                }
+            }
+            return Ok(Some(plan));
+        }
+
+        let max_legs = { self.context.config.read().await.trade_max_legs.max(1) as usize };
+
+        let plan = self
+            .calculator
+            .get_best_plan(
+                &ship_clone,
+                &self.routes_tracker,
+                trading_config.clone(),
+                max_legs,
+            )
+            .await?;
+
+        let (trade_plan, routes) = match plan {
+            Some(plan) => (database::TradePlan::from(&plan), plan.trade_routes()),
+            None => {
+                // markets with unknown prices are only covered by single routes
+                let Some(route) = self
+                    .calculator
+                    .get_best_route(&ship_clone, &self.routes_tracker, trading_config)
+                    .await?
+                else {
+                    return Ok(None);
+                };
+                let trade_plan = database::TradePlan {
+                    ship_symbol: route.ship_symbol.clone(),
+                    status: database::ShipmentStatus::InTransit,
+                    predicted_profit: (route.predicted_sell_price - route.predicted_purchase_price)
+                        * route.trade_volume,
+                    ..Default::default()
+                };
+                (trade_plan, vec![(0, route)])
+            }
+        };
+
+        let min_routes = routes
+            .iter()
+            .map(|(_, route)| route.clone().into())
+            .collect::<Vec<_>>();
+
+        if !self.routes_tracker.lock_all(&min_routes) {
+            return Err("Failed to lock trade plan".into());
+        }
+
+        let erg = self.record_plan_start(trade_plan, routes).await;
+
+        if erg.is_err() {
+            for route in min_routes.iter() {
+                self.routes_tracker.unlock(route);
+            }
+        }
+
+        erg.map(Some)
+    }
+
+    async fn record_plan_start(
+        &self,
+        trade_plan: database::TradePlan,
+        routes: Vec<(i32, database::TradeRoute)>,
+    ) -> Result<database::TradePlan> {
+        let id = database::TradePlan::insert_new(&self.context.database_pool, &trade_plan).await?;
+
+        for (leg_index, mut route) in routes {
+            // only the first leg is reserved up front, the later legs are reserved when they start,
+            // after the sales of the previous leg came in
+            if leg_index == 0 && route.reserved_fund.is_none() {
+                let total_expense = (route.predicted_purchase_price * route.trade_volume) as i64;
+
+                let reservation = self
+                    .context
+                    .budget_manager
+                    .reserve_funds_with_remain(&self.context.database_pool, total_expense, 1_000)
+                    .await?;
+
+                route.reserved_fund = Some(reservation.id);
+            }
+
+            let route = self.record_trade_start(&route).await?;
+            database::TradePlan::add_route(&self.context.database_pool, id, leg_index, route.id)
+                .await?;
+        }
+
+        Ok(database::TradePlan { id, ..trade_plan })
+    }
+
+    async fn complete_trade_plan(
+        &mut self,
+        trade_plan: database::TradePlan,
+    ) -> Result<database::TradePlan> {
+        let completed_plan = trade_plan.complete();
+        database::TradePlan::upsert(&self.context.database_pool, &completed_plan).await?;
+        Ok(completed_plan)
+    }
+
+    /// Reserves the funds of every route of the leg, the leg only starts when all of them can be reserved
+    async fn start_trade_leg(
+        &mut self,
+        trade_routes: Vec<database::TradeRoute>,
+    ) -> Result<Vec<database::TradeRoute>> {
+        let mut reserved = Vec::new();
+        let mut started = Vec::new();
+
+        for mut route in trade_routes {
+            if route.reserved_fund.is_none() {
+                let total_expense = (route.predicted_purchase_price * route.trade_volume) as i64;
+
+                let reservation = match self
+                    .context
+                    .budget_manager
+                    .reserve_funds_with_remain(&self.context.database_pool, total_expense, 1_000)
+                    .await
+                {
+                    Ok(reservation) => reservation,
+                    Err(err) => {
+                        for reservation_id in reserved {
+                            self.context
+                                .budget_manager
+                                .cancel_reservation(&self.context.database_pool, reservation_id)
+                                .await?;
+                        }
+                        return Err(err);
+                    }
+                };
+
+                reserved.push(reservation.id);
+                route.reserved_fund = Some(reservation.id);
+                database::TradeRoute::upsert(&self.context.database_pool, &route).await?;
+            }
+            started.push(route);
+        }
+
+        Ok(started)
+    }
+
+    /// Fails the routes of the plan which were not flown yet, so their markets and funds are free for other ships
+    async fn abort_trade_plan(
+        &mut self,
+        trade_plan: database::TradePlan,
+    ) -> Result<database::TradePlan> {
+        let routes =
+            database::TradePlan::get_routes(&self.context.database_pool, trade_plan.id).await?;
+
+        for (_, route) in routes
+            .into_iter()
+            .filter(|(_, r)| r.status == database::ShipmentStatus::InTransit)
+        {
+            if let Some(reservation_id) = route.reserved_fund {
+                self.context
+                    .budget_manager
+                    .cancel_reservation(&self.context.database_pool, reservation_id)
+                    .await?;
+            }
+            let failed_route = route.fail();
+            database::TradeRoute::upsert(&self.context.database_pool, &failed_route).await?;
+            self.routes_tracker.unlock(&failed_route.into());
+        }
+
+        let failed_plan = trade_plan.fail();
+        database::TradePlan::upsert(&self.context.database_pool, &failed_plan).await?;
+        Ok(failed_plan)
+    }
+
     async fn complete_trade_route(
         &mut self,
         trade_route: database::TradeRoute,
@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use space_traders_client::models;
+
+use super::routes::{
+    ConcreteTradePlan, ConcreteTradeRoute, MARKET_VISIT_REQUESTS, PlannedGood, RouteStats,
+    TradeLeg, TripStats, trade_batches,
+};
+
+/// how many of the best legs starting at a waypoint are followed when chaining legs
+pub const LEG_BRANCHING: usize = 4;
+
+/// Chains legs A -> B -> C -> ... into trade plans, every leg fills the cargo with several goods.
+#[derive(Debug)]
+pub struct TradePlanner {
+    ship_symbol: String,
+    max_legs: usize,
+    fuel_price: i32,
+    mode: database::TradeMode,
+}
+
+impl TradePlanner {
+    pub fn new(
+        ship_symbol: String,
+        max_legs: usize,
+        fuel_price: i32,
+        mode: database::TradeMode,
+    ) -> Self {
+        Self {
+            ship_symbol,
+            max_legs,
+            fuel_price,
+            mode,
+        }
+    }
+
+    /// Groups the routes by their markets and fills the cargo of each pair with the goods of the highest profit per unit.
+    /// Only the `LEG_BRANCHING` best legs of each purchase market are kept, their travel is resolved by `travel`.
+    pub fn build_legs(
+        routes: Vec<ConcreteTradeRoute>,
+        capacity: i32,
+        mut travel: impl FnMut(&str, &str) -> Option<RouteStats>,
+    ) -> HashMap<String, Vec<TradeLeg>> {
+        let mut pairs: HashMap<(String, String), Vec<ConcreteTradeRoute>> = HashMap::new();
+        for route in routes {
+            pairs
+                .entry((
+                    route.route.purchase.waypoint_symbol.clone(),
+                    route.route.sell.waypoint_symbol.clone(),
+                ))
+                .or_default()
+                .push(route);
+        }
+
+        let mut legs: HashMap<String, Vec<TradeLeg>> = HashMap::new();
+        for ((purchase_wp_symbol, sell_wp_symbol), mut routes) in pairs {
+            routes.sort_by_key(|route| std::cmp::Reverse(route.data.profit));
+
+            let mut symbols = HashSet::new();
+            let mut free = capacity;
+            let mut goods = Vec::new();
+            for route in routes {
+                if free <= 0 {
+                    break;
+                }
+                if !symbols.insert(route.route.symbol) {
+                    continue;
+                }
+                let volume = route.trip.volume.min(free);
+                if volume <= 0 {
+                    continue;
+                }
+                free -= volume;
+                goods.push(PlannedGood { route, volume });
+            }
+
+            if goods.is_empty() {
+                continue;
+            }
+
+            legs.entry(purchase_wp_symbol.clone())
+                .or_default()
+                .push(TradeLeg {
+                    purchase_wp_symbol,
+                    sell_wp_symbol,
+                    goods,
+                    travel: RouteStats::default(),
+                });
+        }
+
+        for start_legs in legs.values_mut() {
+            start_legs.sort_by_key(|leg| std::cmp::Reverse(leg.goods_profit()));
+            start_legs.truncate(LEG_BRANCHING);
+            start_legs.retain_mut(|leg| {
+                match travel(&leg.purchase_wp_symbol, &leg.sell_wp_symbol) {
+                    Some(stats) => {
+                        leg.travel = stats;
+                        true
+                    }
+                    None => false,
+                }
+            });
+        }
+
+        legs
+    }
+
+    /// The best chain of up to `max_legs` legs, `approach` holds the empty flight from the ship to the start of the first leg
+    pub fn best_plan(
+        &self,
+        legs: &HashMap<String, Vec<TradeLeg>>,
+        approach: &HashMap<String, RouteStats>,
+    ) -> Option<ConcreteTradePlan> {
+        let mut best = None;
+        for (start, stats) in approach {
+            let mut path = Vec::new();
+            let mut used = HashSet::new();
+            self.search(legs, start, *stats, &mut path, &mut used, &mut best);
+        }
+        best
+    }
+
+    fn search(
+        &self,
+        legs: &HashMap<String, Vec<TradeLeg>>,
+        waypoint_symbol: &str,
+        approach: RouteStats,
+        path: &mut Vec<TradeLeg>,
+        used: &mut HashSet<(models::TradeSymbol, String)>,
+        best: &mut Option<ConcreteTradePlan>,
+    ) {
+        let Some(next_legs) = legs.get(waypoint_symbol) else {
+            return;
+        };
+
+        for leg in next_legs {
+            // a market is only traded once per plan, its prices change after the first trade
+            let keys = leg
+                .goods
+                .iter()
+                .map(|g| (g.route.route.symbol, leg.purchase_wp_symbol.clone()))
+                .collect::<Vec<_>>();
+            if keys.iter().any(|k| used.contains(k)) {
+                continue;
+            }
+
+            used.extend(keys.iter().cloned());
+            path.push(leg.clone());
+
+            let plan = self.evaluate(path, approach);
+            let better = match best {
+                Some(b) => plan.compare(b, self.mode).is_gt(),
+                None => true,
+            };
+            if better {
+                *best = Some(plan);
+            }
+
+            if path.len() < self.max_legs {
+                self.search(legs, &leg.sell_wp_symbol, approach, path, used, best);
+            }
+
+            path.pop();
+            for key in keys.iter() {
+                used.remove(key);
+            }
+        }
+    }
+
+    fn evaluate(&self, path: &[TradeLeg], approach: RouteStats) -> ConcreteTradePlan {
+        let goods = path.iter().flat_map(|l| l.goods.iter());
+
+        let fuel_units = approach.fuel_cost + path.iter().map(|l| l.travel.fuel_cost).sum::<i32>();
+        let fuel_cost = fuel_units * self.fuel_price / 100;
+        let time = approach.travel_time + path.iter().map(|l| l.travel.travel_time).sum::<f64>();
+        let distance = approach.distance + path.iter().map(|l| l.travel.distance).sum::<f64>();
+        // every market of the chain is visited once, each good is traded in batches of the market trade volumes
+        let api_requests = approach.api_requests
+            + path.iter().map(|l| l.travel.api_requests).sum::<i32>()
+            + (path.len() as i32 + 1) * MARKET_VISIT_REQUESTS
+            + goods
+                .clone()
+                .map(|g| {
+                    let route = &g.route.route;
+                    let fallback = g.route.data.min_trade_volume;
+                    trade_batches(
+                        g.volume,
+                        route
+                            .purchase_good
+                            .as_ref()
+                            .map_or(fallback, |t| t.trade_volume),
+                    ) + trade_batches(
+                        g.volume,
+                        route
+                            .sell_good
+                            .as_ref()
+                            .map_or(fallback, |t| t.trade_volume),
+                    )
+                })
+                .sum::<i32>();
+
+        let volume = goods.clone().map(|g| g.volume).sum::<i32>();
+        let purchase = goods
+            .clone()
+            .map(|g| g.route.data.purchase_price as i64 * g.volume as i64)
+            .sum::<i64>();
+        let sales = goods
+            .clone()
+            .map(|g| g.route.data.sell_price as i64 * g.volume as i64)
+            .sum::<i64>();
+
+        let total_cost = purchase + fuel_cost as i64;
+        let total_profit = sales - total_cost;
+
+        let trip_per_hour =
+            (time * 1000.0) / (chrono::TimeDelta::hours(1).num_milliseconds()) as f64;
+        let profit_per_hour = if trip_per_hour > 0.0 {
+            total_profit as f64 / trip_per_hour
+        } else {
+            total_profit as f64
+        };
+
+        let balance = goods
+            .map(|g| g.route.balance * g.volume / g.route.trip.volume.max(1))
+            .sum();
+
+        ConcreteTradePlan {
+            legs: path.to_vec(),
+            trip: TripStats {
+                ship_symbol: self.ship_symbol.clone(),
+                fuel_units,
+                time,
+                distance,
+                api_requests,
+                volume,
+                fuel_cost,
+                total_cost,
+                total_profit,
+                trips_per_hour: trip_per_hour as f32,
+                profit_per_hour: profit_per_hour as i64,
+                profit_per_api_request: total_profit / api_requests.max(1) as i64,
+            },
+            balance,
+        }
+    }
+}
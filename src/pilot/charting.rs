@@ -142,7 +142,10 @@ impl ChartPilot {
         Ok(())
     }
 
-    async fn chart_waypoint(&self, ship: &mut ship::MyShip) -> std::result::Result<(), Error> {
+    pub(super) async fn chart_waypoint(
+        &self,
+        ship: &mut ship::MyShip,
+    ) -> std::result::Result<(), Error> {
         let symbol = ship.symbol.clone();
 
         let erg = self.context.api.create_chart(&symbol).await;
@@ -0,0 +1,288 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicI64, Ordering},
+};
+
+use database::DatabaseConnectorAsync;
+use tracing::debug;
+use tracing::instrument;
+use utils::WaypointCan;
+
+use crate::{
+    error::{Error, Result},
+    utils::ConductorContext,
+};
+
+/// the conditions are only known after reloading the ship, so it is checked at most this often (in seconds)
+const CHECK_INTERVAL: i64 = 30 * 60;
+
+/// Whether the ship should be scrapped at the shipyard, the replacement price is what the cheapest capable ship costs
+///
+/// The integrity of a worn ship can't be repaired, it is replaced as soon as that is affordable after the scrap income.
+/// A damaged ship is only replaced when that costs no more than the repair.
+pub fn should_scrap(
+    worn: bool,
+    repair_price: i64,
+    scrap_price: i64,
+    replacement_price: Option<i64>,
+    spendable_funds: i64,
+) -> bool {
+    replacement_price.is_some_and(|replacement_price| {
+        let net_replacement_price = replacement_price - scrap_price;
+        if worn {
+            net_replacement_price <= spendable_funds
+        } else {
+            repair_price >= net_replacement_price
+        }
+    })
+}
+
+pub enum MaintenanceOutcome {
+    Continue,
+    /// the ship was scrapped and no longer exists
+    Scrapped,
+}
+
+/// Watches the condition and integrity of a ship, repairs it at a shipyard when it is damaged
+/// and scraps it when it is worn out or a replacement is cheaper than the repair.
+pub struct MaintenancePilot {
+    context: ConductorContext,
+    ship_symbol: String,
+    last_check: Arc<AtomicI64>,
+}
+
+impl MaintenancePilot {
+    pub fn new(context: ConductorContext, ship_symbol: String) -> Self {
+        Self {
+            context,
+            ship_symbol,
+            last_check: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    #[instrument(level = "info", name = "spacetraders::pilot::maintenance::maintain_ship", skip(self, pilot, ship_assignment), fields(self.ship_symbol = %self.ship_symbol, ship_assignment_id = ship_assignment.id))]
+    pub async fn maintain_ship(
+        &self,
+        pilot: &super::Pilot,
+        ship_assignment: &database::ShipAssignment,
+    ) -> Result<MaintenanceOutcome> {
+        let (repair_threshold, scrap_threshold) = {
+            let config = self.context.config.read().await;
+            (
+                config.repair_condition_threshold as f64,
+                config.scrap_integrity_threshold as f64,
+            )
+        };
+        if repair_threshold <= 0.0 && scrap_threshold <= 0.0 {
+            return Ok(MaintenanceOutcome::Continue);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        if now - self.last_check.load(Ordering::Relaxed) < CHECK_INTERVAL {
+            return Ok(MaintenanceOutcome::Continue);
+        }
+        self.last_check.store(now, Ordering::Relaxed);
+
+        let mut erg = pilot.context.ship_manager.get_mut(&self.ship_symbol).await;
+        let ship = erg
+            .value_mut()
+            .ok_or(Error::General("Ship not found".to_string()))?;
+
+        ship.reload(&self.context.api).await?;
+        ship.notify(true).await;
+
+        let condition = ship.get_min_condition();
+        let integrity = ship.get_min_integrity();
+        let damaged = condition < repair_threshold;
+        let worn = integrity < scrap_threshold;
+
+        debug!(
+            condition,
+            integrity, damaged, worn, "Checked ship conditions"
+        );
+
+        if !damaged && !worn {
+            return Ok(MaintenanceOutcome::Continue);
+        }
+
+        let replacement_price = self
+            .context
+            .fleet_manager
+            .get_replacement_price(ship_assignment.id)
+            .await?;
+        let spendable_funds = self.context.budget_manager.get_spendable_funds().await;
+
+        // only fly to the shipyard when something can be done there, the repair price is only known at the shipyard
+        let can_repair = damaged && spendable_funds > 0;
+        let can_replace =
+            replacement_price.is_some_and(|replacement_price| replacement_price <= spendable_funds);
+        if !can_repair && !can_replace {
+            debug!(
+                replacement_price,
+                spendable_funds,
+                "Neither a repair nor a replacement is affordable, keeping the ship"
+            );
+            return Ok(MaintenanceOutcome::Continue);
+        }
+
+        let Some(waypoint_symbol) = self.get_nearest_shipyard(ship).await? else {
+            tracing::warn!(ship_symbol = %ship.symbol, "No shipyard in the system for maintenance");
+            return Ok(MaintenanceOutcome::Continue);
+        };
+
+        let previous_status = ship.status.status.clone();
+        ship.status.status = ship::AssignmentStatus::Maintenance {
+            waypoint_symbol: waypoint_symbol.clone(),
+        };
+        ship.notify(true).await;
+
+        let budget_manager = self.context.budget_manager.clone();
+
+        let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+        ship.nav_to(
+            &waypoint_symbol,
+            true,
+            database::TransactionReason::None,
+            &self.context.database_pool,
+            &self.context.api,
+            update_funds_fn,
+        )
+        .await?;
+
+        ship.ensure_docked(&self.context.api).await?;
+
+        let repair_quote = ship.get_repair_quote(&self.context.api).await?;
+        let scrap_quote = ship.get_scrap_quote(&self.context.api).await?;
+        let spendable_funds = self.context.budget_manager.get_spendable_funds().await;
+
+        let scrap = should_scrap(
+            worn,
+            repair_quote.total_price as i64,
+            scrap_quote.total_price as i64,
+            replacement_price,
+            spendable_funds,
+        );
+
+        debug!(
+            repair_price = repair_quote.total_price,
+            scrap_price = scrap_quote.total_price,
+            replacement_price,
+            spendable_funds,
+            scrap,
+            "Got maintenance quotes"
+        );
+
+        if scrap {
+            let budget_manager = self.context.budget_manager.clone();
+
+            let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+            let transaction = ship
+                .scrap(
+                    &self.context.api,
+                    &self.context.database_pool,
+                    update_funds_fn,
+                )
+                .await?;
+
+            tracing::info!(ship_symbol = %self.ship_symbol, price = transaction.total_price, worn, "Scrapped ship");
+
+            if let Some(mut ship_info) =
+                database::ShipInfo::get_by_id(&self.context.database_pool, &self.ship_symbol)
+                    .await?
+            {
+                ship_info.active = false;
+                ship_info.assignment_id = None;
+                ship_info.temp_assignment_id = None;
+                database::ShipInfo::upsert(&self.context.database_pool, &ship_info).await?;
+            }
+
+            self.context.ship_manager.remove_ship(erg).await;
+
+            return Ok(MaintenanceOutcome::Scrapped);
+        }
+
+        if damaged {
+            self.repair(ship, repair_quote.total_price as i64).await?;
+        }
+
+        ship.status.status = previous_status;
+        ship.notify(true).await;
+
+        Ok(MaintenanceOutcome::Continue)
+    }
+
+    /// Repairs the docked ship when the funds allow it, the ship keeps flying damaged otherwise
+    async fn repair(&self, ship: &mut ship::MyShip, repair_price: i64) -> Result<()> {
+        let reservation = match self
+            .context
+            .budget_manager
+            .reserve_funds(&self.context.database_pool, repair_price)
+            .await
+        {
+            Ok(reservation) => reservation,
+            Err(Error::NotEnoughFunds { .. }) => {
+                debug!(repair_price, "Not enough funds to repair the ship");
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
+        let budget_manager = self.context.budget_manager.clone();
+
+        let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+        let erg = ship
+            .repair(
+                &self.context.api,
+                &self.context.database_pool,
+                update_funds_fn,
+            )
+            .await;
+
+        let spent = erg
+            .as_ref()
+            .map(|transaction| transaction.total_price as i64)
+            .unwrap_or(0);
+        self.context
+            .budget_manager
+            .complete_use_reservation(&self.context.database_pool, reservation.id, spent)
+            .await?;
+
+        let transaction = erg?;
+
+        debug!(price = transaction.total_price, "Repaired ship");
+
+        Ok(())
+    }
+
+    async fn get_nearest_shipyard(&self, ship: &ship::MyShip) -> Result<Option<String>> {
+        let waypoints = database::Waypoint::get_by_system(
+            &self.context.database_pool,
+            &ship.nav.system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items;
+
+        let Some(current) = waypoints
+            .iter()
+            .find(|w| w.symbol == ship.nav.waypoint_symbol)
+        else {
+            return Ok(None);
+        };
+
+        Ok(waypoints
+            .iter()
+            .filter(|w| w.is_shipyard())
+            .min_by(|a, b| {
+                let distance_a =
+                    utils::distance_between_waypoints((current.x, current.y), (a.x, a.y));
+                let distance_b =
+                    utils::distance_between_waypoints((current.x, current.y), (b.x, b.y));
+                distance_a.total_cmp(&distance_b)
+            })
+            .map(|w| w.symbol.clone()))
+    }
+}
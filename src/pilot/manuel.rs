@@ -0,0 +1,246 @@
+use database::DatabaseConnectorAsync;
+use tracing::debug;
+use tracing::instrument;
+
+use crate::{
+    error::{Error, Result},
+    utils::ConductorContext,
+};
+
+/// Ships of a Manuel fleet execute the commands queued for them through the control api, one after the other.
+/// A failed command cancels the commands queued after it, they most likely depend on it.
+pub struct ManuelPilot {
+    context: ConductorContext,
+    ship_symbol: String,
+}
+
+impl ManuelPilot {
+    pub fn new(context: ConductorContext, ship_symbol: String) -> Self {
+        Self {
+            context,
+            ship_symbol,
+        }
+    }
+
+    #[instrument(level = "info", name = "spacetraders::pilot::manuel::pilot_manuel", skip(self, pilot, fleet), fields(self.ship_symbol = %self.ship_symbol, fleet_id = fleet.id))]
+    pub async fn execute_pilot_circle(
+        &self,
+        pilot: &super::Pilot,
+        fleet: database::Fleet,
+    ) -> Result<()> {
+        let commands_queued = self.context.ship_tasks.commands_queued();
+        tokio::pin!(commands_queued);
+        commands_queued.as_mut().enable();
+
+        let Some(mut command) =
+            database::ShipCommand::get_next_by_ship(&self.context.database_pool, &self.ship_symbol)
+                .await?
+        else {
+            debug!(fleet_id = fleet.id, ship_symbol = %self.ship_symbol, "No commands queued, waiting");
+            // the sleep re-checks the assignment from time to time
+            tokio::select! {
+                _ = commands_queued => {},
+                _ = tokio::time::sleep(std::time::Duration::from_millis(
+                    60_000 + rand::random::<u64>() % 1_000,
+                )) => {},
+            }
+            return Ok(());
+        };
+
+        if command.status == database::ShipCommandStatus::Running {
+            // the bot stopped while executing it, its outcome is unknown
+            return self
+                .fail_command(command, "Interrupted before completion".to_string())
+                .await;
+        }
+
+        command.status = database::ShipCommandStatus::Running;
+        database::ShipCommand::upsert(&self.context.database_pool, &command).await?;
+
+        debug!(command = ?command, "Executing command");
+
+        match self.execute_command(pilot, &command).await {
+            Ok(()) => {
+                command.status = database::ShipCommandStatus::Done;
+                database::ShipCommand::upsert(&self.context.database_pool, &command).await?;
+                Ok(())
+            }
+            Err(err) => {
+                tracing::warn!(ship_symbol = %self.ship_symbol, command_id = command.id, error = %err, "Command failed");
+                self.fail_command(command, err.to_string()).await
+            }
+        }
+    }
+
+    async fn fail_command(&self, mut command: database::ShipCommand, error: String) -> Result<()> {
+        command.status = database::ShipCommandStatus::Failed;
+        command.error = Some(error);
+        database::ShipCommand::upsert(&self.context.database_pool, &command).await?;
+
+        let cancelled = database::ShipCommand::cancel_pending_by_ship(
+            &self.context.database_pool,
+            &self.ship_symbol,
+        )
+        .await?;
+        debug!(ship_symbol = %self.ship_symbol, cancelled, "Cancelled queued commands");
+
+        Ok(())
+    }
+
+    async fn execute_command(
+        &self,
+        pilot: &super::Pilot,
+        command: &database::ShipCommand,
+    ) -> Result<()> {
+        let mut erg = self.context.ship_manager.get_mut(&self.ship_symbol).await;
+        let ship = erg
+            .value_mut()
+            .ok_or(Error::General("Ship not found".to_string()))?;
+
+        ship.wait_for_arrival_mut(&self.context.api)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let budget_manager = self.context.budget_manager.clone();
+        let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+        match command.command {
+            database::ShipCommandType::Navigate => {
+                ship.nav_to(
+                    &Self::required(&command.waypoint_symbol, "waypoint_symbol")?,
+                    true,
+                    database::TransactionReason::None,
+                    &self.context.database_pool,
+                    &self.context.api,
+                    update_funds_fn,
+                )
+                .await?;
+            }
+            database::ShipCommandType::Dock => {
+                ship.ensure_docked(&self.context.api).await?;
+            }
+            database::ShipCommandType::Orbit => {
+                ship.ensure_undocked(&self.context.api).await?;
+            }
+            database::ShipCommandType::Purchase => {
+                ship.ensure_docked(&self.context.api).await?;
+                ship.purchase_cargo(
+                    &self.context.api,
+                    &Self::required(&command.trade_symbol, "trade_symbol")?,
+                    Self::required(&command.units, "units")?,
+                    &self.context.database_pool,
+                    database::TransactionReason::None,
+                    update_funds_fn,
+                )
+                .await?;
+            }
+            database::ShipCommandType::Sell => {
+                ship.ensure_docked(&self.context.api).await?;
+                ship.sell_cargo(
+                    &self.context.api,
+                    &Self::required(&command.trade_symbol, "trade_symbol")?,
+                    Self::required(&command.units, "units")?,
+                    &self.context.database_pool,
+                    database::TransactionReason::None,
+                    update_funds_fn,
+                )
+                .await?;
+            }
+            database::ShipCommandType::Transfer => {
+                ship.simple_transfer_cargo(
+                    Self::required(&command.trade_symbol, "trade_symbol")?,
+                    Self::required(&command.units, "units")?,
+                    &self.context.api,
+                    &Self::required(&command.target_ship, "target_ship")?,
+                )
+                .await?;
+            }
+            database::ShipCommandType::Jettison => {
+                ship.jettison(
+                    &self.context.api,
+                    Self::required(&command.trade_symbol, "trade_symbol")?,
+                    Self::required(&command.units, "units")?,
+                )
+                .await?;
+            }
+            database::ShipCommandType::Refuel => {
+                ship.ensure_docked(&self.context.api).await?;
+                let units = command
+                    .units
+                    .unwrap_or(ship.fuel.capacity - ship.fuel.current);
+                ship.refuel_ship(
+                    &self.context.api,
+                    units,
+                    command.from_cargo.unwrap_or(false),
+                )
+                .await?;
+            }
+            database::ShipCommandType::Survey => {
+                ship.ensure_undocked(&self.context.api).await?;
+                ship.wait_for_cooldown().await;
+
+                let ship_before = ship.snapshot(&self.context.database_pool).await?;
+                let surveys = ship.survey(&self.context.api).await?;
+                let ship_after = ship.snapshot(&self.context.database_pool).await?;
+
+                let surveys = surveys
+                    .data
+                    .surveys
+                    .into_iter()
+                    .map(|f| {
+                        database::Survey::from_model(
+                            f,
+                            ship_before,
+                            ship_after,
+                            ship.symbol.clone(),
+                        )
+                    })
+                    .collect::<database::Result<Vec<_>, _>>()?;
+                database::Survey::insert_bulk(&self.context.database_pool, &surveys).await?;
+            }
+            database::ShipCommandType::Extract => {
+                ship.ensure_undocked(&self.context.api).await?;
+                ship.wait_for_cooldown().await;
+
+                let now = chrono::Utc::now();
+                let ship_before = ship.snapshot(&self.context.database_pool).await?;
+                let extraction = ship.extract(&self.context.api).await?;
+                let ship_after = ship.snapshot(&self.context.database_pool).await?;
+
+                let extraction = database::Extraction {
+                    id: 0,
+                    ship_symbol: ship.symbol.clone(),
+                    waypoint_symbol: ship.nav.waypoint_symbol.clone(),
+                    ship_info_before: ship_before,
+                    ship_info_after: ship_after,
+                    siphon: false,
+                    survey: None,
+                    yield_symbol: extraction.data.extraction.r#yield.symbol,
+                    yield_units: extraction.data.extraction.r#yield.units,
+                    created_at: now,
+                };
+                database::Extraction::upsert(&self.context.database_pool, &extraction).await?;
+            }
+            database::ShipCommandType::Chart => {
+                pilot.chart_pilot.chart_waypoint(ship).await?;
+            }
+            database::ShipCommandType::Jump => {
+                ship.ensure_undocked(&self.context.api).await?;
+                ship.wait_for_cooldown().await;
+                ship.jump(
+                    &self.context.api,
+                    &Self::required(&command.waypoint_symbol, "waypoint_symbol")?,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn required<T: Clone>(value: &Option<T>, field: &str) -> Result<T> {
+        value
+            .clone()
+            .ok_or_else(|| Error::General(format!("Command is missing {}", field)))
+    }
+}
@@ -2,7 +2,6 @@ use std::sync::{Arc, atomic::AtomicI32};
 
 use database::DatabaseConnectorAsync;
 use futures::FutureExt;
-use rand::seq::SliceRandom;
 use ship::status::{ExtractorState, MiningShipAssignment};
 use space_traders_client::models;
 use tracing::debug;
@@ -14,6 +13,8 @@ use crate::{
     utils::ConductorContext,
 };
 
+use super::survey_value::SurveyValuator;
+
 pub struct ExtractionPilot {
     count: Arc<AtomicI32>,
     context: ConductorContext,
@@ -286,19 +287,10 @@ impl ExtractionPilot {
 
         match action {
             ActionType::Extract => {
-                let survey: Option<database::Survey> = self.get_best_survey(ship).await?;
-
-                if let Some(survey) = survey {
-                    let prefer_list =
-                        { self.context.config.read().await.mining_prefer_list.clone() };
+                let survey = self.get_best_survey(ship).await?;
 
-                    let count_a = survey.get_percent();
-                    let score_a = count_a
-                        .iter()
-                        .filter(|f| prefer_list.contains(&f.0))
-                        .map(|f| f.1)
-                        .sum::<f64>();
-                    debug!(survey_signature = %survey.signature, ship_symbol = %ship.symbol, score = score_a, "Using survey on ship");
+                if let Some((survey, score)) = survey {
+                    debug!(survey_signature = %survey.signature, ship_symbol = %ship.symbol, score, "Using survey on ship");
 
                     let survey_erg = ship
                         .extract_with_survey(&self.context.api, &(&survey).into())
@@ -652,8 +644,12 @@ impl ExtractionPilot {
         Ok(())
     }
 
-    async fn get_best_survey(&self, ship: &mut ship::MyShip) -> Result<Option<database::Survey>> {
-        let mut working_surveys = database::Survey::get_working_for_waypoint(
+    /// The survey worth the most for the next extraction of the ship, and its score
+    async fn get_best_survey(
+        &self,
+        ship: &mut ship::MyShip,
+    ) -> Result<Option<(database::Survey, f64)>> {
+        let working_surveys = database::Survey::get_working_for_waypoint(
             &self.context.database_pool,
             &ship.nav.waypoint_symbol,
             database::PaginatedQuery::unpaged(),
@@ -661,27 +657,19 @@ impl ExtractionPilot {
         .await?
         .items;
 
-        working_surveys.shuffle(&mut rand::thread_rng());
-
-        let prefer_list = { self.context.config.read().await.mining_prefer_list.clone() };
-
-        let best_survey = working_surveys.iter().max_by(|a, b| {
-            let count_a = a.get_percent();
-            let score_a = count_a
-                .iter()
-                .filter(|f| prefer_list.contains(&f.0))
-                .map(|f| f.1)
-                .sum::<f64>();
-            let count_b = b.get_percent();
-            let score_b = count_b
-                .iter()
-                .filter(|f| prefer_list.contains(&f.0))
-                .map(|f| f.1)
-                .sum::<f64>();
+        if working_surveys.is_empty() {
+            return Ok(None);
+        }
 
-            score_b.partial_cmp(&score_a).unwrap()
-        });
+        let valuator = SurveyValuator::load(
+            &self.context,
+            &ship.nav.system_symbol,
+            &ship.nav.waypoint_symbol,
+        )
+        .await?;
 
-        best_survey.cloned().map_or(Ok(None), |f| Ok(Some(f)))
+        Ok(valuator
+            .best(&working_surveys, &ship.symbol)
+            .map(|(survey, score)| (survey.clone(), score)))
     }
 }
@@ -1,7 +1,10 @@
 mod extraction;
+pub mod survey_value;
 mod surveyor;
 mod transport;
 
+pub(crate) use transport::handle_transfer_request;
+
 use ship::status::MiningShipAssignment;
 use tracing::debug;
 use tracing::instrument;
@@ -15,6 +18,7 @@ pub struct MiningPilot {
     extraction: extraction::ExtractionPilot,
     transport: transport::TransportPilot,
     survey: surveyor::SurveyPilot,
+    #[allow(dead_code)]
     context: ConductorContext,
     ship_symbol: String,
 }
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use space_traders_client::models;
+
+use crate::{error::Result, utils::ConductorContext};
+
+/// seconds between two extractions of a miner
+const EXTRACTION_COOLDOWN: f64 = 70.0;
+/// ships which extracted with a survey within these seconds still count as using it
+const RECENT_USE: i64 = 5 * 60;
+/// a good on `mining_prefer_list` counts this many times its sell price
+const PREFER_BONUS: f64 = 1.5;
+/// a miner only takes a survey it can expect at least this many extractions from, the less the lower it scores
+const MIN_EXTRACTIONS_PER_MINER: f64 = 3.0;
+/// the surveyor surveys again once the useful surveys cover fewer extractions per miner than this
+const SURVEY_AHEAD_EXTRACTIONS: f64 = 10.0;
+/// surveys worth less than this share of the best one are not worth keeping the surveyor waiting for
+const USEFUL_SHARE: f64 = 0.5;
+
+/// rough number of extractions a survey lasts before it is exhausted, the server does not tell
+fn survey_capacity(size: models::SurveySize) -> f64 {
+    match size {
+        models::SurveySize::Small => 10.0,
+        models::SurveySize::Moderate => 25.0,
+        models::SurveySize::Large => 50.0,
+    }
+}
+
+/// Values the surveys of a waypoint by what their deposits sell for, how long they last and how many miners share them
+#[derive(Debug, Clone)]
+pub struct SurveyValuator {
+    /// the best sell price of every good in the system, where the transporters sell it
+    sell_prices: HashMap<models::TradeSymbol, i32>,
+    prefer_list: Vec<models::TradeSymbol>,
+    usage: HashMap<String, database::SurveyUsage>,
+}
+
+impl SurveyValuator {
+    pub async fn load(
+        context: &ConductorContext,
+        system_symbol: &str,
+        waypoint_symbol: &str,
+    ) -> Result<SurveyValuator> {
+        let mut sell_prices: HashMap<models::TradeSymbol, i32> = HashMap::new();
+        for good in database::MarketTradeGood::get_last_by_system(
+            &context.database_pool,
+            system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items
+        {
+            let price = sell_prices.entry(good.symbol).or_default();
+            *price = (*price).max(good.sell_price);
+        }
+
+        let usage = database::SurveyUsage::get_by_waypoint(
+            &context.database_pool,
+            waypoint_symbol,
+            chrono::Utc::now() - chrono::Duration::seconds(RECENT_USE),
+        )
+        .await?
+        .into_iter()
+        .map(|usage| (usage.signature.clone(), usage))
+        .collect();
+
+        let prefer_list = { context.config.read().await.mining_prefer_list.clone() };
+
+        Ok(SurveyValuator::new(sell_prices, prefer_list, usage))
+    }
+
+    pub fn new(
+        sell_prices: HashMap<models::TradeSymbol, i32>,
+        prefer_list: Vec<models::TradeSymbol>,
+        usage: HashMap<String, database::SurveyUsage>,
+    ) -> SurveyValuator {
+        SurveyValuator {
+            sell_prices,
+            prefer_list,
+            usage,
+        }
+    }
+
+    /// the expected sell value of one extraction with the survey
+    ///
+    /// Without any known sell price every deposit counts 1, those on `mining_prefer_list` `PREFER_BONUS`,
+    /// so the surveys are still used and ranked by their share of preferred deposits.
+    pub fn value_per_extraction(&self, survey: &database::Survey) -> f64 {
+        let prices_known = self.sell_prices.values().any(|price| *price > 0);
+        survey
+            .get_percent()
+            .iter()
+            .map(|(symbol, share)| {
+                let price = if prices_known {
+                    self.sell_prices.get(symbol).copied().unwrap_or_default() as f64
+                } else {
+                    1.0
+                };
+                let bonus = if self.prefer_list.contains(symbol) {
+                    PREFER_BONUS
+                } else {
+                    1.0
+                };
+                price * share * bonus
+            })
+            .sum()
+    }
+
+    /// the extractions left before the survey is exhausted
+    fn remaining_extractions(&self, survey: &database::Survey) -> f64 {
+        let used = self
+            .usage
+            .get(&survey.signature)
+            .map(|usage| usage.extractions)
+            .unwrap_or_default();
+        (survey_capacity(survey.size) - used as f64).max(0.0)
+    }
+
+    /// the extractions one miner can still do before the survey expires
+    fn extractions_until_expiry(
+        &self,
+        survey: &database::Survey,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> f64 {
+        ((survey.expiration - now).num_seconds() as f64 / EXTRACTION_COOLDOWN).max(0.0)
+    }
+
+    /// the value of the next extraction of the ship, lower when the survey will be gone before the ship got its share
+    pub fn score(&self, survey: &database::Survey, ship_symbol: &str) -> f64 {
+        let now = chrono::Utc::now();
+        let others = self
+            .usage
+            .get(&survey.signature)
+            .map(|usage| {
+                usage
+                    .recent_ships
+                    .iter()
+                    .filter(|s| s.as_str() != ship_symbol)
+                    .count()
+            })
+            .unwrap_or_default();
+
+        let share = (self.remaining_extractions(survey) / (others + 1) as f64)
+            .min(self.extractions_until_expiry(survey, now));
+
+        self.value_per_extraction(survey) * (share / MIN_EXTRACTIONS_PER_MINER).min(1.0)
+    }
+
+    /// The best survey for the ship, on equal scores the one expiring first so none goes to waste
+    pub fn best<'a>(
+        &self,
+        surveys: &'a [database::Survey],
+        ship_symbol: &str,
+    ) -> Option<(&'a database::Survey, f64)> {
+        surveys
+            .iter()
+            .map(|survey| (survey, self.score(survey, ship_symbol)))
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|a, b| {
+                a.1.total_cmp(&b.1)
+                    .then_with(|| b.0.expiration.cmp(&a.0.expiration))
+            })
+    }
+
+    /// When the surveyor should survey the waypoint again, `None` when right away
+    ///
+    /// Waits while the useful surveys still cover `SURVEY_AHEAD_EXTRACTIONS` of every miner,
+    /// at the latest until the first of them expires.
+    pub fn next_survey_at(
+        &self,
+        surveys: &[database::Survey],
+        miners: usize,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        let now = chrono::Utc::now();
+        let miners = miners.max(1) as f64;
+
+        let best_value = surveys
+            .iter()
+            .map(|survey| self.value_per_extraction(survey))
+            .fold(0.0, f64::max);
+        if best_value <= 0.0 {
+            return None;
+        }
+
+        let useful = surveys
+            .iter()
+            .filter(|survey| self.value_per_extraction(survey) >= best_value * USEFUL_SHARE)
+            .collect::<Vec<_>>();
+
+        let coverage = useful
+            .iter()
+            .map(|survey| {
+                self.remaining_extractions(survey)
+                    .min(self.extractions_until_expiry(survey, now) * miners)
+            })
+            .sum::<f64>();
+
+        let spare = coverage / miners - SURVEY_AHEAD_EXTRACTIONS;
+        if spare <= 0.0 {
+            return None;
+        }
+
+        let first_expiry = useful.iter().map(|survey| survey.expiration).min()?;
+        let covered_until = now + chrono::Duration::seconds((spare * EXTRACTION_COOLDOWN) as i64);
+        Some(covered_until.min(first_expiry))
+    }
+}
@@ -9,6 +9,11 @@ use tracing::instrument;
 
 use crate::{error::Result, utils::ConductorContext};
 
+use super::survey_value::SurveyValuator;
+
+/// the longest the surveyor waits before checking the surveys of its waypoint again
+const MAX_SURVEY_WAIT: chrono::Duration = chrono::Duration::seconds(60);
+
 pub struct SurveyPilot {
     count: Arc<AtomicI32>,
     context: ConductorContext,
@@ -35,7 +40,7 @@ impl SurveyPilot {
             return Ok(());
         }
 
-        let waypoint = waypoint.unwrap();
+        let (waypoint, miners) = waypoint.unwrap();
 
         tracing::Span::current().record("waypoint", &waypoint);
 
@@ -66,6 +71,16 @@ impl SurveyPilot {
 
         ship.wait_for_cooldown().await;
 
+        if let Some(next_survey) = self.get_next_survey(ship, &waypoint, miners).await? {
+            tracing::debug!(ship_symbol = %ship.symbol, waypoint, %next_survey, "Surveys still cover the miners, waiting");
+            let wait = (next_survey - chrono::Utc::now())
+                .clamp(chrono::Duration::zero(), MAX_SURVEY_WAIT)
+                .to_std()
+                .unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            return Ok(());
+        }
+
         let ship_before = ship.snapshot(&self.context.database_pool).await?;
 
         let surveys = ship.survey(&self.context.api).await?;
@@ -102,7 +117,29 @@ impl SurveyPilot {
         Ok(())
     }
 
-    async fn get_waypoint(&self, ship: &mut ship::MyShip) -> Option<String> {
+    /// When to survey the waypoint again, `None` when the miners need new surveys now
+    async fn get_next_survey(
+        &self,
+        ship: &ship::MyShip,
+        waypoint: &str,
+        miners: usize,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let working_surveys = database::Survey::get_working_for_waypoint(
+            &self.context.database_pool,
+            waypoint,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items;
+
+        let valuator =
+            SurveyValuator::load(&self.context, &ship.nav.system_symbol, waypoint).await?;
+
+        Ok(valuator.next_survey_at(&working_surveys, miners))
+    }
+
+    /// The waypoint with the most surveyors and miners and its number of miners
+    async fn get_waypoint(&self, ship: &mut ship::MyShip) -> Option<(String, usize)> {
         let all_system_ships = self
             .context
             .ship_manager
@@ -153,6 +190,6 @@ impl SurveyPilot {
             .iter()
             .max_by(|s1, s2| s1.1 .1.cmp(&s2.1 .1).then(s1.1 .0.cmp(&s2.1 .0)));
 
-        wps.map(|f| (**f.0).clone())
+        wps.map(|f| ((**f.0).clone(), f.1 .0))
     }
 }
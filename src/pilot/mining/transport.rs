@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, atomic::AtomicI32},
 };
 
@@ -10,13 +10,18 @@ use tracing::instrument;
 
 use crate::{
     error::Result,
-    manager::mining_manager::{ExtractorTransferRequest, TransportTransferRequest},
+    manager::{
+        mining_manager::{ExtractorTransferRequest, TransportTransferRequest},
+        trade_manager::{PRICE_MODEL_TTL, PriceModel},
+    },
     utils::ConductorContext,
 };
 
 pub struct TransportPilot {
     count: Arc<AtomicI32>,
     context: ConductorContext,
+    /// the last learned price model per system and when it was learned
+    price_models: tokio::sync::Mutex<HashMap<String, (chrono::DateTime<chrono::Utc>, PriceModel)>>,
 }
 
 impl TransportPilot {
@@ -24,10 +29,31 @@ impl TransportPilot {
         Self {
             count: Arc::new(AtomicI32::new(0)),
             context,
+            price_models: tokio::sync::Mutex::new(HashMap::new()),
         }
     }
 
+    async fn get_price_model(
+        &self,
+        database_pool: &database::DbPool,
+        system_symbol: &str,
+        fallback_per_unit: f64,
+    ) -> Result<PriceModel> {
+        let mut price_models = self.price_models.lock().await;
+        let now = chrono::Utc::now();
+        if let Some((learned_at, model)) = price_models.get(system_symbol)
+            && (now - *learned_at).num_seconds() < PRICE_MODEL_TTL
+        {
+            return Ok(model.clone());
+        }
+
+        let model = PriceModel::load(database_pool, system_symbol, fallback_per_unit).await?;
+        price_models.insert(system_symbol.to_string(), (now, model.clone()));
+        Ok(model)
+    }
+
     #[instrument(level = "info", name = "spacetraders::pilot::mining::pilot_transport", skip(self, pilot, ship), fields(self.ship_symbol = pilot.ship_symbol))]
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
     pub async fn execute_transport_circle(
         &self,
         ship: &mut ship::MyShip,
@@ -141,6 +167,7 @@ impl TransportPilot {
         Ok(next_transport)
     }
 
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
     async fn handle_cargo_loading(
         &self,
         ship: &mut ship::MyShip,
@@ -209,7 +236,7 @@ impl TransportPilot {
                     debug!(ship_symbol = %ship.symbol, "Received transfer request for ship");
                     let from = transfer_request.from_symbol.clone();
                     let to = transfer_request.to_symbol.clone();
-                    let erg = self.handle_transfer_request(ship, transfer_request).await;
+                    let erg = handle_transfer_request(ship, transfer_request).await;
                     if let Err(error) = erg {
                         tracing::error!(
                             ship_symbol = ship.symbol,
@@ -230,63 +257,6 @@ impl TransportPilot {
         Ok(())
     }
 
-    async fn handle_transfer_request(
-        &self,
-        ship: &mut ship::MyShip,
-        request: TransportTransferRequest,
-    ) -> Result<()> {
-        let (callback, receiver) = tokio::sync::oneshot::channel();
-
-        let extractor_req = ExtractorTransferRequest {
-            from_symbol: request.from_symbol.clone(),
-            to_symbol: ship.symbol.clone(),
-            amount: request.amount,
-            trade_symbol: request.trade_symbol,
-            callback,
-        };
-
-        let erg = request.extractor_contact.send(extractor_req).await;
-
-        if let Err(err) = erg {
-            // the mining ship had dropped
-            tracing::error!(
-              error=?err,
-              "Failed to send message to extractor",
-            );
-            let _erg = request.callback.send(());
-
-            return Ok(());
-        }
-
-        let transfer = receiver.await;
-
-        let transfer = match transfer {
-            Ok(transfer) => transfer,
-            Err(e) => {
-                tracing::error!(
-                  error=?e,
-                  "Failed to send message to extractor",
-                );
-                return Ok(());
-            }
-        };
-
-        let transfer = if let Some(transfer) = transfer {
-            transfer
-        } else {
-            return Ok(());
-        };
-
-        ship.cargo
-            .handle_cago_update(transfer.units, transfer.trade_symbol)?;
-
-        ship.notify(true).await;
-
-        let _erg = request.callback.send(());
-
-        Ok(())
-    }
-
     async fn sell_all_cargo(
         &self,
         pilot: &crate::pilot::Pilot,
@@ -294,6 +264,7 @@ impl TransportPilot {
         _waypoints: &std::collections::HashMap<String, database::Waypoint>,
         mining_waypoint: String,
     ) -> Result<()> {
+        let mut visited = HashSet::new();
         while ship.cargo.get_units_no_fuel() > 0 {
             if pilot.slow_cancellation_token.is_cancelled() {
                 tracing::info!(symbol = %ship.symbol, "Transport cycle cancelled");
@@ -308,8 +279,12 @@ impl TransportPilot {
                 },
             };
             ship.notify(true).await;
-            let (next_waypoint, trade_symbols) =
-                self.get_next_best_sell_waypoint(ship).await.unwrap();
+            let Some((next_waypoint, trade_symbols)) =
+                self.get_next_best_sell_waypoint(ship, &visited).await
+            else {
+                tracing::warn!(symbol = %ship.symbol, "No market left to sell the remaining cargo");
+                break;
+            };
 
             let budget_manager = self.context.budget_manager.clone();
 
@@ -340,8 +315,11 @@ impl TransportPilot {
                 &self.context.database_pool,
                 database::TransactionReason::MiningWaypoint(mining_waypoint.clone()),
                 trade_symbols,
+                &visited,
             )
             .await?;
+
+            visited.insert(next_waypoint);
         }
 
         Ok(())
@@ -350,6 +328,7 @@ impl TransportPilot {
     async fn get_next_best_sell_waypoint(
         &self,
         ship: &ship::MyShip,
+        visited: &HashSet<String>,
     ) -> Option<(String, Vec<models::TradeSymbol>)> {
         let cargo_data = &ship.cargo;
 
@@ -364,6 +343,7 @@ impl TransportPilot {
 
         let filtered_trades = all_trades
             .into_iter()
+            .filter(|t| !visited.contains(&t.waypoint_symbol))
             .map(|t| (cargo_data.get_amount(&t.symbol), t))
             .filter(|(amount, _)| *amount > 0)
             .collect::<Vec<_>>();
@@ -414,9 +394,34 @@ impl TransportPilot {
         database_pool: &database::DbPool,
         reason: database::TransactionReason,
         trade_symbols: Vec<models::TradeSymbol>,
+        visited: &HashSet<String>,
     ) -> Result<()> {
         let possible_trades = ship.get_market_info(api, database_pool).await?;
 
+        let fallback = { self.context.config.read().await.price_impact_fallback as f64 };
+        let price_model = self
+            .get_price_model(database_pool, &ship.nav.system_symbol, fallback)
+            .await?;
+
+        // the best price for each good at the markets still ahead, selling here stops once the price falls below it
+        let mut alternatives: HashMap<models::TradeSymbol, i32> = HashMap::new();
+        for trade in database::MarketTradeGood::get_last_by_system(
+            database_pool,
+            &ship.nav.system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items
+        {
+            if trade.waypoint_symbol == ship.nav.waypoint_symbol
+                || visited.contains(&trade.waypoint_symbol)
+            {
+                continue;
+            }
+            let best = alternatives.entry(trade.symbol).or_insert(trade.sell_price);
+            *best = (*best).max(trade.sell_price);
+        }
+
         ship.ensure_docked(api).await?;
 
         for trade in trade_symbols {
@@ -433,6 +438,24 @@ impl TransportPilot {
                 tracing::info!(trade = ?trade, "Skipping trade as cargo is empty");
                 continue;
             }
+            let amount = match (
+                possible_trades.iter().find(|t| t.symbol == trade),
+                alternatives.get(&trade),
+            ) {
+                (Some(market_trade), Some(alternative)) => price_model
+                    .sell_impact(&ship.nav.waypoint_symbol, trade)
+                    .units_until(
+                        market_trade.sell_price,
+                        market_trade.trade_volume,
+                        *alternative as f64,
+                        amount,
+                    ),
+                _ => amount,
+            };
+            if amount == 0 {
+                debug!(trade = ?trade, "Skipping trade as another market pays more");
+                continue;
+            }
             debug!(amount = amount, trade = ?trade, ship_symbol = %ship.symbol, "Selling units of trade for ship");
 
             let budget_manager = self.context.budget_manager.clone();
@@ -454,6 +477,63 @@ impl TransportPilot {
     }
 }
 
+/// Asks the extractor of the request to transfer its cargo to `ship` and tracks the received cargo
+pub(crate) async fn handle_transfer_request(
+    ship: &mut ship::MyShip,
+    request: TransportTransferRequest,
+) -> Result<()> {
+    let (callback, receiver) = tokio::sync::oneshot::channel();
+
+    let extractor_req = ExtractorTransferRequest {
+        from_symbol: request.from_symbol.clone(),
+        to_symbol: ship.symbol.clone(),
+        amount: request.amount,
+        trade_symbol: request.trade_symbol,
+        callback,
+    };
+
+    let erg = request.extractor_contact.send(extractor_req).await;
+
+    if let Err(err) = erg {
+        // the mining ship had dropped
+        tracing::error!(
+          error=?err,
+          "Failed to send message to extractor",
+        );
+        let _erg = request.callback.send(());
+
+        return Ok(());
+    }
+
+    let transfer = receiver.await;
+
+    let transfer = match transfer {
+        Ok(transfer) => transfer,
+        Err(e) => {
+            tracing::error!(
+              error=?e,
+              "Failed to send message to extractor",
+            );
+            return Ok(());
+        }
+    };
+
+    let transfer = if let Some(transfer) = transfer {
+        transfer
+    } else {
+        return Ok(());
+    };
+
+    ship.cargo
+        .handle_cago_update(transfer.units, transfer.trade_symbol)?;
+
+    ship.notify(true).await;
+
+    let _erg = request.callback.send(());
+
+    Ok(())
+}
+
 fn calculate_median(vec: Vec<models::SupplyLevel>) -> Option<models::SupplyLevel> {
     let len = vec.len();
 
@@ -1,7 +1,11 @@
 mod charting;
 mod construction;
 mod contract;
+pub mod maintenance;
+mod manuel;
 pub mod mining;
+mod outfitting;
+mod refining;
 mod scraper;
 mod trading;
 
@@ -9,8 +13,13 @@ use charting::ChartPilot;
 use construction::ConstructionPilot;
 use contract::ContractPilot;
 use database::DatabaseConnectorAsync;
+use maintenance::{MaintenanceOutcome, MaintenancePilot};
+use manuel::ManuelPilot;
 use mining::MiningPilot;
+use outfitting::{OutfitOutcome, OutfittingPilot};
+use refining::RefiningPilot;
 use scraper::ScraperPilot;
+use space_traders_client::RequestPriority;
 use tokio_util::sync::CancellationToken;
 use tracing::debug;
 use tracing::instrument;
@@ -33,6 +42,10 @@ pub struct Pilot {
     contract_pilot: ContractPilot,
     mining_pilot: MiningPilot,
     chart_pilot: ChartPilot,
+    refining_pilot: RefiningPilot,
+    outfitting_pilot: OutfittingPilot,
+    maintenance_pilot: MaintenancePilot,
+    manuel_pilot: ManuelPilot,
     error_count: u32,
 }
 
@@ -46,20 +59,63 @@ impl Pilot {
         debug!(ship_symbol, "Creating pilot for ship");
 
         Self {
-            context: context.clone(),
+            context: context.with_api_subsystem("pilot"),
             ship_symbol: ship_symbol.clone(),
             fast_cancellation_token,
             slow_cancellation_token,
-            construction_pilot: ConstructionPilot::new(context.clone(), ship_symbol.clone()),
-            trading_pilot: TradingPilot::new(context.clone(), ship_symbol.clone()),
-            scraper_pilot: ScraperPilot::new(context.clone(), ship_symbol.clone()),
-            contract_pilot: ContractPilot::new(context.clone(), ship_symbol.clone()),
-            mining_pilot: MiningPilot::new(context.clone(), ship_symbol.clone()),
-            chart_pilot: ChartPilot::new(context.clone(), ship_symbol.clone()),
+            construction_pilot: ConstructionPilot::new(
+                context.with_api_subsystem("construction_pilot"),
+                ship_symbol.clone(),
+            ),
+            trading_pilot: TradingPilot::new(
+                context
+                    .with_api_priority(RequestPriority::High)
+                    .with_api_subsystem("trading_pilot"),
+                ship_symbol.clone(),
+            ),
+            scraper_pilot: ScraperPilot::new(
+                context
+                    .with_api_priority(RequestPriority::Low)
+                    .with_api_subsystem("scraper_pilot"),
+                ship_symbol.clone(),
+            ),
+            contract_pilot: ContractPilot::new(
+                context
+                    .with_api_priority(RequestPriority::High)
+                    .with_api_subsystem("contract_pilot"),
+                ship_symbol.clone(),
+            ),
+            mining_pilot: MiningPilot::new(
+                context.with_api_subsystem("mining_pilot"),
+                ship_symbol.clone(),
+            ),
+            chart_pilot: ChartPilot::new(
+                context
+                    .with_api_priority(RequestPriority::Low)
+                    .with_api_subsystem("chart_pilot"),
+                ship_symbol.clone(),
+            ),
+            refining_pilot: RefiningPilot::new(
+                context.with_api_subsystem("refining_pilot"),
+                ship_symbol.clone(),
+            ),
+            outfitting_pilot: OutfittingPilot::new(
+                context.with_api_subsystem("outfitting_pilot"),
+                ship_symbol.clone(),
+            ),
+            maintenance_pilot: MaintenancePilot::new(
+                context.with_api_subsystem("maintenance_pilot"),
+                ship_symbol.clone(),
+            ),
+            manuel_pilot: ManuelPilot::new(
+                context.with_api_subsystem("manuel_pilot"),
+                ship_symbol.clone(),
+            ),
             error_count: 0,
         }
     }
 
+    #[allow(dead_code)]
     pub fn get_cancel_token(&self) -> CancellationToken {
         self.slow_cancellation_token.clone()
     }
@@ -158,9 +214,84 @@ impl Pilot {
         }
     }
 
+    /// Whether the ship and the fleet of its current assignment are active
+    async fn is_active(&self) -> Result<bool> {
+        let (ship_info, assignment) = self.get_ship_assignment().await?;
+        Ok(ship_info.active && assignment.is_none_or(|(_, fleet, _)| fleet.active))
+    }
+
+    /// Parks the ship until it or its fleet is activated again.
+    /// The current step is already finished, so only the mining waypoint is still held and released here.
     async fn wait_for_activation(&self) -> Result<()> {
-        debug!("Waiting for activation");
-        todo!()
+        debug!(ship_symbol = %self.ship_symbol, "Waiting for activation");
+
+        self.park().await?;
+
+        loop {
+            let activation_changed = self.context.ship_tasks.activation_changed();
+            tokio::pin!(activation_changed);
+            activation_changed.as_mut().enable();
+
+            if self.is_active().await? {
+                break;
+            }
+
+            tokio::select! {
+                _ = activation_changed => {},
+                _ = self.slow_cancellation_token.cancelled() => return Ok(()),
+            }
+        }
+
+        debug!(ship_symbol = %self.ship_symbol, "Ship activated again");
+        self.set_paused(false).await?;
+        Ok(())
+    }
+
+    async fn park(&self) -> Result<()> {
+        let ship_clone = {
+            let mut erg = self.context.ship_manager.get_mut(&self.ship_symbol).await;
+            let ship = erg
+                .value_mut()
+                .ok_or(Error::General("Ship not found".to_string()))?;
+
+            if ship.status.paused {
+                return Ok(());
+            }
+
+            ship.status.paused = true;
+            ship.notify(true).await;
+            ship.to_immutable()
+        };
+
+        if let ship::AssignmentStatus::Mining {
+            assignment:
+                ship::status::MiningShipAssignment::Extractor { .. }
+                | ship::status::MiningShipAssignment::Siphoner { .. },
+        } = ship_clone.status.status
+            && let Err(err) = self
+                .context
+                .mining_manager
+                .unassign_waypoint(ship_clone)
+                .await
+        {
+            tracing::warn!(ship_symbol = %self.ship_symbol, error = ?err, "Failed to release mining waypoint of parked ship");
+        }
+
+        Ok(())
+    }
+
+    async fn set_paused(&self, paused: bool) -> Result<()> {
+        let mut erg = self.context.ship_manager.get_mut(&self.ship_symbol).await;
+        let ship = erg
+            .value_mut()
+            .ok_or(Error::General("Ship not found".to_string()))?;
+
+        if ship.status.paused != paused {
+            ship.status.paused = paused;
+            ship.notify(true).await;
+        }
+
+        Ok(())
     }
 
     #[instrument(level = "info", name = "spacetraders::pilot::pilot_circle", skip(self), fields(self.ship_symbol = %self.ship_symbol), err(Debug))]
@@ -205,6 +336,54 @@ impl Pilot {
 
             self.fly_to_system(&fleet, &assignment).await?;
 
+            // ships of manuel fleets are only controlled through their queued commands
+            let is_manuel = fleet.fleet_type == database::FleetType::Manuel;
+
+            if !is_manuel
+                && let MaintenanceOutcome::Scrapped = self
+                    .maintenance_pilot
+                    .maintain_ship(self, &assignment)
+                    .await?
+            {
+                debug!(ship_symbol = %self.ship_symbol, "Ship was scrapped, stopping pilot");
+                self.slow_cancellation_token.cancel();
+                return Ok(());
+            }
+
+            let outfit_ships = { self.context.config.read().await.outfit_ships };
+            let outcome = if outfit_ships && !is_manuel {
+                self.outfitting_pilot
+                    .outfit_ship(self, &fleet, &assignment)
+                    .await?
+            } else {
+                OutfitOutcome::Ready
+            };
+            match outcome {
+                OutfitOutcome::Ready => {}
+                OutfitOutcome::Waiting => return Ok(()),
+                OutfitOutcome::Impossible => {
+                    debug!(assignment_id = assignment.id, ship_symbol = %self.ship_symbol, "Ship can't fulfill the assignment, unassigning ship");
+                    if is_temp {
+                        database::ShipInfo::unassign_temp_ship(
+                            &self.context.database_pool,
+                            &self.ship_symbol,
+                        )
+                        .await?;
+                    } else {
+                        database::ShipInfo::unassign_ship(
+                            &self.context.database_pool,
+                            &self.ship_symbol,
+                        )
+                        .await?;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(
+                        60_000 + rand::random::<u64>() % 1_000,
+                    ))
+                    .await;
+                    return Ok(());
+                }
+            }
+
             match fleet.get_config()? {
                 database::FleetConfig::Trading(trading_config) => {
                     self.trading_pilot
@@ -236,12 +415,14 @@ impl Pilot {
                         .execute_pilot_circle(self, fleet, assignment, contract_config)
                         .await?;
                 }
+                database::FleetConfig::Refining(refining_config) => {
+                    self.refining_pilot
+                        .execute_pilot_circle(self, fleet, assignment, refining_config)
+                        .await?;
+                }
                 database::FleetConfig::Manuel(_) => {
-                    debug!(fleet_id = fleet.id, ship_symbol = %self.ship_symbol, "Fleet is manuel, ship piloting idle behavior");
-                    tokio::time::sleep(std::time::Duration::from_millis(
-                        60_000 + rand::random::<u64>() % 1_000,
-                    ))
-                    .await;
+                    debug!(fleet_id = fleet.id, ship_symbol = %self.ship_symbol, "Fleet is manuel, executing queued commands");
+                    self.manuel_pilot.execute_pilot_circle(self, fleet).await?;
                 }
             }
 
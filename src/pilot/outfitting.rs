@@ -0,0 +1,347 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use space_traders_client::models;
+use tracing::debug;
+use tracing::instrument;
+use utils::WaypointCan;
+
+use crate::{
+    error::{Error, Result},
+    manager::fleet_manager::{OutfitPlan, OutfitPlanner, OutfitStep},
+    utils::ConductorContext,
+};
+
+/// the shipyard fee for installing or removing a part is not known in advance, reserve this share of the part prices for it
+const FEE_MARGIN: f64 = 0.25;
+
+/// how long the loaded part infos of the ship are reused before they are loaded again (in seconds)
+const PLANNER_TTL: i64 = 10 * 60;
+
+pub enum OutfitOutcome {
+    /// the ship fulfills the requirements of the assignment
+    Ready,
+    /// the ship has to wait, e.g. for funds to buy the parts, for a known shipyard selling them or for free cargo space
+    Waiting,
+    /// the parts of the ship can never fulfill the requirements of the assignment
+    Impossible,
+}
+
+/// Swaps the mounts and modules of a ship at a shipyard so it fulfills the requirements of its assignment,
+/// this lets a hull be repurposed between fleets instead of buying a new ship.
+pub struct OutfittingPilot {
+    context: ConductorContext,
+    ship_symbol: String,
+    /// the last loaded planner and when it was loaded
+    planner: tokio::sync::Mutex<Option<(chrono::DateTime<chrono::Utc>, Arc<OutfitPlanner>)>>,
+}
+
+impl OutfittingPilot {
+    pub fn new(context: ConductorContext, ship_symbol: String) -> Self {
+        Self {
+            context,
+            ship_symbol,
+            planner: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    async fn get_planner(&self, ship_clone: &ship::MyShipCopy) -> Result<Arc<OutfitPlanner>> {
+        let mut cached = self.planner.lock().await;
+        let now = chrono::Utc::now();
+        if let Some((loaded_at, planner)) = cached.as_ref()
+            && (now - *loaded_at).num_seconds() < PLANNER_TTL
+            && planner.is_for(ship_clone)
+        {
+            return Ok(planner.clone());
+        }
+
+        let planner = Arc::new(OutfitPlanner::load(&self.context.database_pool, ship_clone).await?);
+        *cached = Some((now, planner.clone()));
+        Ok(planner)
+    }
+
+    #[instrument(level = "info", name = "spacetraders::pilot::outfitting::outfit_ship", skip(self, pilot, fleet, ship_assignment), fields(self.ship_symbol = %self.ship_symbol, fleet_id = fleet.id, ship_assignment_id = ship_assignment.id))]
+    pub async fn outfit_ship(
+        &self,
+        pilot: &super::Pilot,
+        fleet: &database::Fleet,
+        ship_assignment: &database::ShipAssignment,
+    ) -> Result<OutfitOutcome> {
+        let mut erg = pilot.context.ship_manager.get_mut(&self.ship_symbol).await;
+        let ship = erg
+            .value_mut()
+            .ok_or(Error::General("Ship not found".to_string()))?;
+
+        let ship_clone = ship.to_immutable();
+        let planner = self.get_planner(&ship_clone).await?;
+        let Some(plan) = planner.plan(&ship_clone, ship_assignment) else {
+            debug!("Ship can't be outfitted for the assignment");
+            return Ok(OutfitOutcome::Impossible);
+        };
+
+        if plan.is_empty() {
+            return Ok(OutfitOutcome::Ready);
+        }
+
+        let Some((waypoint_symbol, prices)) = self
+            .get_outfitting_waypoint(&ship.nav.system_symbol, &ship.nav.waypoint_symbol, &plan)
+            .await?
+        else {
+            debug!(plan = ?plan, "No known shipyard sells the needed parts, waiting");
+            drop(erg);
+            Self::wait().await;
+            return Ok(OutfitOutcome::Waiting);
+        };
+
+        let free_cargo = ship.cargo.capacity - ship.cargo.units;
+        if free_cargo < 1 {
+            tracing::warn!(ship_symbol = %ship.symbol, "No free cargo space to swap parts, waiting");
+            drop(erg);
+            Self::wait().await;
+            return Ok(OutfitOutcome::Waiting);
+        }
+
+        let parts_cost = plan
+            .parts_to_buy()
+            .iter()
+            .map(|part| prices[part] as i64)
+            .sum::<i64>();
+        let estimated_cost = parts_cost + (parts_cost as f64 * FEE_MARGIN) as i64;
+        let reservation = match self
+            .context
+            .budget_manager
+            .reserve_funds(&self.context.database_pool, estimated_cost)
+            .await
+        {
+            Ok(reservation) => reservation,
+            Err(Error::NotEnoughFunds { .. }) => {
+                debug!(
+                    estimated_cost,
+                    "Not enough funds to outfit the ship, waiting"
+                );
+                drop(erg);
+                Self::wait().await;
+                return Ok(OutfitOutcome::Waiting);
+            }
+            Err(err) => return Err(err),
+        };
+
+        ship.status.status = ship::AssignmentStatus::Outfitting {
+            fleet_id: fleet.id,
+            assignment_id: ship_assignment.id,
+            waypoint_symbol: waypoint_symbol.clone(),
+        };
+        ship.notify(true).await;
+
+        let mut spent = 0;
+        let erg = self
+            .execute_plan(ship, &plan, &waypoint_symbol, &mut spent)
+            .await;
+
+        self.context
+            .budget_manager
+            .complete_use_reservation(&self.context.database_pool, reservation.id, spent.max(0))
+            .await?;
+
+        erg?;
+
+        Ok(OutfitOutcome::Ready)
+    }
+
+    async fn wait() {
+        tokio::time::sleep(std::time::Duration::from_millis(
+            60_000 + rand::random::<u64>() % 1_000,
+        ))
+        .await;
+    }
+
+    async fn execute_plan(
+        &self,
+        ship: &mut ship::MyShip,
+        plan: &OutfitPlan,
+        waypoint_symbol: &str,
+        spent: &mut i64,
+    ) -> Result<()> {
+        let budget_manager = self.context.budget_manager.clone();
+
+        let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+        ship.nav_to(
+            waypoint_symbol,
+            true,
+            database::TransactionReason::None,
+            &self.context.database_pool,
+            &self.context.api,
+            update_funds_fn,
+        )
+        .await?;
+
+        let market_trades = ship
+            .get_market_info(&self.context.api, &self.context.database_pool)
+            .await?;
+
+        ship.ensure_docked(&self.context.api).await?;
+
+        for step in plan.steps.iter() {
+            let trade_symbol = step
+                .trade_symbol()
+                .ok_or(Error::General(format!("No trade symbol for {:?}", step)))?;
+
+            if step.is_install() {
+                let budget_manager = self.context.budget_manager.clone();
+
+                let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+                *spent += ship
+                    .purchase_cargo(
+                        &self.context.api,
+                        &trade_symbol,
+                        1,
+                        &self.context.database_pool,
+                        database::TransactionReason::None,
+                        update_funds_fn,
+                    )
+                    .await?;
+            }
+
+            let budget_manager = self.context.budget_manager.clone();
+
+            let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+            let transaction = match step {
+                OutfitStep::RemoveMount(symbol) => {
+                    ship.remove_mount(
+                        &self.context.api,
+                        *symbol,
+                        &self.context.database_pool,
+                        update_funds_fn,
+                    )
+                    .await?
+                }
+                OutfitStep::RemoveModule(symbol) => {
+                    ship.remove_module(
+                        &self.context.api,
+                        *symbol,
+                        &self.context.database_pool,
+                        update_funds_fn,
+                    )
+                    .await?
+                }
+                OutfitStep::InstallMount(symbol) => {
+                    ship.install_mount(
+                        &self.context.api,
+                        *symbol,
+                        &self.context.database_pool,
+                        update_funds_fn,
+                    )
+                    .await?
+                }
+                OutfitStep::InstallModule(symbol) => {
+                    ship.install_module(
+                        &self.context.api,
+                        *symbol,
+                        &self.context.database_pool,
+                        update_funds_fn,
+                    )
+                    .await?
+                }
+            };
+            *spent += transaction.total_price as i64;
+
+            debug!(step = ?step, price = transaction.total_price, "Outfitting step done");
+
+            if !step.is_install() && ship.cargo.has(&trade_symbol) {
+                if market_trades.iter().any(|t| t.symbol == trade_symbol) {
+                    let budget_manager = self.context.budget_manager.clone();
+
+                    let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+                    let income = ship
+                        .sell_cargo(
+                            &self.context.api,
+                            &trade_symbol,
+                            ship.cargo.get_amount(&trade_symbol),
+                            &self.context.database_pool,
+                            database::TransactionReason::None,
+                            update_funds_fn,
+                        )
+                        .await?;
+                    *spent -= income;
+                } else {
+                    tracing::warn!(ship_symbol = %ship.symbol, trade_symbol = %trade_symbol, "Removed part can't be sold here, keeping it in the cargo");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the shipyard in the system selling all parts of the plan for the lowest total and the prices of the parts there
+    ///
+    /// A plan which only removes parts takes the nearest shipyard.
+    async fn get_outfitting_waypoint(
+        &self,
+        system_symbol: &str,
+        waypoint_symbol: &str,
+        plan: &OutfitPlan,
+    ) -> Result<Option<(String, HashMap<models::TradeSymbol, i32>)>> {
+        let waypoints = database::Waypoint::get_by_system(
+            &self.context.database_pool,
+            system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items;
+
+        let parts = plan.parts_to_buy();
+
+        if parts.is_empty() {
+            let Some(current) = waypoints.iter().find(|w| w.symbol == waypoint_symbol) else {
+                return Ok(None);
+            };
+            return Ok(waypoints
+                .iter()
+                .filter(|w| w.is_shipyard())
+                .min_by(|a, b| {
+                    let distance_a =
+                        utils::distance_between_waypoints((current.x, current.y), (a.x, a.y));
+                    let distance_b =
+                        utils::distance_between_waypoints((current.x, current.y), (b.x, b.y));
+                    distance_a.total_cmp(&distance_b)
+                })
+                .map(|w| (w.symbol.clone(), HashMap::new())));
+        }
+
+        let shipyards = waypoints
+            .into_iter()
+            .filter(|w| w.is_shipyard() && w.is_marketplace())
+            .map(|w| w.symbol)
+            .collect::<HashSet<_>>();
+
+        let trades = database::MarketTradeGood::get_last_by_system(
+            &self.context.database_pool,
+            system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items;
+
+        let mut waypoints: HashMap<String, HashMap<models::TradeSymbol, i32>> = HashMap::new();
+        for trade in trades {
+            if !shipyards.contains(&trade.waypoint_symbol) || !parts.contains(&trade.symbol) {
+                continue;
+            }
+            waypoints
+                .entry(trade.waypoint_symbol)
+                .or_default()
+                .insert(trade.symbol, trade.purchase_price);
+        }
+
+        Ok(waypoints
+            .into_iter()
+            .filter(|(_, prices)| parts.iter().all(|part| prices.contains_key(part)))
+            .min_by_key(|(_, prices)| parts.iter().map(|part| prices[part]).sum::<i32>()))
+    }
+}
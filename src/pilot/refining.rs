@@ -0,0 +1,465 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, atomic::AtomicI32},
+};
+
+use ship::status::RefinerState;
+use space_traders_client::models::{self, TradeSymbol, ship_refine_request::Produce};
+use tracing::debug;
+use tracing::instrument;
+
+use crate::{
+    error::{Error, Result},
+    utils::ConductorContext,
+};
+
+/// units of ore consumed by one refine
+const ORE_PER_REFINE: i32 = 30;
+/// units of refined goods produced by one refine
+const PRODUCT_PER_REFINE: i32 = 10;
+
+/// ore, what it is refined into and the trade symbol of the refined good
+const REFINE_RECIPES: [(TradeSymbol, Produce, TradeSymbol); 8] = [
+    (TradeSymbol::IronOre, Produce::Iron, TradeSymbol::Iron),
+    (TradeSymbol::CopperOre, Produce::Copper, TradeSymbol::Copper),
+    (TradeSymbol::SilverOre, Produce::Silver, TradeSymbol::Silver),
+    (TradeSymbol::GoldOre, Produce::Gold, TradeSymbol::Gold),
+    (
+        TradeSymbol::AluminumOre,
+        Produce::Aluminum,
+        TradeSymbol::Aluminum,
+    ),
+    (
+        TradeSymbol::PlatinumOre,
+        Produce::Platinum,
+        TradeSymbol::Platinum,
+    ),
+    (
+        TradeSymbol::UraniteOre,
+        Produce::Uranite,
+        TradeSymbol::Uranite,
+    ),
+    (
+        TradeSymbol::MeritiumOre,
+        Produce::Meritium,
+        TradeSymbol::Meritium,
+    ),
+];
+
+/// Refinery ships act as transporters for the mining fleet: they load ore from the extractors at the asteroids,
+/// refine it when the refined good sells for enough more than the ore and sell the result.
+pub struct RefiningPilot {
+    context: ConductorContext,
+    ship_symbol: String,
+    count: Arc<AtomicI32>,
+}
+
+impl RefiningPilot {
+    pub fn new(context: ConductorContext, ship_symbol: String) -> Self {
+        Self {
+            context,
+            ship_symbol,
+            count: Arc::new(AtomicI32::new(0)),
+        }
+    }
+
+    #[instrument(level = "info", name = "spacetraders::pilot::refining::pilot_refining", skip(self, pilot, fleet, ship_assignment, refining_config), fields(self.ship_symbol = %self.ship_symbol, fleet_id = fleet.id, ship_assignment_id = ship_assignment.id))]
+    pub async fn execute_pilot_circle(
+        &self,
+        pilot: &super::Pilot,
+        fleet: database::Fleet,
+        ship_assignment: database::ShipAssignment,
+        refining_config: database::RefiningFleetConfig,
+    ) -> Result<()> {
+        let mut erg = pilot.context.ship_manager.get_mut(&self.ship_symbol).await;
+        let ship = erg
+            .value_mut()
+            .ok_or(Error::General("Ship not found".to_string()))?;
+
+        self.count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        self.set_state(ship, RefinerState::Unknown, None).await;
+
+        let mut mining_waypoint = ship.nav.waypoint_symbol.clone();
+
+        if !self.is_full(ship) {
+            match self
+                .context
+                .mining_manager
+                .get_next_transport(ship.to_immutable())
+                .await
+            {
+                Ok(next_mining_waypoint) => {
+                    mining_waypoint = next_mining_waypoint;
+                    self.load_ore(ship, pilot, &mining_waypoint).await?;
+                }
+                Err(Error::General(err)) if err == "No routes found" => {
+                    debug!("No mining waypoint to load ore from");
+                    if ship.cargo.get_units_no_fuel() == 0 {
+                        tokio::time::sleep(std::time::Duration::from_millis(
+                            10_000 + rand::random::<u64>() % 1_000,
+                        ))
+                        .await;
+                        return Ok(());
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let prices = self.get_best_sell_prices(&ship.nav.system_symbol).await?;
+
+        self.refine_cargo(
+            ship,
+            pilot,
+            &prices,
+            refining_config.refine_profit_threshold,
+        )
+        .await?;
+
+        self.sell_cargo(
+            ship,
+            pilot,
+            &prices,
+            &mining_waypoint,
+            refining_config.refine_profit_threshold,
+        )
+        .await?;
+
+        self.set_state(ship, RefinerState::Unknown, None).await;
+
+        Ok(())
+    }
+
+    async fn set_state(
+        &self,
+        ship: &mut ship::MyShip,
+        state: RefinerState,
+        waypoint_symbol: Option<String>,
+    ) {
+        ship.status.status = ship::AssignmentStatus::Refining {
+            cycle: Some(self.count.load(std::sync::atomic::Ordering::Relaxed)),
+            state,
+            waypoint_symbol,
+            refined: None,
+        };
+        ship.notify(true).await;
+    }
+
+    fn is_full(&self, ship: &ship::MyShip) -> bool {
+        let capacity = ship.cargo.capacity - ship.cargo.get_amount(&TradeSymbol::Fuel);
+        capacity <= 0 || ship.cargo.get_units_no_fuel() as f32 / capacity as f32 > 0.95
+    }
+
+    /// flies to the mining waypoint and takes cargo from the extractors, the same way mining transporters do
+    async fn load_ore(
+        &self,
+        ship: &mut ship::MyShip,
+        pilot: &super::Pilot,
+        mining_waypoint: &str,
+    ) -> Result<()> {
+        self.set_state(
+            ship,
+            RefinerState::InTransitToAsteroid,
+            Some(mining_waypoint.to_string()),
+        )
+        .await;
+
+        let budget_manager = self.context.budget_manager.clone();
+
+        let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+        ship.nav_to_prepare(
+            mining_waypoint,
+            true,
+            database::TransactionReason::MiningWaypoint(mining_waypoint.to_string()),
+            true,
+            &self.context.database_pool,
+            &self.context.api,
+            update_funds_fn,
+        )
+        .await?;
+
+        let mut rec = self
+            .context
+            .mining_manager
+            .transport_contact(&ship.symbol)
+            .await?;
+
+        let _erg = self
+            .context
+            .mining_manager
+            .transport_arrived(&ship.symbol, &ship.nav.waypoint_symbol)
+            .await?;
+
+        while !self.is_full(ship) {
+            self.set_state(
+                ship,
+                RefinerState::WaitingForCargo,
+                Some(mining_waypoint.to_string()),
+            )
+            .await;
+
+            let msg = tokio::select! {
+                _ = pilot.slow_cancellation_token.cancelled() => None,
+                msg = rec.recv() => msg,
+            };
+
+            let Some(transfer_request) = msg else {
+                debug!(ship_symbol = %ship.symbol, "No more transfers, leaving the asteroid");
+                break;
+            };
+
+            self.set_state(
+                ship,
+                RefinerState::LoadingCargo,
+                Some(mining_waypoint.to_string()),
+            )
+            .await;
+
+            super::mining::handle_transfer_request(ship, transfer_request).await?;
+        }
+
+        drop(rec);
+
+        Ok(())
+    }
+
+    /// best known sell price of every good in the system
+    async fn get_best_sell_prices(&self, system_symbol: &str) -> Result<HashMap<TradeSymbol, i32>> {
+        let trades = database::MarketTradeGood::get_last_by_system(
+            &self.context.database_pool,
+            system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items;
+
+        let mut prices: HashMap<TradeSymbol, i32> = HashMap::new();
+        for trade in trades {
+            let price = prices.entry(trade.symbol).or_insert(trade.sell_price);
+            *price = (*price).max(trade.sell_price);
+        }
+        Ok(prices)
+    }
+
+    /// profit of refining 30 units of `ore` into 10 units of `product`, `None` if one of them has no known market
+    fn refine_profit(
+        prices: &HashMap<TradeSymbol, i32>,
+        ore: &TradeSymbol,
+        product: &TradeSymbol,
+    ) -> Option<i32> {
+        let ore_price = prices.get(ore)?;
+        let product_price = prices.get(product)?;
+        Some(product_price * PRODUCT_PER_REFINE - ore_price * ORE_PER_REFINE)
+    }
+
+    async fn refine_cargo(
+        &self,
+        ship: &mut ship::MyShip,
+        pilot: &super::Pilot,
+        prices: &HashMap<TradeSymbol, i32>,
+        profit_threshold: i32,
+    ) -> Result<()> {
+        if !ship.modules.can_refine_ore() {
+            tracing::warn!(ship_symbol = %ship.symbol, "Ship has no ore refinery, only transporting");
+            return Ok(());
+        }
+
+        let mut refined = 0;
+
+        for (ore, produce, product) in REFINE_RECIPES.iter() {
+            let profit = Self::refine_profit(prices, ore, product);
+            if profit.is_none_or(|profit| profit < profit_threshold) {
+                debug!(ore = %ore, profit = ?profit, "Refining not profitable");
+                continue;
+            }
+
+            while ship.cargo.get_amount(ore) >= ORE_PER_REFINE {
+                if pilot.slow_cancellation_token.is_cancelled() {
+                    return Ok(());
+                }
+
+                ship.status.status = ship::AssignmentStatus::Refining {
+                    cycle: Some(self.count.load(std::sync::atomic::Ordering::Relaxed)),
+                    state: RefinerState::Refining,
+                    waypoint_symbol: Some(ship.nav.waypoint_symbol.clone()),
+                    refined: Some(refined),
+                };
+                ship.notify(true).await;
+
+                ship.wait_for_cooldown().await;
+                ship.ensure_undocked(&self.context.api).await?;
+
+                let refine = ship.refine(&self.context.api, *produce).await?;
+                refined += refine
+                    .data
+                    .produced
+                    .iter()
+                    .map(|produced| produced.units)
+                    .sum::<i32>();
+
+                debug!(ore = %ore, product = %product, profit = ?profit, "Refined ore");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the cargo the ship keeps for the next cycle, leftovers of ores worth refining which are not enough for a refine yet
+    fn get_kept_cargo(
+        &self,
+        ship: &ship::MyShip,
+        prices: &HashMap<TradeSymbol, i32>,
+        profit_threshold: i32,
+    ) -> HashMap<TradeSymbol, i32> {
+        let kept = REFINE_RECIPES
+            .iter()
+            .filter(|(ore, _, product)| {
+                Self::refine_profit(prices, ore, product)
+                    .is_some_and(|profit| profit >= profit_threshold)
+            })
+            .map(|(ore, _, _)| (*ore, ship.cargo.get_amount(ore)))
+            .filter(|(_, amount)| *amount > 0 && *amount < ORE_PER_REFINE)
+            .collect::<HashMap<_, _>>();
+
+        // don't clog the cargo hold with leftovers
+        if kept.values().sum::<i32>() * 2 > ship.cargo.capacity {
+            return HashMap::new();
+        }
+        kept
+    }
+
+    async fn sell_cargo(
+        &self,
+        ship: &mut ship::MyShip,
+        pilot: &super::Pilot,
+        prices: &HashMap<TradeSymbol, i32>,
+        mining_waypoint: &str,
+        profit_threshold: i32,
+    ) -> Result<()> {
+        let kept = self.get_kept_cargo(ship, prices, profit_threshold);
+        let reason = database::TransactionReason::MiningWaypoint(mining_waypoint.to_string());
+
+        loop {
+            if pilot.slow_cancellation_token.is_cancelled() {
+                break;
+            }
+
+            let to_sell = ship
+                .cargo
+                .inventory
+                .iter()
+                .filter(|(symbol, _)| **symbol != TradeSymbol::Fuel && !kept.contains_key(symbol))
+                .map(|(symbol, amount)| (*symbol, *amount))
+                .collect::<HashMap<_, _>>();
+
+            if to_sell.is_empty() {
+                break;
+            }
+
+            let Some((waypoint_symbol, trade_symbols)) = self
+                .get_next_sell_waypoint(&ship.nav.system_symbol, &to_sell)
+                .await?
+            else {
+                tracing::warn!(ship_symbol = %ship.symbol, cargo = ?to_sell, "No market to sell the cargo");
+                break;
+            };
+
+            self.set_state(
+                ship,
+                RefinerState::InTransitToMarket,
+                Some(waypoint_symbol.clone()),
+            )
+            .await;
+
+            let budget_manager = self.context.budget_manager.clone();
+
+            let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+            ship.nav_to(
+                &waypoint_symbol,
+                true,
+                reason.clone(),
+                &self.context.database_pool,
+                &self.context.api,
+                update_funds_fn,
+            )
+            .await?;
+
+            self.set_state(
+                ship,
+                RefinerState::SellingCargo,
+                Some(waypoint_symbol.clone()),
+            )
+            .await;
+
+            let market_trades = ship
+                .get_market_info(&self.context.api, &self.context.database_pool)
+                .await?;
+
+            ship.ensure_docked(&self.context.api).await?;
+
+            let mut sold = false;
+            for trade_symbol in trade_symbols {
+                let amount = ship.cargo.get_amount(&trade_symbol);
+                if amount == 0 || !market_trades.iter().any(|t| t.symbol == trade_symbol) {
+                    continue;
+                }
+
+                let budget_manager = self.context.budget_manager.clone();
+
+                let update_funds_fn = move |amount| budget_manager.set_current_funds(amount);
+
+                ship.sell_cargo(
+                    &self.context.api,
+                    &trade_symbol,
+                    amount,
+                    &self.context.database_pool,
+                    reason.clone(),
+                    update_funds_fn,
+                )
+                .await?;
+                sold = true;
+            }
+
+            if !sold {
+                tracing::warn!(ship_symbol = %ship.symbol, waypoint_symbol, "Market did not buy the expected goods");
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the market in the system paying the most for the cargo and the goods to sell there
+    async fn get_next_sell_waypoint(
+        &self,
+        system_symbol: &str,
+        to_sell: &HashMap<TradeSymbol, i32>,
+    ) -> Result<Option<(String, Vec<models::TradeSymbol>)>> {
+        let trades = database::MarketTradeGood::get_last_by_system(
+            &self.context.database_pool,
+            system_symbol,
+            database::PaginatedQuery::unpaged(),
+        )
+        .await?
+        .items;
+
+        let mut waypoints: HashMap<String, (i32, Vec<TradeSymbol>)> = HashMap::new();
+        for trade in trades {
+            let Some(amount) = to_sell.get(&trade.symbol) else {
+                continue;
+            };
+            let waypoint = waypoints.entry(trade.waypoint_symbol).or_default();
+            waypoint.0 += amount * trade.sell_price;
+            waypoint.1.push(trade.symbol);
+        }
+
+        Ok(waypoints
+            .into_iter()
+            .max_by_key(|(_, (value, _))| *value)
+            .map(|(waypoint_symbol, (_, trade_symbols))| (waypoint_symbol, trade_symbols)))
+    }
+}
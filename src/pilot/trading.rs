@@ -48,6 +48,11 @@ impl TradingPilot {
 
         ship.notify(true).await;
 
+        let multi_leg = { self.context.config.read().await.trade_max_legs > 1 };
+        if multi_leg {
+            return self.execute_plan_circle(ship, pilot, trading_config).await;
+        }
+
         let route = self
             .context
             .trade_manager
@@ -92,6 +97,88 @@ impl TradingPilot {
 
         Ok(())
     }
+    /// Flies a trade plan leg by leg, every good is bought and sold like a single route and completed on its own
+    async fn execute_plan_circle(
+        &self,
+        ship: &mut ship::MyShip,
+        pilot: &crate::pilot::Pilot,
+        trading_config: database::TradingFleetConfig,
+    ) -> Result<()> {
+        let plan = self
+            .context
+            .trade_manager
+            .get_plan(ship.to_immutable(), trading_config)
+            .await?;
+
+        let Some(plan) = plan else {
+            self.wait(ship, pilot).await?;
+            return Ok(());
+        };
+
+        self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        tracing::Span::current().record("trade_route", format!("{:?}", plan));
+
+        let routes = database::TradePlan::get_routes(&self.context.database_pool, plan.id).await?;
+
+        let mut aborted = false;
+        for leg in routes.chunk_by(|a, b| a.0 == b.0) {
+            let open_routes = leg
+                .iter()
+                .map(|(_, route)| route)
+                .filter(|route| route.status == database::ShipmentStatus::InTransit)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let open_routes = match self.context.trade_manager.start_leg(open_routes).await {
+                Ok(open_routes) => open_routes,
+                Err(Error::NotEnoughFunds { .. }) => {
+                    debug!(
+                        "Not enough funds for leg {} of trade plan {}, aborting the plan",
+                        leg[0].0, plan.id
+                    );
+                    self.context.trade_manager.abort_plan(&plan).await?;
+                    aborted = true;
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+
+            debug!(
+                "Executing leg {} of trade plan {} with {} goods",
+                leg[0].0,
+                plan.id,
+                open_routes.len()
+            );
+
+            for route in open_routes.iter() {
+                self.execute_purchase(ship, route, pilot).await?;
+            }
+
+            for route in open_routes.iter() {
+                if ship.cargo.has(&route.symbol) {
+                    self.execute_sale(ship, route).await?;
+                }
+                self.context.trade_manager.complete_trade(route).await?;
+            }
+        }
+
+        if !aborted {
+            let _completed_plan = self.context.trade_manager.complete_plan(&plan).await?;
+        }
+        ship.status.status = ship::AssignmentStatus::Trader {
+            shipment_id: None,
+            cycle: None,
+            shipping_status: None,
+            waiting_for_manager: false,
+            on_sleep: false,
+        };
+
+        ship.notify(true).await;
+
+        Ok(())
+    }
+
     async fn wait(&self, ship: &mut ship::MyShip, pilot: &crate::pilot::Pilot) -> Result<()> {
         ship.status.status = ship::AssignmentStatus::Trader {
             shipment_id: None,
@@ -0,0 +1,174 @@
+use std::path::{Path, PathBuf};
+
+use tracing::{debug, info, instrument, warn};
+
+use crate::reset_runner::ResetSummary;
+
+/// where the knowledge carried from one reset into the next is written, next to the database dumps
+pub const BACKUP_DIR: &str = "./db_backup";
+const PRIORS_SUFFIX: &str = ".priors.json";
+const CONFIG_SUFFIX: &str = ".config.json";
+const TOKENS_SUFFIX: &str = ".tokens.json";
+
+/// Writes the knowledge of the finished reset to `<file_name>.priors.json`, next to the database dump
+#[instrument(skip(database_pool, reset_info))]
+pub async fn export_priors(
+    database_pool: &database::DbPool,
+    reset_info: &ResetSummary,
+    file_name: &str,
+) -> anyhow::Result<String> {
+    let priors = database::ResetPriors::collect(
+        database_pool,
+        file_name,
+        &reset_info.agent_symbol,
+        reset_info.start_date,
+        reset_info.end_date,
+    )
+    .await?;
+
+    tokio::fs::create_dir_all(BACKUP_DIR).await?;
+    let priors_file = format!("{}/{}{}", BACKUP_DIR, file_name, PRIORS_SUFFIX);
+    tokio::fs::write(&priors_file, serde_json::to_string_pretty(&priors)?).await?;
+
+    info!(priors_file, "Reset priors exported");
+    Ok(priors_file)
+}
+
+/// Loads the priors of an earlier reset into the database
+///
+/// Takes the file of `PRIOR_RESET_EXPORT`, otherwise the newest export in the backup directory.
+/// Importing the same export again does nothing.
+#[instrument(skip(database_pool))]
+pub async fn import_priors(database_pool: &database::DbPool) -> anyhow::Result<()> {
+    let priors_file = match std::env::var("PRIOR_RESET_EXPORT") {
+        Ok(file) => Some(PathBuf::from(file)),
+        Err(_) => newest_export(Path::new(BACKUP_DIR), PRIORS_SUFFIX).await?,
+    };
+
+    let Some(priors_file) = priors_file else {
+        debug!("No earlier reset export found, starting without priors");
+        return Ok(());
+    };
+
+    let content = tokio::fs::read_to_string(&priors_file).await?;
+    let priors: database::ResetPriors = serde_json::from_str(&content)?;
+
+    if priors.insert(database_pool).await? {
+        info!(
+            priors_file = %priors_file.display(),
+            source_reset = %priors.source_reset,
+            good_prices = priors.good_prices.len(),
+            ship_types = priors.ship_types.len(),
+            extraction_yields = priors.extraction_yields.len(),
+            jump_gate_materials = priors.jump_gate_materials.len(),
+            "Imported reset priors"
+        );
+    } else {
+        debug!(source_reset = %priors.source_reset, "Reset priors already imported");
+    }
+
+    Ok(())
+}
+
+/// Writes the config versions to `<file_name>.config.json`, the configuration table is dropped with the database
+#[instrument(skip(database_pool))]
+pub async fn export_config(
+    database_pool: &database::DbPool,
+    file_name: &str,
+) -> anyhow::Result<String> {
+    tokio::fs::create_dir_all(BACKUP_DIR).await?;
+    let config_file = format!("{}/{}{}", BACKUP_DIR, file_name, CONFIG_SUFFIX);
+    let versions =
+        crate::manager::config_manager::export_versions(database_pool, Path::new(&config_file))
+            .await?;
+
+    info!(config_file, versions, "Config versions exported");
+    Ok(config_file)
+}
+
+/// Restores the config versions of the newest export, so runtime overrides survive the reset
+#[instrument(skip(database_pool))]
+pub async fn import_config(database_pool: &database::DbPool) -> anyhow::Result<()> {
+    let Some(config_file) = newest_export(Path::new(BACKUP_DIR), CONFIG_SUFFIX).await? else {
+        debug!("No earlier config export found, starting with config.toml");
+        return Ok(());
+    };
+
+    let versions =
+        crate::manager::config_manager::import_versions(database_pool, &config_file).await?;
+    if versions > 0 {
+        info!(config_file = %config_file.display(), versions, "Imported config versions");
+    } else {
+        debug!("Config versions already present, skipping the import");
+    }
+
+    Ok(())
+}
+
+/// Writes the control api tokens to `<file_name>.tokens.json`, the configuration table is dropped with the database
+#[instrument(skip(database_pool))]
+pub async fn export_tokens(
+    database_pool: &database::DbPool,
+    file_name: &str,
+) -> anyhow::Result<String> {
+    tokio::fs::create_dir_all(BACKUP_DIR).await?;
+    let tokens_file = format!("{}/{}{}", BACKUP_DIR, file_name, TOKENS_SUFFIX);
+    let tokens =
+        crate::control_api::auth::ControlApiTokens::export(database_pool, Path::new(&tokens_file))
+            .await?;
+
+    info!(tokens_file, tokens, "Control api tokens exported");
+    Ok(tokens_file)
+}
+
+/// Restores the control api tokens of the newest export, so clients keep their access across resets
+#[instrument(skip(database_pool))]
+pub async fn import_tokens(database_pool: &database::DbPool) -> anyhow::Result<()> {
+    let Some(tokens_file) = newest_export(Path::new(BACKUP_DIR), TOKENS_SUFFIX).await? else {
+        debug!("No earlier control api token export found");
+        return Ok(());
+    };
+
+    let tokens =
+        crate::control_api::auth::ControlApiTokens::import(database_pool, &tokens_file).await?;
+    if tokens > 0 {
+        info!(tokens_file = %tokens_file.display(), tokens, "Imported control api tokens");
+    } else {
+        debug!("Control api tokens already present, skipping the import");
+    }
+
+    Ok(())
+}
+
+async fn newest_export(dir: &Path, suffix: &str) -> anyhow::Result<Option<PathBuf>> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut newest: Option<(std::time::SystemTime, PathBuf)> = None;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if !path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(suffix))
+        {
+            continue;
+        }
+
+        let modified = match entry.metadata().await.and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(err) => {
+                warn!(path = %path.display(), error = %err, "Can not read the export, skipping it");
+                continue;
+            }
+        };
+        if newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            newest = Some((modified, path));
+        }
+    }
+
+    Ok(newest.map(|(_, path)| path))
+}
@@ -0,0 +1,439 @@
+use std::{collections::HashMap, fmt::Write, path::Path, sync::Arc};
+
+use itertools::Itertools;
+use space_traders_client::RequestPriority;
+use tokio::sync::RwLock;
+
+use crate::utils::ConductorContext;
+
+const REPORT_DIR: &str = "./db_backup";
+const BEST_TRADE_ROUTES: i64 = 20;
+/// how often the leaderboard is captured while the run is live (in seconds)
+const LEADERBOARD_INTERVAL: u64 = 30 * 60;
+
+/// Everything worth comparing between resets, written next to the database dump
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ResetReport {
+    pub agent_symbol: String,
+    pub version: String,
+    pub start_date: chrono::DateTime<chrono::Utc>,
+    pub end_date: chrono::DateTime<chrono::Utc>,
+    pub final_funds: i64,
+    /// sum of all ledger entries of the reset
+    pub ledger_net: i64,
+    /// credit changes of the agent the ledger can not explain
+    pub unexplained_credits: i64,
+    pub ship_count_by_hour: Vec<database::ShipCountPoint>,
+    pub fleet_sizes_by_hour: Vec<database::FleetSize>,
+    pub pnl_by_activity: Vec<database::ActivityPnl>,
+    pub best_trade_routes: Vec<database::TradeRouteResult>,
+    pub ship_purchases: Vec<database::ShipPayback>,
+    pub api_requests_by_subsystem: Vec<ApiRequestCount>,
+    pub api_requests_by_priority: Vec<ApiRequestCount>,
+    pub api_requests_by_endpoint: Vec<ApiRequestCount>,
+    /// the last position captured while the run was live, `None` when it never could be fetched
+    pub leaderboard: Option<LeaderboardPosition>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ApiRequestCount {
+    pub subsystem: Option<String>,
+    pub priority: Option<RequestPriority>,
+    pub endpoint: Option<String>,
+    pub count: u64,
+}
+
+/// The place of the agent on the leaderboards, `None` when it is not listed
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LeaderboardPosition {
+    pub credits_position: Option<usize>,
+    pub credits_listed: usize,
+    pub charts_position: Option<usize>,
+    pub charts_listed: usize,
+}
+
+/// Captures the leaderboard position of the agent while the run is live
+///
+/// The server only lists the leaderboard of the current reset, once the run ends on the reset it is gone.
+#[derive(Debug)]
+pub struct LeaderboardTracker {
+    latest: Arc<RwLock<Option<LeaderboardPosition>>>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl LeaderboardTracker {
+    pub fn start(context: &ConductorContext) -> Self {
+        let latest = Arc::new(RwLock::new(None));
+        let context = context
+            .with_api_priority(RequestPriority::Background)
+            .with_api_subsystem("reset_report");
+
+        let handle = tokio::spawn({
+            let latest = latest.clone();
+            async move {
+                loop {
+                    Self::capture(&context, &latest).await;
+                    tokio::time::sleep(std::time::Duration::from_secs(LEADERBOARD_INTERVAL)).await;
+                }
+            }
+        });
+
+        Self { latest, handle }
+    }
+
+    /// Captures the position once more and stops, the last position of the reset is kept
+    pub async fn finish(self, context: &ConductorContext) -> Option<LeaderboardPosition> {
+        self.handle.abort();
+        Self::capture(context, &self.latest).await;
+        self.latest.read().await.clone()
+    }
+
+    async fn capture(context: &ConductorContext, latest: &RwLock<Option<LeaderboardPosition>>) {
+        let run_info = context.run_info.read().await.clone();
+        if let Some(position) = ResetReport::leaderboard(context, &run_info).await {
+            *latest.write().await = Some(position);
+        }
+    }
+}
+
+impl ResetReport {
+    pub async fn generate(
+        context: &ConductorContext,
+        end_date: chrono::DateTime<chrono::Utc>,
+        final_funds: i64,
+        leaderboard: Option<LeaderboardPosition>,
+    ) -> crate::error::Result<Self> {
+        let run_info = context.run_info.read().await.clone();
+        let database_pool = &context.database_pool;
+        let start_date = run_info.reset_date;
+
+        let ledger_net =
+            database::LedgerEntry::get_net(database_pool, start_date, end_date).await?;
+        let unexplained_credits = database::LedgerReconciliation::get_by_agent(
+            database_pool,
+            &run_info.agent_symbol,
+            start_date,
+            end_date,
+            true,
+        )
+        .await?
+        .iter()
+        .map(|r| r.difference)
+        .sum();
+
+        let (api_requests_by_subsystem, api_requests_by_priority, api_requests_by_endpoint) =
+            Self::api_requests(context);
+
+        Ok(Self {
+            ship_count_by_hour: database::ShipCountPoint::get_by_agent(
+                database_pool,
+                &run_info.agent_symbol,
+                start_date,
+                end_date,
+            )
+            .await?,
+            fleet_sizes_by_hour: database::FleetSize::get_by_range(
+                database_pool,
+                start_date,
+                end_date,
+            )
+            .await?,
+            pnl_by_activity: database::ActivityPnl::get_by_range(
+                database_pool,
+                start_date,
+                end_date,
+            )
+            .await?,
+            best_trade_routes: database::TradeRouteResult::get_best(
+                database_pool,
+                start_date,
+                end_date,
+                BEST_TRADE_ROUTES,
+            )
+            .await?,
+            ship_purchases: database::ShipPayback::get_by_agent(
+                database_pool,
+                &run_info.agent_symbol,
+                start_date,
+                end_date,
+            )
+            .await?,
+            leaderboard,
+            api_requests_by_subsystem,
+            api_requests_by_priority,
+            api_requests_by_endpoint,
+            agent_symbol: run_info.agent_symbol,
+            version: run_info.version,
+            start_date,
+            end_date,
+            final_funds,
+            ledger_net,
+            unexplained_credits,
+        })
+    }
+
+    fn api_requests(
+        context: &ConductorContext,
+    ) -> (
+        Vec<ApiRequestCount>,
+        Vec<ApiRequestCount>,
+        Vec<ApiRequestCount>,
+    ) {
+        let sent = context.api.get_limiter().get_sent_counts();
+
+        let mut by_subsystem = HashMap::new();
+        let mut by_priority = HashMap::new();
+        let mut by_endpoint = HashMap::new();
+        for (request, count) in sent {
+            *by_subsystem.entry(request.subsystem).or_default() += count;
+            *by_priority.entry(request.priority).or_default() += count;
+            *by_endpoint.entry(request.endpoint).or_default() += count;
+        }
+
+        let mut by_subsystem = by_subsystem
+            .into_iter()
+            .map(|(subsystem, count)| ApiRequestCount {
+                subsystem: Some(subsystem.to_string()),
+                priority: None,
+                endpoint: None,
+                count,
+            })
+            .collect::<Vec<_>>();
+        by_subsystem.sort_by_key(|c| std::cmp::Reverse(c.count));
+
+        let mut by_priority = by_priority
+            .into_iter()
+            .map(|(priority, count)| ApiRequestCount {
+                subsystem: None,
+                priority: Some(priority),
+                endpoint: None,
+                count,
+            })
+            .collect::<Vec<_>>();
+        by_priority.sort_by_key(|c| std::cmp::Reverse(c.priority));
+
+        let mut by_endpoint = by_endpoint
+            .into_iter()
+            .map(|(endpoint, count)| ApiRequestCount {
+                subsystem: None,
+                priority: None,
+                endpoint: Some(endpoint),
+                count,
+            })
+            .collect::<Vec<_>>();
+        by_endpoint.sort_by_key(|c| std::cmp::Reverse(c.count));
+
+        (by_subsystem, by_priority, by_endpoint)
+    }
+
+    async fn leaderboard(
+        context: &ConductorContext,
+        run_info: &crate::utils::RunInfo,
+    ) -> Option<LeaderboardPosition> {
+        let status = match context.api.get_status().await {
+            Ok(status) => status,
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to fetch the leaderboard for the reset report");
+                return None;
+            }
+        };
+
+        if status.reset_date.parse::<chrono::NaiveDate>().ok()
+            != Some(run_info.reset_date.date_naive())
+        {
+            tracing::info!(reset_date = %status.reset_date, "The server already reset, keeping the last captured leaderboard");
+            return None;
+        }
+
+        let leaderboards = status.leaderboards;
+        Some(LeaderboardPosition {
+            credits_position: leaderboards
+                .most_credits
+                .iter()
+                .position(|e| e.agent_symbol == run_info.agent_symbol)
+                .map(|p| p + 1),
+            credits_listed: leaderboards.most_credits.len(),
+            charts_position: leaderboards
+                .most_submitted_charts
+                .iter()
+                .position(|e| e.agent_symbol == run_info.agent_symbol)
+                .map(|p| p + 1),
+            charts_listed: leaderboards.most_submitted_charts.len(),
+        })
+    }
+
+    /// Writes `<file_name>.report.json` and `<file_name>.report.md`, returns both paths
+    pub async fn save(&self, file_name: &str) -> crate::error::Result<(String, String)> {
+        tokio::fs::create_dir_all(Path::new(REPORT_DIR))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let json_file = format!("{}/{}.report.json", REPORT_DIR, file_name);
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        tokio::fs::write(&json_file, json)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let markdown_file = format!("{}/{}.report.md", REPORT_DIR, file_name);
+        tokio::fs::write(&markdown_file, self.to_markdown())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((json_file, markdown_file))
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+
+        let _ = writeln!(md, "# Reset report {}\n", self.agent_symbol);
+        let _ = writeln!(md, "| | |\n|---|---|");
+        let _ = writeln!(md, "| Version | {} |", self.version);
+        let _ = writeln!(md, "| Start | {} |", self.start_date);
+        let _ = writeln!(md, "| End | {} |", self.end_date);
+        let _ = writeln!(md, "| Final funds | {} |", self.final_funds);
+        let _ = writeln!(md, "| Ledger net | {} |", self.ledger_net);
+        let _ = writeln!(md, "| Unexplained credits | {} |", self.unexplained_credits);
+        match &self.leaderboard {
+            Some(leaderboard) => {
+                let _ = writeln!(
+                    md,
+                    "| Credits leaderboard | {} of {} |",
+                    position(leaderboard.credits_position),
+                    leaderboard.credits_listed
+                );
+                let _ = writeln!(
+                    md,
+                    "| Charts leaderboard | {} of {} |",
+                    position(leaderboard.charts_position),
+                    leaderboard.charts_listed
+                );
+            }
+            None => {
+                let _ = writeln!(md, "| Leaderboard | unknown |");
+            }
+        }
+
+        let _ = writeln!(md, "\n## Income by activity\n");
+        let _ = writeln!(md, "| Activity | Income | Expenses | Net | Entries |");
+        let _ = writeln!(md, "|---|---:|---:|---:|---:|");
+        for pnl in &self.pnl_by_activity {
+            let _ = writeln!(
+                md,
+                "| {:?} | {} | {} | {} | {} |",
+                pnl.activity, pnl.income, pnl.expenses, pnl.net, pnl.entry_count
+            );
+        }
+
+        let _ = writeln!(md, "\n## Best trade routes\n");
+        let _ = writeln!(md, "| Good | From | To | Runs | Units sold | Profit |");
+        let _ = writeln!(md, "|---|---|---|---:|---:|---:|");
+        for route in &self.best_trade_routes {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} | {} |",
+                route.symbol,
+                route.purchase_waypoint,
+                route.sell_waypoint,
+                route.runs,
+                route.units_sold,
+                route.profit
+            );
+        }
+
+        let _ = writeln!(md, "\n## Ships purchased\n");
+        let _ = writeln!(md, "| Ship | Type | Price | Purchased | Earned | Payback |");
+        let _ = writeln!(md, "|---|---|---:|---|---:|---|");
+        for purchase in &self.ship_purchases {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} | {} |",
+                purchase.ship_symbol.as_deref().unwrap_or("unknown"),
+                purchase.ship_type,
+                purchase.price,
+                purchase.purchased_at,
+                purchase.earned,
+                purchase
+                    .payback_seconds
+                    .map(|s| format!("{:.1} h", s / 3600.0))
+                    .unwrap_or("not paid back".to_string())
+            );
+        }
+
+        let _ = writeln!(md, "\n## Fleet sizes over time\n");
+        let fleets = self
+            .fleet_sizes_by_hour
+            .iter()
+            .map(|size| (size.fleet_id, (size.fleet_type, size.system_symbol.clone())))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        let _ = write!(md, "| Hour |");
+        for (fleet_id, (fleet_type, system_symbol)) in &fleets {
+            let _ = write!(md, " #{} {:?} {} |", fleet_id, fleet_type, system_symbol);
+        }
+        let _ = writeln!(md, "\n|---|{}", "---:|".repeat(fleets.len()));
+        for (hour, sizes) in &self.fleet_sizes_by_hour.iter().chunk_by(|size| size.hour) {
+            let sizes = sizes
+                .map(|size| (size.fleet_id, size.ship_count))
+                .collect::<HashMap<_, _>>();
+            let _ = write!(md, "| {} |", hour);
+            for fleet_id in fleets.keys() {
+                let _ = write!(
+                    md,
+                    " {} |",
+                    sizes.get(fleet_id).copied().unwrap_or_default()
+                );
+            }
+            let _ = writeln!(md);
+        }
+
+        let _ = writeln!(md, "\n## Ships over time\n");
+        let _ = writeln!(md, "| Hour | Ships |");
+        let _ = writeln!(md, "|---|---:|");
+        for point in &self.ship_count_by_hour {
+            let _ = writeln!(md, "| {} | {} |", point.hour, point.ship_count);
+        }
+
+        let _ = writeln!(md, "\n## API requests by subsystem\n");
+        let _ = writeln!(md, "| Subsystem | Requests |");
+        let _ = writeln!(md, "|---|---:|");
+        for count in &self.api_requests_by_subsystem {
+            let _ = writeln!(
+                md,
+                "| {} | {} |",
+                count.subsystem.as_deref().unwrap_or_default(),
+                count.count
+            );
+        }
+
+        let _ = writeln!(md, "\n## API requests by priority\n");
+        let _ = writeln!(md, "| Priority | Requests |");
+        let _ = writeln!(md, "|---|---:|");
+        for count in &self.api_requests_by_priority {
+            let _ = writeln!(
+                md,
+                "| {} | {} |",
+                count.priority.unwrap_or_default(),
+                count.count
+            );
+        }
+
+        let _ = writeln!(md, "\n## API requests by endpoint\n");
+        let _ = writeln!(md, "| Endpoint | Requests |");
+        let _ = writeln!(md, "|---|---:|");
+        for count in &self.api_requests_by_endpoint {
+            let _ = writeln!(
+                md,
+                "| {} | {} |",
+                count.endpoint.as_deref().unwrap_or_default(),
+                count.count
+            );
+        }
+
+        md
+    }
+}
+
+fn position(position: Option<usize>) -> String {
+    position
+        .map(|p| format!("#{}", p))
+        .unwrap_or("not listed".to_string())
+}
@@ -22,6 +22,7 @@ use crate::{
         ship_task::ShipTaskHandler,
         trade_manager::TradeManager,
     },
+    reset_report::{LeaderboardTracker, ResetReport},
     utils::{CancellationTokens, ConductorContext, RunInfo},
 };
 
@@ -36,6 +37,8 @@ pub struct ResetSummary {
     pub iron_reserve: i64,
     pub reserved_amount: i64,
     pub spendable: i64,
+    /// `None` when the report could not be generated
+    pub report: Option<ResetReport>,
 }
 
 #[instrument(skip(api_key, database_pool, global_cancel_token))]
@@ -44,11 +47,15 @@ pub async fn run_reset(
     database_pool: database::DbPool,
     global_cancel_token: CancellationToken,
     socket_address: String,
+    api_base_path: Option<&str>,
 ) -> Result<ResetSummary, anyhow::Error> {
     tracing::info!("Starting reset run");
 
-    let api: space_traders_client::Api =
+    let mut api: space_traders_client::Api =
         space_traders_client::Api::new(Some(api_key.to_string()), 500, NonZeroU32::new(2).unwrap());
+    if let Some(api_base_path) = api_base_path {
+        api = api.with_base_path(api_base_path);
+    }
 
     let run_cancel_token = global_cancel_token.child_token();
 
@@ -95,41 +102,63 @@ pub async fn run_reset(
     tracing::info!("Starting ship pilots");
     start_ships(&context).await?;
 
+    let leaderboard_tracker = LeaderboardTracker::start(&context);
+
     tracing::info!("Waiting for managers to complete");
     let manager = managers_handles
         .wait(&global_cancel_token, &run_cancel_token)
         .await?;
 
     tracing::info!("Analyzing run results");
-    let run_result = analyze_run(&context, &manager).await?;
+    let run_result = analyze_run(&context, &manager, leaderboard_tracker).await?;
 
     tracing::info!(
         agent = %run_result.agent_symbol,
         funds = run_result.current_funds,
         spendable = run_result.spendable,
+        ledger_net = run_result.report.as_ref().map(|report| report.ledger_net),
+        unexplained_credits = run_result
+            .report
+            .as_ref()
+            .map(|report| report.unexplained_credits),
         "Reset run completed successfully"
     );
 
     Ok(run_result)
 }
 
-#[instrument(skip(context, _manager))]
+#[instrument(skip(context, _manager, leaderboard_tracker))]
 async fn analyze_run(
     context: &ConductorContext,
     _manager: &ManagerManager,
+    leaderboard_tracker: LeaderboardTracker,
 ) -> Result<ResetSummary, anyhow::Error> {
-    let run_info = context.run_info.read().await;
     let money = context.budget_manager.get_budget_info().await;
+    let end_date = chrono::Utc::now();
+
+    let leaderboard = leaderboard_tracker.finish(context).await;
+    // the report is only statistics, the database export after it must not depend on it
+    let report =
+        match ResetReport::generate(context, end_date, money.current_funds, leaderboard).await {
+            Ok(report) => Some(report),
+            Err(err) => {
+                tracing::error!(error = ?err, "Failed to generate the reset report");
+                None
+            }
+        };
+
+    let run_info = context.run_info.read().await;
 
     Ok(ResetSummary {
         start_date: run_info.reset_date,
-        end_date: chrono::Utc::now(),
+        end_date,
         agent_symbol: run_info.agent_symbol.clone(),
         version: run_info.version.clone(),
         current_funds: money.current_funds,
         iron_reserve: money.iron_reserve,
         reserved_amount: money.reserved_amount,
         spendable: money.spendable,
+        report,
     })
 }
 
@@ -183,8 +212,7 @@ async fn populate_context(
     my_agent: &models::Agent,
     run_info: RunInfo,
 ) -> Result<ConductorContext, anyhow::Error> {
-    let config: crate::utils::Config =
-        toml_edit::de::from_str(&std::fs::read_to_string("config.toml").unwrap()).unwrap();
+    let config = manager::config_manager::load_config(&context.database_pool).await?;
 
     let mut write_config = context.config.write().await;
     let iron_reserve = config.iron_reserve;
@@ -443,19 +471,19 @@ async fn init_managers(
     let construction_manager = ConstructionManager::new(
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.child_token(),
-        context.clone(),
+        context.with_api_subsystem("construction_manager"),
         manager_receivers.construction_manager,
     );
     let contract_manager = ContractManager::new(
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.child_token(),
-        context.clone(),
+        context.with_api_subsystem("contract_manager"),
         manager_receivers.contract_manager,
     );
     let mining_manager = MiningManager::new(
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.child_token(),
-        context.clone(),
+        context.with_api_subsystem("mining_manager"),
         manager_receivers.mining_manager,
         manager_receivers.transfer_manager,
         context.config.read().await.max_miners_per_waypoint,
@@ -463,13 +491,13 @@ async fn init_managers(
     let scrapping_manager = ScrappingManager::new(
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.child_token(),
-        context.clone(),
+        context.with_api_subsystem("scrapping_manager"),
         manager_receivers.scrapping_manager,
     );
     let trade_manager = TradeManager::init(
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.child_token(),
-        context.clone(),
+        context.with_api_subsystem("trade_manager"),
         manager_receivers.trade_manager,
     )
     .await?;
@@ -477,14 +505,14 @@ async fn init_managers(
     let chart_manager = ChartManager::new(
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.child_token(),
-        context.clone(),
+        context.with_api_subsystem("chart_manager"),
         manager_receivers.chart_manager,
     );
 
     let fleet_manager = FleetManager::new(
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.child_token(),
-        context.clone(),
+        context.with_api_subsystem("fleet_manager"),
         manager_receivers.fleet_manager,
     );
 
@@ -494,12 +522,17 @@ async fn init_managers(
         fast_manager_cancel_token.child_token(),
         fast_manager_cancel_token.child_token(),
         slow_manager_cancel_token.clone(),
-        context.clone(),
+        context.with_api_subsystem("ship_task"),
         manager_receivers.ship_task,
     );
 
+    let config_manager = manager::config_manager::ConfigManager::new(
+        fast_manager_cancel_token.child_token(),
+        context.with_api_subsystem("config_manager"),
+    );
+
     let control_api = control_api::server::ControlApiServer::new(
-        context.clone(),
+        context.with_api_subsystem("control_api"),
         context.ship_manager.get_rx(),
         fast_manager_cancel_token.child_token(),
         socket_address,
@@ -514,6 +547,7 @@ async fn init_managers(
         fleet_manager,
         chart_manager,
         ship_task_handler,
+        config_manager,
         control_api,
     );
 
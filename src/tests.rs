@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 pub mod tests {
 
     use space_traders_client::models;
@@ -14,6 +15,736 @@ pub mod tests {
         panic!("Make this test fail");
     }
 
+    /// Creates a fresh, migrated database on the postgres server of `TEST_DATABASE_URL`
+    ///
+    /// Tests using it are ignored by default, CI runs them with `cargo test -- --include-ignored`
+    /// against a postgres service container.
+    pub async fn scratch_database(name: &str) -> (database::DbPool, String) {
+        use sqlx::Connection;
+
+        let server_url = std::env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL has to point to a postgres server");
+        let db_name = format!(
+            "test_{}_{}",
+            name,
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        );
+
+        let mut conn = sqlx::PgConnection::connect(&server_url).await.unwrap();
+        sqlx::query(&format!("CREATE DATABASE \"{db_name}\""))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        conn.close().await.unwrap();
+
+        let (base, _) = server_url.rsplit_once('/').unwrap();
+        let database_url = format!("{base}/{db_name}");
+        let database_pool = crate::db_administration::create_database_pool(&database_url, None)
+            .await
+            .unwrap();
+        sqlx::migrate!()
+            .run(&database_pool.database_pool)
+            .await
+            .unwrap();
+
+        (database_pool, database_url)
+    }
+
+    /// Runs the whole manager and pilot stack against the offline mock server.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "needs a postgres server in TEST_DATABASE_URL"]
+    async fn reset_run_against_mock_server() {
+        let server = mock_server::MockServer::start(
+            mock_server::MockConfig::default(),
+            "127.0.0.1:0".parse().unwrap(),
+        )
+        .await;
+
+        let account_api =
+            space_traders_client::Api::new(None, 10, std::num::NonZeroU32::new(10).unwrap())
+                .with_base_path(server.base_path());
+        let registered = account_api
+            .register(
+                "MOCKRUN".to_string(),
+                models::FactionSymbol::Cosmic,
+                "account-token".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let (database_pool, _) = scratch_database("mock_run").await;
+
+        let cancel_token = tokio_util::sync::CancellationToken::new();
+        let stop_token = cancel_token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            stop_token.cancel();
+        });
+
+        let summary = crate::reset_runner::run_reset(
+            &registered.token,
+            database_pool,
+            cancel_token,
+            "127.0.0.1:0".to_string(),
+            Some(&server.base_path()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.agent_symbol, "MOCKRUN");
+        assert!(summary.current_funds > 0);
+
+        server.shutdown().await;
+    }
+
+    /// The ledger only holds our own trades, attributed to the fleet of the ship at the time of the trade.
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_DATABASE_URL"]
+    async fn ledger_excludes_foreign_transactions() {
+        let (database_pool, _) = scratch_database("ledger").await;
+        let pool = &database_pool.database_pool;
+
+        for statement in [
+            "INSERT INTO system (symbol, sector_symbol, system_type, x, y) VALUES ('X1-T', 'X1', 'RED_STAR', 0, 0)",
+            "INSERT INTO waypoint (symbol, system_symbol, x, y, type, traits, is_under_construction, orbitals, modifiers)
+               VALUES ('X1-T-A', 'X1-T', 0, 0, 'PLANET', '{}', false, '{}', '{}')",
+            "INSERT INTO fleet (id, system_symbol, fleet_type) VALUES (1, 'X1-T', 'Trading'), (2, 'X1-T', 'Mining')",
+            "INSERT INTO ship_assignment (id, fleet_id) VALUES (1, 1), (2, 2)",
+            "INSERT INTO shipyard_transaction (id, waypoint_symbol, ship_type, price, agent_symbol, timestamp)
+               VALUES (1, 'X1-T-A', 'SHIP_LIGHT_HAULER', 100000, 'MOCKRUN', now() - interval '2 days'),
+                      (2, 'X1-T-A', 'SHIP_LIGHT_HAULER', 100000, 'OTHER', now() - interval '2 days')",
+            "INSERT INTO ship_info (symbol, display_name, active, assignment_id, purchase_id)
+               VALUES ('MOCKRUN-1', 'MOCKRUN-1', true, 1, 1)",
+            "INSERT INTO market_transaction (waypoint_symbol, ship_symbol, type, units, price_per_unit, total_price, trade_symbol, timestamp)
+               VALUES ('X1-T-A', 'MOCKRUN-1', 'PURCHASE', 10, 100, 1000, 'IRON', now() - interval '1 day'),
+                      ('X1-T-A', 'OTHER-1', 'SELL', 10, 120, 1200, 'IRON', now() - interval '1 day'),
+                      ('X1-T-A', 'MOCKRUN-1', 'SELL', 10, 150, 1500, 'IRON', now() + interval '1 hour')",
+        ] {
+            sqlx::query(statement).execute(pool).await.unwrap();
+        }
+
+        // the ship moves to the mining fleet after its first trade
+        sqlx::query("UPDATE ship_info SET assignment_id = 2 WHERE symbol = 'MOCKRUN-1'")
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let mut entries = database::LedgerEntry::get_by_range(
+            &database_pool,
+            chrono::Utc::now() - chrono::Duration::days(3),
+            chrono::Utc::now() + chrono::Duration::days(1),
+            database::PaginatedQuery::unpaged(),
+        )
+        .await
+        .unwrap()
+        .items;
+        entries.reverse();
+
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.source,
+                    entry.ship_symbol.as_deref(),
+                    entry.amount,
+                    entry.fleet_id,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    database::LedgerSource::Shipyard,
+                    Some("MOCKRUN-1"),
+                    -100000,
+                    Some(1)
+                ),
+                (
+                    database::LedgerSource::Market,
+                    Some("MOCKRUN-1"),
+                    -1000,
+                    Some(1)
+                ),
+                (
+                    database::LedgerSource::Market,
+                    Some("MOCKRUN-1"),
+                    1500,
+                    Some(2)
+                ),
+            ]
+        );
+    }
+
+    /// Saved mining assignments come back for the ships still in a mining fleet, by their assignment in the database.
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_DATABASE_URL"]
+    async fn mining_assignments_are_restored() {
+        use crate::manager::mining_manager::{AssignLevel, mining_places::MiningPlaces};
+
+        let (database_pool, _) = scratch_database("mining_restore").await;
+
+        for statement in [
+            "INSERT INTO system (symbol, sector_symbol, system_type, x, y) VALUES ('X1-T', 'X1', 'RED_STAR', 0, 0)",
+            "INSERT INTO fleet (id, system_symbol, fleet_type) VALUES (1, 'X1-T', 'Mining'), (2, 'X1-T', 'Trading')",
+            "INSERT INTO ship_assignment (id, fleet_id) VALUES (1, 1), (2, 2)",
+            "INSERT INTO ship_info (symbol, display_name, active, assignment_id, temp_assignment_id)
+               VALUES ('MINER-1', 'MINER-1', true, 1, NULL),
+                      ('MINER-2', 'MINER-2', true, 1, NULL),
+                      ('MINER-3', 'MINER-3', true, 1, 2)",
+        ] {
+            sqlx::query(statement)
+                .execute(&database_pool.database_pool)
+                .await
+                .unwrap();
+        }
+        for ship_symbol in ["MINER-1", "MINER-2", "MINER-3"] {
+            database::MiningAssignment::upsert(
+                &database_pool,
+                ship_symbol,
+                "X1-T-A",
+                database::MiningAssignLevel::Active,
+            )
+            .await
+            .unwrap();
+        }
+
+        let mining_ships =
+            database::ShipInfo::get_by_fleet_type(&database_pool, database::FleetType::Mining)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|ship_info| ship_info.symbol)
+                .collect();
+        let positions = [
+            ("MINER-1", "X1-T-A", false),
+            ("MINER-2", "X1-T-A", true),
+            ("MINER-3", "X1-T-A", false),
+        ]
+        .into_iter()
+        .map(|(symbol, waypoint, in_transit)| {
+            (symbol.to_string(), (waypoint.to_string(), in_transit))
+        })
+        .collect();
+
+        let mut places = MiningPlaces::new(3);
+        let dropped = places.restore_all(
+            database::MiningAssignment::get_all(&database_pool)
+                .await
+                .unwrap(),
+            &mining_ships,
+            &positions,
+        );
+
+        assert_eq!(
+            places.get_ship("MINER-1"),
+            Some(("X1-T-A".to_string(), AssignLevel::Active))
+        );
+        assert_eq!(
+            places.get_ship("MINER-2"),
+            Some(("X1-T-A".to_string(), AssignLevel::OnTheWay))
+        );
+        // the temporary trading assignment takes precedence
+        assert_eq!(places.get_ship("MINER-3"), None);
+        assert_eq!(
+            dropped
+                .iter()
+                .map(|assignment| assignment.ship_symbol.as_str())
+                .collect::<Vec<_>>(),
+            vec!["MINER-3"]
+        );
+    }
+
+    /// The config history is written before the database is dropped and stored again in the fresh one.
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_DATABASE_URL"]
+    async fn config_versions_survive_reset() {
+        use crate::manager::config_manager::{self, ConfigVersion};
+
+        let (old_pool, _) = scratch_database("config_export").await;
+        let mut config = crate::utils::Config::default();
+        for version in 1..=2 {
+            config.trade_max_legs = version as i32;
+            let value = serde_json::to_value(ConfigVersion {
+                version,
+                source: "edit_config".to_string(),
+                overrides: serde_json::json!({ "trade_max_legs": version }),
+                config: config.clone(),
+                created_at: chrono::Utc::now(),
+            })
+            .unwrap();
+            database::Configuration::set_config_version(&old_pool, version, value)
+                .await
+                .unwrap();
+        }
+
+        let export_file = std::env::temp_dir().join(format!(
+            "config_versions_{}.json",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        assert_eq!(
+            config_manager::export_versions(&old_pool, &export_file)
+                .await
+                .unwrap(),
+            2
+        );
+
+        let (new_pool, _) = scratch_database("config_import").await;
+        assert_eq!(
+            config_manager::import_versions(&new_pool, &export_file)
+                .await
+                .unwrap(),
+            2
+        );
+        // a database with versions is not touched again
+        assert_eq!(
+            config_manager::import_versions(&new_pool, &export_file)
+                .await
+                .unwrap(),
+            0
+        );
+        let _ = std::fs::remove_file(&export_file);
+
+        let history = config_manager::get_history(&new_pool).await.unwrap();
+        assert_eq!(
+            history.iter().map(|v| v.version).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let latest = config_manager::get_latest(&new_pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.overrides, serde_json::json!({ "trade_max_legs": 2 }));
+        assert_eq!(latest.config.trade_max_legs, 2);
+    }
+
+    /// A declined offer stays declined when the contract is stored again, an accepted one is no longer reported.
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_DATABASE_URL"]
+    async fn declined_contracts_survive_updates() {
+        let (database_pool, _) = scratch_database("contract_declined").await;
+        let contract = |id: &str, accepted: bool| {
+            models::Contract::new(
+                id.to_string(),
+                "COSMIC".to_string(),
+                models::contract::Type::Procurement,
+                models::ContractTerms::new(
+                    "2030-01-01T00:00:00Z".to_string(),
+                    models::ContractPayment::new(1_000, 10_000),
+                ),
+                accepted,
+                false,
+                "2030-01-01T00:00:00Z".to_string(),
+            )
+        };
+
+        for id in ["OFFERED", "ACCEPTED"] {
+            database::Contract::insert_contract(&database_pool, contract(id, false), None)
+                .await
+                .unwrap();
+            database::Contract::mark_declined(&database_pool, id)
+                .await
+                .unwrap();
+        }
+        database::Contract::insert_contract(&database_pool, contract("OFFERED", false), None)
+            .await
+            .unwrap();
+        database::Contract::insert_contract(&database_pool, contract("ACCEPTED", true), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            database::Contract::get_declined_ids(&database_pool)
+                .await
+                .unwrap(),
+            vec!["OFFERED".to_string()]
+        );
+    }
+
+    /// The control api tokens are exported owner-readable only and stored again in the fresh database.
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_DATABASE_URL"]
+    async fn control_api_tokens_survive_reset() {
+        use crate::control_api::auth::ControlApiTokens;
+
+        let (old_pool, _) = scratch_database("tokens_export").await;
+        let tokens = vec![database::ControlApiToken {
+            name: "dashboard".to_string(),
+            role: database::ControlRole::Operator,
+            token: "secret".to_string(),
+        }];
+        database::Configuration::set_control_api_tokens(&old_pool, &tokens)
+            .await
+            .unwrap();
+
+        let export_file = std::env::temp_dir().join(format!(
+            "control_api_tokens_{}.json",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        assert_eq!(
+            ControlApiTokens::export(&old_pool, &export_file)
+                .await
+                .unwrap(),
+            1
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&export_file)
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let (new_pool, _) = scratch_database("tokens_import").await;
+        assert_eq!(
+            ControlApiTokens::import(&new_pool, &export_file)
+                .await
+                .unwrap(),
+            1
+        );
+        // a database with tokens is not touched again
+        assert_eq!(
+            ControlApiTokens::import(&new_pool, &export_file)
+                .await
+                .unwrap(),
+            0
+        );
+        let _ = std::fs::remove_file(&export_file);
+
+        assert_eq!(
+            database::Configuration::get_control_api_tokens(&new_pool)
+                .await
+                .unwrap(),
+            tokens
+        );
+        let loaded = ControlApiTokens::load(&new_pool).await.unwrap();
+        assert_eq!(
+            loaded.authenticate("Bearer secret").map(|u| u.name),
+            Some("dashboard".to_string())
+        );
+    }
+
+    #[test]
+    fn maintenance_scraps_worn_ships_when_a_replacement_is_affordable() {
+        use crate::pilot::maintenance::should_scrap;
+
+        // worn: replaced once the replacement minus the scrap income fits the funds, whatever the repair costs
+        assert!(should_scrap(true, 0, 20_000, Some(100_000), 80_000));
+        assert!(!should_scrap(true, 0, 20_000, Some(100_000), 79_999));
+        // damaged only: replaced when the repair costs at least as much as the replacement after the scrap income
+        assert!(should_scrap(
+            false,
+            80_000,
+            20_000,
+            Some(100_000),
+            1_000_000
+        ));
+        assert!(!should_scrap(
+            false,
+            79_999,
+            20_000,
+            Some(100_000),
+            1_000_000
+        ));
+        // nothing can take over the assignment
+        assert!(!should_scrap(true, 1_000_000, 20_000, None, 1_000_000));
+    }
+
+    fn survey(signature: &str, deposits: Vec<models::TradeSymbol>) -> database::Survey {
+        database::Survey {
+            ship_info_before: 0,
+            ship_info_after: 0,
+            ship_symbol: "SURVEYOR-1".to_string(),
+            signature: signature.to_string(),
+            waypoint_symbol: "X1-T-B".to_string(),
+            deposits,
+            expiration: chrono::Utc::now() + chrono::Duration::hours(2),
+            size: models::SurveySize::Large,
+            exhausted_since: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Without known sell prices the surveys are ranked by their share of preferred deposits, like before the valuation.
+    #[test]
+    fn surveys_are_used_without_known_prices() {
+        use crate::pilot::mining::survey_value::SurveyValuator;
+        use models::TradeSymbol::{CopperOre, IronOre, QuartzSand};
+
+        let valuator = SurveyValuator::new(Default::default(), vec![IronOre], Default::default());
+        let surveys = vec![
+            survey("MOSTLY-QUARTZ", vec![IronOre, QuartzSand, QuartzSand]),
+            survey("MOSTLY-IRON", vec![IronOre, IronOre, CopperOre]),
+        ];
+
+        let (best, score) = valuator.best(&surveys, "MINER-1").unwrap();
+        assert_eq!(best.signature, "MOSTLY-IRON");
+        assert!(score > 0.0);
+
+        // the surveys cover the miner, so the surveyor waits instead of surveying again right away
+        assert!(valuator.next_survey_at(&surveys, 1).is_some());
+    }
+
+    fn price_transaction(
+        r#type: models::market_transaction::Type,
+        price_per_unit: i32,
+        minutes: i64,
+    ) -> database::MarketTransaction {
+        database::MarketTransaction {
+            waypoint_symbol: "X1-T-A".to_string(),
+            ship_symbol: "TRADER-1".to_string(),
+            trade_symbol: models::TradeSymbol::Iron,
+            r#type,
+            units: 10,
+            price_per_unit,
+            total_price: price_per_unit * 10,
+            timestamp: chrono::DateTime::UNIX_EPOCH + chrono::Duration::minutes(minutes),
+            ..Default::default()
+        }
+    }
+
+    fn price_snapshot(purchase_price: i32, hours: i64) -> database::MarketTradeGood {
+        let created = chrono::DateTime::UNIX_EPOCH + chrono::Duration::hours(hours);
+        database::MarketTradeGood {
+            symbol: models::TradeSymbol::Iron,
+            waypoint_symbol: "X1-T-A".to_string(),
+            r#type: models::market_trade_good::Type::Exchange,
+            trade_volume: 10,
+            supply: models::SupplyLevel::Moderate,
+            activity: None,
+            purchase_price,
+            sell_price: purchase_price - 10,
+            created,
+            created_at: created,
+        }
+    }
+
+    #[test]
+    fn price_model_learns_the_impact_of_our_trades() {
+        use crate::manager::trade_manager::PriceModel;
+        use models::market_transaction::Type;
+
+        let model = PriceModel::learn(
+            &[
+                // buying 10 units raises the price by 10%, selling lowers it by 5%
+                price_transaction(Type::Purchase, 100, 0),
+                price_transaction(Type::Purchase, 110, 1),
+                price_transaction(Type::Sell, 200, 2),
+                price_transaction(Type::Sell, 190, 3),
+            ],
+            &[],
+            0.002,
+        );
+
+        let purchase = model.purchase_impact("X1-T-A", models::TradeSymbol::Iron);
+        let sell = model.sell_impact("X1-T-A", models::TradeSymbol::Iron);
+        assert!((purchase.per_unit - 0.01).abs() < 1e-9);
+        assert!((sell.per_unit + 0.005).abs() < 1e-9);
+
+        // the price only moves between batches of the trade volume
+        assert_eq!(purchase.total_price(100, 20, 10), 100 * 10 + 110 * 10);
+        assert_eq!(purchase.units_until(100, 10, 115.0, 100), 20);
+
+        // markets without history use the fallback
+        let unknown = model.purchase_impact("X1-T-B", models::TradeSymbol::Iron);
+        assert!((unknown.per_unit - 0.002).abs() < 1e-9);
+        assert_eq!(unknown.baseline, None);
+    }
+
+    #[test]
+    fn price_model_ignores_noise_against_the_trade() {
+        use crate::manager::trade_manager::PriceModel;
+        use models::market_transaction::Type;
+
+        // the price fell while buying, which can only be another trader
+        let model = PriceModel::learn(
+            &[
+                price_transaction(Type::Purchase, 100, 0),
+                price_transaction(Type::Purchase, 90, 1),
+                // too far apart to be batches of the same trade
+                price_transaction(Type::Sell, 200, 2),
+                price_transaction(Type::Sell, 100, 60),
+            ],
+            &[],
+            0.002,
+        );
+
+        assert_eq!(
+            model
+                .purchase_impact("X1-T-A", models::TradeSymbol::Iron)
+                .per_unit,
+            0.0
+        );
+        assert!(
+            (model
+                .sell_impact("X1-T-A", models::TradeSymbol::Iron)
+                .per_unit
+                + 0.002)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn price_model_learns_the_recovery_to_the_baseline() {
+        use crate::manager::trade_manager::PriceModel;
+
+        // the price is pushed up to 140 and recovers half of the displacement within an hour
+        let model = PriceModel::learn(
+            &[],
+            &[
+                price_snapshot(100, 0),
+                price_snapshot(100, 1),
+                price_snapshot(100, 2),
+                price_snapshot(140, 3),
+                price_snapshot(120, 4),
+            ],
+            0.002,
+        );
+
+        let purchase = model.purchase_impact("X1-T-A", models::TradeSymbol::Iron);
+        assert_eq!(purchase.baseline, Some(100.0));
+        assert!((purchase.recovery_per_hour - 0.5).abs() < 1e-9);
+        assert_eq!(purchase.recovered_price(140, 1.0), 120);
+        assert_eq!(purchase.recovered_price(140, 0.0), 140);
+        // without trades of our own the configured impact stays
+        assert!((purchase.per_unit - 0.002).abs() < 1e-9);
+    }
+
+    #[test]
+    fn route_volume_stops_when_the_next_batch_is_unprofitable() {
+        use crate::manager::trade_manager::PriceModel;
+        use models::market_transaction::Type;
+
+        let model = PriceModel::learn(
+            &[
+                price_transaction(Type::Purchase, 100, 0),
+                price_transaction(Type::Purchase, 110, 1),
+                price_transaction(Type::Sell, 200, 2),
+                price_transaction(Type::Sell, 180, 3),
+            ],
+            &[],
+            0.002,
+        );
+        let purchase = model.purchase_impact("X1-T-A", models::TradeSymbol::Iron);
+        let sell = model.sell_impact("X1-T-A", models::TradeSymbol::Iron);
+
+        // buying at 100, 110, 121 and selling at 130, 117, 105.3
+        assert_eq!(
+            PriceModel::route_volume(&purchase, 100, 10, &sell, 130, 10, 100),
+            20
+        );
+        assert_eq!(
+            PriceModel::route_volume(&purchase, 100, 10, &sell, 130, 10, 15),
+            15
+        );
+    }
+
+    fn assignment(cargo_min: i32, survey: bool, extractor: bool) -> database::ShipAssignment {
+        database::ShipAssignment {
+            id: 1,
+            fleet_id: 1,
+            priority: 100,
+            max_purchase_price: 1_000_000,
+            credits_threshold: 100_000,
+            disabled: false,
+            range_min: 0,
+            cargo_min,
+            survey,
+            extractor,
+            siphon: false,
+            warp_drive: false,
+            refinery: false,
+        }
+    }
+
+    #[test]
+    fn outfitting_removes_parts_the_assignment_does_not_need() {
+        use std::collections::HashMap;
+
+        use crate::manager::fleet_manager::{OutfitPlan, OutfitPlanner, OutfitStep};
+        use models::{ship_module, ship_mount};
+
+        let planner = OutfitPlanner::new(
+            database::FrameInfo {
+                symbol: models::ship_frame::Symbol::Miner,
+                name: "Miner".to_string(),
+                description: String::new(),
+                module_slots: 3,
+                mounting_points: 3,
+                fuel_capacity: 300,
+                power_required: Some(2),
+                crew_required: Some(0),
+                slots_required: None,
+            },
+            database::ReactorInfo {
+                symbol: models::ship_reactor::Symbol::SolarI,
+                name: "Solar Reactor".to_string(),
+                description: String::new(),
+                power_output: 100,
+                power_required: None,
+                crew_required: Some(0),
+                slots_required: None,
+            },
+            database::EngineInfo {
+                symbol: models::ship_engine::Symbol::ImpulseDriveI,
+                name: "Impulse Drive".to_string(),
+                description: String::new(),
+                speed: 10,
+                power_required: Some(1),
+                crew_required: Some(0),
+                slots_required: None,
+            },
+            HashMap::new(),
+            HashMap::new(),
+        );
+        let mounts = [
+            ship_mount::Symbol::MiningLaserI,
+            ship_mount::Symbol::SurveyorI,
+        ];
+        let modules = [
+            ship_module::Symbol::MineralProcessorI,
+            ship_module::Symbol::CargoHoldI,
+        ];
+
+        // a former miner hauling goods keeps only its cargo hold
+        let plan = OutfitPlan::plan(
+            &mounts,
+            &modules,
+            300,
+            &assignment(10, false, false),
+            &planner,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.steps,
+            vec![
+                OutfitStep::RemoveMount(ship_mount::Symbol::MiningLaserI),
+                OutfitStep::RemoveMount(ship_mount::Symbol::SurveyorI),
+                OutfitStep::RemoveModule(ship_module::Symbol::MineralProcessorI),
+            ]
+        );
+        assert!(plan.parts_to_buy().is_empty());
+
+        // an extractor without surveys only loses its surveyor
+        let plan = OutfitPlan::plan(
+            &mounts,
+            &modules,
+            300,
+            &assignment(10, false, true),
+            &planner,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.steps,
+            vec![OutfitStep::RemoveMount(ship_mount::Symbol::SurveyorI)]
+        );
+    }
+
+    #[allow(dead_code)]
     pub fn get_waypoints() -> Vec<models::Waypoint> {
         let waypoints: Vec<models::Waypoint> = serde_json::from_str(
             r#"
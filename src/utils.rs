@@ -36,6 +36,24 @@ pub struct ConductorContext {
     pub cancellation_tokens: Arc<CancellationTokens>,
 }
 
+impl ConductorContext {
+    /// Copy of the context whose api requests are queued with the given priority
+    pub fn with_api_priority(&self, priority: space_traders_client::RequestPriority) -> Self {
+        Self {
+            api: self.api.with_priority(priority),
+            ..self.clone()
+        }
+    }
+
+    /// Copy of the context whose api requests are counted for the given subsystem
+    pub fn with_api_subsystem(&self, subsystem: &'static str) -> Self {
+        Self {
+            api: self.api.with_subsystem(subsystem),
+            ..self.clone()
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CancellationTokens {
     pub global_cancel_token: CancellationToken,
@@ -46,7 +64,7 @@ pub struct CancellationTokens {
     pub slow_ship_cancel_token: CancellationToken,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, async_graphql::SimpleObject)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, async_graphql::SimpleObject)]
 pub struct ChannelInfo {
     pub state: ChannelState,
     pub total_capacity: usize,
@@ -94,6 +112,10 @@ pub struct Config {
     // Markup and margin percentages (as decimals)
     pub markup_percentage: f32,
     pub margin_percentage: f32,
+    /// relative price change per unit traded at markets without learned price history
+    pub price_impact_fallback: f32,
+    /// minimum expected profit of a contract, payment minus purchase and travel costs, to accept it
+    pub contract_profit_threshold: i32,
 
     pub markets_per_ship: i64,
 
@@ -103,13 +125,21 @@ pub struct Config {
 
     pub trade_mode: database::TradeMode,
     pub trade_profit_threshold: i32,
+    /// the maximum number of legs chained into one trade plan, 1 only plans single A -> B legs
+    pub trade_max_legs: i32,
 
     pub ship_purchase_percentile: f32,
     pub ship_purchase_stop: bool,
     pub expand: bool,
     pub ship_purchase_amount: i32,
+    /// how many hours of expected earnings are subtracted from the price when comparing ships to purchase
+    pub ship_earnings_horizon_hours: f32,
 
     pub use_exploration_fleet: bool,
+    pub outfit_ships: bool,
+
+    pub repair_condition_threshold: f32,
+    pub scrap_integrity_threshold: f32,
 
     pub iron_reserve: i64,
 }
@@ -138,18 +168,110 @@ impl Default for Config {
             max_update_interval: 0,
             markup_percentage: 0.0,
             margin_percentage: 0.0,
+            price_impact_fallback: 0.0,
+            contract_profit_threshold: 0,
             markets_per_ship: 0,
             mining_waypoints_per_system: 0,
             mining_ships_per_waypoint: 0,
             transport_capacity_per_waypoint: 0,
             trade_mode: database::TradeMode::default(),
             trade_profit_threshold: 0,
+            trade_max_legs: 1,
             ship_purchase_percentile: 0.0,
             ship_purchase_stop: false,
             expand: false,
             ship_purchase_amount: 0,
+            ship_earnings_horizon_hours: 0.0,
             iron_reserve: 0,
             use_exploration_fleet: false,
+            outfit_ships: false,
+            repair_condition_threshold: 0.0,
+            scrap_integrity_threshold: 0.0,
+        }
+    }
+}
+
+impl Config {
+    /// Checks the values which would break the bot instead of only making it play worse
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        let mut check = |ok: bool, message: &str| {
+            if !ok {
+                errors.push(message.to_string());
+            }
+        };
+
+        let fraction = |v: f32| v.is_finite() && (0.0..=1.0).contains(&v);
+
+        check(
+            self.purchase_multiplier.is_finite() && self.purchase_multiplier > 0.0,
+            "purchase_multiplier must be positive",
+        );
+        check(
+            fraction(self.markup_percentage),
+            "markup_percentage must be between 0 and 1",
+        );
+        check(
+            fraction(self.margin_percentage),
+            "margin_percentage must be between 0 and 1",
+        );
+        check(
+            self.price_impact_fallback.is_finite() && self.price_impact_fallback >= 0.0,
+            "price_impact_fallback must not be negative",
+        );
+        check(
+            self.ship_purchase_percentile.is_finite()
+                && (0.0..=100.0).contains(&self.ship_purchase_percentile),
+            "ship_purchase_percentile must be between 0 and 100",
+        );
+        check(
+            self.ship_earnings_horizon_hours.is_finite() && self.ship_earnings_horizon_hours >= 0.0,
+            "ship_earnings_horizon_hours must not be negative",
+        );
+        check(
+            fraction(self.repair_condition_threshold),
+            "repair_condition_threshold must be between 0 and 1",
+        );
+        check(
+            fraction(self.scrap_integrity_threshold),
+            "scrap_integrity_threshold must be between 0 and 1",
+        );
+        check(
+            self.trade_max_legs >= 1,
+            "trade_max_legs must be at least 1",
+        );
+        check(
+            self.max_update_interval > 0,
+            "max_update_interval must be positive",
+        );
+        check(
+            self.unstable_since_timeout >= 0,
+            "unstable_since_timeout must not be negative",
+        );
+        check(
+            self.fuel_cost >= 0 && self.antimatter_price >= 0,
+            "fuel_cost and antimatter_price must not be negative",
+        );
+        check(
+            self.default_purchase_price >= 0 && self.default_sell_price >= 0,
+            "default prices must not be negative",
+        );
+        check(
+            self.markets_per_ship >= 0
+                && self.mining_waypoints_per_system >= 0
+                && self.mining_ships_per_waypoint >= 0
+                && self.transport_capacity_per_waypoint >= 0
+                && self.extra_mining_transporter >= 0
+                && self.ship_purchase_amount >= 0,
+            "counts must not be negative",
+        );
+        check(self.iron_reserve >= 0, "iron_reserve must not be negative");
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
     }
 }
@@ -6,12 +6,14 @@ use lockable::{AsyncLimit, Lockable, LockableHashMap, SyncLimit};
 use rand::Rng;
 
 /// Trait representing an observer that can be updated
+#[allow(async_fn_in_trait)]
 pub trait Observer<K> {
     /// Asynchronous method to update the observer with new data
     async fn update(&self, data: K);
 }
 
 /// Trait representing a subject that can manage and notify observers
+#[allow(async_fn_in_trait)]
 pub trait Subject<T: Observer<K>, K> {
     /// Register a new observer
     fn register_observer(&mut self, observer: Weak<T>);
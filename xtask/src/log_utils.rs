@@ -5,6 +5,7 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub struct LogLine {
     pub timestamp: DateTime<Utc>,
     pub level: String,
@@ -18,6 +19,7 @@ pub struct LogLine {
 }
 
 #[derive(Debug, Default, Deserialize)]
+#[allow(dead_code)]
 pub struct Fields {
     #[serde(default)]
     pub message: String,
@@ -26,6 +28,7 @@ pub struct Fields {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub struct SpanInfo {
     pub name: String,
     #[serde(flatten)]